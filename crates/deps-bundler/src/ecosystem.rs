@@ -2,14 +2,18 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{EntryExtent, SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::BundlerFormatter;
 use crate::registry::RubyGemsRegistry;
+use crate::types::DependencyGroup;
 
 /// Bundler ecosystem implementation.
 ///
@@ -84,6 +88,13 @@ impl Ecosystem for BundlerEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: BundlerFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::GemfileLockParser))
     }
@@ -116,11 +127,114 @@ impl Ecosystem for BundlerEcosystem {
         })
     }
 
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        _content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::BundlerParseResult>()
+        else {
+            return vec![];
+        };
+        let entries = result.dependencies.iter().map(|dep| {
+            (
+                group_label(&dep.group),
+                SortableEntry::new(dep.name.clone(), dep.name_range.start.line),
+            )
+        });
+        SortableSection::group(GEMFILE_STYLE, entries)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let group = match &dependency.kind {
+                DependencyKind::Normal => DependencyGroup::Default,
+                DependencyKind::Dev => DependencyGroup::Development,
+                DependencyKind::Test => DependencyGroup::Test,
+                DependencyKind::Other(name) => {
+                    DependencyGroup::Custom(name.trim_start_matches(':').to_string())
+                }
+                _ => return None,
+            };
+            let label = group_label(&group);
+            let entry = gem_entry(content, &dependency.name, &dependency.version);
+
+            let sections = self.sortable_sections(parse_result, content);
+            let edits = add_dependency::insert_into_section(
+                content,
+                &sections,
+                &label,
+                &dependency.name,
+                &entry,
+            )
+            .unwrap_or_else(|| {
+                let newline = add_dependency::newline(content);
+                if group == DependencyGroup::Default {
+                    return match add_dependency::find_line(content, |l| l.starts_with("source ")) {
+                        Some(line) => vec![add_dependency::insert_after_line(
+                            content,
+                            line,
+                            &format!("{newline}{entry}"),
+                        )],
+                        None => vec![add_dependency::append_block(content, &entry)],
+                    };
+                }
+                let block = format!(
+                    "{label} do{newline}{}{entry}{newline}end",
+                    add_dependency::indent_unit(content)
+                );
+                vec![add_dependency::append_block(content, &block)]
+            });
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Gemfile entries are method calls that continue over lines ending in `,`.
+const GEMFILE_STYLE: SectionStyle = SectionStyle {
+    comment_prefix: "#",
+    separator: None,
+    extent: EntryExtent::Brackets,
+};
+
+/// Formats a `gem` line with a pessimistic requirement on the minor version,
+/// quoted like the existing entries.
+fn gem_entry(content: &str, name: &str, version: &str) -> String {
+    let quote = if content.matches("gem \"").count() > content.matches("gem '").count() {
+        '"'
+    } else {
+        '\''
+    };
+    let requirement = if add_dependency::is_bare_version(version) {
+        let minor: Vec<&str> = version.split('.').take(2).collect();
+        format!("~> {}", minor.join("."))
+    } else {
+        version.to_string()
+    };
+    format!("gem {quote}{name}{quote}, {quote}{requirement}{quote}")
+}
+
+fn group_label(group: &DependencyGroup) -> String {
+    match group {
+        DependencyGroup::Default => "gems".into(),
+        DependencyGroup::Development => "group :development".into(),
+        DependencyGroup::Test => "group :test".into(),
+        DependencyGroup::Production => "group :production".into(),
+        DependencyGroup::Custom(name) => format!("group :{name}"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,4 +324,77 @@ gem 'rails', '~> 7.0'";
         let result = ecosystem.parse_manifest(gemfile, &uri).await.unwrap();
         assert_eq!(result.dependencies().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = BundlerEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Gemfile");
+        let gemfile = "source 'https://rubygems.org'\n\ngem 'rails', '~> 7.0'\ngem 'puma',\n  '~> 6.0'\ngem 'bootsnap', require: false\n\ngroup :test do\n  gem 'rspec'\n  gem 'capybara'\nend\n";
+        let result = ecosystem.parse_manifest(gemfile, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(result.as_ref(), gemfile);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "gems");
+        assert_eq!(sections[1].label, "group :test");
+
+        let runs = deps_core::sort::sort_section(gemfile, &sections[0]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "gem 'bootsnap', require: false\ngem 'puma',\n  '~> 6.0'\ngem 'rails', '~> 7.0'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = BundlerEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Gemfile");
+        let content = "source 'https://rubygems.org'\n\ngem 'puma', '~> 6.0'\ngem 'rails', '~> 7.0'\n\ngroup :test do\n  gem 'rspec', '~> 3.12'\nend\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "2.4.1".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("pg", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "source 'https://rubygems.org'\n\ngem 'pg', '~> 2.4'\ngem 'puma', '~> 6.0'\ngem 'rails', '~> 7.0'\n\ngroup :test do\n  gem 'rspec', '~> 3.12'\nend\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("capybara", Some("test")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "source 'https://rubygems.org'\n\ngem 'puma', '~> 6.0'\ngem 'rails', '~> 7.0'\n\ngroup :test do\n  gem 'capybara', '~> 2.4'\n  gem 'rspec', '~> 3.12'\nend\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("pry", Some("development")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "source 'https://rubygems.org'\n\ngem 'puma', '~> 6.0'\ngem 'rails', '~> 7.0'\n\ngroup :test do\n  gem 'rspec', '~> 3.12'\nend\n\ngroup :development do\n  gem 'pry', '~> 2.4'\nend\n"
+        );
+    }
 }
@@ -23,6 +23,10 @@ impl GemfileLockParser {
 static GEM_SPEC_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\s{4}([a-zA-Z0-9_-]+)\s+\(([^)]+)\)").expect("Invalid regex"));
 
+// Regex for a spec's own dependencies: "      gemname (requirement)"
+static GEM_DEPENDENCY_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s{6}([a-zA-Z0-9_-]+)").expect("Invalid regex"));
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Section {
     None,
@@ -64,10 +68,15 @@ pub fn parse_gemfile_lock(content: &str) -> Result<ResolvedPackages> {
         checksum: String::new(),
     };
     let mut in_specs = false;
+    // Spec whose dependency lines are being read
+    let mut current_spec: Option<ResolvedPackage> = None;
 
     for line in content.lines() {
         // Check for section headers
         if let Some(section) = detect_section(line) {
+            if let Some(spec) = current_spec.take() {
+                packages.insert(spec);
+            }
             current_section = section;
             in_specs = false;
 
@@ -125,22 +134,30 @@ pub fn parse_gemfile_lock(content: &str) -> Result<ResolvedPackages> {
             continue;
         }
 
-        // Parse gem specs
-        if in_specs
-            && matches!(current_section, Section::Gem | Section::Git | Section::Path)
-            && let Some(caps) = GEM_SPEC_PATTERN.captures(line)
-        {
-            let name = caps[1].to_string();
-            let version = caps[2].to_string();
+        if !in_specs || !matches!(current_section, Section::Gem | Section::Git | Section::Path) {
+            continue;
+        }
 
-            packages.insert(ResolvedPackage {
-                name,
-                version,
+        // Parse gem specs
+        if let Some(caps) = GEM_SPEC_PATTERN.captures(line) {
+            let spec = ResolvedPackage {
+                name: caps[1].to_string(),
+                version: caps[2].to_string(),
                 source: current_source.clone(),
                 dependencies: vec![],
-            });
+            };
+            if let Some(previous) = current_spec.replace(spec) {
+                packages.insert(previous);
+            }
+        } else if let Some(caps) = GEM_DEPENDENCY_PATTERN.captures(line)
+            && let Some(spec) = current_spec.as_mut()
+        {
+            spec.dependencies.push(caps[1].to_string());
         }
     }
+    if let Some(spec) = current_spec {
+        packages.insert(spec);
+    }
 
     tracing::info!("Parsed Gemfile.lock: {} packages", packages.len());
 
@@ -193,6 +210,38 @@ BUNDLED WITH
         assert_eq!(packages.get_version("puma"), Some("6.4.0"));
     }
 
+    #[test]
+    fn test_parse_spec_dependencies() {
+        let lockfile = r"GEM
+  remote: https://rubygems.org/
+  specs:
+    actionpack (7.0.8)
+      rack (>= 2.2.4)
+      rack-test (>= 0.6.3)
+    rack (2.2.8)
+    rack-test (2.1.0)
+      rack (>= 1.3)
+
+PLATFORMS
+  ruby
+
+DEPENDENCIES
+  actionpack
+";
+
+        let packages = parse_gemfile_lock(lockfile).unwrap();
+        assert_eq!(packages.len(), 3);
+        assert_eq!(
+            packages.get("actionpack").unwrap().dependencies,
+            vec!["rack", "rack-test"]
+        );
+        assert!(packages.get("rack").unwrap().dependencies.is_empty());
+        assert_eq!(
+            packages.get("rack-test").unwrap().dependencies,
+            vec!["rack"]
+        );
+    }
+
     #[test]
     fn test_parse_git_source() {
         let lockfile = r"GIT
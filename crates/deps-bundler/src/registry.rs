@@ -7,6 +7,7 @@ use crate::version::{compare_versions, version_matches_requirement};
 use deps_core::{HttpCache, Result};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 const RUBYGEMS_API_BASE: &str = "https://rubygems.org/api/v1";
@@ -26,17 +27,32 @@ pub fn gem_url(name: &str) -> String {
 #[derive(Clone)]
 pub struct RubyGemsRegistry {
     cache: Arc<HttpCache>,
+    api_base: Cow<'static, str>,
 }
 
 impl RubyGemsRegistry {
     /// Creates a new registry client with the given HTTP cache.
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            api_base: Cow::Borrowed(RUBYGEMS_API_BASE),
+        }
+    }
+
+    /// Returns a client for the gem server at `url` (a Gemfile `source`
+    /// serving the rubygems.org API under `/api/v1`), sharing this client's
+    /// cache.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            api_base: Cow::Owned(format!("{}/api/v1", url.trim_end_matches('/'))),
+        }
     }
 
     /// Fetches all versions for a gem.
     pub async fn get_versions(&self, name: &str) -> Result<Vec<BundlerVersion>> {
-        let url = format!("{}/versions/{}.json", RUBYGEMS_API_BASE, name);
+        let url = format!("{}/versions/{}.json", self.api_base, name);
         let data = self.cache.get_cached(&url).await?;
         parse_versions_response(&data, name)
     }
@@ -57,7 +73,7 @@ impl RubyGemsRegistry {
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<GemInfo>> {
         let url = format!(
             "{}/search.json?query={}",
-            RUBYGEMS_API_BASE,
+            self.api_base,
             urlencoding::encode(query)
         );
         let data = self.cache.get_cached(&url).await?;
@@ -67,7 +83,7 @@ impl RubyGemsRegistry {
 
     /// Gets detailed gem information.
     pub async fn get_gem_info(&self, name: &str) -> Result<GemInfo> {
-        let url = format!("{}/gems/{}.json", RUBYGEMS_API_BASE, name);
+        let url = format!("{}/gems/{}.json", self.api_base, name);
         let data = self.cache.get_cached(&url).await?;
         parse_gem_info(&data)
     }
@@ -177,6 +193,10 @@ impl deps_core::Version for BundlerVersion {
         self.yanked
     }
 
+    fn published_at(&self) -> Option<&str> {
+        self.created_at.as_deref()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -252,6 +272,14 @@ impl deps_core::Registry for RubyGemsRegistry {
         gem_url(name)
     }
 
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        Some(format!(
+            "{}/versions/{}",
+            gem_url(name),
+            urlencoding::encode(version)
+        ))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,366 @@
+//! Registry settings from Cargo configuration files.
+//!
+//! Cargo reads `.cargo/config.toml` (or the legacy `.cargo/config`) in the
+//! manifest directory and each of its parents, then the one in
+//! `$CARGO_HOME`; nearer files take precedence. Only the keys deciding where
+//! crates are downloaded from are read here: `[registries.<name>].index` and
+//! `[source]` replacement.
+//!
+//! # Examples
+//!
+//! ```
+//! use deps_cargo::config::CargoConfig;
+//!
+//! let config = CargoConfig::parse(r#"
+//! [registries.internal]
+//! index = "sparse+https://cargo.example.com/index/"
+//! "#);
+//!
+//! let index = config.resolve("internal").unwrap();
+//! assert_eq!(index.sparse_url(), Some("https://cargo.example.com/index"));
+//! assert!(config.resolve("crates-io").is_none());
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use toml_span::value::Table;
+
+/// Name of the default registry.
+pub const CRATES_IO: &str = "crates-io";
+
+/// Longest `replace-with` chain followed before giving up.
+const MAX_REPLACEMENTS: usize = 8;
+
+/// Registry and source settings merged from every Cargo configuration file
+/// that applies to a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoConfig {
+    /// `[registries.<name>].index`
+    registries: HashMap<String, String>,
+    /// `[source.<name>]` tables
+    sources: HashMap<String, SourceEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SourceEntry {
+    replace_with: Option<String>,
+    registry: Option<String>,
+}
+
+/// Index a registry's crates are fetched from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryIndex {
+    /// Registry or source name, used for `CARGO_REGISTRIES_<NAME>_TOKEN`
+    pub name: String,
+    /// Index URL as configured (`sparse+https://…` or a git URL), `None`
+    /// when the registry is not configured at all
+    pub url: Option<String>,
+}
+
+impl RegistryIndex {
+    /// Base URL of a sparse index, without the `sparse+` prefix and
+    /// trailing slash. `None` for git indexes and unknown registries.
+    pub fn sparse_url(&self) -> Option<&str> {
+        self.url
+            .as_deref()?
+            .strip_prefix("sparse+")
+            .map(|url| url.trim_end_matches('/'))
+    }
+
+    /// Token from `CARGO_REGISTRIES_<NAME>_TOKEN`, sent as `Authorization`.
+    pub fn token(&self) -> Option<String> {
+        std::env::var(registry_env_var(&self.name, "TOKEN"))
+            .ok()
+            .filter(|token| !token.is_empty())
+    }
+}
+
+impl CargoConfig {
+    /// Loads the configuration applying to a manifest in `manifest_dir`.
+    ///
+    /// Unreadable or invalid files are skipped.
+    pub fn load(manifest_dir: &Path) -> Self {
+        let mut config = Self::default();
+        let home = cargo_home();
+        for dir in manifest_dir.ancestors() {
+            let cargo_dir = dir.join(".cargo");
+            // $CARGO_HOME is read last, whether or not it is an ancestor
+            if home.as_deref() != Some(cargo_dir.as_path()) {
+                config.merge_dir(&cargo_dir);
+            }
+        }
+        if let Some(home) = home {
+            config.merge_dir(&home);
+        }
+        config
+    }
+
+    /// Parses a single configuration file.
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+        if let Ok(doc) = toml_span::parse(content)
+            && let Some(table) = doc.as_table()
+        {
+            config.merge_table(table);
+        }
+        config
+    }
+
+    /// Resolves the index of `registry`, following `[source]` replacement.
+    ///
+    /// Replacements by a source without an index (`directory`,
+    /// `local-registry` or `git`, as `cargo vendor` writes) are ignored, so
+    /// versions are still looked up in the original registry. Returns `None`
+    /// when crates are fetched from crates.io as usual.
+    pub fn resolve(&self, registry: &str) -> Option<RegistryIndex> {
+        let mut name = registry;
+        for _ in 0..MAX_REPLACEMENTS {
+            match self
+                .sources
+                .get(name)
+                .and_then(|s| s.replace_with.as_deref())
+            {
+                Some(next) => name = next,
+                None => break,
+            }
+        }
+
+        match self.sources.get(name) {
+            Some(SourceEntry {
+                registry: Some(url),
+                ..
+            }) => {
+                return Some(RegistryIndex {
+                    name: name.to_string(),
+                    url: Some(url.clone()),
+                });
+            }
+            Some(SourceEntry {
+                replace_with: None,
+                registry: None,
+            }) if !self.registries.contains_key(name) => name = registry,
+            _ => {}
+        }
+        if name == CRATES_IO {
+            return None;
+        }
+        let url = std::env::var(registry_env_var(name, "INDEX"))
+            .ok()
+            .or_else(|| self.registries.get(name).cloned());
+        Some(RegistryIndex {
+            name: name.to_string(),
+            url,
+        })
+    }
+
+    fn merge_dir(&mut self, dir: &Path) {
+        for file in ["config.toml", "config"] {
+            let path = dir.join(file);
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                if deps_core::check_toml_nesting_depth(&content, deps_core::MAX_TOML_NESTING_DEPTH)
+                    .is_err()
+                {
+                    tracing::warn!(path = %path.display(), "skipping Cargo config: nesting depth exceeds maximum");
+                } else {
+                    let nearer = std::mem::take(self);
+                    *self = Self::parse(&content);
+                    self.override_with(nearer);
+                }
+                // Cargo ignores `config` when `config.toml` exists
+                return;
+            }
+        }
+    }
+
+    /// Lets every setting of `nearer` take precedence over `self`.
+    fn override_with(&mut self, nearer: Self) {
+        self.registries.extend(nearer.registries);
+        for (name, entry) in nearer.sources {
+            let merged = self.sources.entry(name).or_default();
+            if entry.replace_with.is_some() {
+                merged.replace_with = entry.replace_with;
+            }
+            if entry.registry.is_some() {
+                merged.registry = entry.registry;
+            }
+        }
+    }
+
+    fn merge_table(&mut self, root: &Table<'_>) {
+        if let Some(registries) = root.get("registries").and_then(|v| v.as_table()) {
+            for (name, value) in registries {
+                if let Some(index) = value
+                    .as_table()
+                    .and_then(|t| t.get("index"))
+                    .and_then(|v| v.as_str())
+                {
+                    self.registries
+                        .insert(name.name.to_string(), index.to_string());
+                }
+            }
+        }
+
+        if let Some(sources) = root.get("source").and_then(|v| v.as_table()) {
+            for (name, value) in sources {
+                let Some(table) = value.as_table() else {
+                    continue;
+                };
+                let string = |key: &str| table.get(key).and_then(|v| v.as_str()).map(String::from);
+                self.sources.insert(
+                    name.name.to_string(),
+                    SourceEntry {
+                        replace_with: string("replace-with"),
+                        registry: string("registry"),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// `$CARGO_HOME`, defaulting to `~/.cargo`.
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::home_dir().map(|home| home.join(".cargo")))
+}
+
+/// `CARGO_REGISTRIES_<NAME>_<KEY>`, with the name upper-cased and dashes
+/// turned into underscores as Cargo does.
+fn registry_env_var(name: &str, key: &str) -> String {
+    format!(
+        "CARGO_REGISTRIES_{}_{key}",
+        name.to_uppercase().replace('-', "_")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_registry() {
+        let config = CargoConfig::parse(
+            r#"
+[registries.my-registry]
+index = "sparse+https://cargo.example.com/index/"
+
+[registries.git-registry]
+index = "https://github.com/acme/index"
+"#,
+        );
+
+        let index = config.resolve("my-registry").unwrap();
+        assert_eq!(index.name, "my-registry");
+        assert_eq!(index.sparse_url(), Some("https://cargo.example.com/index"));
+
+        let git = config.resolve("git-registry").unwrap();
+        assert_eq!(git.sparse_url(), None);
+
+        let unknown = config.resolve("nowhere").unwrap();
+        assert_eq!(unknown.url, None);
+        assert_eq!(config.resolve(CRATES_IO), None);
+    }
+
+    #[test]
+    fn test_resolve_source_replacement() {
+        let config = CargoConfig::parse(
+            r#"
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+registry = "sparse+https://mirror.example.com/"
+"#,
+        );
+
+        let index = config.resolve(CRATES_IO).unwrap();
+        assert_eq!(index.name, "mirror");
+        assert_eq!(index.sparse_url(), Some("https://mirror.example.com"));
+    }
+
+    #[test]
+    fn test_resolve_ignores_vendored_sources() {
+        // As printed by `cargo vendor`
+        let config = CargoConfig::parse(
+            r#"
+[source.crates-io]
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+"#,
+        );
+        assert_eq!(config.resolve(CRATES_IO), None);
+
+        let config = CargoConfig::parse(
+            r#"
+[registries.internal]
+index = "sparse+https://cargo.example.com/index/"
+
+[source.internal]
+replace-with = "git-mirror"
+
+[source.git-mirror]
+git = "https://example.com/mirror"
+"#,
+        );
+        let index = config.resolve("internal").unwrap();
+        assert_eq!(index.name, "internal");
+        assert_eq!(index.sparse_url(), Some("https://cargo.example.com/index"));
+    }
+
+    #[test]
+    fn test_resolve_replacement_loop() {
+        let config = CargoConfig::parse(
+            r#"
+[source.a]
+replace-with = "b"
+
+[source.b]
+replace-with = "a"
+"#,
+        );
+        assert_eq!(config.resolve("a").unwrap().url, None);
+    }
+
+    #[test]
+    fn test_registry_env_var() {
+        assert_eq!(
+            registry_env_var("my-registry", "TOKEN"),
+            "CARGO_REGISTRIES_MY_REGISTRY_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_load_prefers_nearer_files() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        let member = project.join("member");
+        std::fs::create_dir_all(root.path().join(".cargo")).unwrap();
+        std::fs::create_dir_all(project.join(".cargo")).unwrap();
+        std::fs::create_dir_all(&member).unwrap();
+
+        std::fs::write(
+            root.path().join(".cargo/config.toml"),
+            "[registries.shared]\nindex = \"sparse+https://outer.example.com/\"\n\n[registries.outer]\nindex = \"sparse+https://outer-only.example.com/\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            project.join(".cargo/config"),
+            "[registries.shared]\nindex = \"sparse+https://inner.example.com/\"\n",
+        )
+        .unwrap();
+
+        let config = CargoConfig::load(&member);
+        assert_eq!(
+            config.resolve("shared").unwrap().sparse_url(),
+            Some("https://inner.example.com")
+        );
+        assert_eq!(
+            config.resolve("outer").unwrap().sparse_url(),
+            Some("https://outer-only.example.com")
+        );
+    }
+}
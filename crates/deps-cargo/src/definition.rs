@@ -0,0 +1,96 @@
+//! Go-to-definition for workspace-inherited dependencies.
+//!
+//! A member dependency declared as `serde = { workspace = true }` takes its
+//! version from `[workspace.dependencies]` in the workspace root manifest.
+//! This module locates that declaration.
+
+use std::path::Path;
+use tower_lsp_server::ls_types::{Location, Uri};
+
+use crate::parser::parse_cargo_toml;
+use crate::types::DependencySection;
+
+/// Finds the `[workspace.dependencies]` entry for `name` in the workspace root.
+///
+/// Returns the location of the entry's version requirement, or of its key when
+/// the entry has no version (e.g. a git or path dependency). Returns `None` if
+/// the root manifest cannot be read or does not declare `name`.
+pub async fn find_workspace_dependency(workspace_root: &Path, name: &str) -> Option<Location> {
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let content = tokio::fs::read_to_string(&manifest_path).await.ok()?;
+    let uri = Uri::from_file_path(&manifest_path)?;
+    let parsed = parse_cargo_toml(&content, &uri).ok()?;
+
+    let dep = parsed.dependencies.iter().find(|d| {
+        d.name == name && matches!(d.section, DependencySection::WorkspaceDependencies)
+    })?;
+
+    Some(Location::new(
+        uri,
+        dep.version_range.unwrap_or(dep.name_range),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    #[tokio::test]
+    async fn test_find_workspace_dependency_version_range() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\nserde = \"1.0\"\ntokio = { version = \"1.40\", features = [\"full\"] }\n",
+        )
+        .unwrap();
+
+        let location = find_workspace_dependency(temp_dir.path(), "tokio")
+            .await
+            .unwrap();
+        assert!(location.uri.as_str().ends_with("Cargo.toml"));
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(5, 21), Position::new(5, 25))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_workspace_dependency_without_version() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\n\n[workspace.dependencies]\nlocal = { path = \"crates/local\" }\n",
+        )
+        .unwrap();
+
+        let location = find_workspace_dependency(temp_dir.path(), "local")
+            .await
+            .unwrap();
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(3, 0), Position::new(3, 5))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_workspace_dependency_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\n\n[dependencies]\nserde = \"1.0\"\n",
+        )
+        .unwrap();
+
+        assert!(
+            find_workspace_dependency(temp_dir.path(), "serde")
+                .await
+                .is_none()
+        );
+        assert!(
+            find_workspace_dependency(&temp_dir.path().join("missing"), "serde")
+                .await
+                .is_none()
+        );
+    }
+}
@@ -3,17 +3,34 @@
 //! This module implements the `Ecosystem` trait for Cargo/Rust projects,
 //! providing LSP functionality for `Cargo.toml` files.
 
+use futures::stream::{self, StreamExt};
 use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CompletionItem, Diagnostic, DiagnosticSeverity, Hover,
+    HoverContents, InlayHint, InlayHintTooltip, Location, NumberOrString, Position, TextEdit, Uri,
+    WorkspaceEdit,
+};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, Version,
+    Ecosystem, EcosystemConfig, LoadingState, ParseResult as ParseResultTrait, Registry, Result,
+    Version, VersionData,
+    add_dependency::{self, DependencyKind, NewDependency},
     lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
+    workspace::WorkspaceCache,
 };
 
+use crate::features;
 use crate::formatter::CargoFormatter;
+use crate::msrv::{self, MsrvSuggestion, RustVersion};
+use crate::parser::ParseResult;
 use crate::registry::CratesIoRegistry;
+use crate::table_completion;
+use crate::types::{CargoVersion, DependencySection, DependencySource, ParsedDependency};
+use crate::workspace::{self, Workspace};
 
 /// Cargo ecosystem implementation.
 ///
@@ -27,6 +44,7 @@ use crate::registry::CratesIoRegistry;
 pub struct CargoEcosystem {
     registry: Arc<CratesIoRegistry>,
     formatter: CargoFormatter,
+    workspaces: Arc<WorkspaceCache>,
 }
 
 impl CargoEcosystem {
@@ -35,6 +53,39 @@ impl CargoEcosystem {
         Self {
             registry: Arc::new(CratesIoRegistry::new(cache)),
             formatter: CargoFormatter,
+            workspaces: Arc::default(),
+        }
+    }
+
+    /// Keeps loaded workspaces in `workspaces`, so the server can drop them
+    /// when a manifest changes on disk.
+    #[must_use]
+    pub fn with_workspace_cache(self, workspaces: Arc<WorkspaceCache>) -> Self {
+        Self { workspaces, ..self }
+    }
+
+    /// The workspace rooted at `root`, read from disk on first use.
+    async fn workspace(&self, root: &Path) -> Option<Arc<Workspace>> {
+        if let Some(workspace) = self.workspaces.get(root) {
+            return Some(workspace);
+        }
+        let workspace = Arc::new(Workspace::load(root).await?);
+        self.workspaces.insert(root, Arc::clone(&workspace));
+        Some(workspace)
+    }
+
+    /// This ecosystem with the registry routes of `parse_result`'s manifest.
+    fn routed(&self, parse_result: &dyn ParseResultTrait) -> Self {
+        let registry = match parse_result.as_any().downcast_ref::<ParseResult>() {
+            Some(result) if !result.indexes.is_empty() => {
+                Arc::new(self.registry.with_routes(result.indexes.clone()))
+            }
+            _ => Arc::clone(&self.registry),
+        };
+        Self {
+            registry,
+            formatter: CargoFormatter,
+            workspaces: Arc::clone(&self.workspaces),
         }
     }
 
@@ -84,6 +135,265 @@ impl CargoEcosystem {
             .map(|feature| build_feature_completion(&feature, package_name, None))
             .collect()
     }
+
+    /// Fetches the version whose features `dep` is checked against: the
+    /// newest one matching its requirement.
+    async fn matched_version(&self, dep: &ParsedDependency) -> Option<CargoVersion> {
+        let req = dep.version_req.as_deref()?;
+        match self
+            .registry
+            .get_latest_matching(dep.package_name(), req)
+            .await
+        {
+            Ok(version) => version,
+            Err(e) => {
+                tracing::debug!("Cannot check features of '{}': {}", dep.package_name(), e);
+                None
+            }
+        }
+    }
+
+    /// Checks the features `dep` enables against the release its
+    /// requirement resolves to.
+    async fn dependency_feature_diagnostics(&self, dep: &ParsedDependency) -> Vec<Diagnostic> {
+        self.matched_version(dep)
+            .await
+            .map_or_else(Vec::new, |version| {
+                features::feature_diagnostics(dep, &version.num, &version.features)
+            })
+    }
+
+    /// Suggests the newest release of `dep` building with the manifest's
+    /// `rust-version` when `latest` requires a newer Rust.
+    ///
+    /// Only uses versions fetched earlier, never the network.
+    fn msrv_suggestion(
+        &self,
+        result: &ParseResult,
+        dep: &ParsedDependency,
+        latest: &str,
+    ) -> Option<MsrvSuggestion> {
+        let msrv = RustVersion::parse(result.rust_version.as_deref()?)?;
+        if !is_registry_dependency(dep) {
+            return None;
+        }
+        let versions = self.registry.fetched_versions(dep.package_name())?;
+        msrv::suggest(&versions, latest, msrv)
+    }
+
+    /// Update to the newest release building with `rust-version`, offered
+    /// on a dependency whose latest release requires a newer Rust.
+    fn msrv_code_action(
+        &self,
+        result: &ParseResult,
+        position: Position,
+        uri: &Uri,
+    ) -> Option<CodeAction> {
+        let dep = result.dependencies.iter().find(|dep| {
+            self.formatter
+                .is_position_on_dependency(*dep as &dyn deps_core::Dependency, position)
+        })?;
+        let version_range = dep.version_range?;
+        let latest = self
+            .registry
+            .fetched_versions(dep.package_name())?
+            .iter()
+            .find(|v| !v.yanked && !v.num.contains('-'))?
+            .num
+            .clone();
+        let compatible = self.msrv_suggestion(result, dep, &latest)?.compatible?;
+        if dep.version_req.as_deref() == Some(compatible.as_str()) {
+            return None;
+        }
+
+        let edit = TextEdit {
+            range: version_range,
+            new_text: self.formatter.format_version_for_text_edit(&compatible),
+        };
+        Some(CodeAction {
+            title: format!(
+                "Update to {compatible} (latest compatible with Rust {})",
+                result.rust_version.as_deref()?
+            ),
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                ..Default::default()
+            }),
+            is_preferred: Some(true),
+            ..Default::default()
+        })
+    }
+
+    /// Moves the pinned dependency at `position` into the root's
+    /// `[workspace.dependencies]` (unless already declared there) and
+    /// rewrites every member pinning a semver-compatible requirement to
+    /// `{ workspace = true }`. Members pinning another release line are left
+    /// alone and named in the title.
+    ///
+    /// Other manifests, the root included, are edited as saved on disk.
+    async fn hoist_code_action(
+        &self,
+        result: &ParseResult,
+        position: Position,
+        uri: &Uri,
+    ) -> Option<CodeAction> {
+        use deps_core::lsp_helpers::position_in_range;
+
+        let dep = result.dependencies.iter().find(|dep| {
+            workspace::is_pinned(dep)
+                && (position_in_range(position, dep.name_range)
+                    || dep
+                        .version_range
+                        .is_some_and(|range| position_in_range(position, range)))
+        })?;
+        let workspace = self.workspace(result.workspace_root.as_deref()?).await?;
+
+        let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+        let (requirement, default_features) = match workspace.workspace_dependency(&dep.name) {
+            Some(declared) => (declared.version_req.as_deref()?, declared.default_features),
+            None => {
+                let root = &workspace.root;
+                let header = section_label(&DependencySection::WorkspaceDependencies);
+                let entry = workspace::workspace_entry(dep);
+                let sections = self.sortable_sections(&root.result, &root.content);
+                let edits = add_dependency::insert_into_section(
+                    &root.content,
+                    &sections,
+                    &header,
+                    &dep.name,
+                    &entry,
+                )
+                .unwrap_or_else(|| {
+                    vec![add_dependency::insert_toml_table_entry(
+                        &root.content,
+                        &header,
+                        &entry,
+                    )]
+                });
+                changes.entry(root.uri.clone()).or_default().extend(edits);
+                (dep.version_req.as_deref()?, dep.default_features)
+            }
+        };
+        if !workspace::is_compatible_requirement(requirement, dep.version_req.as_deref()?) {
+            return None;
+        }
+
+        // The current document as edited, the others from disk
+        let current = (!workspace.manifests().any(|manifest| manifest.uri == *uri))
+            .then(|| (uri, "this manifest".to_string(), result));
+        let manifests = workspace
+            .manifests()
+            .map(|manifest| {
+                let parsed = if manifest.uri == *uri {
+                    result
+                } else {
+                    &manifest.result
+                };
+                (&manifest.uri, workspace.display_dir(manifest), parsed)
+            })
+            .chain(current);
+        let mut skipped = Vec::new();
+        for (manifest_uri, dir, parsed) in manifests {
+            for other in parsed
+                .dependencies
+                .iter()
+                .filter(|other| other.name == dep.name && workspace::is_pinned(other))
+            {
+                let pinned = other.version_req.as_deref().unwrap_or_default();
+                if !workspace::is_compatible_requirement(requirement, pinned) {
+                    skipped.push(format!("{dir} ({pinned})"));
+                    continue;
+                }
+                let edits = workspace::inherit_edit(other, default_features);
+                changes
+                    .entry(manifest_uri.clone())
+                    .or_default()
+                    .extend(edits);
+            }
+        }
+        changes.retain(|_, edits| !edits.is_empty());
+
+        let mut title = format!("Move `{}` to [workspace.dependencies]", dep.name);
+        if !skipped.is_empty() {
+            title.push_str(&format!(", keeping {}", skipped.join(", ")));
+        }
+        Some(CodeAction {
+            title,
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Warns about locked versions requiring a newer Rust than the
+    /// manifest's `rust-version`.
+    fn locked_rust_version_diagnostics(
+        &self,
+        result: &ParseResult,
+        versions: VersionData<'_>,
+    ) -> Vec<Diagnostic> {
+        let Some(declared) = result.rust_version.as_deref() else {
+            return vec![];
+        };
+        let Some(msrv) = RustVersion::parse(declared) else {
+            return vec![];
+        };
+
+        result
+            .dependencies
+            .iter()
+            .filter(|dep| is_registry_dependency(dep))
+            .filter_map(|dep| {
+                let locked = versions.resolved.get(dep.package_name())?;
+                let fetched = self.registry.fetched_versions(dep.package_name())?;
+                let required = msrv::required_rust(&fetched, locked, msrv)?;
+                Some(Diagnostic {
+                    range: dep.version_range.unwrap_or(dep.name_range),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String(msrv::RUST_VERSION_CODE.into())),
+                    message: format!(
+                        "Locked version {locked} requires Rust {required}, but rust-version is {declared}"
+                    ),
+                    source: Some("deps-lsp".into()),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Removes the unused `[workspace.dependencies]` entry whose name is at
+/// `position`, when the document is the workspace root.
+fn remove_unused_code_action(
+    workspace: &Workspace,
+    result: &ParseResult,
+    position: Position,
+    uri: &Uri,
+) -> Option<CodeAction> {
+    use deps_core::lsp_helpers::position_in_range;
+
+    let dep = result.dependencies.iter().find(|dep| {
+        matches!(dep.section, DependencySection::WorkspaceDependencies)
+            && position_in_range(position, dep.name_range)
+    })?;
+    workspace::unused_dependencies(workspace, result)
+        .into_iter()
+        .find(|unused| unused.name == dep.name)
+        .and_then(|unused| workspace::remove_unused_action(unused, uri))
+}
+
+/// Whether `dep` is fetched from a registry, as opposed to a path, git or
+/// override source.
+fn is_registry_dependency(dep: &ParsedDependency) -> bool {
+    matches!(
+        dep.source,
+        DependencySource::Registry | DependencySource::CustomRegistry { .. }
+    ) && dep.overridden_by.is_none()
+        && !dep.section.is_override()
 }
 
 impl deps_core::ecosystem::private::Sealed for CargoEcosystem {}
@@ -120,6 +430,18 @@ impl Ecosystem for CargoEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: CargoFormatter,
+            workspaces: Arc::clone(&self.workspaces),
+        }))
+    }
+
+    fn registry_for(&self, parse_result: &dyn ParseResultTrait) -> Arc<dyn Registry> {
+        self.routed(parse_result).registry as Arc<dyn Registry>
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::CargoLockParser))
     }
@@ -128,6 +450,224 @@ impl Ecosystem for CargoEcosystem {
         &self.formatter
     }
 
+    fn generate_inlay_hints<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        versions: VersionData<'a>,
+        loading_state: LoadingState,
+        config: &'a EcosystemConfig,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Vec<InlayHint>> {
+        Box::pin(async move {
+            let mut hints = deps_core::lsp_helpers::generate_inlay_hints(
+                parse_result,
+                versions,
+                loading_state,
+                config,
+                self.formatter(),
+            );
+            let Some(result) = parse_result.as_any().downcast_ref::<ParseResult>() else {
+                return hints;
+            };
+
+            for dep in &result.dependencies {
+                let (Some(range), Some(latest)) =
+                    (dep.version_range, versions.cached.get(dep.package_name()))
+                else {
+                    continue;
+                };
+                let requirement = dep.version_req.as_deref().unwrap_or("");
+                if self
+                    .formatter
+                    .is_requirement_up_to_date(requirement, latest)
+                {
+                    continue;
+                }
+                let Some(suggestion) = self.msrv_suggestion(result, dep, latest) else {
+                    continue;
+                };
+                // The label keeps the user's hint template; the suggestion
+                // goes below whatever tooltip the hint already has
+                if let Some(hint) = hints.iter_mut().find(|hint| hint.position == range.end) {
+                    let note = format!(
+                        "{} (rust-version is {})",
+                        suggestion.message(),
+                        result.rust_version.as_deref().unwrap_or_default()
+                    );
+                    let tooltip = match hint.tooltip.take() {
+                        Some(InlayHintTooltip::String(existing)) => format!("{existing}\n{note}"),
+                        _ => note,
+                    };
+                    hint.tooltip = Some(InlayHintTooltip::String(tooltip));
+                }
+            }
+            hints
+        })
+    }
+
+    fn generate_hover<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        versions: VersionData<'a>,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Hover>> {
+        Box::pin(async move {
+            let registry = self.routed(parse_result).registry;
+            let mut hover = deps_core::lsp_helpers::generate_hover(
+                parse_result,
+                position,
+                versions,
+                registry.as_ref(),
+                self.formatter(),
+            )
+            .await?;
+
+            let Some(result) = parse_result.as_any().downcast_ref::<ParseResult>() else {
+                return Some(hover);
+            };
+            // The hover covers the name of the dependency it describes
+            let Some(dep) = hover.range.and_then(|range| {
+                result
+                    .dependencies
+                    .iter()
+                    .find(|dep| dep.name_range == range)
+            }) else {
+                return Some(hover);
+            };
+
+            let mut lines = Vec::new();
+            if let Some(suggestion) = versions
+                .cached
+                .get(dep.package_name())
+                .and_then(|latest| self.msrv_suggestion(result, dep, latest))
+            {
+                lines.push(format!("**Rust version**: {}", suggestion.message()));
+            }
+            if matches!(dep.section, DependencySection::WorkspaceDependencies)
+                && let Some(root) = result.workspace_root.as_deref()
+                && let Some(workspace) = self.workspace(root).await
+                && workspace.root.uri == result.uri
+            {
+                let consumers = workspace.consumers(result, &dep.name);
+                lines.push(if consumers.is_empty() {
+                    "**Used by**: no workspace member".to_string()
+                } else {
+                    format!("**Used by**: {}", consumers.join(", "))
+                });
+            }
+            if let HoverContents::Markup(markup) = &mut hover.contents {
+                for line in lines {
+                    let line = format!("{line}\n\n");
+                    // Above the closing `---` separator
+                    match markup.value.rfind("\n---\n") {
+                        Some(at) => markup.value.insert_str(at + 1, &line),
+                        None => markup.value.push_str(&format!("\n\n{line}")),
+                    }
+                }
+            }
+            Some(hover)
+        })
+    }
+
+    fn generate_code_actions<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        uri: &'a Uri,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Vec<CodeAction>> {
+        Box::pin(async move {
+            let routed = self.routed(parse_result);
+            let mut actions = deps_core::lsp_helpers::generate_code_actions(
+                parse_result,
+                position,
+                uri,
+                routed.registry.as_ref(),
+                self.formatter(),
+            )
+            .await;
+
+            let result = parse_result.as_any().downcast_ref::<ParseResult>();
+            if let Some(result) = result
+                && let Some(action) = self.msrv_code_action(result, position, uri)
+            {
+                // The latest release does not build with `rust-version`
+                for update in &mut actions {
+                    update.is_preferred = Some(false);
+                }
+                actions.push(action);
+            }
+            if let Some(result) = result {
+                actions.extend(self.hoist_code_action(result, position, uri).await);
+                if let Some(root) = result.workspace_root.as_deref()
+                    && let Some(workspace) = self.workspace(root).await
+                {
+                    actions.extend(remove_unused_code_action(&workspace, result, position, uri));
+                }
+            }
+
+            let feature_at = result.and_then(|result| {
+                result.dependencies.iter().find_map(|dep| {
+                    let index = dep.feature_ranges.iter().position(|range| {
+                        deps_core::lsp_helpers::position_in_range(position, *range)
+                    })?;
+                    Some((dep, index))
+                })
+            });
+            if let Some((dep, index)) = feature_at.filter(|(dep, _)| features::is_checkable(dep))
+                && let Some(version) = routed.matched_version(dep).await
+            {
+                actions.extend(features::feature_quick_fix(
+                    dep,
+                    index,
+                    &version.features,
+                    uri,
+                ));
+            }
+            actions
+        })
+    }
+
+    fn generate_diagnostics<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        versions: VersionData<'a>,
+        _uri: &'a Uri,
+        max_concurrent: usize,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Vec<Diagnostic>> {
+        Box::pin(async move {
+            let mut diagnostics = deps_core::lsp_helpers::generate_diagnostics_from_cache(
+                parse_result,
+                versions,
+                self.formatter(),
+            );
+
+            let Some(result) = parse_result.as_any().downcast_ref::<ParseResult>() else {
+                return diagnostics;
+            };
+            let routed = self.routed(parse_result);
+            diagnostics.extend(self.locked_rust_version_diagnostics(result, versions));
+            diagnostics.extend(features::manifest_feature_diagnostics(result));
+            if let Some(root) = result.workspace_root.as_deref()
+                && let Some(workspace) = self.workspace(root).await
+            {
+                diagnostics.extend(workspace::consistency_diagnostics(&workspace, result));
+                diagnostics.extend(workspace::unused_diagnostics(&workspace, result));
+            }
+            let checks: Vec<_> = result
+                .dependencies
+                .iter()
+                .filter(|dep| features::is_checkable(dep))
+                .map(|dep| routed.dependency_feature_diagnostics(dep))
+                .collect();
+            let feature_diagnostics: Vec<Vec<Diagnostic>> = stream::iter(checks)
+                // Ordered, so diagnostics keep the manifest's order
+                .buffered(max_concurrent.max(1))
+                .collect()
+                .await;
+            diagnostics.extend(feature_diagnostics.into_iter().flatten());
+            diagnostics
+        })
+    }
+
     fn generate_completions<'a>(
         &'a self,
         parse_result: &'a dyn ParseResultTrait,
@@ -138,6 +678,7 @@ impl Ecosystem for CargoEcosystem {
             use deps_core::completion::{CompletionContext, detect_completion_context};
 
             let context = detect_completion_context(parse_result, position, content);
+            let routed = self.routed(parse_result);
 
             match context {
                 CompletionContext::PackageName { prefix } => {
@@ -146,13 +687,145 @@ impl Ecosystem for CargoEcosystem {
                 CompletionContext::Version {
                     package_name,
                     prefix,
-                } => self.complete_versions(&package_name, &prefix).await,
+                } => routed.complete_versions(&package_name, &prefix).await,
                 CompletionContext::Feature {
                     package_name,
                     prefix,
-                } => self.complete_features(&package_name, &prefix).await,
-                CompletionContext::None => vec![],
+                } => routed.complete_features(&package_name, &prefix).await,
+                CompletionContext::None => {
+                    let manifest_path = parse_result.uri().to_file_path();
+                    let manifest_dir = manifest_path.as_deref().and_then(std::path::Path::parent);
+                    table_completion::table_completions(content, position, manifest_dir)
+                }
+            }
+        })
+    }
+
+    fn generate_unparsed_completions(
+        &self,
+        content: &str,
+        position: Position,
+        uri: &Uri,
+    ) -> Vec<CompletionItem> {
+        let manifest_path = uri.to_file_path();
+        let manifest_dir = manifest_path.as_deref().and_then(std::path::Path::parent);
+        table_completion::table_completions(content, position, manifest_dir)
+    }
+
+    fn generate_definition<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        _content: &'a str,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            if let Some(result) = parse_result.as_any().downcast_ref::<ParseResult>()
+                && let Some(range) = features::feature_definition(result, position)
+            {
+                return Some(Location {
+                    uri: result.uri.clone(),
+                    range,
+                });
+            }
+
+            let dep = deps_core::lsp_helpers::find_dependency_at(parse_result, position)?;
+            if !matches!(dep.source(), deps_core::DependencySource::Workspace) {
+                return None;
+            }
+            // Inherited entries are keyed by their (possibly renamed) key
+            let key = dep
+                .as_any()
+                .downcast_ref::<ParsedDependency>()
+                .map_or(dep.name(), |dep| dep.name.as_str());
+            let workspace_root = parse_result.workspace_root()?;
+            crate::definition::find_workspace_dependency(workspace_root, key).await
+        })
+    }
+
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::ParseResult>()
+        else {
+            return vec![];
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        let entries = result.dependencies.iter().filter_map(|dep| {
+            let line = dep.name_range.start.line;
+            // `[dependencies.serde]` tables are not entries of the parent table
+            if lines.get(line as usize)?.trim_start().starts_with('[') {
+                return None;
+            }
+            // Dotted keys may spread one dependency over several lines
+            let dotted = format!("{}.", dep.name);
+            let mut entry = SortableEntry::new(dep.name.clone(), line);
+            while lines
+                .get(entry.last_line as usize + 1)
+                .is_some_and(|next| next.trim_start().starts_with(&dotted))
+            {
+                entry.last_line += 1;
             }
+            Some((section_label(&dep.section), entry))
+        });
+        SortableSection::group(SectionStyle::TOML_TABLE, entries)
+    }
+
+    fn has_dependency(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        content: &str,
+        name: &str,
+        kind: &DependencyKind,
+    ) -> bool {
+        let Some(result) = parse_result.as_any().downcast_ref::<ParseResult>() else {
+            return false;
+        };
+        let Some(section) = target_section(content, kind) else {
+            return false;
+        };
+        result
+            .dependencies
+            .iter()
+            .any(|dep| dep.name == name && dep.section == section)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let section = target_section(content, &dependency.kind)?;
+            let header = section_label(&section);
+            let entry = if dependency.kind == DependencyKind::Optional {
+                format!(
+                    "{} = {{ version = \"{}\", optional = true }}",
+                    dependency.name, dependency.version
+                )
+            } else {
+                format!("{} = \"{}\"", dependency.name, dependency.version)
+            };
+
+            let sections = self.sortable_sections(parse_result, content);
+            let edits = add_dependency::insert_into_section(
+                content,
+                &sections,
+                &header,
+                &dependency.name,
+                &entry,
+            )
+            .unwrap_or_else(|| {
+                vec![add_dependency::insert_toml_table_entry(
+                    content, &header, &entry,
+                )]
+            });
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
         })
     }
 
@@ -161,10 +834,53 @@ impl Ecosystem for CargoEcosystem {
     }
 }
 
+/// Section a new dependency of `kind` goes into.
+fn target_section(content: &str, kind: &DependencyKind) -> Option<DependencySection> {
+    match kind {
+        // A virtual manifest only has workspace dependencies
+        DependencyKind::Normal
+            if add_dependency::find_line(content, |l| l == "[package]").is_none()
+                && add_dependency::find_line(content, |l| l == "[workspace]").is_some() =>
+        {
+            Some(DependencySection::WorkspaceDependencies)
+        }
+        DependencyKind::Normal | DependencyKind::Optional => Some(DependencySection::Dependencies),
+        DependencyKind::Dev | DependencyKind::Test => Some(DependencySection::DevDependencies),
+        DependencyKind::Build => Some(DependencySection::BuildDependencies),
+        DependencyKind::Peer | DependencyKind::Other(_) => None,
+    }
+}
+
+fn section_label(section: &DependencySection) -> String {
+    match section {
+        DependencySection::Dependencies => "[dependencies]".to_string(),
+        DependencySection::DevDependencies => "[dev-dependencies]".to_string(),
+        DependencySection::BuildDependencies => "[build-dependencies]".to_string(),
+        DependencySection::WorkspaceDependencies => "[workspace.dependencies]".to_string(),
+        DependencySection::Target { target, kind } => {
+            format!("[target.{}.{}]", table_key(target), kind.table_name())
+        }
+        DependencySection::Patch { registry } => format!("[patch.{}]", table_key(registry)),
+        DependencySection::Replace => "[replace]".to_string(),
+    }
+}
+
+/// Quotes `key` unless it is a bare TOML key.
+fn table_key(key: &str) -> String {
+    let bare = key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        format!("'{key}'")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DependencySection, DependencySource, ParsedDependency};
+    use crate::types::{DependencySource, ParsedDependency};
     use deps_core::{EcosystemConfig, VersionData};
     use std::collections::HashMap;
     use tower_lsp_server::ls_types::{InlayHintLabel, Position, Range};
@@ -191,8 +907,15 @@ mod tests {
             }),
             features: vec![],
             features_range: None,
+            feature_ranges: Vec::new(),
+            default_features: true,
+            optional: false,
+            value_range: None,
             source: DependencySource::Registry,
             section: DependencySection::Dependencies,
+            registry: None,
+            package: None,
+            overridden_by: None,
         }
     }
 
@@ -270,6 +993,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Lock file has the latest version
@@ -307,6 +1031,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Lock file has the latest version
@@ -332,7 +1057,7 @@ mod tests {
         let ecosystem = CargoEcosystem::new(cache);
 
         let parse_result = MockParseResult {
-            dependencies: vec![mock_dependency("serde", Some("1.0.100"), 5, 5)],
+            dependencies: vec![mock_dependency("serde", Some("0.9"), 5, 5)],
         };
 
         let mut cached_versions = HashMap::new();
@@ -344,6 +1069,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         let resolved_versions = HashMap::new();
@@ -379,6 +1105,7 @@ mod tests {
             show_up_to_date_hints: false,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Lock file has the latest version - but show_up_to_date_hints is false
@@ -415,6 +1142,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         let resolved_versions = HashMap::new();
@@ -449,6 +1177,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Should not panic, should return update hint
@@ -473,6 +1202,44 @@ mod tests {
         assert!(any.is::<CargoEcosystem>());
     }
 
+    #[tokio::test]
+    async fn test_generate_definition_workspace_dependency() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n\n[workspace.dependencies]\nserde = \"1.0\"\n",
+        )
+        .unwrap();
+        let member_dir = temp_dir.path().join("member");
+        std::fs::create_dir(&member_dir).unwrap();
+        let member_toml = "[package]\nname = \"member\"\n\n[dependencies]\nserde = { workspace = true }\nanyhow = \"1.0\"\n";
+        let member_path = member_dir.join("Cargo.toml");
+        std::fs::write(&member_path, member_toml).unwrap();
+
+        let uri = Uri::from_file_path(&member_path).unwrap();
+        let parse_result = crate::parser::parse_cargo_toml(member_toml, &uri).unwrap();
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+
+        let location = ecosystem
+            .generate_definition(&parse_result, Position::new(4, 2), member_toml)
+            .await
+            .unwrap();
+        assert_eq!(
+            location.uri,
+            Uri::from_file_path(temp_dir.path().join("Cargo.toml")).unwrap()
+        );
+        assert_eq!(location.range.start.line, 4);
+
+        // Registry dependency does not inherit anything
+        assert!(
+            ecosystem
+                .generate_definition(&parse_result, Position::new(5, 2), member_toml)
+                .await
+                .is_none()
+        );
+    }
+
     #[tokio::test]
     async fn test_complete_package_names_minimum_prefix() {
         let cache = Arc::new(deps_core::HttpCache::new());
@@ -648,6 +1415,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         let hints = tokio_test::block_on(ecosystem.generate_inlay_hints(
@@ -671,4 +1439,446 @@ mod tests {
             panic!("Expected tooltip for loading state");
         }
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = r#"[dependencies]
+tokio = { version = "1", features = [
+    "full",
+] }
+serde.version = "1"
+serde.features = ["derive"]
+anyhow = "1"
+
+[dev-dependencies]
+insta = "1"
+
+[dependencies.clap]
+version = "4"
+"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(parse_result.as_ref(), content);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "[dependencies]");
+        assert_eq!(sections[0].entries.len(), 3);
+
+        let runs = deps_core::sort::sort_section(content, &sections[0]);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "anyhow = \"1\"\nserde.version = \"1\"\nserde.features = [\"derive\"]\ntokio = { version = \"1\", features = [\n    \"full\",\n] }"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_patched_dependency_hints_and_diagnostics() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = r#"[dependencies]
+serde = "1.0"
+anyhow = "1.0"
+
+[patch.crates-io]
+serde = { git = "https://github.com/serde-rs/serde" }
+"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let mut cached_versions = HashMap::new();
+        cached_versions.insert("serde".to_string(), "2.0.0".to_string());
+        cached_versions.insert("anyhow".to_string(), "2.0.0".to_string());
+        let resolved_versions = HashMap::new();
+        let versions = VersionData::new(&cached_versions, &resolved_versions);
+
+        let hints = ecosystem
+            .generate_inlay_hints(
+                parse_result.as_ref(),
+                versions,
+                deps_core::LoadingState::Loaded,
+                &EcosystemConfig::default(),
+            )
+            .await;
+        let labels: Vec<_> = hints
+            .iter()
+            .map(|h| match &h.label {
+                InlayHintLabel::String(s) => (h.position.line, s.as_str()),
+                InlayHintLabel::LabelParts(_) => panic!("Expected String label"),
+            })
+            .collect();
+        assert!(labels.contains(&(1, "patched → git https://github.com/serde-rs/serde")));
+        assert!(
+            labels
+                .iter()
+                .any(|(line, s)| *line == 2 && s.contains("2.0.0"))
+        );
+
+        let diagnostics = ecosystem
+            .generate_diagnostics(parse_result.as_ref(), versions, &uri, 20)
+            .await;
+        let outdated: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.message.starts_with("Newer version"))
+            .map(|d| d.range.start.line)
+            .collect();
+        assert_eq!(outdated, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_rust_version_aware_suggestions() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = "[package]\nname = \"app\"\nrust-version = \"1.70\"\n\n[dependencies]\ntokio = \"=1.30.0\"\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let release = |num: &str, rust_version: &str| CargoVersion {
+            num: num.into(),
+            yanked: false,
+            features: HashMap::new(),
+            rust_version: Some(rust_version.into()),
+            checksum: None,
+        };
+        ecosystem.registry.remember(
+            "tokio",
+            &[
+                release("1.40.0", "1.80"),
+                release("1.38.1", "1.70"),
+                release("1.30.0", "1.63"),
+            ],
+        );
+
+        let cached_versions = HashMap::from([("tokio".to_string(), "1.40.0".to_string())]);
+        let resolved_versions = HashMap::from([("tokio".to_string(), "1.40.0".to_string())]);
+        let versions = VersionData::new(&cached_versions, &resolved_versions);
+
+        // Locked at the pinned version for the hint
+        let pinned = HashMap::from([("tokio".to_string(), "1.30.0".to_string())]);
+        let hints = ecosystem
+            .generate_inlay_hints(
+                parse_result.as_ref(),
+                VersionData::new(&cached_versions, &pinned),
+                deps_core::LoadingState::Loaded,
+                &EcosystemConfig::default(),
+            )
+            .await;
+        assert_eq!(hints.len(), 1);
+        let InlayHintLabel::String(label) = &hints[0].label else {
+            panic!("Expected String label");
+        };
+        assert_eq!(label, "❌ 1.40.0");
+        let Some(InlayHintTooltip::String(tooltip)) = &hints[0].tooltip else {
+            panic!("Expected String tooltip");
+        };
+        assert_eq!(
+            tooltip,
+            "latest 1.40.0 requires Rust 1.80, latest compatible: 1.38.1 (rust-version is 1.70)"
+        );
+
+        let result = parse_result.as_any().downcast_ref::<ParseResult>().unwrap();
+        let action = ecosystem
+            .msrv_code_action(result, Position::new(5, 10), &uri)
+            .unwrap();
+        assert_eq!(
+            action.title,
+            "Update to 1.38.1 (latest compatible with Rust 1.70)"
+        );
+        let edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "1.38.1");
+
+        let diagnostics = ecosystem.locked_rust_version_diagnostics(result, versions);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Locked version 1.40.0 requires Rust 1.80, but rust-version is 1.70"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hoist_to_workspace_dependencies() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a\", \"b\", \"c\"]\n\n[workspace.dependencies]\nanyhow = \"1\"\n",
+        )
+        .unwrap();
+        for (member, entry) in [
+            (
+                "a",
+                "serde = { version = \"1.0.150\", features = [\"derive\"] }",
+            ),
+            ("b", "serde = \"1.0.190\""),
+            ("c", "serde = \"0.9\""),
+        ] {
+            std::fs::create_dir_all(root.path().join(member)).unwrap();
+            std::fs::write(
+                root.path().join(member).join("Cargo.toml"),
+                format!("[package]\nname = \"{member}\"\n\n[dependencies]\n{entry}\n"),
+            )
+            .unwrap();
+        }
+
+        let ecosystem = CargoEcosystem::new(Arc::new(deps_core::HttpCache::new()));
+        let path = root.path().join("a/Cargo.toml");
+        let uri = Uri::from_file_path(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+        let result = parse_result.as_any().downcast_ref::<ParseResult>().unwrap();
+
+        let action = ecosystem
+            .hoist_code_action(result, Position::new(4, 2), &uri)
+            .await
+            .unwrap();
+        assert_eq!(
+            action.title,
+            "Move `serde` to [workspace.dependencies], keeping c (0.9)"
+        );
+        let changes = action.edit.unwrap().changes.unwrap();
+        assert_eq!(changes.len(), 3);
+
+        let root_uri = Uri::from_file_path(root.path().join("Cargo.toml")).unwrap();
+        assert_eq!(changes[&root_uri][0].new_text, "\nserde = \"1.0.150\"");
+        assert_eq!(
+            changes[&uri][0].new_text,
+            "{ workspace = true, features = [\"derive\"] }"
+        );
+        let b_uri = Uri::from_file_path(root.path().join("b/Cargo.toml")).unwrap();
+        assert_eq!(changes[&b_uri][0].new_text, "{ workspace = true }");
+
+        let diagnostics = ecosystem
+            .generate_diagnostics(
+                parse_result.as_ref(),
+                VersionData::new(&HashMap::new(), &HashMap::new()),
+                &uri,
+                20,
+            )
+            .await;
+        assert!(diagnostics.iter().any(|d| d.code
+            == Some(NumberOrString::String(
+                workspace::DIVERGING_VERSION_CODE.into()
+            ))));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_is_cached_until_invalidated() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = []\n\n[workspace.dependencies]\nserde = \"1\"\n",
+        )
+        .unwrap();
+        let workspaces = Arc::new(WorkspaceCache::new());
+        let ecosystem = CargoEcosystem::new(Arc::new(deps_core::HttpCache::new()))
+            .with_workspace_cache(Arc::clone(&workspaces));
+
+        let workspace = ecosystem.workspace(root.path()).await.unwrap();
+        assert!(workspace.workspace_dependency("serde").is_some());
+        assert_eq!(workspaces.len(), 1);
+
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = []\n",
+        )
+        .unwrap();
+        let cached = ecosystem.workspace(root.path()).await.unwrap();
+        assert!(Arc::ptr_eq(&workspace, &cached));
+
+        workspaces.invalidate(&root.path().join("Cargo.toml"));
+        let reloaded = ecosystem.workspace(root.path()).await.unwrap();
+        assert!(reloaded.workspace_dependency("serde").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unused_workspace_dependency() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\nanyhow = \"1\"\nserde = \"1\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.path().join("a")).unwrap();
+        std::fs::write(
+            root.path().join("a/Cargo.toml"),
+            "[package]\nname = \"a\"\n\n[dependencies]\nserde = { workspace = true }\n",
+        )
+        .unwrap();
+
+        let ecosystem = CargoEcosystem::new(Arc::new(deps_core::HttpCache::new()));
+        let path = root.path().join("Cargo.toml");
+        let uri = Uri::from_file_path(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+
+        let diagnostics = ecosystem
+            .generate_diagnostics(
+                parse_result.as_ref(),
+                VersionData::new(&HashMap::new(), &HashMap::new()),
+                &uri,
+                20,
+            )
+            .await;
+        let unused: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::String(
+                        workspace::UNUSED_WORKSPACE_DEPENDENCY_CODE.into(),
+                    ))
+            })
+            .collect();
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].range.start.line, 4);
+
+        let actions = ecosystem
+            .generate_code_actions(parse_result.as_ref(), Position::new(4, 2), &uri)
+            .await;
+        let action = actions
+            .iter()
+            .find(|action| action.title == "Remove unused workspace dependency `anyhow`")
+            .unwrap();
+        let edit = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri][0];
+        assert_eq!(edit.range.start, Position::new(4, 0));
+        assert_eq!(edit.range.end, Position::new(5, 0));
+
+        let actions = ecosystem
+            .generate_code_actions(parse_result.as_ref(), Position::new(5, 2), &uri)
+            .await;
+        assert!(
+            !actions
+                .iter()
+                .any(|action| action.title.starts_with("Remove unused"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sortable_sections_target() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = r#"[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+windows-sys = "0.59"
+
+[target.wasm32-unknown-unknown.dev-dependencies]
+wasm-bindgen-test = "0.3"
+"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(parse_result.as_ref(), content);
+        let labels: Vec<_> = sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "[target.'cfg(windows)'.dependencies]",
+                "[target.wasm32-unknown-unknown.dev-dependencies]",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content =
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\ntokio = \"1\"\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "1.0.0".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("serde", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\nserde = \"1.0.0\"\ntokio = \"1\"\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("insta", Some("dev")))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\ntokio = \"1\"\n\n[dev-dependencies]\ninsta = \"1.0.0\"\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("rayon", Some("optional")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\nrayon = { version = \"1.0.0\", optional = true }\ntokio = \"1\"\n"
+        );
+
+        assert!(
+            ecosystem
+                .add_dependency(parse_result.as_ref(), content, &add("x", Some("peer")))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_has_dependency_checks_target_section() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = "[package]\nname = \"app\"\n\n[dependencies]\nserde = \"1\"\n\n[dev-dependencies]\ninsta = \"1\"\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+        let has = |name: &str, section: Option<&str>| {
+            ecosystem.has_dependency(
+                parse_result.as_ref(),
+                content,
+                name,
+                &DependencyKind::parse(section),
+            )
+        };
+
+        assert!(has("serde", None));
+        assert!(has("serde", Some("optional")));
+        assert!(!has("serde", Some("dev")));
+        assert!(has("insta", Some("dev")));
+        assert!(!has("insta", None));
+        assert!(!has("insta", Some("build")));
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_virtual_manifest() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = CargoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let content = "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "serde".into(),
+            version: "1.0.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\nserde = \"1.0.0\"\n"
+        );
+    }
 }
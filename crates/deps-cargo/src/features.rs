@@ -0,0 +1,536 @@
+//! Validation of dependency features against the registry, and of the
+//! manifest's own `[features]` table.
+//!
+//! The features listed on a dependency are checked against the feature map
+//! of the version its requirement resolves to, so a typo such as
+//! `features = ["deriv"]` is reported before `cargo build` fails on it.
+//! Entries of `[features]` (`std`, `dep:serde`, `serde/std`) are checked
+//! against the features and optional dependencies the manifest declares.
+//!
+//! # Examples
+//!
+//! ```
+//! use deps_cargo::features::suggest_feature;
+//! use std::collections::HashMap;
+//!
+//! let features = HashMap::from([("derive".to_string(), vec![])]);
+//! assert_eq!(suggest_feature("deriv", &features), Some("derive"));
+//! assert_eq!(suggest_feature("tokio", &features), None);
+//! ```
+
+use crate::parser::ParseResult;
+use crate::types::{DependencySource, FeatureReference, FeatureTarget, ParsedDependency};
+use deps_core::lsp_helpers::position_in_range;
+use std::collections::HashMap;
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range,
+    TextEdit, Uri, WorkspaceEdit,
+};
+
+/// Diagnostic code of features the matched version does not define.
+pub const UNKNOWN_FEATURE_CODE: &str = "unknown-feature";
+
+/// Diagnostic code of features contradicting `default-features = false`.
+pub const DEFAULT_FEATURES_CODE: &str = "default-features";
+
+/// Diagnostic code of `[features]` entries naming no feature or
+/// dependency of the manifest.
+pub const FEATURE_REFERENCE_CODE: &str = "feature-reference";
+
+/// Feature always accepted by Cargo, whether or not the crate defines it.
+const DEFAULT_FEATURE: &str = "default";
+
+/// Problem with one entry of a dependency's `features`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureIssue {
+    /// Not a feature of the matched version, with the closest one if any
+    Unknown { suggestion: Option<String> },
+    /// Only enabled through `default`, which `default-features = false`
+    /// turns off
+    DefaultOnly,
+    /// `default` listed next to `default-features = false`
+    ReenablesDefault,
+}
+
+/// Whether the features of `dep` can be checked against the registry.
+///
+/// Only plain registry requirements qualify: workspace, path and git
+/// dependencies and overridden ones build other sources.
+pub fn is_checkable(dep: &ParsedDependency) -> bool {
+    let registry = matches!(
+        dep.source,
+        DependencySource::Registry | DependencySource::CustomRegistry { .. }
+    );
+    registry
+        && dep.version_req.is_some()
+        && !dep.features.is_empty()
+        && dep.overridden_by.is_none()
+        && !dep.section.is_override()
+}
+
+/// Checks each entry of `dep.features` against `available`, the feature map
+/// of the matched version. Returns the index of every problematic entry.
+pub fn check_features(
+    dep: &ParsedDependency,
+    available: &HashMap<String, Vec<String>>,
+) -> Vec<(usize, FeatureIssue)> {
+    let enabled_by_default = available
+        .get(DEFAULT_FEATURE)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    dep.features
+        .iter()
+        .enumerate()
+        .filter_map(|(index, feature)| {
+            // `dependency/feature` entries are resolved by Cargo itself
+            if feature.contains('/') {
+                return None;
+            }
+            let issue = if feature == DEFAULT_FEATURE {
+                if dep.default_features {
+                    return None;
+                }
+                FeatureIssue::ReenablesDefault
+            } else if available.contains_key(feature) {
+                return None;
+            } else if !dep.default_features
+                && enabled_by_default
+                    .iter()
+                    .any(|f| f.strip_prefix("dep:").unwrap_or(f) == feature)
+            {
+                FeatureIssue::DefaultOnly
+            } else {
+                FeatureIssue::Unknown {
+                    suggestion: suggest_feature(feature, available).map(String::from),
+                }
+            };
+            Some((index, issue))
+        })
+        .collect()
+}
+
+/// Builds the diagnostics of [`check_features`] for `dep` at `version`.
+pub fn feature_diagnostics(
+    dep: &ParsedDependency,
+    version: &str,
+    available: &HashMap<String, Vec<String>>,
+) -> Vec<Diagnostic> {
+    check_features(dep, available)
+        .into_iter()
+        .filter_map(|(index, issue)| {
+            let feature = &dep.features[index];
+            let (severity, code, message) = match issue {
+                FeatureIssue::Unknown { suggestion } => {
+                    let mut message =
+                        format!(
+                            "Feature `{feature}` does not exist in {} {version}",
+                            dep.package_name()
+                        );
+                    if let Some(suggestion) = suggestion {
+                        message.push_str(&format!(", did you mean `{suggestion}`?"));
+                    }
+                    (DiagnosticSeverity::ERROR, UNKNOWN_FEATURE_CODE, message)
+                }
+                FeatureIssue::DefaultOnly => (
+                    DiagnosticSeverity::ERROR,
+                    DEFAULT_FEATURES_CODE,
+                    format!(
+                        "Feature `{feature}` is only enabled through `default`, which `default-features = false` turns off"
+                    ),
+                ),
+                FeatureIssue::ReenablesDefault => (
+                    DiagnosticSeverity::WARNING,
+                    DEFAULT_FEATURES_CODE,
+                    "`default` enables the default features again despite `default-features = false`"
+                        .to_string(),
+                ),
+            };
+            Some(Diagnostic {
+                range: *dep.feature_ranges.get(index)?,
+                severity: Some(severity),
+                code: Some(NumberOrString::String(code.into())),
+                message,
+                source: Some("deps-lsp".into()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Quick fix replacing the unknown feature at `index` with its closest
+/// match, if there is one.
+pub fn feature_quick_fix(
+    dep: &ParsedDependency,
+    index: usize,
+    available: &HashMap<String, Vec<String>>,
+    uri: &Uri,
+) -> Option<CodeAction> {
+    let (_, issue) = check_features(dep, available)
+        .into_iter()
+        .find(|(i, _)| *i == index)?;
+    let FeatureIssue::Unknown {
+        suggestion: Some(suggestion),
+    } = issue
+    else {
+        return None;
+    };
+
+    let edit = TextEdit {
+        range: *dep.feature_ranges.get(index)?,
+        new_text: suggestion.clone(),
+    };
+    Some(CodeAction {
+        title: format!("Replace with `{suggestion}`"),
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// Reports the entries of `[features]` naming no feature, optional
+/// dependency or dependency of the manifest, and `dep:` entries on
+/// dependencies that are not optional.
+pub fn manifest_feature_diagnostics(result: &ParseResult) -> Vec<Diagnostic> {
+    references(result)
+        .filter_map(|reference| {
+            let message = match reference.target() {
+                FeatureTarget::Feature(name) => {
+                    if result.features.iter().any(|f| f.name == name)
+                        || implicit_feature(result, name).is_some()
+                    {
+                        return None;
+                    }
+                    format!("No feature or optional dependency named `{name}`")
+                }
+                FeatureTarget::Dependency(name) => {
+                    let mut declared = declared_dependencies(result, name).peekable();
+                    if declared.peek().is_none() {
+                        format!("No dependency named `{name}`")
+                    } else if declared.any(|dep| dep.optional) {
+                        return None;
+                    } else {
+                        format!(
+                            "`{name}` is not an optional dependency, `dep:` requires `optional = true`"
+                        )
+                    }
+                }
+                FeatureTarget::DependencyFeature { dependency, .. } => {
+                    if declared_dependencies(result, dependency).next().is_some() {
+                        return None;
+                    }
+                    format!("No dependency named `{dependency}`")
+                }
+            };
+            Some(Diagnostic {
+                range: reference.range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(FEATURE_REFERENCE_CODE.into())),
+                message,
+                source: Some("deps-lsp".into()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Definition of what the `[features]` entry at `position` refers to: the
+/// feature or the dependency it names. On the name of an optional
+/// dependency, the first `[features]` entry referring to it instead.
+pub fn feature_definition(result: &ParseResult, position: Position) -> Option<Range> {
+    if let Some(reference) = references(result).find(|r| position_in_range(position, r.range)) {
+        return match reference.target() {
+            FeatureTarget::Feature(name) => result
+                .features
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.name_range)
+                .or_else(|| implicit_feature(result, name).map(|dep| dep.name_range)),
+            FeatureTarget::Dependency(name)
+            | FeatureTarget::DependencyFeature {
+                dependency: name, ..
+            } => declared_dependencies(result, name)
+                .next()
+                .map(|dep| dep.name_range),
+        };
+    }
+
+    let dep = result.dependencies.iter().find(|dep| {
+        dep.optional && dep.section.can_be_optional() && position_in_range(position, dep.name_range)
+    })?;
+    references(result)
+        .find(|reference| match reference.target() {
+            FeatureTarget::Feature(name) => {
+                name == dep.name && !result.features.iter().any(|f| f.name == name)
+            }
+            FeatureTarget::Dependency(name)
+            | FeatureTarget::DependencyFeature {
+                dependency: name, ..
+            } => name == dep.name,
+        })
+        .map(|reference| reference.range)
+}
+
+/// Every entry of every `[features]` list.
+fn references(result: &ParseResult) -> impl Iterator<Item = &FeatureReference> {
+    result.features.iter().flat_map(|f| &f.enables)
+}
+
+/// Dependencies named `key` that `[features]` can refer to.
+fn declared_dependencies<'a>(
+    result: &'a ParseResult,
+    key: &'a str,
+) -> impl Iterator<Item = &'a ParsedDependency> {
+    result
+        .dependencies
+        .iter()
+        .filter(move |dep| dep.name == key && dep.section.can_be_optional())
+}
+
+/// Optional dependency enabled by the implicit feature `name`. Cargo only
+/// creates it when no entry refers to the dependency with `dep:name`.
+fn implicit_feature<'a>(result: &'a ParseResult, name: &'a str) -> Option<&'a ParsedDependency> {
+    if references(result).any(|r| r.target() == FeatureTarget::Dependency(name)) {
+        return None;
+    }
+    declared_dependencies(result, name).find(|dep| dep.optional)
+}
+
+/// Returns the feature of `available` closest to `name`, if within a third
+/// of its length (at least one edit) away.
+pub fn suggest_feature<'a>(
+    name: &str,
+    available: &'a HashMap<String, Vec<String>>,
+) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    available
+        .keys()
+        .map(|feature| (edit_distance(name, feature), feature.as_str()))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, feature)| feature)
+}
+
+/// Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_cargo_toml;
+    use deps_core::test_util::test_uri;
+
+    fn features(names: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        names
+            .iter()
+            .map(|(name, enables)| {
+                (
+                    (*name).to_string(),
+                    enables.iter().map(|f| (*f).to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn parse_dep(toml: &str) -> ParsedDependency {
+        let uri = test_uri("/test/Cargo.toml");
+        parse_cargo_toml(toml, &uri).unwrap().dependencies.remove(0)
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("deriv", "derive"), 1);
+        assert_eq!(edit_distance("derive", "derive"), 0);
+        assert_eq!(edit_distance("", "std"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unknown_feature_with_suggestion() {
+        let dep = parse_dep(
+            "[dependencies]\nserde = { version = \"1\", features = [\"deriv\", \"std\"] }",
+        );
+        let available = features(&[("derive", &[]), ("std", &[]), ("rc", &[])]);
+
+        assert_eq!(
+            check_features(&dep, &available),
+            [(
+                0,
+                FeatureIssue::Unknown {
+                    suggestion: Some("derive".into())
+                }
+            )]
+        );
+
+        let diagnostics = feature_diagnostics(&dep, "1.0.210", &available);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Feature `deriv` does not exist in serde 1.0.210, did you mean `derive`?"
+        );
+        assert_eq!(diagnostics[0].range, dep.feature_ranges[0]);
+        assert_eq!(diagnostics[0].range.start.character, 38);
+        assert_eq!(diagnostics[0].range.end.character, 43);
+
+        let uri = test_uri("/test/Cargo.toml");
+        let action = feature_quick_fix(&dep, 0, &available, &uri).unwrap();
+        assert_eq!(action.title, "Replace with `derive`");
+        assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+        let edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "derive");
+        assert!(feature_quick_fix(&dep, 1, &available, &uri).is_none());
+    }
+
+    #[test]
+    fn test_unknown_feature_without_suggestion() {
+        let dep = parse_dep("[dependencies]\nserde = { version = \"1\", features = [\"tokio\"] }");
+        let available = features(&[("derive", &[])]);
+
+        let diagnostics = feature_diagnostics(&dep, "1.0.0", &available);
+        assert_eq!(
+            diagnostics[0].message,
+            "Feature `tokio` does not exist in serde 1.0.0"
+        );
+        assert!(feature_quick_fix(&dep, 0, &available, &test_uri("/test/Cargo.toml")).is_none());
+    }
+
+    #[test]
+    fn test_default_features_disabled() {
+        let available = features(&[("default", &["dep:backtrace", "std"]), ("std", &[])]);
+
+        let dep = parse_dep(
+            "[dependencies]\nanyhow = { version = \"1\", default-features = false, features = [\"std\", \"backtrace\", \"default\"] }",
+        );
+        assert_eq!(
+            check_features(&dep, &available),
+            [
+                (1, FeatureIssue::DefaultOnly),
+                (2, FeatureIssue::ReenablesDefault)
+            ]
+        );
+
+        let dep = parse_dep(
+            "[dependencies]\nanyhow = { version = \"1\", features = [\"default\", \"backtrace\"] }",
+        );
+        assert!(matches!(
+            check_features(&dep, &available)[..],
+            [(1, FeatureIssue::Unknown { .. })]
+        ));
+    }
+
+    #[test]
+    fn test_dependency_features_are_skipped() {
+        let dep =
+            parse_dep("[dependencies]\nfoo = { version = \"1\", features = [\"serde/std\"] }");
+        assert!(check_features(&dep, &HashMap::new()).is_empty());
+    }
+
+    const MANIFEST_FEATURES: &str = r#"[dependencies]
+serde = { version = "1", optional = true }
+tokio = { version = "1", optional = true }
+log = "0.4"
+
+[dev-dependencies]
+criterion = { version = "0.5", optional = true }
+
+[features]
+default = ["std"]
+std = ["serde?/std", "dep:tokio", "log/std"]
+serialize = ["serde"]
+async = ["tokio", "dep:log", "tokoi/rt", "criterion", "dep:serd"]
+"#;
+
+    #[test]
+    fn test_manifest_feature_diagnostics() {
+        let uri = test_uri("/test/Cargo.toml");
+        let result = parse_cargo_toml(MANIFEST_FEATURES, &uri).unwrap();
+        assert_eq!(result.features.len(), 4);
+
+        let diagnostics = manifest_feature_diagnostics(&result);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        // `[features]` keys iterate in sorted order
+        assert_eq!(
+            messages,
+            [
+                "No feature or optional dependency named `tokio`",
+                "`log` is not an optional dependency, `dep:` requires `optional = true`",
+                "No dependency named `tokoi`",
+                "No feature or optional dependency named `criterion`",
+                "No dependency named `serd`",
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].code,
+            Some(NumberOrString::String(FEATURE_REFERENCE_CODE.into()))
+        );
+        assert_eq!(diagnostics[2].range.start.line, 12);
+        assert_eq!(diagnostics[2].range.start.character, 30);
+    }
+
+    #[test]
+    fn test_feature_definition() {
+        let uri = test_uri("/test/Cargo.toml");
+        let result = parse_cargo_toml(MANIFEST_FEATURES, &uri).unwrap();
+        let name_range = |name: &str| {
+            result
+                .dependencies
+                .iter()
+                .find(|dep| dep.name == name)
+                .unwrap()
+                .name_range
+        };
+        let (serde, tokio) = (name_range("serde"), name_range("tokio"));
+
+        // `"serde?/std"` on the `std` line
+        assert_eq!(
+            feature_definition(&result, Position::new(10, 9)),
+            Some(serde)
+        );
+        // `"dep:tokio"`
+        assert_eq!(
+            feature_definition(&result, Position::new(10, 25)),
+            Some(tokio)
+        );
+        // `"std"` in `default`
+        assert_eq!(
+            feature_definition(&result, Position::new(9, 13)),
+            Some(result.features[3].name_range)
+        );
+        // `"tokio"` has no implicit feature since `dep:tokio` is used
+        assert_eq!(feature_definition(&result, Position::new(12, 11)), None);
+
+        // From the optional dependency to its first reference
+        let range = feature_definition(&result, Position::new(1, 2)).unwrap();
+        assert_eq!((range.start.line, range.start.character), (11, 14));
+        assert_eq!(feature_definition(&result, Position::new(3, 1)), None);
+    }
+
+    #[test]
+    fn test_is_checkable() {
+        assert!(is_checkable(&parse_dep(
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }"
+        )));
+        assert!(!is_checkable(&parse_dep("[dependencies]\nserde = \"1\"")));
+        assert!(!is_checkable(&parse_dep(
+            "[dependencies]\nserde = { path = \"../serde\", features = [\"derive\"] }"
+        )));
+    }
+}
@@ -10,6 +10,18 @@ impl EcosystemFormatter for CargoFormatter {
     fn package_url(&self, name: &str) -> String {
         crate::registry::crate_url(name)
     }
+
+    /// Cargo semantics: a bare requirement such as `1.0.80` is a caret
+    /// requirement and accepts any later semver-compatible version.
+    fn version_satisfies_requirement(&self, version: &str, requirement: &str) -> bool {
+        match (
+            semver::Version::parse(version),
+            semver::VersionReq::parse(requirement),
+        ) {
+            (Ok(version), Ok(requirement)) => requirement.matches(&version),
+            _ => version == requirement,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -62,5 +74,9 @@ mod tests {
 
         assert!(!formatter.version_satisfies_requirement("1.2.3", "2.0.0"));
         assert!(!formatter.version_satisfies_requirement("1.2.3", "1.3"));
+
+        assert!(formatter.version_satisfies_requirement("1.0.86", "1.0.80"));
+        assert!(!formatter.version_satisfies_requirement("1.0.86", "=1.0.80"));
+        assert!(!formatter.version_satisfies_requirement("0.3.0", "0.2"));
     }
 }
@@ -10,6 +10,7 @@
 //! - Fetching version data from crates.io sparse index
 //! - Supporting registry, git, and path dependencies
 //! - Workspace inheritance (`workspace = true`)
+//! - Alternate registries and source replacement from `.cargo/config.toml`
 //!
 //! # Examples
 //!
@@ -20,13 +21,19 @@
 //! let _deps: Vec<ParsedDependency> = vec![];
 //! ```
 
+pub mod config;
+pub mod definition;
 pub mod ecosystem;
 pub mod error;
+pub mod features;
 pub mod formatter;
 pub mod lockfile;
+pub mod msrv;
 pub mod parser;
 pub mod registry;
+pub mod table_completion;
 pub mod types;
+pub mod workspace;
 
 // Re-export commonly used types
 pub use ecosystem::CargoEcosystem;
@@ -35,4 +42,7 @@ pub use formatter::CargoFormatter;
 pub use lockfile::CargoLockParser;
 pub use parser::{CargoParser, ParseResult, parse_cargo_toml};
 pub use registry::{CratesIoRegistry, crate_url};
-pub use types::{CargoVersion, CrateInfo, DependencySection, DependencySource, ParsedDependency};
+pub use types::{
+    CargoVersion, CrateInfo, DependencySection, DependencySource, ParsedDependency,
+    TargetDependencyKind,
+};
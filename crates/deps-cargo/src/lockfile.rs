@@ -132,7 +132,12 @@ impl LockFileProvider for CargoLockParser {
                 };
 
                 // Parse source (optional for path dependencies)
-                let source = parse_cargo_source(table.get("source").and_then(|v| v.as_str()));
+                let mut source = parse_cargo_source(table.get("source").and_then(|v| v.as_str()));
+                if let ResolvedSource::Registry { checksum, .. } = &mut source
+                    && let Some(value) = table.get("checksum").and_then(|v| v.as_str())
+                {
+                    *checksum = value.to_string();
+                }
 
                 // Parse dependencies array (optional)
                 let dependencies = parse_cargo_dependencies_from_table(table);
@@ -390,6 +395,7 @@ checksum = "def456"
         let serde_pkg = resolved.get("serde").unwrap();
         assert_eq!(serde_pkg.dependencies.len(), 1);
         assert_eq!(serde_pkg.dependencies[0], "serde_derive");
+        assert_eq!(serde_pkg.source.checksum(), Some("abc123"));
     }
 
     #[tokio::test]
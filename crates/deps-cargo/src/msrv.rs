@@ -0,0 +1,209 @@
+//! Version suggestions honouring the manifest's `rust-version`.
+//!
+//! The sparse index records the `rust-version` of every release. When the
+//! latest release requires a newer toolchain than the manifest declares,
+//! the newest release that still builds with it is suggested instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use deps_cargo::msrv::RustVersion;
+//!
+//! let msrv = RustVersion::parse("1.70").unwrap();
+//! assert!(RustVersion::parse("1.70.0").unwrap() <= msrv);
+//! assert!(RustVersion::parse("1.80").unwrap() > msrv);
+//! ```
+
+use crate::types::CargoVersion;
+use std::fmt;
+
+/// Diagnostic code of locked versions requiring a newer Rust than
+/// `rust-version`.
+pub const RUST_VERSION_CODE: &str = "rust-version";
+
+/// Rust release as written in `rust-version`: `1.70` or `1.70.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RustVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl RustVersion {
+    /// Parses `major.minor` or `major.minor.patch`.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.').map(str::parse::<u64>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Latest release requiring a newer Rust, and the newest one that doesn't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsrvSuggestion {
+    pub latest: String,
+    /// `rust-version` of the latest release
+    pub latest_requires: String,
+    /// Newest stable, non-yanked release building with the declared
+    /// `rust-version`
+    pub compatible: Option<String>,
+}
+
+impl MsrvSuggestion {
+    /// `latest 1.40 requires Rust 1.80, latest compatible: 1.38`
+    pub fn message(&self) -> String {
+        let mut message = format!(
+            "latest {} requires Rust {}",
+            self.latest, self.latest_requires
+        );
+        match &self.compatible {
+            Some(compatible) => message.push_str(&format!(", latest compatible: {compatible}")),
+            None => message.push_str(", no compatible release"),
+        }
+        message
+    }
+}
+
+/// Whether `version` builds with `msrv`. Releases without a (valid)
+/// `rust-version` are assumed to.
+pub fn is_compatible(version: &CargoVersion, msrv: RustVersion) -> bool {
+    version
+        .rust_version
+        .as_deref()
+        .and_then(RustVersion::parse)
+        .is_none_or(|required| required <= msrv)
+}
+
+/// Suggests the newest release compatible with `msrv` when `latest`
+/// requires a newer Rust. `versions` are sorted newest first.
+pub fn suggest(
+    versions: &[CargoVersion],
+    latest: &str,
+    msrv: RustVersion,
+) -> Option<MsrvSuggestion> {
+    let latest = versions.iter().find(|v| v.num == latest)?;
+    if is_compatible(latest, msrv) {
+        return None;
+    }
+
+    let compatible = versions
+        .iter()
+        .filter(|v| !v.yanked && !v.num.contains('-'))
+        .find(|v| is_compatible(v, msrv))
+        .map(|v| v.num.clone());
+    Some(MsrvSuggestion {
+        latest: latest.num.clone(),
+        latest_requires: latest.rust_version.clone()?,
+        compatible,
+    })
+}
+
+/// `rust-version` of `version` when it is newer than `msrv`.
+pub fn required_rust<'a>(
+    versions: &'a [CargoVersion],
+    version: &str,
+    msrv: RustVersion,
+) -> Option<&'a str> {
+    versions
+        .iter()
+        .find(|v| v.num == version)
+        .filter(|v| !is_compatible(v, msrv))?
+        .rust_version
+        .as_deref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn version(num: &str, rust_version: Option<&str>) -> CargoVersion {
+        CargoVersion {
+            num: num.into(),
+            yanked: false,
+            features: HashMap::new(),
+            rust_version: rust_version.map(String::from),
+            checksum: None,
+        }
+    }
+
+    fn versions() -> Vec<CargoVersion> {
+        vec![
+            version("1.40.0", Some("1.80")),
+            version("1.39.0-rc.1", Some("1.65")),
+            version("1.39.0", Some("1.75")),
+            version("1.38.1", Some("1.70.0")),
+            version("1.38.0", None),
+        ]
+    }
+
+    #[test]
+    fn test_parse_rust_version() {
+        let v = RustVersion::parse("1.70").unwrap();
+        assert_eq!(v, RustVersion::parse("1.70.0").unwrap());
+        assert!(RustVersion::parse("1.70.1").unwrap() > v);
+        assert_eq!(v.to_string(), "1.70");
+        assert_eq!(RustVersion::parse("1.70.1").unwrap().to_string(), "1.70.1");
+        assert_eq!(RustVersion::parse("1"), None);
+        assert_eq!(RustVersion::parse("1.x"), None);
+        assert_eq!(RustVersion::parse("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_suggest_compatible() {
+        let msrv = RustVersion::parse("1.70").unwrap();
+        let suggestion = suggest(&versions(), "1.40.0", msrv).unwrap();
+        assert_eq!(suggestion.compatible.as_deref(), Some("1.38.1"));
+        assert_eq!(
+            suggestion.message(),
+            "latest 1.40.0 requires Rust 1.80, latest compatible: 1.38.1"
+        );
+
+        assert_eq!(
+            suggest(&versions(), "1.40.0", RustVersion::parse("1.80").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_suggest_without_compatible_release() {
+        let versions = vec![
+            version("2.0.0", Some("1.80")),
+            version("1.0.0", Some("1.75")),
+        ];
+        let suggestion = suggest(&versions, "2.0.0", RustVersion::parse("1.60").unwrap()).unwrap();
+        assert_eq!(suggestion.compatible, None);
+        assert_eq!(
+            suggestion.message(),
+            "latest 2.0.0 requires Rust 1.80, no compatible release"
+        );
+    }
+
+    #[test]
+    fn test_required_rust() {
+        let msrv = RustVersion::parse("1.70").unwrap();
+        assert_eq!(required_rust(&versions(), "1.39.0", msrv), Some("1.75"));
+        assert_eq!(required_rust(&versions(), "1.38.1", msrv), None);
+        assert_eq!(required_rust(&versions(), "1.38.0", msrv), None);
+        assert_eq!(required_rust(&versions(), "9.9.9", msrv), None);
+    }
+}
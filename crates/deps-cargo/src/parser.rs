@@ -8,7 +8,9 @@
 //!
 //! - Position-preserving parsing via toml-span spans
 //! - Handles all dependency formats: inline, table, workspace inheritance
-//! - Extracts dependencies from all sections: dependencies, dev-dependencies, build-dependencies
+//! - Extracts dependencies from all sections: dependencies, dev-dependencies, build-dependencies,
+//!   including their `[target.<cfg>]` variants
+//! - Extracts the `[features]` table
 //! - Converts byte offsets to LSP Position (line, UTF-16 character)
 //!
 //! # Examples
@@ -28,9 +30,14 @@
 //! assert_eq!(result.dependencies[0].name, "serde");
 //! ```
 
+use crate::config::{CRATES_IO, CargoConfig, RegistryIndex};
 use crate::error::{CargoError, Result};
-use crate::types::{DependencySection, DependencySource, ParsedDependency};
+use crate::types::{
+    DependencySection, DependencySource, FeatureReference, ManifestFeature, ParsedDependency,
+    TargetDependencyKind,
+};
 use std::any::Any;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use toml_span::value::{Table, Value};
 use tower_lsp_server::ls_types::{Range, Uri};
@@ -47,6 +54,15 @@ pub struct ParseResult {
     pub dependencies: Vec<ParsedDependency>,
     /// Workspace root path if this is a workspace member
     pub workspace_root: Option<PathBuf>,
+    /// Index of every dependency not fetched from crates.io (alternate
+    /// registries, or all of them when crates.io is replaced), by crate name
+    pub indexes: HashMap<String, RegistryIndex>,
+    /// `package.rust-version`, inherited from `[workspace.package]` with
+    /// `rust-version.workspace = true`. Virtual manifests use their own
+    /// `[workspace.package]` value.
+    pub rust_version: Option<String>,
+    /// Entries of the `[features]` table
+    pub features: Vec<ManifestFeature>,
     /// Document URI
     pub uri: Uri,
 }
@@ -105,7 +121,7 @@ pub fn parse_cargo_toml(content: &str, doc_uri: &Uri) -> Result<ParseResult> {
             deps,
             content,
             &line_table,
-            DependencySection::Dependencies,
+            &DependencySection::Dependencies,
         ));
     }
 
@@ -116,7 +132,7 @@ pub fn parse_cargo_toml(content: &str, doc_uri: &Uri) -> Result<ParseResult> {
             dev_deps,
             content,
             &line_table,
-            DependencySection::DevDependencies,
+            &DependencySection::DevDependencies,
         ));
     }
 
@@ -127,10 +143,40 @@ pub fn parse_cargo_toml(content: &str, doc_uri: &Uri) -> Result<ParseResult> {
             build_deps,
             content,
             &line_table,
-            DependencySection::BuildDependencies,
+            &DependencySection::BuildDependencies,
         ));
     }
 
+    // Parse platform-specific dependencies: [target.'cfg(...)'.dependencies]
+    if let Some(target_val) = get_val(root_table, "target")
+        && let Some(targets) = target_val.as_table()
+    {
+        for (target, value) in targets {
+            let Some(target_table) = value.as_table() else {
+                continue;
+            };
+            for kind in [
+                TargetDependencyKind::Normal,
+                TargetDependencyKind::Dev,
+                TargetDependencyKind::Build,
+            ] {
+                if let Some(deps_val) = get_val(target_table, kind.table_name())
+                    && let Some(deps) = deps_val.as_table()
+                {
+                    dependencies.extend(parse_dependencies_section(
+                        deps,
+                        content,
+                        &line_table,
+                        &DependencySection::Target {
+                            target: target.name.to_string(),
+                            kind,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
     // Parse workspace dependencies (for workspace root Cargo.toml)
     if let Some(workspace_val) = get_val(root_table, "workspace")
         && let Some(workspace_table) = workspace_val.as_table()
@@ -141,19 +187,146 @@ pub fn parse_cargo_toml(content: &str, doc_uri: &Uri) -> Result<ParseResult> {
             workspace_deps,
             content,
             &line_table,
-            DependencySection::WorkspaceDependencies,
+            &DependencySection::WorkspaceDependencies,
         ));
     }
 
+    dependencies.extend(parse_override_sections(root_table, content, &line_table));
+
     let workspace_root = find_workspace_root(doc_uri)?;
+    let inherited = workspace_root
+        .as_ref()
+        .map_or(&[][..], |root| root.overrides.as_slice());
+    apply_overrides(&mut dependencies, inherited);
+
+    let config = doc_uri
+        .to_file_path()
+        .and_then(|path| path.parent().map(CargoConfig::load))
+        .unwrap_or_default();
+    let indexes = resolve_registries(&mut dependencies, &config);
+
+    let rust_version = match get_val(root_table, "package") {
+        Some(package) => match package.as_table().and_then(|t| get_val(t, "rust-version")) {
+            Some(value) if value.as_str().is_some() => value.as_str().map(String::from),
+            Some(_) => workspace_root
+                .as_ref()
+                .and_then(|root| root.rust_version.clone()),
+            None => None,
+        },
+        None => workspace_rust_version(root_table),
+    };
+
+    let features = get_val(root_table, "features")
+        .and_then(Value::as_table)
+        .map(|table| parse_features_section(table, content, &line_table))
+        .unwrap_or_default();
 
     Ok(ParseResult {
         dependencies,
-        workspace_root,
+        workspace_root: workspace_root.map(|root| root.path),
+        indexes,
+        rust_version,
+        features,
         uri: doc_uri.clone(),
     })
 }
 
+/// Resolves the index of every dependency not fetched from crates.io, and
+/// records the index URL of alternate registry dependencies as their source.
+fn resolve_registries(
+    dependencies: &mut [ParsedDependency],
+    config: &CargoConfig,
+) -> HashMap<String, RegistryIndex> {
+    let crates_io = config.resolve(CRATES_IO);
+    let mut indexes = HashMap::new();
+
+    for dep in dependencies {
+        let index = match dep.registry.as_deref() {
+            Some(registry) => config.resolve(registry),
+            None if dep.source == DependencySource::Registry => crates_io.clone(),
+            None => None,
+        };
+        let Some(index) = index else {
+            continue;
+        };
+        if dep.registry.is_some()
+            && let Some(url) = &index.url
+        {
+            dep.source = DependencySource::CustomRegistry { url: url.clone() };
+        }
+        indexes.insert(dep.package_name().to_string(), index);
+    }
+
+    indexes
+}
+
+/// `[workspace.package].rust-version` of a workspace root.
+fn workspace_rust_version(root_table: &Table<'_>) -> Option<String> {
+    let workspace = get_val(root_table, "workspace")?.as_table()?;
+    let package = get_val(workspace, "package")?.as_table()?;
+    get_val(package, "rust-version")?.as_str().map(String::from)
+}
+
+/// Parses `[patch.<registry>]` and `[replace]` entries.
+fn parse_override_sections(
+    root_table: &Table<'_>,
+    content: &str,
+    line_table: &LineOffsetTable,
+) -> Vec<ParsedDependency> {
+    let mut overrides = Vec::new();
+
+    if let Some(patch_val) = get_val(root_table, "patch")
+        && let Some(registries) = patch_val.as_table()
+    {
+        for (registry, value) in registries {
+            if let Some(patches) = value.as_table() {
+                overrides.extend(parse_dependencies_section(
+                    patches,
+                    content,
+                    line_table,
+                    &DependencySection::Patch {
+                        registry: registry.name.to_string(),
+                    },
+                ));
+            }
+        }
+    }
+
+    if let Some(replace_val) = get_val(root_table, "replace")
+        && let Some(replace) = replace_val.as_table()
+    {
+        let mut replaced =
+            parse_dependencies_section(replace, content, line_table, &DependencySection::Replace);
+        // Keys are package IDs: "foo:1.0.0"
+        for dep in &mut replaced {
+            if let Some((name, _)) = dep.name.split_once(':') {
+                dep.name = name.to_string();
+            }
+        }
+        overrides.extend(replaced);
+    }
+
+    overrides
+}
+
+/// Marks dependencies overridden by a `[patch]` or `[replace]` entry of the
+/// manifest itself or of its workspace root (`inherited`).
+fn apply_overrides(dependencies: &mut [ParsedDependency], inherited: &[ParsedDependency]) {
+    let notes: Vec<Option<String>> = dependencies
+        .iter()
+        .map(|dep| {
+            dependencies
+                .iter()
+                .chain(inherited)
+                .find(|entry| entry.overrides(dep))
+                .and_then(ParsedDependency::override_note)
+        })
+        .collect();
+    for (dep, note) in dependencies.iter_mut().zip(notes) {
+        dep.overridden_by = note;
+    }
+}
+
 fn get_val<'a>(table: &'a Table<'a>, key: &str) -> Option<&'a Value<'a>> {
     table.get(key)
 }
@@ -163,7 +336,7 @@ fn parse_dependencies_section(
     table: &Table<'_>,
     content: &str,
     line_table: &LineOffsetTable,
-    section: DependencySection,
+    section: &DependencySection,
 ) -> Vec<ParsedDependency> {
     let mut deps = Vec::new();
 
@@ -178,17 +351,28 @@ fn parse_dependencies_section(
             version_range: None,
             features: Vec::new(),
             features_range: None,
+            feature_ranges: Vec::new(),
+            default_features: true,
+            optional: false,
+            value_range: None,
             source: DependencySource::Registry,
-            section,
+            section: section.clone(),
+            registry: None,
+            package: None,
+            overridden_by: None,
         };
 
         if let Some(s) = value.as_str() {
             // Simple string version: serde = "1.0"
             dep.version_req = Some(s.to_string());
             dep.version_range = Some(span_to_range(content, line_table, value.span));
+            dep.value_range = quoted_range(content, line_table, value.span);
         } else if let Some(t) = value.as_table() {
             // Inline table or full table: serde = { version = "1.0" }
             parse_table_dependency(&mut dep, t, content, line_table);
+            if content[value.span.start..].starts_with('{') {
+                dep.value_range = Some(span_to_range(content, line_table, value.span));
+            }
         } else {
             continue;
         }
@@ -199,6 +383,35 @@ fn parse_dependencies_section(
     deps
 }
 
+/// Parses the `[features]` table.
+fn parse_features_section(
+    table: &Table<'_>,
+    content: &str,
+    line_table: &LineOffsetTable,
+) -> Vec<ManifestFeature> {
+    table
+        .iter()
+        .map(|(key, value)| ManifestFeature {
+            name: key.name.to_string(),
+            name_range: span_to_range(content, line_table, key.span),
+            enables: value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| {
+                            Some(FeatureReference {
+                                value: item.as_str()?.to_string(),
+                                range: span_to_range(content, line_table, item.span),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
 /// Parses a table (inline or full) dependency entry.
 fn parse_table_dependency(
     dep: &mut ParsedDependency,
@@ -216,13 +429,24 @@ fn parse_table_dependency(
             }
             "features" => {
                 if let Some(arr) = value.as_array() {
-                    dep.features = arr
-                        .iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect();
+                    for item in arr {
+                        if let Some(feature) = item.as_str() {
+                            dep.features.push(feature.to_string());
+                            dep.feature_ranges
+                                .push(span_to_range(content, line_table, item.span));
+                        }
+                    }
                     dep.features_range = Some(span_to_range(content, line_table, value.span));
                 }
             }
+            "default-features" | "default_features" => {
+                if let Some(enabled) = value.as_bool() {
+                    dep.default_features = enabled;
+                }
+            }
+            "optional" => {
+                dep.optional = value.as_bool() == Some(true);
+            }
             "workspace" if value.as_bool() == Some(true) => {
                 dep.source = DependencySource::Workspace;
             }
@@ -242,9 +466,21 @@ fn parse_table_dependency(
                     };
                 }
             }
+            "registry" => {
+                dep.registry = value.as_str().map(String::from);
+            }
+            "package" => {
+                dep.package = value.as_str().map(String::from);
+            }
             _ => {}
         }
     }
+
+    if let DependencySource::Git { rev, .. } = &mut dep.source {
+        *rev = ["rev", "tag", "branch"]
+            .into_iter()
+            .find_map(|key| get_val(table, key)?.as_str().map(String::from));
+    }
 }
 
 /// Converts toml-span byte offsets to LSP Range using pre-computed line table.
@@ -254,10 +490,35 @@ fn span_to_range(content: &str, line_table: &LineOffsetTable, span: toml_span::S
     Range::new(start, end)
 }
 
+/// Range of a single-line string value including its quotes.
+fn quoted_range(
+    content: &str,
+    line_table: &LineOffsetTable,
+    span: toml_span::Span,
+) -> Option<Range> {
+    let quote = content[..span.start].chars().next_back()?;
+    if !matches!(quote, '"' | '\'') || !content[span.end..].starts_with(quote) {
+        return None;
+    }
+    let start = line_table.byte_offset_to_position(content, span.start - 1);
+    let end = line_table.byte_offset_to_position(content, span.end + 1);
+    Some(Range::new(start, end))
+}
+
+/// Workspace root of a member manifest.
+struct WorkspaceRoot {
+    path: PathBuf,
+    /// `[patch]` and `[replace]` entries of the root manifest, empty when the
+    /// root is the parsed document itself
+    overrides: Vec<ParsedDependency>,
+    /// `[workspace.package].rust-version`
+    rust_version: Option<String>,
+}
+
 /// Finds the workspace root by walking up the directory tree.
 ///
 /// Looks for a Cargo.toml file with a [workspace] section.
-fn find_workspace_root(doc_uri: &Uri) -> Result<Option<PathBuf>> {
+fn find_workspace_root(doc_uri: &Uri) -> Result<Option<WorkspaceRoot>> {
     let path = doc_uri
         .to_file_path()
         .ok_or_else(|| CargoError::invalid_uri(format!("{doc_uri:?}")))?;
@@ -278,12 +539,20 @@ fn find_workspace_root(doc_uri: &Uri) -> Result<Option<PathBuf>> {
                     "skipping ancestor Cargo.toml during workspace root discovery: nesting depth exceeds maximum"
                 );
             } else if let Ok(doc) = toml_span::parse(&content)
-                && doc
-                    .as_table()
-                    .and_then(|t| get_val(t, "workspace"))
-                    .is_some()
+                && let Some(table) = doc.as_table()
+                && get_val(table, "workspace").is_some()
             {
-                return Ok(Some(dir.to_path_buf()));
+                let overrides = if workspace_toml == path {
+                    Vec::new()
+                } else {
+                    let line_table = LineOffsetTable::new(&content);
+                    parse_override_sections(table, &content, &line_table)
+                };
+                return Ok(Some(WorkspaceRoot {
+                    path: dir.to_path_buf(),
+                    overrides,
+                    rust_version: workspace_rust_version(table),
+                }));
             }
         }
 
@@ -308,7 +577,7 @@ impl deps_core::ManifestParser for CargoParser {
 // Implement DependencyInfo trait for ParsedDependency
 impl deps_core::DependencyInfo for ParsedDependency {
     fn name(&self) -> &str {
-        &self.name
+        self.package_name()
     }
 
     fn name_range(&self) -> Range {
@@ -478,6 +747,76 @@ cc = "1.0"
         ));
     }
 
+    #[test]
+    fn test_parse_target_sections() {
+        let toml = r#"
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+
+[target.x86_64-unknown-linux-gnu.dev-dependencies]
+nix = { version = "0.29", features = ["fs"] }
+
+[target."cfg(unix)".build-dependencies]
+cc = "1.0"
+
+[target.'cfg(unix)'.dependencies.libc]
+version = "0.2"
+"#;
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        let mut sections: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.section.clone()))
+            .collect();
+        sections.sort_by_key(|(name, _)| *name);
+        assert_eq!(
+            sections,
+            vec![
+                (
+                    "cc",
+                    DependencySection::Target {
+                        target: "cfg(unix)".into(),
+                        kind: TargetDependencyKind::Build,
+                    }
+                ),
+                (
+                    "libc",
+                    DependencySection::Target {
+                        target: "cfg(unix)".into(),
+                        kind: TargetDependencyKind::Normal,
+                    }
+                ),
+                (
+                    "nix",
+                    DependencySection::Target {
+                        target: "x86_64-unknown-linux-gnu".into(),
+                        kind: TargetDependencyKind::Dev,
+                    }
+                ),
+                (
+                    "winapi",
+                    DependencySection::Target {
+                        target: "cfg(windows)".into(),
+                        kind: TargetDependencyKind::Normal,
+                    }
+                ),
+            ]
+        );
+
+        let nix = result
+            .dependencies
+            .iter()
+            .find(|d| d.name == "nix")
+            .unwrap();
+        assert_eq!(nix.version_req.as_deref(), Some("0.29"));
+        assert_eq!(nix.features, vec!["fs"]);
+        assert_eq!(
+            deps_core::Dependency::markers(nix),
+            Some("x86_64-unknown-linux-gnu")
+        );
+        assert_eq!(nix.name_range.start.line, 5);
+    }
+
     #[test]
     fn test_line_offset_table() {
         let content = "abc\ndef";
@@ -623,6 +962,241 @@ tokio = "1.0"
         ));
     }
 
+    #[test]
+    fn test_parse_patch_and_replace() {
+        let toml = r#"
+[dependencies]
+serde = "1.0"
+tokio = { version = "1", features = ["full"] }
+mio = "1.0"
+fork = { git = "https://github.com/acme/fork" }
+
+[patch.crates-io]
+serde = { git = "https://github.com/serde-rs/serde", rev = "abc123" }
+tokio = { path = "../tokio" }
+
+[patch."https://github.com/acme/fork"]
+fork = { path = "vendor/fork" }
+
+[replace]
+"mio:1.0.2" = { git = "https://github.com/tokio-rs/mio", branch = "main" }
+"#;
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        let find = |name: &str, patched: bool| {
+            result
+                .dependencies
+                .iter()
+                .find(|d| d.name == name && d.section.is_override() == patched)
+                .unwrap()
+        };
+
+        let serde_patch = find("serde", true);
+        assert_eq!(
+            serde_patch.section,
+            DependencySection::Patch {
+                registry: "crates-io".into()
+            }
+        );
+        assert_eq!(
+            serde_patch.source,
+            DependencySource::Git {
+                url: "https://github.com/serde-rs/serde".into(),
+                rev: Some("abc123".into()),
+            }
+        );
+        assert_eq!(serde_patch.overridden_by, None);
+
+        assert_eq!(
+            find("serde", false).overridden_by.as_deref(),
+            Some("patched → git https://github.com/serde-rs/serde (abc123)")
+        );
+        assert_eq!(
+            find("tokio", false).overridden_by.as_deref(),
+            Some("patched → path ../tokio")
+        );
+        assert_eq!(
+            find("fork", false).overridden_by.as_deref(),
+            Some("patched → path vendor/fork")
+        );
+        assert_eq!(find("mio", true).section, DependencySection::Replace);
+        assert_eq!(
+            find("mio", false).overridden_by.as_deref(),
+            Some("replaced → git https://github.com/tokio-rs/mio (main)")
+        );
+    }
+
+    #[test]
+    fn test_patch_does_not_apply_to_other_sources() {
+        let toml = r#"
+[dependencies]
+serde = { path = "../serde" }
+
+[patch."https://github.com/acme/fork"]
+serde = { path = "vendor/serde" }
+"#;
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        assert!(
+            result
+                .dependencies
+                .iter()
+                .all(|d| d.overridden_by.is_none())
+        );
+    }
+
+    #[test]
+    fn test_member_inherits_workspace_patches() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"pkg\"]\n\n[patch.crates-io]\nserde = { path = \"vendor/serde\" }\n",
+        )
+        .unwrap();
+        let pkg_dir = root.path().join("pkg");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let content = "[dependencies]\nserde = { workspace = true }\nanyhow = \"1\"\n";
+        let path = pkg_dir.join("Cargo.toml");
+        std::fs::write(&path, content).unwrap();
+
+        let result = parse_cargo_toml(content, &Uri::from_file_path(&path).unwrap()).unwrap();
+        // Root overrides are applied, not listed as dependencies of the member
+        assert_eq!(result.dependencies.len(), 2);
+        let serde = result
+            .dependencies
+            .iter()
+            .find(|d| d.name == "serde")
+            .unwrap();
+        assert_eq!(
+            serde.overridden_by.as_deref(),
+            Some("patched → path vendor/serde")
+        );
+        let anyhow = result
+            .dependencies
+            .iter()
+            .find(|d| d.name == "anyhow")
+            .unwrap();
+        assert_eq!(anyhow.overridden_by, None);
+    }
+
+    #[test]
+    fn test_parse_renamed_dependency() {
+        use deps_core::Dependency;
+
+        let toml = "[dependencies]\nserde1 = { package = \"serde\", version = \"1\", optional = true }\nserde = \"1\"\n\n[patch.crates-io]\nserde-fork = { package = \"serde\", path = \"../serde\" }\n";
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        let serde1 = &result.dependencies[1];
+        assert_eq!(serde1.name, "serde1");
+        assert_eq!(serde1.package_name(), "serde");
+        assert!(serde1.optional);
+        assert_eq!(Dependency::name(serde1), "serde");
+        assert_eq!(serde1.alias(), Some("serde1"));
+        assert_eq!(result.dependencies[0].alias(), None);
+        // Overrides match on the package name
+        assert_eq!(
+            serde1.overridden_by.as_deref(),
+            Some("patched → path ../serde")
+        );
+    }
+
+    #[test]
+    fn test_parse_features_table() {
+        let toml = "[features]\ndefault = [\"std\"]\nstd = [\"serde?/std\", \"dep:log\"]\n";
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        assert_eq!(result.features.len(), 2);
+        let std = &result.features[1];
+        assert_eq!(std.name, "std");
+        assert_eq!(std.name_range.start.line, 2);
+        let values: Vec<_> = std.enables.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(values, ["serde?/std", "dep:log"]);
+        assert_eq!(
+            (
+                std.enables[1].range.start.character,
+                std.enables[1].range.end.character
+            ),
+            (22, 29)
+        );
+    }
+
+    #[test]
+    fn test_parse_rust_version() {
+        let toml = "[package]\nname = \"app\"\nrust-version = \"1.70\"\n";
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        assert_eq!(result.rust_version.as_deref(), Some("1.70"));
+
+        let result = parse_cargo_toml("[package]\nname = \"app\"\n", &test_url()).unwrap();
+        assert_eq!(result.rust_version, None);
+
+        let toml = "[workspace]\nmembers = []\n\n[workspace.package]\nrust-version = \"1.75\"\n";
+        let result = parse_cargo_toml(toml, &test_url()).unwrap();
+        assert_eq!(result.rust_version.as_deref(), Some("1.75"));
+    }
+
+    #[test]
+    fn test_member_inherits_rust_version() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"pkg\"]\n\n[workspace.package]\nrust-version = \"1.74\"\n",
+        )
+        .unwrap();
+        let pkg_dir = root.path().join("pkg");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let content = "[package]\nname = \"pkg\"\nrust-version.workspace = true\n";
+        let path = pkg_dir.join("Cargo.toml");
+        std::fs::write(&path, content).unwrap();
+
+        let result = parse_cargo_toml(content, &Uri::from_file_path(&path).unwrap()).unwrap();
+        assert_eq!(result.rust_version.as_deref(), Some("1.74"));
+    }
+
+    #[test]
+    fn test_alternate_registries_from_cargo_config() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".cargo")).unwrap();
+        std::fs::write(
+            root.path().join(".cargo/config.toml"),
+            r#"
+[registries.internal]
+index = "sparse+https://cargo.example.com/index/"
+
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+registry = "sparse+https://mirror.example.com/"
+"#,
+        )
+        .unwrap();
+        let content = r#"[dependencies]
+widgets = { version = "1.2", registry = "internal" }
+serde = "1.0"
+local = { path = "../local" }
+"#;
+        let path = root.path().join("Cargo.toml");
+        std::fs::write(&path, content).unwrap();
+
+        let result = parse_cargo_toml(content, &Uri::from_file_path(&path).unwrap()).unwrap();
+        let widgets = result
+            .dependencies
+            .iter()
+            .find(|d| d.name == "widgets")
+            .unwrap();
+        assert_eq!(widgets.registry.as_deref(), Some("internal"));
+        assert_eq!(
+            widgets.source,
+            DependencySource::CustomRegistry {
+                url: "sparse+https://cargo.example.com/index/".into()
+            }
+        );
+
+        assert_eq!(result.indexes.len(), 2);
+        assert_eq!(
+            result.indexes["widgets"].sparse_url(),
+            Some("https://cargo.example.com/index")
+        );
+        assert_eq!(result.indexes["serde"].name, "mirror");
+        assert!(!result.indexes.contains_key("local"));
+    }
+
     #[test]
     fn test_find_workspace_root_skips_over_depth_ancestor() {
         // Directory layout:
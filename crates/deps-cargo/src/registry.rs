@@ -4,6 +4,10 @@
 //! - Sparse index protocol (<https://index.crates.io>) for version lookups
 //! - REST API (<https://crates.io/api/v1>) for search
 //!
+//! Crates from alternate registries, or from a crates.io mirror configured
+//! through `[source]` replacement, are looked up in their own sparse index
+//! by the view [`CratesIoRegistry::with_routes`] returns for a manifest.
+//!
 //! All HTTP requests are cached aggressively using ETag/Last-Modified headers.
 //!
 //! # Examples
@@ -23,13 +27,15 @@
 //! }
 //! ```
 
+use crate::config::RegistryIndex;
 use crate::types::{CargoVersion, CrateInfo};
 use deps_core::{DepsError, HttpCache, Result};
 use semver::{Version, VersionReq};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 const SPARSE_INDEX_BASE: &str = "https://index.crates.io";
 const SEARCH_API_BASE: &str = "https://crates.io/api/v1";
@@ -52,12 +58,74 @@ pub fn crate_url(name: &str) -> String {
 #[derive(Clone)]
 pub struct CratesIoRegistry {
     cache: Arc<HttpCache>,
+    /// Sparse index crates.io crates are fetched from
+    index_url: Cow<'static, str>,
+    /// Index of crates not fetched from crates.io, by crate name
+    routes: Arc<HashMap<String, RegistryIndex>>,
+    /// Versions of each crate as last fetched, for lookups that must not
+    /// touch the network
+    fetched: Arc<RwLock<HashMap<String, Arc<[CargoVersion]>>>>,
 }
 
 impl CratesIoRegistry {
     /// Creates a new registry client with the given HTTP cache.
-    pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+    pub fn new(cache: Arc<HttpCache>) -> Self {
+        Self {
+            cache,
+            index_url: Cow::Borrowed(SPARSE_INDEX_BASE),
+            routes: Arc::default(),
+            fetched: Arc::default(),
+        }
+    }
+
+    /// Returns a client fetching crates.io crates from the sparse index at
+    /// `url` (a mirror, with or without the `sparse+` prefix), sharing this
+    /// client's cache. Search still uses crates.io.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        let url = url.strip_prefix("sparse+").unwrap_or(url);
+        Self {
+            cache: Arc::clone(&self.cache),
+            index_url: Cow::Owned(url.trim_end_matches('/').to_string()),
+            routes: Arc::clone(&self.routes),
+            fetched: Arc::default(),
+        }
+    }
+
+    /// Returns a client fetching `indexes` crates from their own registry
+    /// instead of crates.io, sharing this client's cache.
+    ///
+    /// Routes belong to the manifest whose `indexes` they are, so two
+    /// projects configuring the same crate differently don't interfere.
+    #[must_use]
+    pub fn with_routes(&self, indexes: HashMap<String, RegistryIndex>) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            index_url: self.index_url.clone(),
+            routes: Arc::new(indexes),
+            fetched: Arc::clone(&self.fetched),
+        }
+    }
+
+    /// Versions of `name` from its most recent fetch, newest first, without
+    /// any network request. `None` until the crate has been fetched once.
+    pub fn fetched_versions(&self, name: &str) -> Option<Arc<[CargoVersion]>> {
+        self.fetched
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(name)
+            .cloned()
+    }
+
+    pub(crate) fn remember(&self, name: &str, versions: &[CargoVersion]) {
+        self.fetched
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(name.to_string(), versions.into());
+    }
+
+    fn route_for(&self, name: &str) -> Option<&RegistryIndex> {
+        self.routes.get(name)
     }
 
     /// Fetches all versions for a crate from the sparse index.
@@ -87,10 +155,19 @@ impl CratesIoRegistry {
     /// # }
     /// ```
     pub async fn get_versions(&self, name: &str) -> Result<Vec<CargoVersion>> {
+        let versions = match self.route_for(name) {
+            Some(index) => self.get_versions_from(index, name).await?,
+            None => self.get_crates_io_versions(name).await?,
+        };
+        self.remember(name, &versions);
+        Ok(versions)
+    }
+
+    async fn get_crates_io_versions(&self, name: &str) -> Result<Vec<CargoVersion>> {
         let path = sparse_index_path(name);
-        // Pre-allocate: SPARSE_INDEX_BASE (25 chars) + "/" + path
-        let mut url = String::with_capacity(SPARSE_INDEX_BASE.len() + 1 + path.len());
-        url.push_str(SPARSE_INDEX_BASE);
+        // Pre-allocate: index URL + "/" + path
+        let mut url = String::with_capacity(self.index_url.len() + 1 + path.len());
+        url.push_str(&self.index_url);
         url.push('/');
         url.push_str(&path);
 
@@ -99,6 +176,38 @@ impl CratesIoRegistry {
         parse_index_json(&data, name)
     }
 
+    /// Fetches all versions of a crate from the sparse index of another
+    /// registry, authenticating with its `CARGO_REGISTRIES_<NAME>_TOKEN`.
+    async fn get_versions_from(
+        &self,
+        index: &RegistryIndex,
+        name: &str,
+    ) -> Result<Vec<CargoVersion>> {
+        let Some(base) = index.sparse_url() else {
+            let reason = if index.url.is_some() {
+                "only sparse registry indexes are supported"
+            } else {
+                "registry is not configured in .cargo/config.toml"
+            };
+            return Err(DepsError::Io(std::io::Error::other(format!(
+                "cannot fetch {name} from registry `{}`: {reason}",
+                index.name
+            ))));
+        };
+
+        let url = format!("{base}/{}", sparse_index_path(name));
+        let data = match index.token() {
+            Some(token) => {
+                self.cache
+                    .get_cached_with_headers(&url, &[(reqwest::header::AUTHORIZATION, &token)])
+                    .await?
+            }
+            None => self.cache.get_cached(&url).await?,
+        };
+
+        parse_index_json(&data, name)
+    }
+
     /// Finds the latest version matching the given semver requirement.
     ///
     /// Only returns non-yanked versions.
@@ -236,6 +345,45 @@ struct IndexEntry {
     yanked: bool,
     #[serde(default)]
     features: HashMap<String, Vec<String>>,
+    /// Features using `dep:` or `?` syntax, kept apart for older Cargo
+    #[serde(default)]
+    features2: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    deps: Vec<IndexDependency>,
+    #[serde(default)]
+    rust_version: Option<String>,
+    /// SHA-256 of the `.crate` archive, as recorded in `Cargo.lock`
+    #[serde(default)]
+    cksum: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IndexDependency {
+    /// Name the dependency is referred to by, after renaming
+    name: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+impl IndexEntry {
+    /// Every feature of the version: `features`, `features2` and the
+    /// implicit feature of each optional dependency not referenced with
+    /// `dep:` by any feature.
+    fn all_features(mut self) -> HashMap<String, Vec<String>> {
+        self.features.extend(self.features2);
+        for dep in self.deps.into_iter().filter(|dep| dep.optional) {
+            let dep_feature = format!("dep:{}", dep.name);
+            let hidden = self
+                .features
+                .values()
+                .flatten()
+                .any(|enabled| *enabled == dep_feature);
+            if !hidden && !self.features.contains_key(&dep.name) {
+                self.features.insert(dep.name, vec![dep_feature]);
+            }
+        }
+        self.features
+    }
 }
 
 /// Parses newline-delimited JSON from sparse index.
@@ -248,13 +396,19 @@ fn parse_index_json(data: &[u8], _crate_name: &str) -> Result<Vec<CargoVersion>>
         .lines()
         .filter(|line| !line.trim().is_empty())
         .filter_map(|line| {
-            let entry: IndexEntry = serde_json::from_str(line).ok()?;
+            let mut entry: IndexEntry = serde_json::from_str(line).ok()?;
             let parsed = entry.version.parse::<Version>().ok()?;
+            let num = entry.version.clone();
+            let yanked = entry.yanked;
+            let rust_version = entry.rust_version.take();
+            let checksum = entry.cksum.take();
             Some((
                 CargoVersion {
-                    num: entry.version,
-                    yanked: entry.yanked,
-                    features: entry.features,
+                    num,
+                    yanked,
+                    features: entry.all_features(),
+                    rust_version,
+                    checksum,
                 },
                 parsed,
             ))
@@ -347,6 +501,24 @@ impl deps_core::Registry for CratesIoRegistry {
         crate_url(name)
     }
 
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        Some(format!(
+            "{}/{}",
+            crate_url(name),
+            urlencoding::encode(version)
+        ))
+    }
+
+    fn cached_versions(&self, name: &str) -> Option<Vec<Box<dyn deps_core::Version>>> {
+        let versions = self.fetched_versions(name)?;
+        Some(
+            versions
+                .iter()
+                .map(|v| Box::new(v.clone()) as Box<dyn deps_core::Version>)
+                .collect(),
+        )
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -382,6 +554,44 @@ mod tests {
         assert!(!versions[0].yanked);
     }
 
+    #[test]
+    fn test_cached_versions() {
+        use deps_core::Registry as _;
+
+        let registry = CratesIoRegistry::new(Arc::new(HttpCache::new()));
+        assert!(registry.cached_versions("serde").is_none());
+
+        let json = r#"{"name":"serde","vers":"1.0.0","yanked":false,"features":{},"deps":[],"cksum":"abc"}"#;
+        registry.remember(
+            "serde",
+            &parse_index_json(json.as_bytes(), "serde").unwrap(),
+        );
+        let routed = registry.with_routes(HashMap::new());
+        let versions = routed.cached_versions("serde").unwrap();
+        assert_eq!(versions[0].version_string(), "1.0.0");
+        assert_eq!(versions[0].checksum(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_registry_is_not_fetched_from_crates_io() {
+        let registry = CratesIoRegistry::new(Arc::new(HttpCache::new()));
+        let mut indexes = HashMap::new();
+        indexes.insert(
+            "internal-crate".to_string(),
+            RegistryIndex {
+                name: "internal".to_string(),
+                url: None,
+            },
+        );
+        let routed = registry.with_routes(indexes);
+
+        let err = routed.get_versions("internal-crate").await.unwrap_err();
+        assert!(err.to_string().contains("internal-crate"));
+        assert_eq!(routed.route_for("serde"), None);
+        // Other manifests keep fetching from crates.io
+        assert_eq!(registry.route_for("internal-crate"), None);
+    }
+
     #[test]
     fn test_parse_index_json_with_yanked() {
         let json = r#"{"name":"test","vers":"0.1.0","yanked":true,"features":{},"deps":[]}
@@ -492,6 +702,38 @@ mod tests {
         assert!(versions[0].features.contains_key("std"));
     }
 
+    #[test]
+    fn test_parse_index_json_implicit_features() {
+        let json = r#"{"name":"test","vers":"1.0.0","yanked":false,"features":{"std":[]},"features2":{"json":["dep:serde_json"]},"deps":[{"name":"serde_json","optional":true},{"name":"log","optional":true},{"name":"libc","optional":false}]}"#;
+        let versions = parse_index_json(json.as_bytes(), "test").unwrap();
+
+        let mut features: Vec<_> = versions[0].features.keys().map(String::as_str).collect();
+        features.sort_unstable();
+        assert_eq!(features, ["json", "log", "std"]);
+    }
+
+    #[test]
+    fn test_parse_index_json_rust_version() {
+        let json = r#"{"name":"test","vers":"1.0.0","yanked":false,"features":{},"deps":[],"rust_version":"1.70"}
+{"name":"test","vers":"0.9.0","yanked":false,"features":{},"deps":[]}"#;
+        let versions = parse_index_json(json.as_bytes(), "test").unwrap();
+
+        assert_eq!(versions[0].rust_version.as_deref(), Some("1.70"));
+        assert_eq!(versions[1].rust_version, None);
+    }
+
+    #[test]
+    fn test_parse_index_json_checksum() {
+        let json = r#"{"name":"test","vers":"1.0.0","yanked":false,"features":{},"deps":[],"cksum":"6c2dc2b3c5ba8cd1"}"#;
+        let versions = parse_index_json(json.as_bytes(), "test").unwrap();
+
+        assert_eq!(versions[0].checksum.as_deref(), Some("6c2dc2b3c5ba8cd1"));
+        assert_eq!(
+            deps_core::Version::checksum(&versions[0]),
+            Some("6c2dc2b3c5ba8cd1")
+        );
+    }
+
     #[test]
     fn test_parse_search_response_empty() {
         let json = r#"{"crates": []}"#;
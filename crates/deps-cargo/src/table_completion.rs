@@ -0,0 +1,217 @@
+//! Completion of keys and values inside Cargo dependency tables.
+//!
+//! Typing inside `serde = { ... }` offers the keys Cargo accepts on a
+//! dependency, `true`/`false` for its boolean keys, and the branches and
+//! tags of the repository for `branch` and `tag` when `git` points to a
+//! repository on disk.
+//!
+//! # Examples
+//!
+//! ```
+//! use deps_cargo::table_completion::table_completions;
+//! use tower_lsp_server::ls_types::Position;
+//!
+//! let content = "[dependencies]\nserde = { version = \"1\", opt";
+//! let items = table_completions(content, Position::new(1, 28), None);
+//! assert_eq!(items[0].label, "optional");
+//! ```
+
+use deps_core::table_completion::{
+    self, TableContext, TableKey, TableSchema, TableTarget, ValueKind,
+};
+use std::path::Path;
+use tower_lsp_server::ls_types::{CompletionItem, Position};
+
+/// Keys of a Cargo dependency table.
+pub const DEPENDENCY_TABLE_SCHEMA: TableSchema = TableSchema {
+    keys: &[
+        TableKey {
+            name: "version",
+            value: ValueKind::String,
+            description: "Version requirement",
+        },
+        TableKey {
+            name: "features",
+            value: ValueKind::Array,
+            description: "Features to enable",
+        },
+        TableKey {
+            name: "default-features",
+            value: ValueKind::Boolean,
+            description: "Enable the default features",
+        },
+        TableKey {
+            name: "optional",
+            value: ValueKind::Boolean,
+            description: "Only build when a feature enables it",
+        },
+        TableKey {
+            name: "workspace",
+            value: ValueKind::Boolean,
+            description: "Inherit from [workspace.dependencies]",
+        },
+        TableKey {
+            name: "package",
+            value: ValueKind::String,
+            description: "Package name when renamed",
+        },
+        TableKey {
+            name: "registry",
+            value: ValueKind::String,
+            description: "Alternate registry name",
+        },
+        TableKey {
+            name: "path",
+            value: ValueKind::String,
+            description: "Local path to the package",
+        },
+        TableKey {
+            name: "git",
+            value: ValueKind::String,
+            description: "Git repository URL",
+        },
+        TableKey {
+            name: "branch",
+            value: ValueKind::GitBranch,
+            description: "Git branch",
+        },
+        TableKey {
+            name: "tag",
+            value: ValueKind::GitTag,
+            description: "Git tag",
+        },
+        TableKey {
+            name: "rev",
+            value: ValueKind::String,
+            description: "Git revision",
+        },
+    ],
+};
+
+/// Completes keys and values inside the dependency table at `position`.
+///
+/// Only inline tables that are the value of an entry of a dependency
+/// section (`[dependencies]`, `[target.'cfg(..)'.dev-dependencies]`,
+/// `[workspace.dependencies]`, `[patch.crates-io]`, ...) are completed.
+/// Relative git paths resolve against `manifest_dir`.
+pub fn table_completions(
+    content: &str,
+    position: Position,
+    manifest_dir: Option<&Path>,
+) -> Vec<CompletionItem> {
+    let Some((context, 1)) = table_completion::detect_inline_table(content, position) else {
+        return vec![];
+    };
+    if !in_dependency_section(content, position.line as usize) {
+        return vec![];
+    }
+
+    let git = match context.target {
+        TableTarget::Value { ref key, .. } if key == "branch" || key == "tag" => {
+            git_refs(&context, manifest_dir)
+        }
+        _ => None,
+    };
+    table_completion::complete_table(&DEPENDENCY_TABLE_SCHEMA, &context, git.as_ref())
+}
+
+fn git_refs(
+    context: &TableContext,
+    manifest_dir: Option<&Path>,
+) -> Option<table_completion::GitRefs> {
+    let url = context.value_of("git")?;
+    let repo = table_completion::local_repository(url, manifest_dir.unwrap_or(Path::new(".")))?;
+    table_completion::local_git_refs(&repo)
+}
+
+/// Whether `line` belongs to a table whose entries are dependencies.
+fn in_dependency_section(content: &str, line: usize) -> bool {
+    let Some(header) = content
+        .lines()
+        .take(line)
+        .map(str::trim)
+        .filter(|l| l.starts_with('[') && !l.starts_with("[["))
+        .last()
+    else {
+        return false;
+    };
+    let name = header
+        .trim_start_matches('[')
+        .split(']')
+        .next()
+        .unwrap_or_default()
+        .trim();
+
+    let last = name.rsplit('.').next().unwrap_or_default().trim();
+    matches!(
+        last,
+        "dependencies" | "dev-dependencies" | "build-dependencies"
+    ) || name.starts_with("patch.")
+        || name == "replace"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::CompletionTextEdit;
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_completes_keys_in_dependency_sections() {
+        let content = "[dependencies]\nserde = { version = \"1\", d";
+        let items = table_completions(content, Position::new(1, 26), None);
+        assert_eq!(labels(&items), vec!["default-features"]);
+
+        let content = "[target.'cfg(unix)'.dev-dependencies]\nnix = { ";
+        let items = table_completions(content, Position::new(1, 8), None);
+        assert_eq!(items.len(), DEPENDENCY_TABLE_SCHEMA.keys.len());
+
+        let content = "[patch.crates-io]\nserde = { g";
+        let items = table_completions(content, Position::new(1, 11), None);
+        assert_eq!(labels(&items), vec!["git"]);
+    }
+
+    #[test]
+    fn test_ignores_other_tables() {
+        let content = "[package]\nmetadata = { d";
+        assert!(table_completions(content, Position::new(1, 14), None).is_empty());
+
+        let content = "[dependencies.serde]\nfoo = { d";
+        assert!(table_completions(content, Position::new(1, 9), None).is_empty());
+
+        let content = "[dependencies]\nserde = { a = { d";
+        assert!(table_completions(content, Position::new(1, 17), None).is_empty());
+    }
+
+    #[test]
+    fn test_completes_boolean_values() {
+        let content = "[dependencies]\nserde = { version = \"1\", optional = ";
+        let items = table_completions(content, Position::new(1, 36), None);
+        assert_eq!(labels(&items), vec!["true", "false"]);
+    }
+
+    #[test]
+    fn test_completes_local_git_branches() {
+        let dir = tempfile::tempdir().unwrap();
+        let heads = dir.path().join("lib/.git/refs/heads");
+        std::fs::create_dir_all(&heads).unwrap();
+        std::fs::write(heads.join("main"), "0000\n").unwrap();
+        std::fs::write(heads.join("develop"), "0000\n").unwrap();
+
+        let content = "[dependencies]\nlib = { git = \"lib\", branch = \"ma\" }";
+        let items = table_completions(content, Position::new(1, 33), Some(dir.path()));
+        assert_eq!(labels(&items), vec!["main"]);
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(edit.new_text, "main");
+        assert_eq!(edit.range.start, Position::new(1, 31));
+
+        let content = "[dependencies]\nlib = { git = \"https://example.com/lib\", branch = \"";
+        let items = table_completions(content, Position::new(1, 51), Some(dir.path()));
+        assert!(items.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::CRATES_IO;
 use std::any::Any;
 use std::collections::HashMap;
 use tower_lsp_server::ls_types::Range;
@@ -24,8 +25,15 @@ pub use deps_core::parser::DependencySource;
 ///     version_range: Some(Range::new(Position::new(5, 9), Position::new(5, 14))),
 ///     features: vec!["derive".into()],
 ///     features_range: None,
+///     feature_ranges: vec![],
+///     default_features: true,
+///     optional: false,
+///     value_range: None,
 ///     source: DependencySource::Registry,
 ///     section: DependencySection::Dependencies,
+///     registry: None,
+///     package: None,
+///     overridden_by: None,
 /// };
 ///
 /// assert_eq!(dep.name, "serde");
@@ -39,8 +47,89 @@ pub struct ParsedDependency {
     pub version_range: Option<Range>,
     pub features: Vec<String>,
     pub features_range: Option<Range>,
+    /// Range of each entry of `features`, without quotes
+    pub feature_ranges: Vec<Range>,
+    /// `false` when declared with `default-features = false`
+    pub default_features: bool,
+    /// `optional = true`
+    pub optional: bool,
+    /// Range of the whole value when written inline (a quoted version or a
+    /// `{ … }` table), `None` for `[dependencies.name]` tables and dotted
+    /// keys
+    pub value_range: Option<Range>,
     pub source: DependencySource,
     pub section: DependencySection,
+    /// Alternate registry name (`registry = "my-registry"`)
+    pub registry: Option<String>,
+    /// Package name when the dependency is renamed
+    /// (`serde1 = { package = "serde", version = "1" }`), see
+    /// [`ParsedDependency::package_name`]
+    pub package: Option<String>,
+    /// Description of the `[patch]` or `[replace]` entry building this
+    /// dependency instead of the registry release, see
+    /// [`ParsedDependency::override_note`]
+    pub overridden_by: Option<String>,
+}
+
+impl ParsedDependency {
+    /// Name of the package on the registry: the `package` of a renamed
+    /// dependency, otherwise its key.
+    pub fn package_name(&self) -> &str {
+        self.package.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Describes this `[patch]` or `[replace]` entry as shown on the
+    /// dependencies it overrides: `patched → git <url> (<rev>)`,
+    /// `patched → path <path>` or `replaced → <version>`. `None` for
+    /// entries of other sections.
+    pub fn override_note(&self) -> Option<String> {
+        let verb = match self.section {
+            DependencySection::Patch { .. } => "patched",
+            DependencySection::Replace => "replaced",
+            _ => return None,
+        };
+        let target = match &self.source {
+            DependencySource::Git {
+                url,
+                rev: Some(rev),
+            } => format!("git {url} ({rev})"),
+            DependencySource::Git { url, rev: None } => format!("git {url}"),
+            DependencySource::Path { path } => format!("path {path}"),
+            _ => match &self.version_req {
+                Some(version) => version.clone(),
+                None => return Some(verb.to_string()),
+            },
+        };
+        Some(format!("{verb} → {target}"))
+    }
+
+    /// Whether this `[patch]` or `[replace]` entry applies to `dep`.
+    ///
+    /// `[patch.<registry>]` and `[replace]` override registry dependencies
+    /// (including inherited ones) of the same name and registry;
+    /// `[patch.<url>]` overrides git dependencies on that repository.
+    pub fn overrides(&self, dep: &Self) -> bool {
+        if dep.section.is_override() || self.package_name() != dep.package_name() {
+            return false;
+        }
+        let dep_registry = dep.registry.as_deref().unwrap_or(CRATES_IO);
+        let from_registry = matches!(
+            dep.source,
+            DependencySource::Registry
+                | DependencySource::Workspace
+                | DependencySource::CustomRegistry { .. }
+        );
+        match &self.section {
+            DependencySection::Patch { registry } => match &dep.source {
+                DependencySource::Git { url, .. } => {
+                    url.trim_end_matches('/') == registry.trim_end_matches('/')
+                }
+                _ => from_registry && registry == dep_registry,
+            },
+            DependencySection::Replace => from_registry && dep_registry == CRATES_IO,
+            _ => false,
+        }
+    }
 }
 
 /// Section in Cargo.toml where a dependency is declared.
@@ -51,6 +140,9 @@ pub struct ParsedDependency {
 /// - `[build-dependencies]`: Build script dependencies
 /// - `[workspace.dependencies]`: Workspace-wide dependency definitions
 ///
+/// The first three also exist per platform under `[target.<cfg>]`, and
+/// `[patch.<registry>]` and `[replace]` override other dependencies.
+///
 /// # Examples
 ///
 /// ```
@@ -59,7 +151,7 @@ pub struct ParsedDependency {
 /// let section = DependencySection::Dependencies;
 /// assert!(matches!(section, DependencySection::Dependencies));
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DependencySection {
     /// Runtime dependencies (`[dependencies]`)
     Dependencies,
@@ -69,6 +161,145 @@ pub enum DependencySection {
     BuildDependencies,
     /// Workspace-wide dependency definitions (`[workspace.dependencies]`)
     WorkspaceDependencies,
+    /// Platform-specific dependencies (`[target.'cfg(windows)'.dependencies]`)
+    Target {
+        /// `cfg(...)` expression or target triple, without quotes
+        target: String,
+        kind: TargetDependencyKind,
+    },
+    /// Source overrides (`[patch.crates-io]`, `[patch.'https://github.com/o/r']`)
+    Patch {
+        /// `crates-io` or the URL of the patched source
+        registry: String,
+    },
+    /// Deprecated source overrides (`[replace]`, keyed `"name:version"`)
+    Replace,
+}
+
+impl DependencySection {
+    /// Returns the `cfg(...)` expression or target triple of a
+    /// `[target.*]` section.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            Self::Target { target, .. } => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Whether entries of this section override other dependencies rather
+    /// than adding one.
+    pub const fn is_override(&self) -> bool {
+        matches!(self, Self::Patch { .. } | Self::Replace)
+    }
+
+    /// Whether dependencies of this section can be `optional` and referenced
+    /// from `[features]`: normal and build dependencies, per platform or not.
+    pub const fn can_be_optional(&self) -> bool {
+        matches!(
+            self,
+            Self::Dependencies
+                | Self::BuildDependencies
+                | Self::Target {
+                    kind: TargetDependencyKind::Normal | TargetDependencyKind::Build,
+                    ..
+                }
+        )
+    }
+}
+
+/// Kind of dependency table under `[target.<cfg>]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetDependencyKind {
+    /// `[target.<cfg>.dependencies]`
+    Normal,
+    /// `[target.<cfg>.dev-dependencies]`
+    Dev,
+    /// `[target.<cfg>.build-dependencies]`
+    Build,
+}
+
+impl TargetDependencyKind {
+    /// Table name of this kind (`dependencies`, `dev-dependencies`, ...).
+    pub const fn table_name(self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Dev => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        }
+    }
+}
+
+/// Feature declared in the manifest's `[features]` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFeature {
+    pub name: String,
+    pub name_range: Range,
+    /// Entries of the feature's list
+    pub enables: Vec<FeatureReference>,
+}
+
+/// Entry of a `[features]` list, with its range without quotes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureReference {
+    pub value: String,
+    pub range: Range,
+}
+
+impl FeatureReference {
+    /// What the entry enables, see [`FeatureTarget`].
+    pub fn target(&self) -> FeatureTarget<'_> {
+        if let Some(dependency) = self.value.strip_prefix("dep:") {
+            return FeatureTarget::Dependency(dependency);
+        }
+        match self.value.split_once('/') {
+            Some((dependency, feature)) => match dependency.strip_suffix('?') {
+                Some(dependency) => FeatureTarget::DependencyFeature {
+                    dependency,
+                    feature,
+                    weak: true,
+                },
+                None => FeatureTarget::DependencyFeature {
+                    dependency,
+                    feature,
+                    weak: false,
+                },
+            },
+            None => FeatureTarget::Feature(&self.value),
+        }
+    }
+}
+
+/// Target of a `[features]` entry.
+///
+/// # Examples
+///
+/// ```
+/// use deps_cargo::types::{FeatureReference, FeatureTarget};
+/// use tower_lsp_server::ls_types::Range;
+///
+/// let reference = FeatureReference {
+///     value: "serde?/std".into(),
+///     range: Range::default(),
+/// };
+/// assert_eq!(
+///     reference.target(),
+///     FeatureTarget::DependencyFeature { dependency: "serde", feature: "std", weak: true }
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureTarget<'a> {
+    /// `std`: another feature, or the implicit feature of an optional
+    /// dependency
+    Feature(&'a str),
+    /// `dep:serde`: an optional dependency
+    Dependency(&'a str),
+    /// `serde/std`, or `serde?/std` (`weak`) which does not enable an
+    /// optional `serde` itself
+    DependencyFeature {
+        dependency: &'a str,
+        feature: &'a str,
+        weak: bool,
+    },
 }
 
 /// Version information for a crate from crates.io.
@@ -90,6 +321,8 @@ pub enum DependencySection {
 ///         f.insert("derive".into(), vec!["serde_derive".into()]);
 ///         f
 ///     },
+///     rust_version: Some("1.61".into()),
+///     checksum: None,
 /// };
 ///
 /// assert!(!version.yanked);
@@ -100,6 +333,11 @@ pub struct CargoVersion {
     pub num: String,
     pub yanked: bool,
     pub features: HashMap<String, Vec<String>>,
+    /// Minimum supported Rust version declared by the release
+    /// (`package.rust-version`)
+    pub rust_version: Option<String>,
+    /// SHA-256 of the published `.crate` archive (sparse index `cksum`)
+    pub checksum: Option<String>,
 }
 
 /// Crate metadata from crates.io search API.
@@ -135,7 +373,7 @@ pub struct CrateInfo {
 
 impl deps_core::Dependency for ParsedDependency {
     fn name(&self) -> &str {
-        &self.name
+        self.package_name()
     }
 
     fn name_range(&self) -> Range {
@@ -162,6 +400,18 @@ impl deps_core::Dependency for ParsedDependency {
         self.features_range
     }
 
+    fn markers(&self) -> Option<&str> {
+        self.section.target()
+    }
+
+    fn overridden_by(&self) -> Option<&str> {
+        self.overridden_by.as_deref()
+    }
+
+    fn alias(&self) -> Option<&str> {
+        (self.package_name() != self.name).then_some(self.name.as_str())
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -180,6 +430,10 @@ impl deps_core::Version for CargoVersion {
         self.features.keys().cloned().collect()
     }
 
+    fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -254,12 +508,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_target_section() {
+        let section = DependencySection::Target {
+            target: "cfg(windows)".into(),
+            kind: TargetDependencyKind::Dev,
+        };
+        assert_eq!(section.target(), Some("cfg(windows)"));
+        assert_eq!(DependencySection::Dependencies.target(), None);
+        assert_eq!(TargetDependencyKind::Dev.table_name(), "dev-dependencies");
+    }
+
     #[test]
     fn test_cargo_version_creation() {
         let version = CargoVersion {
             num: "1.0.0".into(),
             yanked: false,
             features: HashMap::new(),
+            rust_version: None,
+            checksum: None,
         };
 
         assert_eq!(version.num, "1.0.0");
@@ -0,0 +1,552 @@
+//! Consistency of dependency declarations across a Cargo workspace.
+//!
+//! Members drift apart: one crate pins `serde = "1.0.150"`, another
+//! `"1.0.190"` and a third inherits `serde = { workspace = true }`. Members
+//! pinning a crate the root declares in `[workspace.dependencies]`, or
+//! pinning another requirement than their siblings, are reported, and
+//! [`inherit_edit`] rewrites a pinned entry to inherit from the root.
+//!
+//! In the other direction, the root lists the members inheriting each of
+//! its `[workspace.dependencies]` entries, and entries no manifest inherits
+//! are reported as unused.
+
+use crate::parser::{ParseResult, parse_cargo_toml};
+use crate::types::{DependencySection, DependencySource, ParsedDependency};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString,
+    Position, Range, TextEdit, Uri, WorkspaceEdit,
+};
+
+/// Diagnostic code of members pinning a crate declared in
+/// `[workspace.dependencies]`.
+pub const WORKSPACE_DEPENDENCY_CODE: &str = "workspace-dependency";
+
+/// Diagnostic code of members pinning other requirements than their
+/// siblings.
+pub const DIVERGING_VERSION_CODE: &str = "diverging-version";
+
+/// Diagnostic code of `[workspace.dependencies]` entries no manifest
+/// inherits.
+pub const UNUSED_WORKSPACE_DEPENDENCY_CODE: &str = "unused-workspace-dependency";
+
+/// Manifest of a workspace, read from disk.
+#[derive(Debug)]
+pub struct WorkspaceManifest {
+    pub path: PathBuf,
+    pub uri: Uri,
+    pub content: String,
+    pub result: ParseResult,
+}
+
+/// Root and member manifests of a workspace.
+#[derive(Debug)]
+pub struct Workspace {
+    pub root: WorkspaceManifest,
+    pub members: Vec<WorkspaceManifest>,
+}
+
+impl Workspace {
+    /// Reads the `Cargo.toml` in `root` and those of its `workspace.members`
+    /// (minus `workspace.exclude`). Unreadable members are skipped.
+    pub async fn load(root: &Path) -> Option<Self> {
+        let root_manifest = read_manifest(root.join("Cargo.toml")).await?;
+        let (patterns, exclude) = member_globs(&root_manifest.content);
+
+        let mut members = Vec::new();
+        for dir in deps_core::workspace::expand_member_globs(root, &patterns, &exclude) {
+            if dir == root {
+                continue;
+            }
+            if let Some(member) = read_manifest(dir.join("Cargo.toml")).await {
+                members.push(member);
+            }
+        }
+        Some(Self {
+            root: root_manifest,
+            members,
+        })
+    }
+
+    /// Every manifest of the workspace, the root first.
+    pub fn manifests(&self) -> impl Iterator<Item = &WorkspaceManifest> {
+        std::iter::once(&self.root).chain(&self.members)
+    }
+
+    /// Entry of `name` in the root's `[workspace.dependencies]`.
+    pub fn workspace_dependency(&self, name: &str) -> Option<&ParsedDependency> {
+        self.root.result.dependencies.iter().find(|dep| {
+            dep.name == name && matches!(dep.section, DependencySection::WorkspaceDependencies)
+        })
+    }
+
+    /// Manifests inheriting `name` with `workspace = true`, as directories
+    /// relative to the root.
+    ///
+    /// `doc` replaces the manifest of the same URI read from disk.
+    pub fn consumers(&self, doc: &ParseResult, name: &str) -> Vec<String> {
+        self.manifests()
+            .filter(|manifest| {
+                let result = if manifest.uri == doc.uri {
+                    doc
+                } else {
+                    &manifest.result
+                };
+                result
+                    .dependencies
+                    .iter()
+                    .any(|dep| dep.name == name && dep.source == DependencySource::Workspace)
+            })
+            .map(|manifest| self.display_dir(manifest))
+            .collect()
+    }
+
+    /// Directory of `manifest` relative to the workspace root, for messages.
+    pub(crate) fn display_dir(&self, manifest: &WorkspaceManifest) -> String {
+        let root = self.root.path.parent();
+        manifest
+            .path
+            .parent()
+            .zip(root)
+            .and_then(|(dir, root)| dir.strip_prefix(root).ok())
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map_or_else(
+                || "the workspace root".to_string(),
+                |relative| relative.to_string_lossy().replace('\\', "/"),
+            )
+    }
+}
+
+/// Whether `dep` pins its own registry version instead of inheriting it.
+pub fn is_pinned(dep: &ParsedDependency) -> bool {
+    matches!(
+        dep.source,
+        DependencySource::Registry | DependencySource::CustomRegistry { .. }
+    ) && dep.version_req.is_some()
+        && !matches!(dep.section, DependencySection::WorkspaceDependencies)
+        && !dep.section.is_override()
+}
+
+/// Reports the pinned dependencies of `doc` that should inherit from the
+/// workspace root or differ from the requirement of other manifests.
+///
+/// `doc` replaces the manifest of the same URI read from disk, so unsaved
+/// edits are taken into account.
+pub fn consistency_diagnostics(workspace: &Workspace, doc: &ParseResult) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for dep in doc.dependencies.iter().filter(|dep| is_pinned(dep)) {
+        if workspace.workspace_dependency(&dep.name).is_some() {
+            diagnostics.push(Diagnostic {
+                range: dep.name_range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(WORKSPACE_DEPENDENCY_CODE.into())),
+                message: format!(
+                    "`{}` is declared in [workspace.dependencies], inherit it with `workspace = true`",
+                    dep.name
+                ),
+                source: Some("deps-lsp".into()),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let others: Vec<String> = workspace
+            .manifests()
+            .filter(|manifest| manifest.uri != doc.uri)
+            .filter_map(|manifest| {
+                let other = manifest
+                    .result
+                    .dependencies
+                    .iter()
+                    .find(|other| other.name == dep.name && is_pinned(other))?;
+                let requirement = other.version_req.as_deref()?;
+                (Some(requirement) != dep.version_req.as_deref())
+                    .then(|| format!("{requirement} in {}", workspace.display_dir(manifest)))
+            })
+            .collect();
+        if others.is_empty() {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            range: dep.version_range.unwrap_or(dep.name_range),
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            code: Some(NumberOrString::String(DIVERGING_VERSION_CODE.into())),
+            message: format!(
+                "Other workspace members require another version of `{}`: {}",
+                dep.name,
+                others.join(", ")
+            ),
+            source: Some("deps-lsp".into()),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Entries of the root's `[workspace.dependencies]` no manifest inherits.
+///
+/// Empty unless `doc` is the workspace root.
+pub fn unused_dependencies<'a>(
+    workspace: &Workspace,
+    doc: &'a ParseResult,
+) -> Vec<&'a ParsedDependency> {
+    if doc.uri != workspace.root.uri {
+        return vec![];
+    }
+    doc.dependencies
+        .iter()
+        .filter(|dep| matches!(dep.section, DependencySection::WorkspaceDependencies))
+        .filter(|dep| workspace.consumers(doc, &dep.name).is_empty())
+        .collect()
+}
+
+/// Reports the `[workspace.dependencies]` entries of the root `doc` that
+/// no manifest inherits.
+pub fn unused_diagnostics(workspace: &Workspace, doc: &ParseResult) -> Vec<Diagnostic> {
+    unused_dependencies(workspace, doc)
+        .into_iter()
+        .map(|dep| Diagnostic {
+            range: dep.name_range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(
+                UNUSED_WORKSPACE_DEPENDENCY_CODE.into(),
+            )),
+            message: format!("`{}` is not inherited by any workspace member", dep.name),
+            source: Some("deps-lsp".into()),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Removes the unused workspace dependency `dep` from the root at `uri`.
+///
+/// `None` for entries not written inline (`[workspace.dependencies.name]`
+/// tables and dotted keys).
+pub fn remove_unused_action(dep: &ParsedDependency, uri: &Uri) -> Option<CodeAction> {
+    let value = dep.value_range?;
+    let edit = TextEdit {
+        range: Range::new(
+            Position::new(dep.name_range.start.line, 0),
+            Position::new(value.end.line + 1, 0),
+        ),
+        new_text: String::new(),
+    };
+    Some(CodeAction {
+        title: format!("Remove unused workspace dependency `{}`", dep.name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// Rewrites a pinned entry to `{ workspace = true }`, keeping its local
+/// `features` and `optional`.
+///
+/// Members can only turn default features back on, so when the workspace
+/// entry disables them (`workspace_default_features` is false) an entry
+/// using them gets `default-features = true`.
+///
+/// `None` for entries not written inline (`[dependencies.name]` tables and
+/// dotted keys).
+pub fn inherit_edit(dep: &ParsedDependency, workspace_default_features: bool) -> Option<TextEdit> {
+    let mut keys = vec!["workspace = true".to_string()];
+    if dep.default_features && !workspace_default_features {
+        keys.push("default-features = true".to_string());
+    }
+    if !dep.features.is_empty() {
+        let features: Vec<String> = dep.features.iter().map(|f| format!("\"{f}\"")).collect();
+        keys.push(format!("features = [{}]", features.join(", ")));
+    }
+    if dep.optional {
+        keys.push("optional = true".to_string());
+    }
+    Some(TextEdit {
+        range: dep.value_range?,
+        new_text: format!("{{ {} }}", keys.join(", ")),
+    })
+}
+
+/// Entry added to `[workspace.dependencies]` when hoisting `dep`.
+pub fn workspace_entry(dep: &ParsedDependency) -> String {
+    let version = dep.version_req.as_deref().unwrap_or("*");
+    let mut keys = Vec::new();
+    if let Some(package) = &dep.package {
+        keys.push(format!("package = \"{package}\""));
+    }
+    keys.push(format!("version = \"{version}\""));
+    if let Some(registry) = &dep.registry {
+        keys.push(format!("registry = \"{registry}\""));
+    }
+    if !dep.default_features {
+        keys.push("default-features = false".to_string());
+    }
+    match keys.as_slice() {
+        [_] => format!("{} = \"{version}\"", dep.name),
+        _ => format!("{} = {{ {} }}", dep.name, keys.join(", ")),
+    }
+}
+
+/// Whether requirements `a` and `b` select the same semver-compatible
+/// release line (`1.0.150` and `1.0.190`, but not `0.9` and `0.10`).
+///
+/// Requirements that don't parse, or have no lower bound like `*`, are
+/// only compatible with themselves.
+pub fn is_compatible_requirement(a: &str, b: &str) -> bool {
+    match (compatibility_line(a), compatibility_line(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.trim() == b.trim(),
+    }
+}
+
+/// Components of the lowest version `requirement` accepts up to its
+/// leftmost non-zero one, which semver-compatible releases share.
+fn compatibility_line(requirement: &str) -> Option<(u64, Option<u64>, Option<u64>)> {
+    use semver::Op;
+
+    let req = semver::VersionReq::parse(requirement).ok()?;
+    let lower = req.comparators.iter().find(|comparator| {
+        matches!(
+            comparator.op,
+            Op::Exact | Op::Greater | Op::GreaterEq | Op::Tilde | Op::Caret | Op::Wildcard
+        )
+    })?;
+    Some(match (lower.major, lower.minor) {
+        (0, Some(0)) => (0, Some(0), lower.patch),
+        (0, minor) => (0, minor, None),
+        (major, _) => (major, None, None),
+    })
+}
+
+async fn read_manifest(path: PathBuf) -> Option<WorkspaceManifest> {
+    let content = tokio::fs::read_to_string(&path).await.ok()?;
+    let uri = Uri::from_file_path(&path)?;
+    let result = parse_cargo_toml(&content, &uri).ok()?;
+    Some(WorkspaceManifest {
+        path,
+        uri,
+        content,
+        result,
+    })
+}
+
+/// `workspace.members` and `workspace.exclude` of a root manifest.
+fn member_globs(content: &str) -> (Vec<String>, Vec<String>) {
+    let Ok(doc) = toml_span::parse(content) else {
+        return (Vec::new(), Vec::new());
+    };
+    let strings = |key: &str| -> Vec<String> {
+        doc.as_table()
+            .and_then(|root| root.get("workspace"))
+            .and_then(|workspace| workspace.as_table())
+            .and_then(|workspace| workspace.get(key))
+            .and_then(|value| value.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    (strings("members"), strings("exclude"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    async fn workspace() -> (tempfile::TempDir, Workspace) {
+        let root = tempfile::tempdir().unwrap();
+        write(
+            &root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/legacy\"]\n\n[workspace.dependencies]\nanyhow = \"1\"\ntokio = \"1.40\"\n",
+        );
+        write(
+            &root.path().join("crates/a/Cargo.toml"),
+            "[package]\nname = \"a\"\n\n[dependencies]\nserde = { version = \"1.0.150\", features = [\"derive\"], optional = true }\ntokio = \"1.38\"\n",
+        );
+        write(
+            &root.path().join("crates/b/Cargo.toml"),
+            "[package]\nname = \"b\"\n\n[dependencies]\nserde = \"1.0.190\"\ntokio = { workspace = true }\n",
+        );
+        write(
+            &root.path().join("crates/legacy/Cargo.toml"),
+            "[package]\nname = \"legacy\"\n\n[dependencies]\nserde = \"0.9\"\n",
+        );
+        let workspace = Workspace::load(root.path()).await.unwrap();
+        (root, workspace)
+    }
+
+    #[tokio::test]
+    async fn test_load_members() {
+        let (_root, workspace) = workspace().await;
+        assert_eq!(workspace.members.len(), 2);
+        assert_eq!(
+            workspace
+                .workspace_dependency("tokio")
+                .unwrap()
+                .version_req
+                .as_deref(),
+            Some("1.40")
+        );
+        assert!(workspace.workspace_dependency("serde").is_none());
+        assert_eq!(workspace.display_dir(&workspace.members[1]), "crates/b");
+        assert_eq!(workspace.display_dir(&workspace.root), "the workspace root");
+    }
+
+    #[tokio::test]
+    async fn test_consistency_diagnostics() {
+        let (_root, workspace) = workspace().await;
+        let diagnostics = consistency_diagnostics(&workspace, &workspace.members[0].result);
+
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Other workspace members require another version of `serde`: 1.0.190 in crates/b",
+                "`tokio` is declared in [workspace.dependencies], inherit it with `workspace = true`",
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].code,
+            Some(NumberOrString::String(WORKSPACE_DEPENDENCY_CODE.into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inherit_edit_keeps_features_and_optional() {
+        let (_root, workspace) = workspace().await;
+        let serde = &workspace.members[0].result.dependencies[0];
+        let edit = inherit_edit(serde, true).unwrap();
+        assert_eq!(
+            edit.new_text,
+            "{ workspace = true, features = [\"derive\"], optional = true }"
+        );
+        assert_eq!(edit.range.start.character, 8);
+
+        let serde = &workspace.members[1].result.dependencies[0];
+        let edit = inherit_edit(serde, true).unwrap();
+        assert_eq!(edit.new_text, "{ workspace = true }");
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (8, 17)
+        );
+        assert_eq!(workspace_entry(serde), "serde = \"1.0.190\"");
+    }
+
+    #[test]
+    fn test_workspace_entry_keeps_rename() {
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let toml = "[dependencies]\nserde1 = { package = \"serde\", version = \"1\" }\n";
+        let result = parse_cargo_toml(toml, &uri).unwrap();
+        assert_eq!(
+            workspace_entry(&result.dependencies[0]),
+            "serde1 = { package = \"serde\", version = \"1\" }"
+        );
+    }
+
+    #[test]
+    fn test_default_features_follow_the_workspace_entry() {
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let toml = "[dependencies]\ntokio = { version = \"1\", default-features = false }\nserde = \"1\"\n";
+        let result = parse_cargo_toml(toml, &uri).unwrap();
+        let dep = |name: &str| {
+            result
+                .dependencies
+                .iter()
+                .find(|dep| dep.name == name)
+                .unwrap()
+        };
+        let (tokio, serde) = (dep("tokio"), dep("serde"));
+
+        assert_eq!(
+            workspace_entry(tokio),
+            "tokio = { version = \"1\", default-features = false }"
+        );
+        assert_eq!(
+            inherit_edit(tokio, false).unwrap().new_text,
+            "{ workspace = true }"
+        );
+        assert_eq!(
+            inherit_edit(serde, false).unwrap().new_text,
+            "{ workspace = true, default-features = true }"
+        );
+        assert_eq!(
+            inherit_edit(serde, true).unwrap().new_text,
+            "{ workspace = true }"
+        );
+    }
+
+    #[test]
+    fn test_is_compatible_requirement() {
+        assert!(is_compatible_requirement("1.0.150", "1.0.190"));
+        assert!(is_compatible_requirement("1", "^1.2"));
+        assert!(is_compatible_requirement("0.9.1", "~0.9"));
+        assert!(!is_compatible_requirement("1", "2"));
+        assert!(!is_compatible_requirement("0.9", "0.10"));
+        assert!(!is_compatible_requirement("0.0.1", "0.0.2"));
+        assert!(is_compatible_requirement("*", "*"));
+        assert!(!is_compatible_requirement("*", "1"));
+    }
+
+    #[test]
+    fn test_inherit_edit_skips_table_entries() {
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let result = parse_cargo_toml("[dependencies.serde]\nversion = \"1\"\n", &uri).unwrap();
+        assert!(inherit_edit(&result.dependencies[0], true).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumers_and_unused_dependencies() {
+        let (_root, workspace) = workspace().await;
+        let root = &workspace.root.result;
+        assert_eq!(workspace.consumers(root, "tokio"), ["crates/b"]);
+        assert!(workspace.consumers(root, "anyhow").is_empty());
+
+        let unused = unused_dependencies(&workspace, root);
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].name, "anyhow");
+        assert!(unused_dependencies(&workspace, &workspace.members[1].result).is_empty());
+
+        let diagnostics = unused_diagnostics(&workspace, root);
+        assert_eq!(
+            diagnostics[0].message,
+            "`anyhow` is not inherited by any workspace member"
+        );
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        let action = remove_unused_action(unused[0], &workspace.root.uri).unwrap();
+        let edits = &action.edit.unwrap().changes.unwrap()[&workspace.root.uri];
+        assert_eq!(
+            edits[0].range,
+            Range::new(Position::new(5, 0), Position::new(6, 0))
+        );
+        assert!(edits[0].new_text.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consumers_use_unsaved_document() {
+        let (_root, workspace) = workspace().await;
+        let member = &workspace.members[1];
+        let edited = parse_cargo_toml(
+            "[package]\nname = \"b\"\n\n[dependencies]\nanyhow = { workspace = true }\n",
+            &member.uri,
+        )
+        .unwrap();
+        assert_eq!(workspace.consumers(&edited, "anyhow"), ["crates/b"]);
+        assert!(workspace.consumers(&edited, "tokio").is_empty());
+    }
+}
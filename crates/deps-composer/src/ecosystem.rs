@@ -5,14 +5,18 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::ComposerFormatter;
 use crate::registry::PackagistRegistry;
+use crate::types::ComposerSection;
 
 /// Composer ecosystem implementation.
 ///
@@ -87,6 +91,13 @@ impl Ecosystem for ComposerEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: ComposerFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::ComposerLockParser))
     }
@@ -120,11 +131,71 @@ impl Ecosystem for ComposerEcosystem {
         })
     }
 
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let section = match &dependency.kind {
+                DependencyKind::Normal => ComposerSection::Require,
+                DependencyKind::Dev | DependencyKind::Test => ComposerSection::RequireDev,
+                DependencyKind::Other(name) if name == "require" => ComposerSection::Require,
+                DependencyKind::Other(name) if name == "require-dev" => ComposerSection::RequireDev,
+                _ => return None,
+            };
+            let key = section_key(section);
+            let version = if add_dependency::is_bare_version(&dependency.version) {
+                format!("^{}", dependency.version)
+            } else {
+                dependency.version.clone()
+            };
+            let entry = format!("\"{}\": \"{version}\"", dependency.name);
+
+            let edits = match add_dependency::insert_into_section(
+                content,
+                &dependency_sections(parse_result),
+                key,
+                &dependency.name,
+                &entry,
+            ) {
+                Some(edits) => edits,
+                None => vec![add_dependency::insert_json_member(content, key, &entry)?],
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Groups dependencies by `require` / `require-dev` object.
+fn dependency_sections(parse_result: &dyn ParseResultTrait) -> Vec<SortableSection> {
+    let Some(result) = parse_result
+        .as_any()
+        .downcast_ref::<crate::parser::ComposerParseResult>()
+    else {
+        return vec![];
+    };
+    let entries = result.dependencies.iter().map(|dep| {
+        (
+            section_key(dep.section).to_string(),
+            SortableEntry::new(dep.name.clone(), dep.name_range.start.line),
+        )
+    });
+    SortableSection::group(SectionStyle::JSON_OBJECT, entries)
+}
+
+const fn section_key(section: ComposerSection) -> &'static str {
+    match section {
+        ComposerSection::Require => "require",
+        ComposerSection::RequireDev => "require-dev",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,4 +302,48 @@ mod tests {
             .await;
         assert!(completions.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = ComposerEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/composer.json");
+        let content = "{\n    \"require\": {\n        \"laravel/framework\": \"^11.0\",\n        \"symfony/console\": \"^7.0\"\n    }\n}\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "2.1.0".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("monolog/monolog", None),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "{\n    \"require\": {\n        \"laravel/framework\": \"^11.0\",\n        \"monolog/monolog\": \"^2.1.0\",\n        \"symfony/console\": \"^7.0\"\n    }\n}\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("phpunit/phpunit", Some("require-dev")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "{\n    \"require\": {\n        \"laravel/framework\": \"^11.0\",\n        \"symfony/console\": \"^7.0\"\n    },\n    \"require-dev\": {\n        \"phpunit/phpunit\": \"^2.1.0\"\n    }\n}\n"
+        );
+    }
 }
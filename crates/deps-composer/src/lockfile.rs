@@ -9,6 +9,7 @@ use deps_core::lockfile::{
     locate_lockfile_for_manifest, read_lockfile_content,
 };
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tower_lsp_server::ls_types::Uri;
 
@@ -44,6 +45,8 @@ struct LockPackage {
     version: String,
     #[serde(default)]
     source: Option<LockSource>,
+    #[serde(default)]
+    require: HashMap<String, serde_json::Value>,
 }
 
 /// Source entry in composer.lock package.
@@ -97,11 +100,21 @@ impl LockFileProvider for ComposerLockParser {
                     },
                 );
 
+                let mut dependencies: Vec<String> = pkg
+                    .require
+                    .into_keys()
+                    // Platform requirements (`php`, `ext-json`) have no vendor
+                    // prefix and are never locked packages
+                    .filter(|name| name.contains('/'))
+                    .map(|name| name.to_lowercase())
+                    .collect();
+                dependencies.sort();
+
                 packages.insert(ResolvedPackage {
                     name: pkg.name.to_lowercase(),
                     version: pkg.version,
                     source,
-                    dependencies: Vec::new(),
+                    dependencies,
                 });
             }
 
@@ -154,6 +167,47 @@ mod tests {
         assert_eq!(resolved.get_version("phpunit/phpunit"), Some("10.0.0"));
     }
 
+    #[tokio::test]
+    async fn test_parse_require_dependencies() {
+        let content = r#"{
+  "packages": [
+    {
+      "name": "symfony/console",
+      "version": "6.0.0",
+      "require": {
+        "php": ">=8.0.2",
+        "ext-mbstring": "*",
+        "Symfony/String": "^5.4|^6.0",
+        "psr/container": "^1.1|^2.0"
+      }
+    },
+    {
+      "name": "psr/container",
+      "version": "2.0.2"
+    }
+  ]
+}"#;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let lock_path = temp_dir.path().join("composer.lock");
+        tokio::fs::write(&lock_path, content).await.unwrap();
+
+        let parser = ComposerLockParser;
+        let resolved = parser.parse_lockfile(&lock_path).await.unwrap();
+
+        assert_eq!(
+            resolved.get("symfony/console").unwrap().dependencies,
+            vec!["psr/container", "symfony/string"]
+        );
+        assert!(
+            resolved
+                .get("psr/container")
+                .unwrap()
+                .dependencies
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_parse_git_source() {
         let content = r#"{
@@ -12,6 +12,7 @@ use crate::types::{ComposerPackage, ComposerVersion};
 use deps_core::{DepsError, HttpCache, Result};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 const PACKAGIST_BASE: &str = "https://repo.packagist.org";
@@ -45,12 +46,27 @@ pub const REGISTRY: &str = "Packagist";
 #[derive(Clone)]
 pub struct PackagistRegistry {
     cache: Arc<HttpCache>,
+    repo_url: Cow<'static, str>,
 }
 
 impl PackagistRegistry {
     /// Creates a new Packagist registry client with the given HTTP cache.
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            repo_url: Cow::Borrowed(PACKAGIST_BASE),
+        }
+    }
+
+    /// Returns a client for the Composer repository at `url` (serving
+    /// `/p2/{vendor}/{package}.json` as Packagist does), sharing this
+    /// client's cache. Search still uses packagist.org.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            repo_url: Cow::Owned(url.trim_end_matches('/').to_string()),
+        }
     }
 
     /// Fetches all versions for a package from the Packagist v2 API.
@@ -65,12 +81,13 @@ impl PackagistRegistry {
         // Packagist names are vendor/package; encode each segment separately
         let url = if let Some((vendor, package)) = name.split_once('/') {
             format!(
-                "{PACKAGIST_BASE}/p2/{}/{}.json",
+                "{}/p2/{}/{}.json",
+                self.repo_url,
                 urlencoding::encode(vendor),
                 urlencoding::encode(package)
             )
         } else {
-            format!("{PACKAGIST_BASE}/p2/{}.json", urlencoding::encode(name))
+            format!("{}/p2/{}.json", self.repo_url, urlencoding::encode(name))
         };
         let data = self.cache.get_cached(&url).await?;
         parse_package_metadata(name, &data)
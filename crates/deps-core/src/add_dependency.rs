@@ -0,0 +1,425 @@
+//! Inserting new dependencies into manifests.
+//!
+//! [`Ecosystem::add_dependency`](crate::Ecosystem::add_dependency) turns a
+//! [`NewDependency`] into a `WorkspaceEdit`. Entries go into an existing
+//! section through [`crate::sort::insert_sorted`], which keeps sorted
+//! sections sorted; the helpers here cover sections that have no entries yet
+//! and the text conventions shared by several manifest formats.
+
+use crate::sort::SortableSection;
+use std::collections::HashMap;
+use tower_lsp_server::ls_types::{Position, Range, TextEdit, Uri, WorkspaceEdit};
+
+/// Kind of dependency requested, independent of manifest syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// Regular runtime dependency.
+    Normal,
+    /// Development-only dependency.
+    Dev,
+    /// Build script dependency.
+    Build,
+    /// Test-only dependency.
+    Test,
+    /// Peer dependency.
+    Peer,
+    /// Optional dependency.
+    Optional,
+    /// Ecosystem-specific section, group, scope or configuration name
+    /// (e.g. a Gemfile group or a Gradle configuration).
+    Other(String),
+}
+
+impl DependencyKind {
+    /// Interprets a section name given by the user.
+    ///
+    /// Accepts short names (`dev`, `build`, `test`) as well as the section
+    /// names of the manifest formats (`dev-dependencies`, `devDependencies`).
+    /// Unknown names are kept as [`DependencyKind::Other`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_core::add_dependency::DependencyKind;
+    ///
+    /// assert_eq!(DependencyKind::parse(None), DependencyKind::Normal);
+    /// assert_eq!(DependencyKind::parse(Some("devDependencies")), DependencyKind::Dev);
+    /// assert_eq!(DependencyKind::parse(Some("build-dependencies")), DependencyKind::Build);
+    /// assert_eq!(
+    ///     DependencyKind::parse(Some("provided")),
+    ///     DependencyKind::Other("provided".into())
+    /// );
+    /// ```
+    pub fn parse(section: Option<&str>) -> Self {
+        let Some(section) = section.map(str::trim).filter(|s| !s.is_empty()) else {
+            return Self::Normal;
+        };
+        let lower = section.to_lowercase();
+        let short = lower
+            .trim_end_matches("dependencies")
+            .trim_end_matches(['-', '_']);
+        match short {
+            "" | "normal" | "runtime" | "main" | "default" | "prod" | "production" => Self::Normal,
+            "dev" | "development" => Self::Dev,
+            "build" => Self::Build,
+            "test" => Self::Test,
+            "peer" => Self::Peer,
+            "optional" => Self::Optional,
+            _ => Self::Other(section.to_string()),
+        }
+    }
+}
+
+/// A dependency to add to a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewDependency {
+    /// Package name as the ecosystem spells it.
+    pub name: String,
+    /// Version or version requirement. A bare version (see
+    /// [`is_bare_version`]) gets the ecosystem's default requirement
+    /// operator; anything else is written as given.
+    pub version: String,
+    /// Section to add the dependency to.
+    pub kind: DependencyKind,
+}
+
+/// Returns `true` for a plain version such as `1.2.3` or `v1.2.3`, as
+/// opposed to a requirement with an operator (`^1.2`, `>=1.0`).
+pub fn is_bare_version(version: &str) -> bool {
+    version
+        .strip_prefix('v')
+        .unwrap_or(version)
+        .starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Wraps edits of a single document into a `WorkspaceEdit`.
+pub fn workspace_edit(uri: &Uri, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit {
+        changes: Some(HashMap::from([(uri.clone(), edits)])),
+        ..Default::default()
+    }
+}
+
+/// Inserts `entry` into the section labelled `label`, keeping it sorted.
+///
+/// Returns `None` when there is no such section or it has no entries.
+pub fn insert_into_section(
+    content: &str,
+    sections: &[SortableSection],
+    label: &str,
+    key: &str,
+    entry: &str,
+) -> Option<Vec<TextEdit>> {
+    let section = sections.iter().find(|section| section.label == label)?;
+    crate::sort::insert_sorted(content, section, key, entry)
+}
+
+/// Returns the line separator used by `content`.
+pub fn newline(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Returns the indentation of the first indented line, or two spaces.
+pub fn indent_unit(content: &str) -> &str {
+    content
+        .lines()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+}
+
+/// Returns the leading whitespace of `line`.
+pub fn indentation_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Prefixes every non-empty line of `text` with `indent`.
+pub fn indent_lines(text: &str, indent: &str, newline: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(newline)
+}
+
+/// Returns the first line whose trimmed text satisfies `predicate`.
+pub fn find_line(content: &str, predicate: impl Fn(&str) -> bool) -> Option<u32> {
+    content
+        .lines()
+        .position(|line| predicate(line.trim()))
+        .map(|line| line as u32)
+}
+
+/// Inserts `text` (one or more lines) after `line`.
+pub fn insert_after_line(content: &str, line: u32, text: &str) -> TextEdit {
+    let newline = newline(content);
+    let end = content
+        .lines()
+        .nth(line as usize)
+        .map_or(0, |l| l.encode_utf16().count() as u32);
+    let position = Position::new(line, end);
+    TextEdit {
+        range: Range::new(position, position),
+        new_text: format!("{newline}{text}"),
+    }
+}
+
+/// Inserts `text` (one or more lines) before `line`.
+pub fn insert_before_line(content: &str, line: u32, text: &str) -> TextEdit {
+    let position = Position::new(line, 0);
+    TextEdit {
+        range: Range::new(position, position),
+        new_text: format!("{text}{}", newline(content)),
+    }
+}
+
+/// Appends `block` at the end of the document, separated by a blank line.
+pub fn append_block(content: &str, block: &str) -> TextEdit {
+    let newline = newline(content);
+    let separator =
+        if content.trim().is_empty() || content.ends_with(&format!("{newline}{newline}")) {
+            ""
+        } else if content.ends_with(newline) {
+            newline
+        } else {
+            &format!("{newline}{newline}")
+        };
+
+    let last_line = content.lines().count().saturating_sub(1);
+    let position = if content.ends_with('\n') {
+        Position::new(content.lines().count() as u32, 0)
+    } else {
+        let last = content.lines().last().unwrap_or("");
+        Position::new(last_line as u32, last.encode_utf16().count() as u32)
+    };
+    TextEdit {
+        range: Range::new(position, position),
+        new_text: format!("{separator}{block}{newline}"),
+    }
+}
+
+/// Adds `entry` to the TOML table `header` (e.g. `[dev-dependencies]`),
+/// right below the header or in a new table at the end of the document.
+pub fn insert_toml_table_entry(content: &str, header: &str, entry: &str) -> TextEdit {
+    match find_line(content, |line| line == header) {
+        Some(line) => insert_after_line(content, line, entry),
+        None => append_block(content, &format!("{header}{}{entry}", newline(content))),
+    }
+}
+
+/// Adds `entry` to the TOML array `key` of `table` when the array has no
+/// entries yet, creating the array (and the table) as needed.
+///
+/// Returns `None` when the array exists but is not the empty `[]` form;
+/// non-empty arrays go through [`crate::sort::insert_sorted`].
+pub fn insert_toml_array_entry(
+    content: &str,
+    table: &str,
+    key: &str,
+    entry: &str,
+) -> Option<TextEdit> {
+    let newline = newline(content);
+    let header = format!("[{table}]");
+    let array = format!("{key} = [{newline}    {entry},{newline}]");
+
+    let Some(header_line) = find_line(content, |line| line == header) else {
+        return Some(append_block(content, &format!("{header}{newline}{array}")));
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    for (i, line) in lines.iter().enumerate().skip(header_line as usize + 1) {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            break;
+        }
+        let Some((name, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if name.trim().trim_matches(['"', '\'']) != key {
+            continue;
+        }
+        if value.trim() != "[]" {
+            return None;
+        }
+        let end = Position::new(i as u32, line.encode_utf16().count() as u32);
+        return Some(TextEdit {
+            range: Range::new(Position::new(i as u32, 0), end),
+            new_text: format!("{}{array}", indentation_of(line)),
+        });
+    }
+    Some(insert_after_line(content, header_line, &array))
+}
+
+/// Adds `entry` to the JSON object member `key` of the root object when the
+/// member has no entries yet, creating the member as needed.
+///
+/// Handles an empty `"key": {}` object and a missing member; returns `None`
+/// for anything else (non-empty objects go through
+/// [`crate::sort::insert_sorted`]).
+pub fn insert_json_member(content: &str, key: &str, entry: &str) -> Option<TextEdit> {
+    let newline = newline(content);
+    let unit = indent_unit(content);
+    let quoted = format!("\"{key}\"");
+
+    let lines: Vec<&str> = content.lines().collect();
+    if let Some(i) = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with(&quoted))
+    {
+        let line = lines[i];
+        let rest = line.trim_start()[quoted.len()..].trim_start();
+        let object = rest.strip_prefix(':')?.trim_start();
+        let tail = object.strip_prefix('{')?.trim_start().strip_prefix('}')?;
+        let indent = indentation_of(line);
+        let end = Position::new(i as u32, line.encode_utf16().count() as u32);
+        return Some(TextEdit {
+            range: Range::new(Position::new(i as u32, 0), end),
+            new_text: format!(
+                "{indent}{quoted}: {{{newline}{indent}{unit}{entry}{newline}{indent}}}{tail}"
+            ),
+        });
+    }
+
+    // Append a new member right after the last value of the root object
+    let close = content.rfind('}')?;
+    let last_value = content[..close].trim_end();
+    let comma = if last_value.ends_with('{') { "" } else { "," };
+    // Keep the closing brace on its own line
+    let close_newline = if content[last_value.len()..close].contains('\n') {
+        ""
+    } else {
+        newline
+    };
+    let table = crate::lsp_helpers::LineOffsetTable::new(content);
+    let position = table.byte_offset_to_position(content, last_value.len());
+    Some(TextEdit {
+        range: Range::new(position, position),
+        new_text: format!(
+            "{comma}{newline}{unit}{quoted}: {{{newline}{unit}{unit}{entry}{newline}{unit}}}{close_newline}"
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(content: &str, edit: &TextEdit) -> String {
+        crate::test_util::apply_edits(content, std::slice::from_ref(edit))
+    }
+
+    #[test]
+    fn test_dependency_kind_parse() {
+        assert_eq!(DependencyKind::parse(Some("")), DependencyKind::Normal);
+        assert_eq!(
+            DependencyKind::parse(Some("dependencies")),
+            DependencyKind::Normal
+        );
+        assert_eq!(DependencyKind::parse(Some("dev")), DependencyKind::Dev);
+        assert_eq!(
+            DependencyKind::parse(Some("dev_dependencies")),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            DependencyKind::parse(Some("peerDependencies")),
+            DependencyKind::Peer
+        );
+        assert_eq!(DependencyKind::parse(Some("Test")), DependencyKind::Test);
+        assert_eq!(
+            DependencyKind::parse(Some("testImplementation")),
+            DependencyKind::Other("testImplementation".into())
+        );
+    }
+
+    #[test]
+    fn test_is_bare_version() {
+        assert!(is_bare_version("1.2.3"));
+        assert!(is_bare_version("v1.9.1"));
+        assert!(!is_bare_version("^1.2"));
+        assert!(!is_bare_version(">=2.0"));
+        assert!(!is_bare_version("latest"));
+    }
+
+    #[test]
+    fn test_append_block() {
+        let content = "[package]\nname = \"app\"\n";
+        let edit = append_block(content, "[dependencies]\nserde = \"1\"");
+        assert_eq!(
+            apply(content, &edit),
+            "[package]\nname = \"app\"\n\n[dependencies]\nserde = \"1\"\n"
+        );
+
+        let content = "[package]\nname = \"app\"";
+        let edit = append_block(content, "[dependencies]");
+        assert_eq!(
+            apply(content, &edit),
+            "[package]\nname = \"app\"\n\n[dependencies]\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_toml_table_entry() {
+        let content = "[package]\nname = \"app\"\n\n[dev-dependencies]\n";
+        let edit = insert_toml_table_entry(content, "[dev-dependencies]", "insta = \"1\"");
+        assert_eq!(
+            apply(content, &edit),
+            "[package]\nname = \"app\"\n\n[dev-dependencies]\ninsta = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_toml_array_entry() {
+        let content = "[project]\nname = \"app\"\ndependencies = []\n";
+        let edit =
+            insert_toml_array_entry(content, "project", "dependencies", "\"httpx>=0.27\"").unwrap();
+        assert_eq!(
+            apply(content, &edit),
+            "[project]\nname = \"app\"\ndependencies = [\n    \"httpx>=0.27\",\n]\n"
+        );
+
+        let content = "[project]\nname = \"app\"\n";
+        let edit =
+            insert_toml_array_entry(content, "dependency-groups", "dev", "\"pytest\"").unwrap();
+        assert_eq!(
+            apply(content, &edit),
+            "[project]\nname = \"app\"\n\n[dependency-groups]\ndev = [\n    \"pytest\",\n]\n"
+        );
+
+        let content = "[project]\ndependencies = [\"a\"]\n";
+        assert!(insert_toml_array_entry(content, "project", "dependencies", "\"b\"").is_none());
+    }
+
+    #[test]
+    fn test_insert_json_member() {
+        let content = "{\n  \"name\": \"app\",\n  \"devDependencies\": {}\n}\n";
+        let edit =
+            insert_json_member(content, "devDependencies", "\"vitest\": \"^1.0.0\"").unwrap();
+        assert_eq!(
+            apply(content, &edit),
+            "{\n  \"name\": \"app\",\n  \"devDependencies\": {\n    \"vitest\": \"^1.0.0\"\n  }\n}\n"
+        );
+
+        let content = "{\n  \"name\": \"app\"\n}\n";
+        let edit = insert_json_member(content, "dependencies", "\"react\": \"^18.0.0\"").unwrap();
+        assert_eq!(
+            apply(content, &edit),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"react\": \"^18.0.0\"\n  }\n}\n"
+        );
+
+        let content = "{}";
+        let edit = insert_json_member(content, "dependencies", "\"react\": \"^18.0.0\"").unwrap();
+        assert_eq!(
+            apply(content, &edit),
+            "{\n  \"dependencies\": {\n    \"react\": \"^18.0.0\"\n  }\n}"
+        );
+    }
+}
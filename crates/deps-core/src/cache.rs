@@ -2,6 +2,7 @@ use crate::error::{DepsError, Result};
 use bytes::{Bytes, BytesMut};
 use dashmap::DashMap;
 use reqwest::{Client, Response, StatusCode, header};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
 /// Maximum number of cached entries to prevent unbounded memory growth.
@@ -143,6 +144,23 @@ pub struct CachedResponse {
 pub struct HttpCache {
     entries: DashMap<String, CachedResponse>,
     client: Client,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    revalidations: AtomicU64,
+}
+
+/// Request counters of an [`HttpCache`], see [`HttpCache::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CacheStats {
+    /// Responses served from a cached body (`304 Not Modified`, or a failed
+    /// revalidation falling back to the cache).
+    pub hits: u64,
+    /// Requests for URLs without a cached entry.
+    pub misses: u64,
+    /// Conditional requests sent for cached entries.
+    pub revalidations: u64,
+    /// Entries currently cached.
+    pub entries: usize,
 }
 
 impl HttpCache {
@@ -160,6 +178,19 @@ impl HttpCache {
         Self {
             entries: DashMap::new(),
             client,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            revalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the request counters accumulated since creation.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            revalidations: self.revalidations.load(Ordering::Relaxed),
+            entries: self.entries.len(),
         }
     }
 
@@ -225,19 +256,25 @@ impl HttpCache {
         // Holding a Ref across .await causes deadlocks when concurrent tasks
         // need write access to the same shard (e.g., conditional_request_with_headers → insert).
         if let Some(cached) = self.entries.get(url).map(|r| r.clone()) {
+            self.revalidations.fetch_add(1, Ordering::Relaxed);
             match self
                 .conditional_request_with_headers(url, &cached, extra_headers)
                 .await
             {
                 Ok(Some(new_body)) => return Ok(new_body),
-                Ok(None) => return Ok(cached.body),
+                Ok(None) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(cached.body);
+                }
                 Err(e) => {
                     tracing::warn!("conditional request failed, using cache: {e}");
+                    self.hits.fetch_add(1, Ordering::Relaxed);
                     return Ok(cached.body);
                 }
             }
         }
 
+        self.misses.fetch_add(1, Ordering::Relaxed);
         self.fetch_and_store_with_headers(url, extra_headers).await
     }
 
@@ -562,6 +599,16 @@ mod tests {
 
         let result2: Bytes = cache.get_cached(&url).await.unwrap();
         assert_eq!(result2.as_ref(), b"original data");
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                revalidations: 1,
+                entries: 1,
+            }
+        );
     }
 
     #[tokio::test]
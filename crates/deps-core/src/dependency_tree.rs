@@ -0,0 +1,536 @@
+//! Resolved dependency graph built from lock files.
+//!
+//! Every `LockFileProvider` records the direct dependencies of each resolved
+//! package in [`ResolvedPackage::dependencies`]. This module walks those edges
+//! to produce the transitive tree below a manifest's direct dependencies, and
+//! to answer "why is this package here?" by listing the paths that lead to it.
+//! It also reports packages resolved at several versions at once, and which
+//! direct dependencies pull in the older copies.
+//!
+//! Dependency references are package names, optionally followed by a version
+//! and a source as Cargo.lock writes them when a name is ambiguous
+//! (`"rand 0.8.5"`, `"rand 0.8.5 (registry+https://...)"`).
+
+use crate::lockfile::{ResolvedPackage, ResolvedPackages, ResolvedSource};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Maximum depth of a dependency tree, guarding against pathological lock files.
+const MAX_TREE_DEPTH: usize = 64;
+
+/// Maximum number of paths returned by [`why_dependency`].
+pub const MAX_WHY_PATHS: usize = 100;
+
+/// A resolved package and its transitive dependencies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyNode {
+    /// Package name as written in the lock file.
+    pub name: String,
+    /// Resolved version.
+    pub version: String,
+    /// Where the package was resolved from.
+    pub source: ResolvedSource,
+    /// Dependencies of this package.
+    pub children: Vec<Self>,
+    /// `true` when the package was already expanded earlier in the tree;
+    /// `children` is left empty to keep the response linear in graph size.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deduplicated: bool,
+}
+
+/// A package on a dependency path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct PackageRef {
+    /// Package name as written in the lock file.
+    pub name: String,
+    /// Resolved version.
+    pub version: String,
+}
+
+impl From<&ResolvedPackage> for PackageRef {
+    fn from(package: &ResolvedPackage) -> Self {
+        Self {
+            name: package.name.clone(),
+            version: package.version.clone(),
+        }
+    }
+}
+
+/// Paths from direct dependencies to a package.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WhyDependency {
+    /// Each path starts at a direct dependency and ends at the queried package.
+    pub paths: Vec<Vec<PackageRef>>,
+    /// `true` when more than [`MAX_WHY_PATHS`] paths exist.
+    pub truncated: bool,
+}
+
+/// A package resolved at more than one version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicatePackage {
+    /// Package name as written in the lock file.
+    pub name: String,
+    /// Every resolved version, lowest first.
+    pub versions: Vec<DuplicateVersion>,
+}
+
+/// One resolved version of a duplicated package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateVersion {
+    /// Resolved version.
+    pub version: String,
+    /// Packages whose dependencies resolve to this version.
+    pub required_by: Vec<PackageRef>,
+}
+
+impl DuplicatePackage {
+    /// Returns the highest resolved version (the last one).
+    pub fn latest(&self) -> Option<&str> {
+        self.versions.last().map(|v| v.version.as_str())
+    }
+}
+
+/// Resolves a lock file dependency reference to a package.
+///
+/// Accepts `"name"`, `"name version"` and `"name version (source)"`. A bare
+/// name resolves to the highest version in the lock file.
+pub fn resolve_reference<'a>(
+    packages: &'a ResolvedPackages,
+    reference: &str,
+) -> Option<&'a ResolvedPackage> {
+    let mut parts = reference.split_whitespace();
+    let name = parts.next()?;
+    match parts.next() {
+        Some(version) => packages
+            .get_all(name)?
+            .iter()
+            .find(|p| p.version == version),
+        None => packages.get(name),
+    }
+}
+
+/// Builds the transitive dependency tree below `roots`.
+///
+/// `roots` are lock file package names; names missing from the lock file are
+/// skipped. Packages reached more than once are expanded only the first time
+/// and marked [`DependencyNode::deduplicated`] afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::dependency_tree::build_tree;
+/// use deps_core::lockfile::{ResolvedPackage, ResolvedPackages, ResolvedSource};
+///
+/// let mut packages = ResolvedPackages::new();
+/// for (name, deps) in [("serde", vec!["serde_derive"]), ("serde_derive", vec![])] {
+///     packages.insert(ResolvedPackage {
+///         name: name.into(),
+///         version: "1.0.0".into(),
+///         source: ResolvedSource::Path { path: String::new() },
+///         dependencies: deps.into_iter().map(String::from).collect(),
+///     });
+/// }
+///
+/// let tree = build_tree(&packages, &["serde"]);
+/// assert_eq!(tree[0].children[0].name, "serde_derive");
+/// ```
+pub fn build_tree(packages: &ResolvedPackages, roots: &[&str]) -> Vec<DependencyNode> {
+    let mut expanded = HashSet::new();
+    roots
+        .iter()
+        .filter_map(|root| resolve_reference(packages, root))
+        .map(|package| build_node(packages, package, &mut expanded, 0))
+        .collect()
+}
+
+fn build_node<'a>(
+    packages: &'a ResolvedPackages,
+    package: &'a ResolvedPackage,
+    expanded: &mut HashSet<(&'a str, &'a str)>,
+    depth: usize,
+) -> DependencyNode {
+    let first_visit = expanded.insert((package.name.as_str(), package.version.as_str()));
+    let children = if first_visit && depth < MAX_TREE_DEPTH {
+        package
+            .dependencies
+            .iter()
+            .filter_map(|reference| resolve_reference(packages, reference))
+            .map(|child| build_node(packages, child, expanded, depth + 1))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    DependencyNode {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        source: package.source.clone(),
+        children,
+        deduplicated: !first_visit,
+    }
+}
+
+/// Lists every path from `roots` to the package named `target`.
+///
+/// Paths never revisit a package, so cycles in the graph terminate. Only
+/// packages from which `target` is reachable are explored, which keeps the
+/// search proportional to the number of paths rather than the graph size.
+pub fn why_dependency(packages: &ResolvedPackages, roots: &[&str], target: &str) -> WhyDependency {
+    let can_reach = packages_reaching(packages, target);
+    let mut result = WhyDependency::default();
+    let mut path = Vec::new();
+
+    for root in roots {
+        let Some(package) = resolve_reference(packages, root) else {
+            continue;
+        };
+        if can_reach.contains(&key(package)) {
+            collect_paths(
+                packages,
+                package,
+                target,
+                &can_reach,
+                &mut path,
+                &mut result,
+            );
+        }
+    }
+
+    result
+}
+
+/// Finds every package resolved at more than one version.
+///
+/// Results are sorted by name; versions are ordered by semver where both
+/// parse, and lexically otherwise.
+pub fn find_duplicates(packages: &ResolvedPackages) -> Vec<DuplicatePackage> {
+    let mut required_by: HashMap<(&str, &str), Vec<PackageRef>> = HashMap::new();
+    for package in packages.iter_all() {
+        for reference in &package.dependencies {
+            if let Some(child) = resolve_reference(packages, reference) {
+                required_by
+                    .entry(key(child))
+                    .or_default()
+                    .push(PackageRef::from(package));
+            }
+        }
+    }
+
+    let mut duplicates: Vec<DuplicatePackage> = packages
+        .iter()
+        .filter_map(|(name, _)| {
+            let all = packages.get_all(name)?;
+            if all.len() < 2 {
+                return None;
+            }
+            let mut versions: Vec<&ResolvedPackage> = all.iter().collect();
+            versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+            versions.dedup_by(|a, b| a.version == b.version);
+            if versions.len() < 2 {
+                return None;
+            }
+            Some(DuplicatePackage {
+                name: name.clone(),
+                versions: versions
+                    .into_iter()
+                    .map(|p| {
+                        let mut required_by = required_by.remove(&key(p)).unwrap_or_default();
+                        required_by.sort_by(|a, b| a.name.cmp(&b.name));
+                        required_by.dedup();
+                        DuplicateVersion {
+                            version: p.version.clone(),
+                            required_by,
+                        }
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a.name.cmp(&b.name));
+    duplicates
+}
+
+/// Returns the older copies of duplicated packages reachable from `root`.
+///
+/// A direct dependency "causes" a duplicate when its transitive closure
+/// (including itself) contains a version other than the highest one.
+pub fn duplicates_introduced_by(
+    packages: &ResolvedPackages,
+    root: &str,
+    duplicates: &[DuplicatePackage],
+) -> Vec<PackageRef> {
+    let Some(start) = resolve_reference(packages, root) else {
+        return vec![];
+    };
+
+    let mut seen = HashSet::from([key(start)]);
+    let mut queue = VecDeque::from([start]);
+    let mut introduced = Vec::new();
+    while let Some(package) = queue.pop_front() {
+        let is_older_copy = duplicates
+            .iter()
+            .any(|d| d.name == package.name && d.latest().is_some_and(|v| v != package.version));
+        if is_older_copy {
+            introduced.push(PackageRef::from(package));
+        }
+        for reference in &package.dependencies {
+            if let Some(child) = resolve_reference(packages, reference)
+                && seen.insert(key(child))
+            {
+                queue.push_back(child);
+            }
+        }
+    }
+
+    introduced.sort_by(|a, b| a.name.cmp(&b.name));
+    introduced
+}
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(va), Ok(vb)) => va.cmp(&vb),
+        _ => a.cmp(b),
+    }
+}
+
+fn key(package: &ResolvedPackage) -> (&str, &str) {
+    (package.name.as_str(), package.version.as_str())
+}
+
+/// Returns every package from which a package named `target` is reachable,
+/// including the target itself.
+fn packages_reaching<'a>(
+    packages: &'a ResolvedPackages,
+    target: &str,
+) -> HashSet<(&'a str, &'a str)> {
+    let mut dependents: HashMap<(&str, &str), Vec<(&str, &str)>> = HashMap::new();
+    for package in packages.iter_all() {
+        for reference in &package.dependencies {
+            if let Some(child) = resolve_reference(packages, reference) {
+                dependents.entry(key(child)).or_default().push(key(package));
+            }
+        }
+    }
+
+    let mut reached: HashSet<(&str, &str)> = packages
+        .get_all(target)
+        .unwrap_or_default()
+        .iter()
+        .map(key)
+        .collect();
+    let mut queue: VecDeque<(&str, &str)> = reached.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        for parent in dependents.get(&current).into_iter().flatten() {
+            if reached.insert(*parent) {
+                queue.push_back(*parent);
+            }
+        }
+    }
+    reached
+}
+
+fn collect_paths<'a>(
+    packages: &'a ResolvedPackages,
+    package: &'a ResolvedPackage,
+    target: &str,
+    can_reach: &HashSet<(&str, &str)>,
+    path: &mut Vec<&'a ResolvedPackage>,
+    result: &mut WhyDependency,
+) {
+    if result.truncated || path.iter().any(|p| key(p) == key(package)) {
+        return;
+    }
+    path.push(package);
+
+    if package.name == target {
+        if result.paths.len() < MAX_WHY_PATHS {
+            result
+                .paths
+                .push(path.iter().map(|p| PackageRef::from(*p)).collect());
+        } else {
+            result.truncated = true;
+        }
+    } else if path.len() < MAX_TREE_DEPTH {
+        for reference in &package.dependencies {
+            if let Some(child) = resolve_reference(packages, reference)
+                && can_reach.contains(&key(child))
+            {
+                collect_paths(packages, child, target, can_reach, path, result);
+            }
+        }
+    }
+
+    path.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(entries: &[(&str, &str, &[&str])]) -> ResolvedPackages {
+        let mut packages = ResolvedPackages::new();
+        for (name, version, deps) in entries {
+            packages.insert(ResolvedPackage {
+                name: (*name).into(),
+                version: (*version).into(),
+                source: ResolvedSource::Registry {
+                    url: "https://github.com/rust-lang/crates.io-index".into(),
+                    checksum: String::new(),
+                },
+                dependencies: deps.iter().map(|d| (*d).to_string()).collect(),
+            });
+        }
+        packages
+    }
+
+    #[test]
+    fn test_resolve_reference_with_version() {
+        let packages = packages(&[("rand", "0.7.3", &[]), ("rand", "0.8.5", &[])]);
+        assert_eq!(
+            resolve_reference(&packages, "rand 0.7.3").unwrap().version,
+            "0.7.3"
+        );
+        assert_eq!(
+            resolve_reference(
+                &packages,
+                "rand 0.7.3 (registry+https://github.com/rust-lang/crates.io-index)"
+            )
+            .unwrap()
+            .version,
+            "0.7.3"
+        );
+        assert_eq!(
+            resolve_reference(&packages, "rand").unwrap().version,
+            "0.8.5"
+        );
+        assert!(resolve_reference(&packages, "rand 0.6.0").is_none());
+        assert!(resolve_reference(&packages, "").is_none());
+    }
+
+    #[test]
+    fn test_build_tree_deduplicates_shared_packages() {
+        let packages = packages(&[
+            ("app-a", "1.0.0", &["shared"]),
+            ("app-b", "1.0.0", &["shared"]),
+            ("shared", "1.0.0", &["leaf"]),
+            ("leaf", "1.0.0", &[]),
+        ]);
+
+        let tree = build_tree(&packages, &["app-a", "app-b", "missing"]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].children[0].name, "shared");
+        assert_eq!(tree[0].children[0].children[0].name, "leaf");
+        assert!(!tree[0].children[0].deduplicated);
+        assert!(tree[1].children[0].deduplicated);
+        assert!(tree[1].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_build_tree_handles_cycles() {
+        let packages = packages(&[("a", "1.0.0", &["b"]), ("b", "1.0.0", &["a"])]);
+        let tree = build_tree(&packages, &["a"]);
+        assert_eq!(tree[0].children[0].children[0].name, "a");
+        assert!(tree[0].children[0].children[0].deduplicated);
+    }
+
+    #[test]
+    fn test_why_dependency_lists_all_paths() {
+        let packages = packages(&[
+            ("tokio", "1.0.0", &["mio", "bytes"]),
+            ("hyper", "1.0.0", &["tokio", "bytes"]),
+            ("mio", "1.0.0", &["libc"]),
+            ("bytes", "1.0.0", &[]),
+            ("libc", "0.2.0", &[]),
+        ]);
+
+        let why = why_dependency(&packages, &["hyper", "tokio"], "bytes");
+        let paths: Vec<Vec<&str>> = why
+            .paths
+            .iter()
+            .map(|p| p.iter().map(|r| r.name.as_str()).collect())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["hyper", "tokio", "bytes"],
+                vec!["hyper", "bytes"],
+                vec!["tokio", "bytes"],
+            ]
+        );
+        assert!(!why.truncated);
+
+        assert!(
+            why_dependency(&packages, &["hyper"], "unknown")
+                .paths
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_why_dependency_direct_and_cyclic() {
+        let packages = packages(&[
+            ("a", "1.0.0", &["b"]),
+            ("b", "1.0.0", &["a", "c"]),
+            ("c", "1.0.0", &[]),
+        ]);
+        let why = why_dependency(&packages, &["a"], "c");
+        assert_eq!(why.paths.len(), 1);
+        assert_eq!(why.paths[0].len(), 3);
+
+        let why = why_dependency(&packages, &["a"], "a");
+        assert_eq!(
+            why.paths,
+            vec![vec![PackageRef::from(packages.get("a").unwrap())]]
+        );
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let packages = packages(&[
+            ("app", "0.1.0", &["rand 0.8.5", "old-crate"]),
+            ("old-crate", "1.0.0", &["rand 0.7.3"]),
+            ("rand", "0.7.3", &[]),
+            ("rand", "0.8.5", &[]),
+            ("serde", "1.0.0", &[]),
+        ]);
+
+        let duplicates = find_duplicates(&packages);
+        assert_eq!(duplicates.len(), 1);
+        let rand = &duplicates[0];
+        assert_eq!(rand.name, "rand");
+        assert_eq!(rand.latest(), Some("0.8.5"));
+        assert_eq!(rand.versions[0].version, "0.7.3");
+        assert_eq!(rand.versions[0].required_by[0].name, "old-crate");
+        assert_eq!(rand.versions[1].required_by[0].name, "app");
+
+        let introduced = duplicates_introduced_by(&packages, "old-crate", &duplicates);
+        assert_eq!(
+            introduced,
+            vec![PackageRef {
+                name: "rand".into(),
+                version: "0.7.3".into()
+            }]
+        );
+        assert!(duplicates_introduced_by(&packages, "serde", &duplicates).is_empty());
+        assert!(duplicates_introduced_by(&packages, "missing", &duplicates).is_empty());
+    }
+
+    #[test]
+    fn test_find_duplicates_orders_versions_semantically() {
+        let packages = packages(&[("dep", "0.10.0", &[]), ("dep", "0.9.0", &[])]);
+        let duplicates = find_duplicates(&packages);
+        assert_eq!(duplicates[0].latest(), Some("0.10.0"));
+    }
+
+    #[test]
+    fn test_serialize_node() {
+        let packages = packages(&[("serde", "1.0.0", &[])]);
+        let tree = build_tree(&packages, &["serde"]);
+        let json = serde_json::to_value(&tree[0]).unwrap();
+        assert_eq!(json["name"], "serde");
+        assert_eq!(json["source"]["kind"], "registry");
+        assert!(json.get("deduplicated").is_none());
+    }
+}
@@ -2,7 +2,8 @@ use std::any::Any;
 use std::pin::Pin;
 use std::sync::Arc;
 use tower_lsp_server::ls_types::{
-    CodeAction, CompletionItem, Diagnostic, Hover, InlayHint, Position, Uri,
+    CodeAction, CompletionItem, Diagnostic, Hover, InlayHint, Location, Position, Uri,
+    WorkspaceEdit,
 };
 
 use crate::{
@@ -162,8 +163,9 @@ pub trait Dependency: Send + Sync {
     }
 
     /// Environment marker expression gating this dependency (e.g. PEP 508's
-    /// `python_version >= '3.8'`). Ecosystem-specific, `None` if not supported
-    /// or not present on this dependency.
+    /// `python_version >= '3.8'`, or the `cfg(windows)` of a Cargo
+    /// `[target.'cfg(windows)'.dependencies]` table). Ecosystem-specific,
+    /// `None` if not supported or not present on this dependency.
     fn markers(&self) -> Option<&str> {
         None
     }
@@ -174,6 +176,21 @@ pub trait Dependency: Send + Sync {
         None
     }
 
+    /// Short description of a local override that is built instead of the
+    /// registry release (e.g. `patched → git https://github.com/o/r (main)`
+    /// for a Cargo `[patch]` entry). Overridden dependencies show it as their
+    /// inlay hint and are never reported outdated. `None` if not overridden.
+    fn overridden_by(&self) -> Option<&str> {
+        None
+    }
+
+    /// Name the dependency is declared under when it differs from the
+    /// package name (e.g. the key of a Cargo `package = "..."` rename),
+    /// shown in hover next to the package name. `None` if not renamed.
+    fn alias(&self) -> Option<&str> {
+        None
+    }
+
     /// Downcast to concrete type
     fn as_any(&self) -> &dyn Any;
 }
@@ -183,14 +200,19 @@ pub trait Dependency: Send + Sync {
 pub struct EcosystemConfig {
     /// Whether to show inlay hints for up-to-date dependencies
     pub show_up_to_date_hints: bool,
-    /// Text to display for up-to-date dependencies
+    /// Text to display for up-to-date dependencies; followed by the locked
+    /// version unless it uses placeholders itself
     pub up_to_date_text: String,
-    /// Text to display for dependencies needing updates (use {} for version placeholder)
+    /// Text to display for dependencies needing updates, a template with the
+    /// placeholders of [`crate::hint_template`] (`{}` is the latest version)
     pub needs_update_text: String,
     /// Text to display while loading registry data
     pub loading_text: String,
     /// Whether to show loading hints in inlay hints
     pub show_loading_hints: bool,
+    /// Whether to show the locked version next to requirements that allow
+    /// other versions, e.g. `→ 1.0.195 (locked)` after `"1"`
+    pub show_locked_versions: bool,
 }
 
 impl Default for EcosystemConfig {
@@ -199,6 +221,7 @@ impl Default for EcosystemConfig {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         }
@@ -329,6 +352,23 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
     /// The registry provides version lookup and package search capabilities.
     fn registry(&self) -> Arc<dyn Registry>;
 
+    /// Get the registry client for the dependencies of `parse_result`.
+    ///
+    /// Override when a manifest can fetch packages from somewhere else than
+    /// the default registry, as Cargo's alternate registries do. Default:
+    /// [`Self::registry`].
+    fn registry_for(&self, _parse_result: &dyn ParseResult) -> Arc<dyn Registry> {
+        self.registry()
+    }
+
+    /// Returns this ecosystem fetching packages from the registry at `url`
+    /// (a mirror or private registry) instead of the public one.
+    ///
+    /// Default: `None`, the registry endpoint is not configurable.
+    fn with_registry_url(&self, _url: &str) -> Option<Arc<dyn Ecosystem>> {
+        None
+    }
+
     /// Get the lock file provider for this ecosystem.
     ///
     /// Returns `None` if the ecosystem doesn't support lock files.
@@ -368,7 +408,7 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
     /// Generate hover information for a position.
     ///
     /// Default implementation delegates to `lsp_helpers::generate_hover`
-    /// using `self.formatter()` and `self.registry()`.
+    /// using `self.formatter()` and `self.registry_for()`.
     fn generate_hover<'a>(
         &'a self,
         parse_result: &'a dyn ParseResult,
@@ -376,7 +416,7 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
         versions: VersionData<'a>,
     ) -> BoxFuture<'a, Option<Hover>> {
         Box::pin(async move {
-            let registry = self.registry();
+            let registry = self.registry_for(parse_result);
             crate::lsp_helpers::generate_hover(
                 parse_result,
                 position,
@@ -391,7 +431,7 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
     /// Generate code actions for a position.
     ///
     /// Default implementation delegates to `lsp_helpers::generate_code_actions`
-    /// using `self.formatter()` and `self.registry()`.
+    /// using `self.formatter()` and `self.registry_for()`.
     fn generate_code_actions<'a>(
         &'a self,
         parse_result: &'a dyn ParseResult,
@@ -399,7 +439,7 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
         uri: &'a Uri,
     ) -> BoxFuture<'a, Vec<CodeAction>> {
         Box::pin(async move {
-            let registry = self.registry();
+            let registry = self.registry_for(parse_result);
             crate::lsp_helpers::generate_code_actions(
                 parse_result,
                 position,
@@ -413,6 +453,9 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
 
     /// Generate diagnostics for the document.
     ///
+    /// Checks that need registry data beyond `versions` fetch at most
+    /// `max_concurrent` packages at a time.
+    ///
     /// Default implementation delegates to `lsp_helpers::generate_diagnostics_from_cache`
     /// using `self.formatter()`.
     fn generate_diagnostics<'a>(
@@ -420,6 +463,7 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
         parse_result: &'a dyn ParseResult,
         versions: VersionData<'a>,
         _uri: &'a Uri,
+        _max_concurrent: usize,
     ) -> BoxFuture<'a, Vec<Diagnostic>> {
         Box::pin(async move {
             crate::lsp_helpers::generate_diagnostics_from_cache(
@@ -440,6 +484,97 @@ pub trait Ecosystem: Send + Sync + private::Sealed {
         content: &'a str,
     ) -> BoxFuture<'a, Vec<CompletionItem>>;
 
+    /// Generate completions for a manifest that failed to parse.
+    ///
+    /// A manifest is often invalid while it is being typed, e.g. with the
+    /// cursor inside an unclosed inline table. Ecosystems that can still
+    /// tell what is being written from the raw `content` complete it here.
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns no items; the server then only completes package names.
+    fn generate_unparsed_completions(
+        &self,
+        _content: &str,
+        _position: Position,
+        _uri: &Uri,
+    ) -> Vec<CompletionItem> {
+        vec![]
+    }
+
+    /// Resolve the declaration an inherited dependency points at.
+    ///
+    /// Used for `textDocument/definition`: when the dependency under `position`
+    /// takes its version from somewhere else (a workspace root, a property, a
+    /// central package file), returns the location of that declaration.
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns `None`, indicating the ecosystem has no inheritance mechanism.
+    fn generate_definition<'a>(
+        &'a self,
+        _parse_result: &'a dyn ParseResult,
+        _position: Position,
+        _content: &'a str,
+    ) -> BoxFuture<'a, Option<Location>> {
+        Box::pin(async move { None })
+    }
+
+    /// Describe the dependency sections whose entries can be sorted.
+    ///
+    /// Used by the sort code action and `textDocument/rangeFormatting`; see
+    /// [`crate::sort`] for how entries are reordered.
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns an empty list, indicating sorting is not supported.
+    fn sortable_sections(
+        &self,
+        _parse_result: &dyn ParseResult,
+        _content: &str,
+    ) -> Vec<crate::sort::SortableSection> {
+        vec![]
+    }
+
+    /// Whether the manifest already declares `name` in the section a
+    /// dependency of `kind` would be added to.
+    ///
+    /// Used by the `deps.addDependency` command to refuse duplicate entries.
+    ///
+    /// # Default Implementation
+    ///
+    /// Looks for `name` among all dependencies, whatever their section.
+    fn has_dependency(
+        &self,
+        parse_result: &dyn ParseResult,
+        _content: &str,
+        name: &str,
+        _kind: &crate::add_dependency::DependencyKind,
+    ) -> bool {
+        parse_result
+            .dependencies()
+            .iter()
+            .any(|dep| dep.name() == name)
+    }
+
+    /// Build the edit adding a new dependency to the manifest.
+    ///
+    /// Used by the `deps.addDependency` command once the version is known.
+    /// The edit usually targets `parse_result.uri()`, but may also touch
+    /// related files (e.g. a Gradle version catalog).
+    ///
+    /// # Default Implementation
+    ///
+    /// Returns `None`, indicating adding dependencies is not supported.
+    fn add_dependency<'a>(
+        &'a self,
+        _parse_result: &'a dyn ParseResult,
+        _content: &'a str,
+        _dependency: &'a crate::add_dependency::NewDependency,
+    ) -> BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move { None })
+    }
+
     /// Support for downcasting to concrete ecosystem type
     ///
     /// This allows ecosystem-specific operations when needed.
@@ -493,6 +628,7 @@ mod tests {
             show_up_to_date_hints: false,
             up_to_date_text: "OK".to_string(),
             needs_update_text: "Update to {}".to_string(),
+            show_locked_versions: false,
             loading_text: "Loading...".to_string(),
             show_loading_hints: false,
         };
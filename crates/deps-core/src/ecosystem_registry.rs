@@ -305,6 +305,24 @@ impl EcosystemRegistry {
         }
         patterns
     }
+
+    /// Get all manifest patterns for file watching and workspace discovery
+    ///
+    /// Returns glob patterns for every manifest filename (e.g., "**/Cargo.toml")
+    /// and manifest extension (e.g., "**/*.csproj") of all registered ecosystems.
+    pub fn all_manifest_patterns(&self) -> Vec<String> {
+        let mut patterns = Vec::new();
+        for entry in self.ecosystems.iter() {
+            let ecosystem = entry.value();
+            for filename in ecosystem.manifest_filenames() {
+                patterns.push(format!("**/{filename}"));
+            }
+            for extension in ecosystem.manifest_extensions() {
+                patterns.push(format!("**/*{extension}"));
+            }
+        }
+        patterns
+    }
 }
 
 impl Default for EcosystemRegistry {
@@ -602,6 +620,19 @@ mod tests {
         assert!(registry.all_lockfile_patterns().is_empty());
     }
 
+    #[test]
+    fn test_all_manifest_patterns() {
+        let registry = EcosystemRegistry::new();
+        registry.register(Arc::new(MockEcosystem {
+            id: "cargo",
+            display_name: "Cargo",
+            filenames: &["Cargo.toml"],
+            lockfiles: &["Cargo.lock"],
+        }));
+
+        assert_eq!(registry.all_manifest_patterns(), vec!["**/Cargo.toml"]);
+    }
+
     #[test]
     fn test_all_lockfile_patterns_single_ecosystem() {
         let registry = EcosystemRegistry::new();
@@ -0,0 +1,431 @@
+//! Placeholder templates for inlay hint labels.
+//!
+//! Hint texts may reference the values of a dependency by name:
+//!
+//! | Placeholder | Value |
+//! | ----------- | ----- |
+//! | `{latest}`, `{}` | Latest version known from the registry |
+//! | `{compatible}` | Newest stable version the requirement accepts |
+//! | `{locked}` | Version resolved in the lock file |
+//! | `{kind}` | `major`, `minor` or `patch`: how far `{latest}` is ahead |
+//! | `{age}` | Time since `{latest}` was published (`3d`, `5w`, `4mo`, `2y`) |
+//! | `{count}` | Number of stable releases newer than the version in use |
+//!
+//! `{compatible}`, `{age}` and `{count}` need the package's release history
+//! ([`ReleaseInfo`]), which is only fetched when a template asks for it (see
+//! [`uses_release_history`]). Placeholders without a value render empty;
+//! unknown placeholders are kept verbatim.
+
+use crate::Version;
+use crate::lsp_helpers::EcosystemFormatter;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Placeholders that need the release history of a package.
+const HISTORY_PLACEHOLDERS: [&str; 3] = ["{compatible}", "{age}", "{count}"];
+
+/// Returns `true` if `template` uses a placeholder that needs the release
+/// history of a package.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::hint_template::uses_release_history;
+///
+/// assert!(uses_release_history("❌ {latest} ({age})"));
+/// assert!(!uses_release_history("❌ {}"));
+/// ```
+pub fn uses_release_history(template: &str) -> bool {
+    HISTORY_PLACEHOLDERS.iter().any(|p| template.contains(p))
+}
+
+/// One release of a package, as recorded for hint templates and tooltips.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub yanked: bool,
+    pub prerelease: bool,
+    /// Publication timestamp as reported by the registry (RFC 3339 or a
+    /// plain `YYYY-MM-DD` date), if the registry reports one.
+    pub published_at: Option<String>,
+}
+
+impl ReleaseInfo {
+    /// Captures a registry version.
+    pub fn from_version(version: &dyn Version) -> Self {
+        Self {
+            version: version.version_string().to_string(),
+            yanked: version.is_yanked(),
+            prerelease: version.is_prerelease(),
+            published_at: version.published_at().map(str::to_string),
+        }
+    }
+
+    /// Whether this release is neither yanked nor a pre-release.
+    pub const fn is_stable(&self) -> bool {
+        !self.yanked && !self.prerelease
+    }
+}
+
+/// How far a newer version is ahead of the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UpdateKind {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl UpdateKind {
+    /// Classifies the step from `current` to `latest` by the first differing
+    /// numeric component.
+    ///
+    /// Leading operators (`^`, `~>`, `>=`, `v`, ...) are skipped, so a
+    /// requirement can stand in for the current version. Returns `None` when
+    /// the versions are equal or either one has no leading number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_core::hint_template::UpdateKind;
+    ///
+    /// assert_eq!(UpdateKind::between("^1.2", "2.0.0"), Some(UpdateKind::Major));
+    /// assert_eq!(UpdateKind::between("1.2.3", "1.3.0"), Some(UpdateKind::Minor));
+    /// assert_eq!(UpdateKind::between("v1.2.3", "v1.2.4"), Some(UpdateKind::Patch));
+    /// assert_eq!(UpdateKind::between("1.2.3", "1.2.3"), None);
+    /// ```
+    pub fn between(current: &str, latest: &str) -> Option<Self> {
+        let current = numeric_components(current);
+        let latest = numeric_components(latest);
+        if current.is_empty() || latest.is_empty() {
+            return None;
+        }
+
+        let len = current.len().max(latest.len());
+        let index = (0..len).find(|&i| {
+            current.get(i).copied().unwrap_or(0) != latest.get(i).copied().unwrap_or(0)
+        })?;
+        Some(match index {
+            0 => Self::Major,
+            1 => Self::Minor,
+            _ => Self::Patch,
+        })
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Patch => "patch",
+            Self::Minor => "minor",
+            Self::Major => "major",
+        }
+    }
+}
+
+/// Parses the leading `X.Y.Z` numbers of a version, after any operator.
+///
+/// A component with a suffix (`3-beta`, `2,<3`) is the last one read.
+fn numeric_components(version: &str) -> Vec<u64> {
+    let start = version
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(version.len());
+
+    let mut components = Vec::new();
+    for part in version[start..].split('.') {
+        let digits = part.bytes().take_while(u8::is_ascii_digit).count();
+        let Ok(value) = part[..digits].parse() else {
+            break;
+        };
+        components.push(value);
+        if digits < part.len() {
+            break;
+        }
+    }
+    components
+}
+
+/// Values substituted into a hint template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HintValues<'a> {
+    pub latest: Option<&'a str>,
+    pub compatible: Option<&'a str>,
+    pub locked: Option<&'a str>,
+    pub kind: Option<UpdateKind>,
+    pub age: Option<String>,
+    pub count: Option<usize>,
+}
+
+impl<'a> HintValues<'a> {
+    /// Derives the values for one dependency.
+    ///
+    /// `releases` is the package's history, newest first; without it
+    /// `compatible`, `age` and `count` stay empty.
+    pub fn compute(
+        requirement: &str,
+        latest: Option<&'a str>,
+        locked: Option<&'a str>,
+        releases: Option<&'a [ReleaseInfo]>,
+        formatter: &dyn EcosystemFormatter,
+    ) -> Self {
+        let current = locked.unwrap_or(requirement);
+        let kind = latest.and_then(|latest| UpdateKind::between(current, latest));
+
+        let compatible = releases.and_then(|releases| {
+            releases
+                .iter()
+                .find(|r| {
+                    r.is_stable()
+                        && formatter.version_satisfies_requirement(&r.version, requirement)
+                })
+                .map(|r| r.version.as_str())
+        });
+
+        let age = releases.zip(latest).and_then(|(releases, latest)| {
+            releases
+                .iter()
+                .find(|r| r.version == latest)
+                .and_then(|r| r.published_at.as_deref())
+                .and_then(|published| format_age(published, SystemTime::now()))
+        });
+
+        let count = releases.and_then(|releases| {
+            let in_use = locked.or(compatible)?;
+            let position = releases.iter().position(|r| r.version == in_use)?;
+            Some(
+                releases[..position]
+                    .iter()
+                    .filter(|r| r.is_stable())
+                    .count(),
+            )
+        });
+
+        Self {
+            latest,
+            compatible,
+            locked,
+            kind,
+            age,
+            count,
+        }
+    }
+}
+
+/// Renders `template`, replacing each placeholder with its value.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::hint_template::{HintValues, UpdateKind, render};
+///
+/// let values = HintValues {
+///     latest: Some("2.0.0"),
+///     locked: Some("1.4.0"),
+///     kind: Some(UpdateKind::Major),
+///     ..Default::default()
+/// };
+/// assert_eq!(render("❌ {}", &values), "❌ 2.0.0");
+/// assert_eq!(render("{locked} → {latest} ({kind})", &values), "1.4.0 → 2.0.0 (major)");
+/// assert_eq!(render("{latest} {age}", &values), "2.0.0");
+/// ```
+pub fn render(template: &str, values: &HintValues<'_>) -> String {
+    let mut out = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open..];
+        let Some(close) = after.find('}') else {
+            rest = after;
+            break;
+        };
+        let placeholder = &after[..=close];
+        match placeholder_value(placeholder, values) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(placeholder),
+        }
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+
+    out.trim().to_string()
+}
+
+/// Value of a known placeholder (empty when unavailable), or `None` for an
+/// unknown one.
+fn placeholder_value(placeholder: &str, values: &HintValues<'_>) -> Option<String> {
+    let value = match placeholder {
+        "{}" | "{latest}" => values.latest.map(str::to_string),
+        "{compatible}" => values.compatible.map(str::to_string),
+        "{locked}" => values.locked.map(str::to_string),
+        "{kind}" => values.kind.map(|k| k.as_str().to_string()),
+        "{age}" => values.age.clone(),
+        "{count}" => values.count.map(|c| c.to_string()),
+        _ => return None,
+    };
+    Some(value.unwrap_or_default())
+}
+
+/// Formats the time between `published_at` and `now` compactly: `today`,
+/// `3d`, `5w`, `4mo` or `2y`.
+///
+/// Only the leading `YYYY-MM-DD` of the timestamp is read. Returns `None`
+/// for unparsable or future dates.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::hint_template::format_age;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let now = UNIX_EPOCH + Duration::from_hours(19_800 * 24); // 2024-03-18
+/// assert_eq!(format_age("2024-03-15T10:30:00Z", now).as_deref(), Some("3d"));
+/// assert_eq!(format_age("2023-01-01", now).as_deref(), Some("1y"));
+/// ```
+pub fn format_age(published_at: &str, now: SystemTime) -> Option<String> {
+    let published = parse_date(published_at)?;
+    let today = i64::try_from(now.duration_since(UNIX_EPOCH).ok()?.as_secs() / 86_400).ok()?;
+    let days = today - published;
+
+    Some(match days {
+        ..0 => return None,
+        0 => "today".to_string(),
+        1..14 => format!("{days}d"),
+        14..60 => format!("{}w", days / 7),
+        60..365 => format!("{}mo", days / 30),
+        _ => format!("{}y", days / 365),
+    })
+}
+
+/// Parses the leading `YYYY-MM-DD` of a timestamp into days since the Unix
+/// epoch.
+pub(crate) fn parse_date(timestamp: &str) -> Option<i64> {
+    let date = timestamp.get(..10)?;
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct Formatter;
+
+    impl EcosystemFormatter for Formatter {
+        fn format_version_for_text_edit(&self, version: &str) -> String {
+            version.to_string()
+        }
+
+        fn package_url(&self, name: &str) -> String {
+            format!("https://example.com/{name}")
+        }
+    }
+
+    fn release(version: &str, published_at: Option<&str>) -> ReleaseInfo {
+        ReleaseInfo {
+            version: version.to_string(),
+            yanked: false,
+            prerelease: version.contains('-'),
+            published_at: published_at.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(parse_date("2024-03-18T00:00:00Z"), Some(19_800));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2024"), None);
+    }
+
+    #[test]
+    fn test_format_age_buckets() {
+        let now = UNIX_EPOCH + Duration::from_hours(19_800 * 24);
+        assert_eq!(format_age("2024-03-18", now).as_deref(), Some("today"));
+        assert_eq!(format_age("2024-02-26", now).as_deref(), Some("3w"));
+        assert_eq!(format_age("2023-11-01", now).as_deref(), Some("4mo"));
+        assert_eq!(format_age("2024-04-01", now), None);
+        assert_eq!(format_age("yesterday", now), None);
+    }
+
+    #[test]
+    fn test_update_kind_between() {
+        assert_eq!(UpdateKind::between("1.0", "1.0.1"), Some(UpdateKind::Patch));
+        assert_eq!(
+            UpdateKind::between(">=1.2,<2", "1.4.0"),
+            Some(UpdateKind::Minor)
+        );
+        assert_eq!(
+            UpdateKind::between("~> 6.1", "7.0.4"),
+            Some(UpdateKind::Major)
+        );
+        assert_eq!(UpdateKind::between("1.2.3-beta", "1.2.3"), None);
+        assert_eq!(UpdateKind::between("latest", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_render_unknown_and_unterminated() {
+        let values = HintValues {
+            latest: Some("1.0.0"),
+            ..Default::default()
+        };
+        assert_eq!(render("{latest} {unknown}", &values), "1.0.0 {unknown}");
+        assert_eq!(render("{latest} {oops", &values), "1.0.0 {oops");
+        assert_eq!(render("up to date", &values), "up to date");
+    }
+
+    #[test]
+    fn test_compute_from_history() {
+        let releases = vec![
+            release("2.0.0-rc.1", None),
+            release("1.6.0", Some("2024-03-01")),
+            release("1.5.0", None),
+            release("1.4.0", None),
+        ];
+        let values = HintValues::compute(
+            "^1.4",
+            Some("1.6.0"),
+            Some("1.4.0"),
+            Some(&releases),
+            &Formatter,
+        );
+
+        assert_eq!(values.compatible, Some("1.6.0"));
+        assert_eq!(values.kind, Some(UpdateKind::Minor));
+        assert_eq!(values.count, Some(2));
+        assert!(values.age.is_some());
+    }
+
+    #[test]
+    fn test_compute_without_history() {
+        let values = HintValues::compute("1.0", Some("2.0.0"), None, None, &Formatter);
+        assert_eq!(values.kind, Some(UpdateKind::Major));
+        assert_eq!(values.compatible, None);
+        assert_eq!(values.count, None);
+        assert_eq!(
+            render("{latest} ({kind}, {count} newer)", &values),
+            "2.0.0 (major,  newer)"
+        );
+    }
+
+    #[test]
+    fn test_uses_release_history() {
+        assert!(uses_release_history("{count} newer"));
+        assert!(uses_release_history("{compatible}"));
+        assert!(!uses_release_history("{locked} → {latest} ({kind})"));
+    }
+}
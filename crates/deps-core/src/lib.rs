@@ -10,22 +10,28 @@
 //! - **HTTP Cache**: Shared caching layer with ETag/Last-Modified validation
 //! - **Error Types**: Unified error handling across all ecosystems
 
+pub mod add_dependency;
 pub mod cache;
 pub mod completion;
+pub mod dependency_tree;
 pub mod ecosystem;
 pub mod ecosystem_registry;
 pub mod error;
+pub mod hint_template;
 pub mod lockfile;
 pub mod lsp_helpers;
 pub mod macros;
 pub mod parser;
 pub mod registry;
+pub mod sort;
+pub mod table_completion;
 #[cfg(any(test, feature = "test-util"))]
 pub mod test_util;
 pub mod version_matcher;
+pub mod workspace;
 
 // Re-export commonly used types
-pub use cache::{CachedResponse, HttpCache};
+pub use cache::{CacheStats, CachedResponse, HttpCache};
 pub use ecosystem::{Dependency, Ecosystem, EcosystemConfig, EcosystemId, ParseResult};
 pub use ecosystem_registry::EcosystemRegistry;
 pub use error::{DepsError, Result};
@@ -7,6 +7,7 @@
 //! without network requests to registries.
 
 use crate::error::{DepsError, Result};
+use crate::registry::Version;
 use dashmap::DashMap;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -152,7 +153,8 @@ pub struct ResolvedPackage {
 /// Source of a resolved dependency.
 ///
 /// Indicates where the package was downloaded from or how it was resolved.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
 pub enum ResolvedSource {
     /// From a registry with optional checksum
     Registry {
@@ -175,6 +177,67 @@ pub enum ResolvedSource {
     },
 }
 
+impl ResolvedSource {
+    /// Checksum recorded for a registry package, `None` when the lock file
+    /// has none.
+    pub fn checksum(&self) -> Option<&str> {
+        match self {
+            Self::Registry { checksum, .. } if !checksum.is_empty() => Some(checksum),
+            _ => None,
+        }
+    }
+}
+
+/// Lock file checksum differing from the one published by the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// Locked version
+    pub version: String,
+    /// Checksum recorded in the lock file
+    pub locked: String,
+    /// Checksum published by the registry
+    pub registry: String,
+}
+
+/// Compares the checksum `package` was locked with to the one `versions`
+/// (the registry's releases of the package) publish for the same version.
+///
+/// `None` when they match, or when either side has no checksum to compare.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::lockfile::{ResolvedPackage, ResolvedSource, verify_checksum};
+///
+/// let package = ResolvedPackage {
+///     name: "serde".into(),
+///     version: "1.0.195".into(),
+///     source: ResolvedSource::Registry {
+///         url: "https://github.com/rust-lang/crates.io-index".into(),
+///         checksum: "abc123".into(),
+///     },
+///     dependencies: vec![],
+/// };
+///
+/// // Nothing published to compare with
+/// assert_eq!(verify_checksum(&package, &[]), None);
+/// ```
+pub fn verify_checksum(
+    package: &ResolvedPackage,
+    versions: &[Box<dyn Version>],
+) -> Option<ChecksumMismatch> {
+    let locked = package.source.checksum()?;
+    let registry = versions
+        .iter()
+        .find(|v| v.version_string() == package.version)?
+        .checksum()?;
+    (!locked.eq_ignore_ascii_case(registry)).then(|| ChecksumMismatch {
+        version: package.version.clone(),
+        locked: locked.to_string(),
+        registry: registry.to_string(),
+    })
+}
+
 /// Collection of resolved packages from a lock file.
 ///
 /// Supports multiple versions per package name, returning the highest
@@ -269,6 +332,11 @@ impl ResolvedPackages {
         })
     }
 
+    /// Returns an iterator over every stored package, including all versions.
+    pub fn iter_all(&self) -> impl Iterator<Item = &ResolvedPackage> {
+        self.packages.values().flatten()
+    }
+
     /// Converts into a HashMap with the best version per package name.
     pub fn into_map(self) -> HashMap<String, ResolvedPackage> {
         self.packages
@@ -345,6 +413,15 @@ pub trait LockFileProvider: Send + Sync {
         lockfile_path: &'a Path,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ResolvedPackages>> + Send + 'a>>;
 
+    /// Whether parsed packages list their own dependencies.
+    ///
+    /// Lock files that only pin versions (`go.sum`, `pubspec.lock`,
+    /// `Package.resolved`) return `false`, so dependency graph features can
+    /// tell an unsupported format from a package without dependencies.
+    fn records_dependencies(&self) -> bool {
+        true
+    }
+
     /// Checks if lock file has been modified since last parse.
     ///
     /// Used for cache invalidation. Default implementation compares
@@ -550,6 +627,24 @@ mod tests {
         assert_eq!(retrieved.unwrap().dependencies.len(), 1);
     }
 
+    #[test]
+    fn test_resolved_packages_iter_all_includes_every_version() {
+        let mut packages = ResolvedPackages::new();
+        for version in ["0.7.3", "0.8.5"] {
+            packages.insert(ResolvedPackage {
+                name: "rand".into(),
+                version: version.into(),
+                source: ResolvedSource::Path {
+                    path: String::new(),
+                },
+                dependencies: vec![],
+            });
+        }
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages.iter_all().count(), 2);
+    }
+
     #[test]
     fn test_resolved_packages_get_nonexistent() {
         let packages = ResolvedPackages::new();
@@ -690,6 +785,54 @@ mod tests {
         assert_ne!(source1, source3);
     }
 
+    struct ChecksummedVersion(&'static str, Option<&'static str>);
+
+    impl Version for ChecksummedVersion {
+        fn version_string(&self) -> &str {
+            self.0
+        }
+        fn is_yanked(&self) -> bool {
+            false
+        }
+        fn checksum(&self) -> Option<&str> {
+            self.1
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let package = |checksum: &str| ResolvedPackage {
+            name: "serde".into(),
+            version: "1.0.0".into(),
+            source: ResolvedSource::Registry {
+                url: "test".into(),
+                checksum: checksum.into(),
+            },
+            dependencies: vec![],
+        };
+        let versions: Vec<Box<dyn Version>> = vec![
+            Box::new(ChecksummedVersion("1.0.1", Some("ffff"))),
+            Box::new(ChecksummedVersion("1.0.0", Some("ABCD"))),
+        ];
+
+        assert_eq!(verify_checksum(&package("abcd"), &versions), None);
+        assert_eq!(
+            verify_checksum(&package("1234"), &versions),
+            Some(ChecksumMismatch {
+                version: "1.0.0".into(),
+                locked: "1234".into(),
+                registry: "ABCD".into(),
+            })
+        );
+        // Nothing to compare
+        assert_eq!(verify_checksum(&package(""), &versions), None);
+        let unpublished: Vec<Box<dyn Version>> = vec![Box::new(ChecksummedVersion("1.0.0", None))];
+        assert_eq!(verify_checksum(&package("1234"), &unpublished), None);
+    }
+
     #[test]
     fn test_resolved_packages_iter() {
         let mut packages = ResolvedPackages::new();
@@ -3,10 +3,11 @@
 use std::collections::HashMap;
 use tower_lsp_server::ls_types::{
     CodeAction, CodeActionKind, Diagnostic, DiagnosticSeverity, Hover, HoverContents, InlayHint,
-    InlayHintKind, InlayHintLabel, InlayHintTooltip, MarkupContent, MarkupKind, Position, Range,
-    TextEdit, Uri, WorkspaceEdit,
+    InlayHintKind, InlayHintLabel, InlayHintTooltip, MarkupContent, MarkupKind, NumberOrString,
+    Position, Range, TextEdit, Uri, WorkspaceEdit,
 };
 
+use crate::hint_template::{self, HintValues, ReleaseInfo};
 use crate::{Dependency, EcosystemConfig, ParseResult, Registry};
 
 /// Bundles the two per-package version maps (`cached`, `resolved`) that LSP handlers pass
@@ -38,6 +39,8 @@ pub struct VersionData<'a> {
     pub cached: &'a HashMap<String, String>,
     /// Versions actually resolved in the lock file, keyed by package name.
     pub resolved: &'a HashMap<String, String>,
+    /// Release histories (newest first) for hint templates, when fetched.
+    pub releases: Option<&'a HashMap<String, Vec<ReleaseInfo>>>,
 }
 
 impl<'a> VersionData<'a> {
@@ -55,7 +58,21 @@ impl<'a> VersionData<'a> {
     /// assert!(versions.cached.is_empty());
     /// ```
     pub fn new(cached: &'a HashMap<String, String>, resolved: &'a HashMap<String, String>) -> Self {
-        Self { cached, resolved }
+        Self {
+            cached,
+            resolved,
+            releases: None,
+        }
+    }
+
+    /// Attaches release histories used by the `{compatible}`, `{age}` and
+    /// `{count}` hint placeholders.
+    #[must_use]
+    pub const fn with_releases(self, releases: &'a HashMap<String, Vec<ReleaseInfo>>) -> Self {
+        Self {
+            releases: Some(releases),
+            ..self
+        }
     }
 }
 
@@ -108,6 +125,36 @@ impl LineOffsetTable {
             .sum();
         Position::new(line as u32, character)
     }
+
+    /// Converts an LSP `Position` back into a byte offset.
+    ///
+    /// Positions past the end of a line clamp to the line end; lines past the
+    /// end of the content clamp to `content.len()`.
+    pub fn position_to_byte_offset(&self, content: &str, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return content.len();
+        };
+        let line_end = self
+            .line_starts
+            .get(position.line as usize + 1)
+            .map_or(content.len(), |&next| next.saturating_sub(1));
+
+        let mut utf16 = 0;
+        for (i, c) in content[line_start..line_end].char_indices() {
+            if utf16 >= position.character {
+                return line_start + i;
+            }
+            utf16 += c.len_utf16() as u32;
+        }
+        line_end
+    }
+
+    /// Returns the source text covered by `range`.
+    pub fn slice<'c>(&self, content: &'c str, range: Range) -> &'c str {
+        let start = self.position_to_byte_offset(content, range.start);
+        let end = self.position_to_byte_offset(content, range.end);
+        content.get(start..end.max(start)).unwrap_or("")
+    }
 }
 
 /// Escapes Markdown syntax characters so untrusted text cannot break out of the
@@ -304,6 +351,20 @@ pub trait EcosystemFormatter: Send + Sync {
     }
 }
 
+/// Finds the dependency whose name or version range contains `position`.
+pub fn find_dependency_at(
+    parse_result: &dyn ParseResult,
+    position: Position,
+) -> Option<&dyn Dependency> {
+    parse_result.dependencies().into_iter().find(|d| {
+        let on_name = position_in_range(position, d.name_range());
+        let on_version = d
+            .version_range()
+            .is_some_and(|r| position_in_range(position, r));
+        on_name || on_version
+    })
+}
+
 pub fn generate_inlay_hints(
     parse_result: &dyn ParseResult,
     versions: VersionData<'_>,
@@ -315,6 +376,23 @@ pub fn generate_inlay_hints(
     let mut hints = Vec::with_capacity(deps.len());
 
     for dep in deps {
+        if let Some(note) = dep.overridden_by() {
+            let anchor = dep.version_range().unwrap_or_else(|| dep.name_range());
+            hints.push(InlayHint {
+                position: anchor.end,
+                label: InlayHintLabel::String(note.to_string()),
+                kind: Some(InlayHintKind::TYPE),
+                tooltip: Some(InlayHintTooltip::String(
+                    "The override is built instead of the registry version".to_string(),
+                )),
+                padding_left: Some(true),
+                padding_right: None,
+                text_edits: None,
+                data: None,
+            });
+            continue;
+        }
+
         let Some(version_range) = dep.version_range() else {
             continue;
         };
@@ -349,22 +427,31 @@ pub fn generate_inlay_hints(
             continue;
         }
 
+        let requirement = dep.version_requirement().unwrap_or("");
+        let releases = versions
+            .releases
+            .and_then(|r| r.get(&normalized_name).or_else(|| r.get(dep.name())))
+            .map(Vec::as_slice);
+        let values = HintValues::compute(
+            requirement,
+            latest_version.map(String::as_str),
+            resolved_version.map(String::as_str),
+            releases,
+            formatter,
+        );
+        let data = Some(serde_json::json!({ "name": dep.name() }));
+
         let Some(latest) = latest_version else {
-            if let Some(resolved) = resolved_version
-                && config.show_up_to_date_hints
-            {
+            if resolved_version.is_some() && config.show_up_to_date_hints {
                 hints.push(InlayHint {
                     position: version_range.end,
-                    label: InlayHintLabel::String(format!(
-                        "{} {}",
-                        config.up_to_date_text, resolved
-                    )),
+                    label: InlayHintLabel::String(up_to_date_label(config, &values)),
                     kind: Some(InlayHintKind::TYPE),
                     padding_left: Some(true),
                     padding_right: None,
                     text_edits: None,
                     tooltip: None,
-                    data: None,
+                    data,
                 });
             }
             continue;
@@ -376,22 +463,28 @@ pub fn generate_inlay_hints(
         let is_up_to_date = if let Some(resolved) = resolved_version {
             resolved.as_str() == latest.as_str()
         } else {
-            let version_req = dep.version_requirement().unwrap_or("");
-            formatter.is_requirement_up_to_date(version_req, latest)
+            formatter.is_requirement_up_to_date(requirement, latest)
         };
 
-        let label_text = if is_up_to_date {
-            if config.show_up_to_date_hints {
-                if let Some(resolved) = resolved_version {
-                    format!("{} {}", config.up_to_date_text, resolved)
-                } else {
-                    config.up_to_date_text.clone()
-                }
-            } else {
-                continue;
-            }
+        if is_up_to_date && !config.show_up_to_date_hints {
+            continue;
+        }
+
+        let locked = resolved_version.filter(|locked| {
+            config.show_locked_versions
+                && is_loose_requirement(requirement, locked, latest, formatter)
+        });
+        let (label_text, tooltip) = if let Some(locked) = locked {
+            let (label, tooltip) =
+                locked_label(config, &values, requirement, locked, latest, formatter);
+            (label, Some(InlayHintTooltip::String(tooltip)))
+        } else if is_up_to_date {
+            (up_to_date_label(config, &values), None)
         } else {
-            config.needs_update_text.replace("{}", latest)
+            (
+                hint_template::render(&config.needs_update_text, &values),
+                None,
+            )
         };
 
         hints.push(InlayHint {
@@ -401,14 +494,83 @@ pub fn generate_inlay_hints(
             padding_left: Some(true),
             padding_right: None,
             text_edits: None,
-            tooltip: None,
-            data: None,
+            tooltip,
+            data,
         });
     }
 
     hints
 }
 
+/// Whether `requirement` is more than a pin of `locked`, so the locked
+/// version tells the reader something the manifest doesn't.
+///
+/// A requirement spelling out the locked version is still loose when it
+/// accepts a newer one, as Cargo's bare `1.0.80` does.
+fn is_loose_requirement(
+    requirement: &str,
+    locked: &str,
+    latest: &str,
+    formatter: &dyn EcosystemFormatter,
+) -> bool {
+    let pinned = requirement.trim().trim_start_matches(['=', ' ']);
+    if pinned.is_empty() {
+        return false;
+    }
+    pinned != locked
+        || (latest != locked && formatter.version_satisfies_requirement(latest, requirement))
+}
+
+/// Label and tooltip of a hint showing the locked version.
+///
+/// An outdated lock is shown as `⇡ latest` when the requirement already
+/// accepts `latest` (updating the lock file is enough), and with
+/// `needs_update_text` when the requirement itself has to change.
+fn locked_label(
+    config: &EcosystemConfig,
+    values: &HintValues<'_>,
+    requirement: &str,
+    locked: &str,
+    latest: &str,
+    formatter: &dyn EcosystemFormatter,
+) -> (String, String) {
+    let prefix = format!("→ {locked} (locked)");
+    if locked == latest {
+        let status = hint_template::render(&config.up_to_date_text, values);
+        return (
+            format!("{prefix} {status}"),
+            "The lock file resolves the latest version".to_string(),
+        );
+    }
+
+    if formatter.version_satisfies_requirement(latest, requirement) {
+        (
+            format!("{prefix} ⇡ {latest}"),
+            format!("The requirement allows {latest}: update the lock file to get it"),
+        )
+    } else {
+        let status = hint_template::render(&config.needs_update_text, values);
+        (
+            format!("{prefix} {status}"),
+            format!(
+                "{latest} is outside the requirement {requirement}: edit the manifest to update"
+            ),
+        )
+    }
+}
+
+/// Label of an up-to-date hint: the rendered template, or for templates
+/// without placeholders the text followed by the locked version.
+fn up_to_date_label(config: &EcosystemConfig, values: &HintValues<'_>) -> String {
+    if config.up_to_date_text.contains('{') {
+        return hint_template::render(&config.up_to_date_text, values);
+    }
+    match values.locked {
+        Some(locked) => format!("{} {}", config.up_to_date_text, locked),
+        None => config.up_to_date_text.clone(),
+    }
+}
+
 pub async fn generate_hover<R: Registry + ?Sized>(
     parse_result: &dyn ParseResult,
     position: Position,
@@ -418,13 +580,7 @@ pub async fn generate_hover<R: Registry + ?Sized>(
 ) -> Option<Hover> {
     use std::fmt::Write;
 
-    let dep = parse_result.dependencies().into_iter().find(|d| {
-        let on_name = position_in_range(position, d.name_range());
-        let on_version = d
-            .version_range()
-            .is_some_and(|r| position_in_range(position, r));
-        on_name || on_version
-    })?;
+    let dep = find_dependency_at(parse_result, position)?;
 
     let available_versions = registry.get_versions(dep.name()).await.ok()?;
 
@@ -440,6 +596,15 @@ pub async fn generate_hover<R: Registry + ?Sized>(
     )
     .unwrap();
 
+    if let Some(alias) = dep.alias() {
+        write!(
+            &mut markdown,
+            "**Declared as**: {}\n\n",
+            markdown_code_span(alias)
+        )
+        .unwrap();
+    }
+
     let normalized_name = formatter.normalize_package_name(dep.name());
 
     let resolved = versions
@@ -471,6 +636,15 @@ pub async fn generate_hover<R: Registry + ?Sized>(
         .unwrap();
     }
 
+    if let Some(note) = dep.overridden_by() {
+        write!(
+            &mut markdown,
+            "**Overridden**: {}\n\n",
+            escape_markdown(note)
+        )
+        .unwrap();
+    }
+
     let latest = versions
         .cached
         .get(&normalized_name)
@@ -569,6 +743,19 @@ pub async fn generate_code_actions<R: Registry + ?Sized>(
     actions
 }
 
+/// Diagnostic code of packages the registry does not know.
+pub const UNKNOWN_PACKAGE_CODE: &str = "unknown-package";
+
+/// Diagnostic code of requirements excluding the latest version.
+pub const OUTDATED_CODE: &str = "outdated";
+
+/// Diagnostic code of yanked (or deprecated) versions.
+pub const YANKED_CODE: &str = "yanked";
+
+fn diagnostic_code(code: &str) -> Option<NumberOrString> {
+    Some(NumberOrString::String(code.into()))
+}
+
 /// Generates diagnostics using cached versions (no network calls).
 ///
 /// Uses pre-fetched version information from the lifecycle's parallel fetch.
@@ -603,6 +790,7 @@ pub fn generate_diagnostics_from_cache(
                 diagnostics.push(Diagnostic {
                     range: dep.name_range(),
                     severity: Some(DiagnosticSeverity::WARNING),
+                    code: diagnostic_code(UNKNOWN_PACKAGE_CODE),
                     message: format!("Unknown package '{}'", dep.name()),
                     source: Some("deps-lsp".into()),
                     ..Default::default()
@@ -618,10 +806,11 @@ pub fn generate_diagnostics_from_cache(
         let version_req = dep.version_requirement().unwrap_or("");
         let is_up_to_date = formatter.is_requirement_up_to_date(version_req, latest);
 
-        if !is_up_to_date {
+        if !is_up_to_date && dep.overridden_by().is_none() {
             diagnostics.push(Diagnostic {
                 range: version_range,
                 severity: Some(DiagnosticSeverity::HINT),
+                code: diagnostic_code(OUTDATED_CODE),
                 message: format!("Newer version available: {}", latest),
                 source: Some("deps-lsp".into()),
                 ..Default::default()
@@ -654,6 +843,7 @@ pub async fn generate_diagnostics<R: Registry + ?Sized>(
                 diagnostics.push(Diagnostic {
                     range: dep.name_range(),
                     severity: Some(DiagnosticSeverity::WARNING),
+                    code: diagnostic_code(UNKNOWN_PACKAGE_CODE),
                     message: format!("Unknown package '{}'", dep.name()),
                     source: Some("deps-lsp".into()),
                     ..Default::default()
@@ -680,6 +870,7 @@ pub async fn generate_diagnostics<R: Registry + ?Sized>(
                 diagnostics.push(Diagnostic {
                     range: version_range,
                     severity: Some(DiagnosticSeverity::WARNING),
+                    code: diagnostic_code(YANKED_CODE),
                     message: formatter.yanked_message().into(),
                     source: Some("deps-lsp".into()),
                     ..Default::default()
@@ -688,11 +879,13 @@ pub async fn generate_diagnostics<R: Registry + ?Sized>(
 
             let latest = crate::registry::find_latest_stable(&versions);
             if let Some(latest) = latest
+                && dep.overridden_by().is_none()
                 && !formatter.is_requirement_up_to_date(version_req, latest.version_string())
             {
                 diagnostics.push(Diagnostic {
                     range: version_range,
                     severity: Some(DiagnosticSeverity::HINT),
+                    code: diagnostic_code(OUTDATED_CODE),
                     message: format!("Newer version available: {}", latest.version_string()),
                     source: Some("deps-lsp".into()),
                     ..Default::default()
@@ -765,6 +958,38 @@ mod tests {
         assert!(position_in_range(position, range));
     }
 
+    #[test]
+    fn test_line_offset_table_round_trip() {
+        let content = "a = 1\nname = \"日本\"\nlast";
+        let table = LineOffsetTable::new(content);
+        for offset in [0, 4, 6, 14, 17, content.len()] {
+            let position = table.byte_offset_to_position(content, offset);
+            assert_eq!(table.position_to_byte_offset(content, position), offset);
+        }
+    }
+
+    #[test]
+    fn test_line_offset_table_position_clamping() {
+        let content = "abc\ndef";
+        let table = LineOffsetTable::new(content);
+        assert_eq!(
+            table.position_to_byte_offset(content, Position::new(0, 99)),
+            3
+        );
+        assert_eq!(
+            table.position_to_byte_offset(content, Position::new(9, 0)),
+            content.len()
+        );
+    }
+
+    #[test]
+    fn test_line_offset_table_slice() {
+        let content = "[deps]\nserde = \"1.0\"\n";
+        let table = LineOffsetTable::new(content);
+        let range = Range::new(Position::new(1, 9), Position::new(1, 12));
+        assert_eq!(table.slice(content, range), "1.0");
+    }
+
     #[test]
     fn test_escape_markdown_link_breakout_payload() {
         let payload = "real-pkg](https://legit-looking-typosquat.example/download)[real-pkg";
@@ -1329,6 +1554,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1376,6 +1602,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1427,6 +1654,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1477,6 +1705,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: false,
         };
@@ -1557,6 +1786,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1629,6 +1859,10 @@ mod tests {
         assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
         assert!(diagnostics[0].message.contains("Unknown package"));
         assert!(diagnostics[0].message.contains("unknown-pkg"));
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String(UNKNOWN_PACKAGE_CODE.into()))
+        );
     }
 
     #[test]
@@ -1663,6 +1897,10 @@ mod tests {
         assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
         assert!(diagnostics[0].message.contains("Newer version available"));
         assert!(diagnostics[0].message.contains("2.0.0"));
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String(OUTDATED_CODE.into()))
+        );
     }
 
     #[test]
@@ -1765,6 +2003,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1816,6 +2055,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
             loading_text: "⏳".to_string(),
             show_loading_hints: true,
         };
@@ -1857,4 +2097,107 @@ mod tests {
             _ => panic!("Expected string label"),
         }
     }
+
+    #[test]
+    fn test_inlay_hint_locked_versions() {
+        use std::collections::HashMap;
+        use tower_lsp_server::ls_types::{Position, Range};
+
+        /// Cargo's requirement semantics, where `1.0.80` means `^1.0.80`.
+        struct CaretFormatter;
+
+        impl EcosystemFormatter for CaretFormatter {
+            fn format_version_for_text_edit(&self, version: &str) -> String {
+                version.to_string()
+            }
+
+            fn package_url(&self, name: &str) -> String {
+                format!("https://example.com/{name}")
+            }
+
+            fn version_satisfies_requirement(&self, version: &str, requirement: &str) -> bool {
+                let version = semver::Version::parse(version).unwrap();
+                semver::VersionReq::parse(requirement)
+                    .unwrap()
+                    .matches(&version)
+            }
+        }
+
+        let config = EcosystemConfig {
+            show_locked_versions: true,
+            ..EcosystemConfig::default()
+        };
+        let dep = |name: &str, version_req: &str, line: u32| MockDep {
+            name: name.to_string(),
+            version_req: version_req.to_string(),
+            version_range: Range::new(Position::new(line, 10), Position::new(line, 20)),
+            name_range: Range::new(Position::new(line, 0), Position::new(line, 9)),
+        };
+        let parse_result = MockParseResult {
+            deps: vec![
+                dep("serde", "1", 0),
+                dep("clap", "3.2", 1),
+                dep("anyhow", "1.0.80", 2),
+                dep("tokio", "1", 3),
+                dep("log", "=0.4.20", 4),
+            ],
+            uri: crate::test_util::test_uri("/test/Cargo.toml"),
+        };
+
+        let cached_versions: HashMap<String, String> = [
+            ("serde", "1.0.210"),
+            ("clap", "4.5.0"),
+            ("anyhow", "1.0.86"),
+            ("tokio", "1.40.0"),
+            ("log", "0.4.22"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let resolved_versions: HashMap<String, String> = [
+            ("serde", "1.0.195"),
+            ("clap", "3.2.25"),
+            ("anyhow", "1.0.80"),
+            ("tokio", "1.40.0"),
+            ("log", "0.4.20"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let hints = generate_inlay_hints(
+            &parse_result,
+            VersionData::new(&cached_versions, &resolved_versions),
+            crate::LoadingState::Loaded,
+            &config,
+            &CaretFormatter,
+        );
+
+        let labels: Vec<_> = hints
+            .iter()
+            .map(|h| match &h.label {
+                InlayHintLabel::String(text) => text.as_str(),
+                InlayHintLabel::LabelParts(_) => panic!("Expected string label"),
+            })
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "→ 1.0.195 (locked) ⇡ 1.0.210",
+                "→ 3.2.25 (locked) ❌ 4.5.0",
+                "→ 1.0.80 (locked) ⇡ 1.0.86",
+                "→ 1.40.0 (locked) ✅",
+                "❌ 0.4.22",
+            ]
+        );
+
+        let tooltip = |i: usize| match &hints[i].tooltip {
+            Some(InlayHintTooltip::String(text)) => text.clone(),
+            _ => String::new(),
+        };
+        assert!(tooltip(0).contains("update the lock file"));
+        assert!(tooltip(1).contains("edit the manifest"));
+        assert!(tooltip(2).contains("update the lock file"));
+        assert!(hints[4].tooltip.is_none());
+    }
 }
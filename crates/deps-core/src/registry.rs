@@ -125,6 +125,22 @@ pub trait Registry: Send + Sync {
     /// Returns a URL that links to the package page on the registry website.
     fn package_url(&self, name: &str) -> String;
 
+    /// Page describing one release of a package: release notes or a
+    /// changelog where the registry has them, the version's page otherwise.
+    ///
+    /// Default: `None`, for registries without per-version pages.
+    fn release_url(&self, _name: &str, _version: &str) -> Option<String> {
+        None
+    }
+
+    /// Versions of `name` this client already fetched, without a network
+    /// request.
+    ///
+    /// Default: `None`, for registries that don't keep fetched versions.
+    fn cached_versions(&self, _name: &str) -> Option<Vec<Box<dyn Version>>> {
+        None
+    }
+
     /// Downcast to concrete registry type for ecosystem-specific operations
     fn as_any(&self) -> &dyn Any;
 }
@@ -159,6 +175,19 @@ pub trait Version: Send + Sync {
         vec![]
     }
 
+    /// Publication timestamp (RFC 3339 or `YYYY-MM-DD`), if the registry
+    /// reports one.
+    fn published_at(&self) -> Option<&str> {
+        None
+    }
+
+    /// Checksum of the published archive as recorded by the registry (the
+    /// sparse index `cksum` for Cargo), compared with the lock file by
+    /// [`verify_checksum`](crate::lockfile::verify_checksum).
+    fn checksum(&self) -> Option<&str> {
+        None
+    }
+
     /// Downcast to concrete version type
     fn as_any(&self) -> &dyn Any;
 
@@ -0,0 +1,757 @@
+//! Sorting of dependency sections.
+//!
+//! Ecosystems describe each dependency section of a manifest as a
+//! [`SortableSection`]: the line every entry starts on, plus the lexical
+//! rules of the manifest format (comment prefix, entry separator, and how an
+//! entry continues over several lines). [`sort_section`] then reorders the
+//! entries line by line, so formatting, comments and unknown content survive
+//! untouched.
+//!
+//! A section is sorted in runs: consecutive entries separated only by
+//! comment lines. Blank lines and any line that belongs to no entry (a table
+//! header, a closing bracket, an unrelated key) end a run, so hand-made
+//! groupings stay where they are. Comment lines directly above an entry move
+//! with it.
+
+use tower_lsp_server::ls_types::{Position, Range, TextEdit};
+
+/// How an entry continues past its first line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryExtent {
+    /// The entry ends on the first line where all brackets opened by the
+    /// entry are closed (TOML, JSON, go.mod, Gemfile).
+    Brackets,
+    /// The entry spans the following lines indented deeper than its first
+    /// line (YAML).
+    Indentation,
+}
+
+/// Lexical rules of a manifest format relevant to sorting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionStyle {
+    /// Prefix of line comments (`#`, `//`).
+    pub comment_prefix: &'static str,
+    /// Separator written after every entry but (optionally) the last one,
+    /// e.g. the commas of a JSON object.
+    pub separator: Option<char>,
+    /// How entries continue over several lines.
+    pub extent: EntryExtent,
+}
+
+impl SectionStyle {
+    /// TOML tables: `#` comments, no separator.
+    pub const TOML_TABLE: Self = Self {
+        comment_prefix: "#",
+        separator: None,
+        extent: EntryExtent::Brackets,
+    };
+
+    /// TOML arrays: `#` comments, comma separated.
+    pub const TOML_ARRAY: Self = Self {
+        comment_prefix: "#",
+        separator: Some(','),
+        extent: EntryExtent::Brackets,
+    };
+
+    /// JSON objects: no comments, comma separated.
+    pub const JSON_OBJECT: Self = Self {
+        comment_prefix: "//",
+        separator: Some(','),
+        extent: EntryExtent::Brackets,
+    };
+
+    /// YAML block mappings: `#` comments, indentation based.
+    pub const YAML_MAPPING: Self = Self {
+        comment_prefix: "#",
+        separator: None,
+        extent: EntryExtent::Indentation,
+    };
+
+    /// XML elements: `<!--` comments, no separator. Entries spanning several
+    /// lines must set [`SortableEntry::last_line`] to their closing tag.
+    pub const XML_ELEMENT: Self = Self {
+        comment_prefix: "<!--",
+        separator: None,
+        extent: EntryExtent::Brackets,
+    };
+}
+
+/// A dependency entry of a [`SortableSection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortableEntry {
+    /// Sort key, usually the dependency name as written.
+    pub key: String,
+    /// Line the entry starts on.
+    pub first_line: u32,
+    /// Last line known to belong to the entry. The sorter extends the entry
+    /// further as dictated by [`SectionStyle::extent`].
+    pub last_line: u32,
+}
+
+impl SortableEntry {
+    /// Creates an entry starting on `line`.
+    pub fn new(key: impl Into<String>, line: u32) -> Self {
+        Self {
+            key: key.into(),
+            first_line: line,
+            last_line: line,
+        }
+    }
+}
+
+/// A dependency section whose entries can be sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortableSection {
+    /// Human readable name of the section (e.g. `[dev-dependencies]`).
+    pub label: String,
+    /// Lexical rules of the manifest format.
+    pub style: SectionStyle,
+    /// Entries of the section, in any order.
+    pub entries: Vec<SortableEntry>,
+}
+
+impl SortableSection {
+    /// Groups entries into sections by label, keeping the order in which
+    /// labels first appear.
+    pub fn group(
+        style: SectionStyle,
+        entries: impl IntoIterator<Item = (String, SortableEntry)>,
+    ) -> Vec<Self> {
+        let mut sections: Vec<Self> = Vec::new();
+        for (label, entry) in entries {
+            match sections.iter_mut().find(|s| s.label == label) {
+                Some(section) => section.entries.push(entry),
+                None => sections.push(Self {
+                    label,
+                    style,
+                    entries: vec![entry],
+                }),
+            }
+        }
+        sections
+    }
+}
+
+/// A run of consecutive entries of a section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortedRun {
+    /// Lines covered by the run, including comments attached to its entries.
+    pub range: Range,
+    /// Edit sorting the run, or `None` when it is already sorted.
+    pub edit: Option<TextEdit>,
+}
+
+/// Sorts every run of `section`.
+///
+/// Returns an empty list when the section cannot be sorted safely, e.g.
+/// when several entries share a line.
+pub fn sort_section(content: &str, section: &SortableSection) -> Vec<SortedRun> {
+    let lines: Vec<&str> = content.lines().collect();
+    let newline = newline(content);
+    let Some(blocks) = blocks(&lines, section) else {
+        return vec![];
+    };
+
+    runs(&blocks)
+        .into_iter()
+        .filter_map(|run| sort_run(&lines, run, &section.style, newline))
+        .collect()
+}
+
+/// Builds the edits inserting `entry`, keyed `key`, into `section`.
+///
+/// When every run of the section is sorted, the entry goes right after the
+/// last entry sorting before it (or before the first entry), so the order is
+/// kept; otherwise it is appended after the last entry. Each line of `entry`
+/// is indented like the neighbouring entry, and separators are added where
+/// the format needs them.
+///
+/// Returns `None` when the section has no entries or cannot be edited safely.
+pub fn insert_sorted(
+    content: &str,
+    section: &SortableSection,
+    key: &str,
+    entry: &str,
+) -> Option<Vec<TextEdit>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let newline = newline(content);
+    let style = &section.style;
+    let blocks = blocks(&lines, section)?;
+
+    let sorted = runs(&blocks).iter().all(|run| {
+        run.windows(2)
+            .all(|w| compare_keys(w[0].key, w[1].key).is_le())
+    });
+    let (anchor, before) = if sorted {
+        blocks
+            .iter()
+            .rposition(|b| compare_keys(b.key, key).is_le())
+            .map_or((blocks.first()?, true), |i| (&blocks[i], false))
+    } else {
+        (blocks.last()?, false)
+    };
+
+    let indent = &lines[anchor.line][..indentation(lines[anchor.line])];
+    let mut indented: Vec<String> = entry
+        .lines()
+        .map(|l| {
+            if l.is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{l}")
+            }
+        })
+        .collect();
+
+    if before {
+        if let (Some(separator), Some(last)) = (style.separator, indented.last_mut()) {
+            last.push(separator);
+        }
+        let position = Position::new(anchor.start as u32, 0);
+        return Some(vec![TextEdit {
+            range: Range::new(position, position),
+            new_text: format!("{}{newline}", indented.join(newline)),
+        }]);
+    }
+
+    let anchor_line = lines[anchor.end];
+    let line_end = Position::new(anchor.end as u32, utf16_len(anchor_line));
+    let mut edits = Vec::new();
+    let mut prefix = String::new();
+    if let Some(separator) = style.separator {
+        let code = code_part(anchor_line, style.comment_prefix).trim_end();
+        if code.ends_with(separator) {
+            if let Some(last) = indented.last_mut() {
+                last.push(separator);
+            }
+        } else if code.len() == anchor_line.len() {
+            prefix.push(separator);
+        } else {
+            let position = Position::new(anchor.end as u32, utf16_len(code));
+            edits.push(TextEdit {
+                range: Range::new(position, position),
+                new_text: separator.to_string(),
+            });
+        }
+    }
+    edits.push(TextEdit {
+        range: Range::new(line_end, line_end),
+        new_text: format!("{prefix}{newline}{}", indented.join(newline)),
+    });
+    Some(edits)
+}
+
+struct Block<'a> {
+    key: &'a str,
+    /// First line of the entry itself.
+    line: usize,
+    /// First line including attached comments.
+    start: usize,
+    end: usize,
+}
+
+/// Computes the lines covered by each entry of `section`, in document order.
+fn blocks<'a>(lines: &[&str], section: &'a SortableSection) -> Option<Vec<Block<'a>>> {
+    let style = &section.style;
+    let mut entries: Vec<&SortableEntry> = section.entries.iter().collect();
+    entries.sort_by_key(|e| e.first_line);
+    if entries
+        .windows(2)
+        .any(|w| w[0].first_line == w[1].first_line)
+    {
+        return None;
+    }
+
+    let mut blocks: Vec<Block<'a>> = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let end = entry_end(lines, entry, style)?;
+        if entries
+            .get(i + 1)
+            .is_some_and(|next| end as u32 >= next.first_line)
+        {
+            return None;
+        }
+        let previous_end = blocks.last().map(|b| b.end);
+        let mut start = entry.first_line as usize;
+        while start > 0
+            && previous_end.is_none_or(|prev| start - 1 > prev)
+            && is_comment(lines[start - 1], style.comment_prefix)
+        {
+            start -= 1;
+        }
+        blocks.push(Block {
+            key: &entry.key,
+            line: entry.first_line as usize,
+            start,
+            end,
+        });
+    }
+    Some(blocks)
+}
+
+/// Splits blocks into runs of directly adjacent entries.
+fn runs<'b, 'a>(blocks: &'b [Block<'a>]) -> Vec<&'b [Block<'a>]> {
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    for i in 1..=blocks.len() {
+        if i == blocks.len() || blocks[i].start != blocks[i - 1].end + 1 {
+            runs.push(&blocks[run_start..i]);
+            run_start = i;
+        }
+    }
+    runs
+}
+
+fn compare_keys(a: &str, b: &str) -> std::cmp::Ordering {
+    a.to_lowercase()
+        .cmp(&b.to_lowercase())
+        .then_with(|| a.cmp(b))
+}
+
+fn newline(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+fn sort_run(
+    lines: &[&str],
+    run: &[Block<'_>],
+    style: &SectionStyle,
+    newline: &str,
+) -> Option<SortedRun> {
+    let first = run.first()?;
+    let last = run.last()?;
+    let end_line = lines[last.end];
+    let range = Range::new(
+        Position::new(first.start as u32, 0),
+        Position::new(last.end as u32, utf16_len(end_line)),
+    );
+
+    let trailing_separator = match style.separator {
+        Some(separator) => {
+            let separated = |block: &Block<'_>| {
+                code_part(lines[block.end], style.comment_prefix)
+                    .trim_end()
+                    .ends_with(separator)
+            };
+            if !run[..run.len() - 1].iter().all(separated) {
+                return None;
+            }
+            separated(last)
+        }
+        None => false,
+    };
+
+    let mut order: Vec<usize> = (0..run.len()).collect();
+    order.sort_by(|&a, &b| compare_keys(run[a].key, run[b].key));
+    if order.iter().enumerate().all(|(i, &o)| i == o) {
+        return Some(SortedRun { range, edit: None });
+    }
+
+    let mut sorted: Vec<String> = Vec::new();
+    for (position, &index) in order.iter().enumerate() {
+        let block = &run[index];
+        sorted.extend(
+            lines[block.start..block.end]
+                .iter()
+                .map(|l| (*l).to_string()),
+        );
+        let last_line = lines[block.end];
+        sorted.push(match style.separator {
+            Some(separator) => {
+                let separated = position + 1 < order.len() || trailing_separator;
+                set_separator(last_line, separator, separated, style.comment_prefix)
+            }
+            None => last_line.to_string(),
+        });
+    }
+
+    Some(SortedRun {
+        range,
+        edit: Some(TextEdit {
+            range,
+            new_text: sorted.join(newline),
+        }),
+    })
+}
+
+/// Returns the last line of `entry`, or `None` if the entry is unterminated
+/// or closes brackets it did not open.
+fn entry_end(lines: &[&str], entry: &SortableEntry, style: &SectionStyle) -> Option<usize> {
+    let first = entry.first_line as usize;
+    let known_last = (entry.last_line as usize).max(first);
+    if known_last >= lines.len() {
+        return None;
+    }
+
+    match style.extent {
+        EntryExtent::Brackets => {
+            let mut depth = 0i32;
+            for (i, line) in lines.iter().enumerate().skip(first) {
+                let code = code_part(line, style.comment_prefix);
+                depth += bracket_delta(code);
+                if depth < 0 {
+                    return None;
+                }
+                let continued = depth > 0
+                    || i < known_last
+                    || (style.separator.is_none() && code.trim_end().ends_with(','));
+                if !continued {
+                    return Some(i);
+                }
+            }
+            None
+        }
+        EntryExtent::Indentation => {
+            let indent = indentation(lines[first]);
+            let mut end = known_last;
+            for (i, line) in lines.iter().enumerate().skip(known_last + 1) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if indentation(line) <= indent {
+                    break;
+                }
+                end = i;
+            }
+            Some(end)
+        }
+    }
+}
+
+fn is_comment(line: &str, comment_prefix: &str) -> bool {
+    line.trim_start().starts_with(comment_prefix)
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Returns `line` up to its trailing comment, ignoring comment prefixes
+/// inside quoted strings.
+fn code_part<'a>(line: &'a str, comment_prefix: &str) -> &'a str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if line[i..].starts_with(comment_prefix) => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+fn bracket_delta(code: &str) -> i32 {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut delta = 0;
+    for c in code.chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' | '[' | '(' => delta += 1,
+                '}' | ']' | ')' => delta -= 1,
+                _ => {}
+            },
+        }
+    }
+    delta
+}
+
+/// Adds or removes the separator at the end of the code part of `line`.
+fn set_separator(line: &str, separator: char, separated: bool, comment_prefix: &str) -> String {
+    let code = code_part(line, comment_prefix).trim_end();
+    let rest = &line[code.len()..];
+    let code = code.strip_suffix(separator).unwrap_or(code);
+    if separated {
+        format!("{code}{separator}{rest}")
+    } else {
+        format!("{code}{rest}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(style: SectionStyle, entries: &[(&str, u32)]) -> SortableSection {
+        SortableSection {
+            label: "test".into(),
+            style,
+            entries: entries
+                .iter()
+                .map(|(key, line)| SortableEntry::new(*key, *line))
+                .collect(),
+        }
+    }
+
+    fn apply(content: &str, runs: &[SortedRun]) -> String {
+        let table = crate::LineOffsetTable::new(content);
+        let mut edits: Vec<&TextEdit> = runs.iter().filter_map(|r| r.edit.as_ref()).collect();
+        edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+        let mut result = content.to_string();
+        for edit in edits {
+            let start = table.position_to_byte_offset(content, edit.range.start);
+            let end = table.position_to_byte_offset(content, edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_sort_toml_table_keeps_comments_attached() {
+        let content = "[dependencies]\n# async runtime\ntokio = \"1\"\nserde = { version = \"1\", features = [\n  \"derive\",\n] }\nanyhow = \"1\" # errors\n";
+        let runs = sort_section(
+            content,
+            &section(
+                SectionStyle::TOML_TABLE,
+                &[("tokio", 2), ("serde", 3), ("anyhow", 6)],
+            ),
+        );
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].range.start, Position::new(1, 0));
+        assert_eq!(
+            apply(content, &runs),
+            "[dependencies]\nanyhow = \"1\" # errors\nserde = { version = \"1\", features = [\n  \"derive\",\n] }\n# async runtime\ntokio = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_already_sorted() {
+        let content = "[dependencies]\nanyhow = \"1\"\nserde = \"1\"\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::TOML_TABLE, &[("anyhow", 1), ("serde", 2)]),
+        );
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].edit.is_none());
+    }
+
+    #[test]
+    fn test_sort_blank_lines_split_runs() {
+        let content = "[dependencies]\nb = \"1\"\na = \"1\"\n\n# group\nd = \"1\"\nc = \"1\"\n";
+        let runs = sort_section(
+            content,
+            &section(
+                SectionStyle::TOML_TABLE,
+                &[("b", 1), ("a", 2), ("d", 5), ("c", 6)],
+            ),
+        );
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            apply(content, &runs),
+            "[dependencies]\na = \"1\"\nb = \"1\"\n\nc = \"1\"\n# group\nd = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_json_rewrites_separators() {
+        let content =
+            "{\n  \"dependencies\": {\n    \"react\": \"^18\",\n    \"axios\": \"^1\"\n  }\n}\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::JSON_OBJECT, &[("react", 2), ("axios", 3)]),
+        );
+        assert_eq!(
+            apply(content, &runs),
+            "{\n  \"dependencies\": {\n    \"axios\": \"^1\",\n    \"react\": \"^18\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_array_keeps_trailing_separator_and_comments() {
+        let content = "dependencies = [\n  \"requests>=2\",  # http\n  \"click\",\n]\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::TOML_ARRAY, &[("requests", 1), ("click", 2)]),
+        );
+        assert_eq!(
+            apply(content, &runs),
+            "dependencies = [\n  \"click\",\n  \"requests>=2\",  # http\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_yaml_indentation() {
+        let content = "dependencies:\n  http: ^1.0.0\n  foo:\n    git:\n      url: https://example.com/foo.git\n  bar: ^2.0.0\ndev_dependencies:\n  test: any\n";
+        let runs = sort_section(
+            content,
+            &section(
+                SectionStyle::YAML_MAPPING,
+                &[("http", 1), ("foo", 2), ("bar", 5)],
+            ),
+        );
+        assert_eq!(
+            apply(content, &runs),
+            "dependencies:\n  bar: ^2.0.0\n  foo:\n    git:\n      url: https://example.com/foo.git\n  http: ^1.0.0\ndev_dependencies:\n  test: any\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_skips_entries_sharing_a_line() {
+        let content = "dependencies = [\"b\", \"a\"]\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::TOML_ARRAY, &[("b", 0), ("a", 0)]),
+        );
+        assert!(runs.is_empty());
+    }
+
+    #[test]
+    fn test_sort_skips_closing_bracket_on_entry_line() {
+        let content = "dependencies = [\n  \"b\",\n  \"a\"]\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::TOML_ARRAY, &[("b", 1), ("a", 2)]),
+        );
+        assert!(runs.is_empty());
+    }
+
+    #[test]
+    fn test_sort_preserves_crlf() {
+        let content = "[dependencies]\r\nb = \"1\"\r\na = \"1\"\r\n";
+        let runs = sort_section(
+            content,
+            &section(SectionStyle::TOML_TABLE, &[("b", 1), ("a", 2)]),
+        );
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "a = \"1\"\r\nb = \"1\""
+        );
+    }
+
+    #[test]
+    fn test_group_by_label() {
+        let sections = SortableSection::group(
+            SectionStyle::TOML_TABLE,
+            [
+                ("[dependencies]".to_string(), SortableEntry::new("a", 1)),
+                ("[dev-dependencies]".to_string(), SortableEntry::new("b", 4)),
+                ("[dependencies]".to_string(), SortableEntry::new("c", 2)),
+            ],
+        );
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "[dependencies]");
+        assert_eq!(sections[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn test_code_part_ignores_prefix_in_strings() {
+        assert_eq!(code_part("\"a#b\" # c", "#"), "\"a#b\" ");
+        assert_eq!(code_part("x = 'it''s'", "#"), "x = 'it''s'");
+    }
+
+    fn insert(content: &str, section: &SortableSection, key: &str, entry: &str) -> String {
+        let edits = insert_sorted(content, section, key, entry).unwrap();
+        let runs: Vec<SortedRun> = edits
+            .into_iter()
+            .map(|edit| SortedRun {
+                range: edit.range,
+                edit: Some(edit),
+            })
+            .collect();
+        apply(content, &runs)
+    }
+
+    #[test]
+    fn test_insert_sorted_keeps_order() {
+        let content = "[dependencies]\nanyhow = \"1\"\n# serde\nserde = \"1\"\n";
+        let section = section(SectionStyle::TOML_TABLE, &[("anyhow", 1), ("serde", 3)]);
+        assert_eq!(
+            insert(content, &section, "rand", "rand = \"0.8\""),
+            "[dependencies]\nanyhow = \"1\"\nrand = \"0.8\"\n# serde\nserde = \"1\"\n"
+        );
+        assert_eq!(
+            insert(content, &section, "aho", "aho = \"1\""),
+            "[dependencies]\naho = \"1\"\nanyhow = \"1\"\n# serde\nserde = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_sorted_appends_to_unsorted_section() {
+        let content = "[dependencies]\nserde = \"1\"\nanyhow = \"1\"";
+        let section = section(SectionStyle::TOML_TABLE, &[("serde", 1), ("anyhow", 2)]);
+        assert_eq!(
+            insert(content, &section, "rand", "rand = \"0.8\""),
+            "[dependencies]\nserde = \"1\"\nanyhow = \"1\"\nrand = \"0.8\""
+        );
+    }
+
+    #[test]
+    fn test_insert_sorted_json_separators() {
+        let content =
+            "{\n  \"dependencies\": {\n    \"axios\": \"^1\",\n    \"react\": \"^18\"\n  }\n}\n";
+        let section = section(SectionStyle::JSON_OBJECT, &[("axios", 2), ("react", 3)]);
+        assert_eq!(
+            insert(content, &section, "zod", "\"zod\": \"^3\""),
+            "{\n  \"dependencies\": {\n    \"axios\": \"^1\",\n    \"react\": \"^18\",\n    \"zod\": \"^3\"\n  }\n}\n"
+        );
+        assert_eq!(
+            insert(content, &section, "lodash", "\"lodash\": \"^4\""),
+            "{\n  \"dependencies\": {\n    \"axios\": \"^1\",\n    \"lodash\": \"^4\",\n    \"react\": \"^18\"\n  }\n}\n"
+        );
+        assert_eq!(
+            insert(content, &section, "ajv", "\"ajv\": \"^8\""),
+            "{\n  \"dependencies\": {\n    \"ajv\": \"^8\",\n    \"axios\": \"^1\",\n    \"react\": \"^18\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_sorted_separator_before_comment() {
+        let content = "deps = [\n  \"a\", # first\n  \"b\" # last\n]\n";
+        let section = section(SectionStyle::TOML_ARRAY, &[("a", 1), ("b", 2)]);
+        assert_eq!(
+            insert(content, &section, "c", "\"c\""),
+            "deps = [\n  \"a\", # first\n  \"b\", # last\n  \"c\"\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_sorted_multiline_entry_indentation() {
+        let content = "<dependencies>\n    <dependency>\n      <artifactId>b</artifactId>\n    </dependency>\n</dependencies>\n";
+        let section = SortableSection {
+            label: "dependencies".into(),
+            style: SectionStyle {
+                comment_prefix: "<!--",
+                separator: None,
+                extent: EntryExtent::Brackets,
+            },
+            entries: vec![SortableEntry {
+                key: "b".into(),
+                first_line: 1,
+                last_line: 3,
+            }],
+        };
+        assert_eq!(
+            insert(
+                content,
+                &section,
+                "a",
+                "<dependency>\n  <artifactId>a</artifactId>\n</dependency>"
+            ),
+            "<dependencies>\n    <dependency>\n      <artifactId>a</artifactId>\n    </dependency>\n    <dependency>\n      <artifactId>b</artifactId>\n    </dependency>\n</dependencies>\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_sorted_empty_section() {
+        let section = section(SectionStyle::TOML_TABLE, &[]);
+        assert!(insert_sorted("[dependencies]\n", &section, "a", "a = \"1\"").is_none());
+    }
+}
@@ -0,0 +1,668 @@
+//! Completion of keys and values inside TOML dependency tables.
+//!
+//! A TOML manifest can give a dependency a table of options instead of a
+//! plain version: `serde = { version = "1", default-features = false }` in
+//! Cargo.toml. The ecosystem describes the options it accepts as a
+//! [`TableSchema`], [`detect_inline_table`] locates the cursor inside an
+//! inline table as a [`TableContext`], and [`complete_table`] then offers the
+//! keys not written yet, or the values of the key being written.
+//!
+//! Cargo is the only ecosystem using it so far.
+//!
+//! Git references are completed from repositories on disk only:
+//! [`local_repository`] maps a git URL to a local path and
+//! [`local_git_refs`] lists its branches and tags without running git.
+
+use crate::completion::utf16_to_byte_offset;
+use std::path::{Path, PathBuf};
+use tower_lsp_server::ls_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Position, Range, TextEdit,
+};
+
+/// The kind of value a table key accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// `true` or `false`.
+    Boolean,
+    /// A free-form string.
+    String,
+    /// An array of strings.
+    Array,
+    /// A branch of the repository named by the table's git key.
+    GitBranch,
+    /// A tag of the repository named by the table's git key.
+    GitTag,
+}
+
+/// A key accepted by a dependency table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableKey {
+    /// Key as written in the manifest.
+    pub name: &'static str,
+    /// Kind of value the key accepts.
+    pub value: ValueKind,
+    /// One-line description shown next to the completion.
+    pub description: &'static str,
+}
+
+/// The keys a dependency table accepts, in the order they are offered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSchema {
+    pub keys: &'static [TableKey],
+}
+
+impl TableSchema {
+    /// Looks up the key `name`.
+    pub fn key(&self, name: &str) -> Option<&TableKey> {
+        self.keys.iter().find(|key| key.name == name)
+    }
+}
+
+/// What the cursor is writing inside a dependency table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableTarget {
+    /// A key.
+    Key,
+    /// The value of `key`; `quoted` when the cursor is inside a string.
+    Value { key: String, quoted: bool },
+}
+
+/// The cursor position inside a dependency table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableContext {
+    /// What is being written.
+    pub target: TableTarget,
+    /// Text typed so far.
+    pub prefix: String,
+    /// Range replaced by the completion: the prefix.
+    pub range: Range,
+    /// The other entries of the table, values unquoted.
+    pub entries: Vec<(String, String)>,
+}
+
+impl TableContext {
+    /// Returns the value written for `key` elsewhere in the table.
+    pub fn value_of(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Branches and tags of a git repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitRefs {
+    pub branches: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+/// Detects the cursor inside a TOML inline table.
+///
+/// Returns `None` outside inline tables, inside arrays nested in the table
+/// (features are completed by [`crate::completion`]), inside keys that are
+/// not bare keys, and after a closed string value. `depth` is the nesting
+/// of the innermost open table on the line, 1 for `dep = { ... }`.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::table_completion::{TableTarget, detect_inline_table};
+/// use tower_lsp_server::ls_types::Position;
+///
+/// let content = r#"serde = { version = "1", default"#;
+/// let (context, depth) = detect_inline_table(content, Position::new(0, 32)).unwrap();
+/// assert_eq!(context.target, TableTarget::Key);
+/// assert_eq!(context.prefix, "default");
+/// assert_eq!(context.value_of("version"), Some("1"));
+/// assert_eq!(depth, 1);
+/// ```
+pub fn detect_inline_table(content: &str, position: Position) -> Option<(TableContext, usize)> {
+    let line = content.lines().nth(position.line as usize)?;
+    let cursor = utf16_to_byte_offset(line, position.character)?;
+    let scan = scan_line(&line[..cursor]);
+    let frame = scan.frames.last().filter(|frame| frame.open == b'{')?;
+    let depth = scan
+        .frames
+        .iter()
+        .filter(|frame| frame.open == b'{')
+        .count();
+
+    let segment_start = frame.boundary + 1;
+    let segment = &line[segment_start..cursor];
+    let range_from = |start: usize| {
+        Range::new(
+            Position::new(position.line, utf16_len(&line[..start])),
+            position,
+        )
+    };
+
+    let (target, prefix, start) = match segment.find('=') {
+        Some(eq) => {
+            let key = segment[..eq].trim();
+            if !is_bare_key(key) {
+                return None;
+            }
+            let value_start = segment_start + eq + 1;
+            match scan.string_start {
+                Some(quote) if quote >= value_start => (
+                    TableTarget::Value {
+                        key: key.to_string(),
+                        quoted: true,
+                    },
+                    &line[quote + 1..cursor],
+                    quote + 1,
+                ),
+                Some(_) => return None,
+                None => {
+                    let value = line[value_start..cursor].trim_start();
+                    if !is_bare_key(value) && !value.is_empty() {
+                        return None;
+                    }
+                    (
+                        TableTarget::Value {
+                            key: key.to_string(),
+                            quoted: false,
+                        },
+                        value,
+                        cursor - value.len(),
+                    )
+                }
+            }
+        }
+        None => {
+            let key = segment.trim_start();
+            if scan.string_start.is_some() || (!key.is_empty() && !is_bare_key(key)) {
+                return None;
+            }
+            (TableTarget::Key, key, cursor - key.len())
+        }
+    };
+
+    Some((
+        TableContext {
+            target,
+            prefix: prefix.to_string(),
+            range: range_from(start),
+            entries: table_entries(line, frame.start + 1, cursor),
+        },
+        depth,
+    ))
+}
+
+/// Builds the completions for `context` from `schema`.
+///
+/// Keys already written in the table are not offered again. Boolean values
+/// complete to `true`/`false`; git branches and tags complete from `git`,
+/// the references of the repository the table points to, when known.
+pub fn complete_table(
+    schema: &TableSchema,
+    context: &TableContext,
+    git: Option<&GitRefs>,
+) -> Vec<CompletionItem> {
+    match &context.target {
+        TableTarget::Key => schema
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| key.name.starts_with(context.prefix.as_str()))
+            .filter(|(_, key)| context.value_of(key.name).is_none())
+            .map(|(index, key)| CompletionItem {
+                label: key.name.to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(key.description.to_string()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: context.range,
+                    new_text: format!("{} = ", key.name),
+                })),
+                sort_text: Some(format!("{index:02}")),
+                ..Default::default()
+            })
+            .collect(),
+        TableTarget::Value { key, quoted } => {
+            let Some(key) = schema.key(key) else {
+                return vec![];
+            };
+            let (values, detail): (Vec<&str>, &str) = match key.value {
+                ValueKind::Boolean if !quoted => (vec!["true", "false"], "Boolean"),
+                ValueKind::GitBranch => (
+                    git.map(|refs| refs.branches.iter().map(String::as_str).collect())
+                        .unwrap_or_default(),
+                    "Branch",
+                ),
+                ValueKind::GitTag => (
+                    git.map(|refs| refs.tags.iter().map(String::as_str).collect())
+                        .unwrap_or_default(),
+                    "Tag",
+                ),
+                _ => return vec![],
+            };
+            values
+                .into_iter()
+                .filter(|value| value.starts_with(context.prefix.as_str()))
+                .enumerate()
+                .map(|(index, value)| {
+                    let quote = if *quoted || key.value == ValueKind::Boolean {
+                        ""
+                    } else {
+                        "\""
+                    };
+                    CompletionItem {
+                        label: value.to_string(),
+                        kind: Some(CompletionItemKind::VALUE),
+                        detail: Some(detail.to_string()),
+                        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                            range: context.range,
+                            new_text: format!("{quote}{value}{quote}"),
+                        })),
+                        sort_text: Some(format!("{index:05}")),
+                        ..Default::default()
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Maps a git URL to a repository on disk.
+///
+/// Accepts `file://` URLs and plain paths, relative ones resolved against
+/// `base` (the manifest directory). Remote URLs return `None`.
+pub fn local_repository(url: &str, base: &Path) -> Option<PathBuf> {
+    let path = match url.strip_prefix("file://") {
+        Some(path) => Path::new(path),
+        None if url.contains("://") || is_scp_like(url) => return None,
+        None => Path::new(url),
+    };
+    Some(if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    })
+}
+
+/// Lists the branches and tags of the repository at `repo`.
+///
+/// Reads loose references and `packed-refs` of work trees, linked work
+/// trees (a `.git` file) and bare repositories. Returns `None` when `repo`
+/// is not a git repository.
+pub fn local_git_refs(repo: &Path) -> Option<GitRefs> {
+    let git_dir = git_dir(repo)?;
+    let mut refs = GitRefs::default();
+    collect_refs(&git_dir.join("refs/heads"), "", &mut refs.branches);
+    collect_refs(&git_dir.join("refs/tags"), "", &mut refs.tags);
+
+    if let Ok(packed) = std::fs::read_to_string(git_dir.join("packed-refs")) {
+        for name in packed
+            .lines()
+            .filter(|line| !line.starts_with(['#', '^']))
+            .filter_map(|line| line.split_whitespace().nth(1))
+        {
+            if let Some(branch) = name.strip_prefix("refs/heads/") {
+                refs.branches.push(branch.to_string());
+            } else if let Some(tag) = name.strip_prefix("refs/tags/") {
+                refs.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    for names in [&mut refs.branches, &mut refs.tags] {
+        names.sort_unstable();
+        names.dedup();
+    }
+    Some(refs)
+}
+
+fn git_dir(repo: &Path) -> Option<PathBuf> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    if dot_git.is_file() {
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        let target = content.trim().strip_prefix("gitdir:")?.trim();
+        return Some(repo.join(target));
+    }
+    (repo.join("HEAD").is_file() && repo.join("refs").is_dir()).then(|| repo.to_path_buf())
+}
+
+fn collect_refs(dir: &Path, prefix: &str, names: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                collect_refs(&entry.path(), &format!("{name}/"), names);
+            }
+            Ok(_) => names.push(name),
+            Err(_) => {}
+        }
+    }
+}
+
+/// `git@host:path` URLs.
+fn is_scp_like(url: &str) -> bool {
+    url.split_once(':')
+        .is_some_and(|(host, _)| host.len() > 1 && !host.contains('/'))
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// A bracket left open on the scanned text.
+struct Frame {
+    open: u8,
+    start: usize,
+    /// The opening bracket or the last comma directly inside it.
+    boundary: usize,
+}
+
+struct LineScan {
+    frames: Vec<Frame>,
+    /// Opening quote of a string left open on the scanned text.
+    string_start: Option<usize>,
+}
+
+fn scan_line(text: &str) -> LineScan {
+    let bytes = text.as_bytes();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut string_start = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if let Some(start) = string_start {
+            let quote = bytes[start];
+            if quote == b'"' && b == b'\\' {
+                i += 1;
+            } else if b == quote {
+                string_start = None;
+            }
+        } else {
+            match b {
+                b'"' | b'\'' => string_start = Some(i),
+                b'{' | b'[' => frames.push(Frame {
+                    open: b,
+                    start: i,
+                    boundary: i,
+                }),
+                b'}' | b']' => {
+                    frames.pop();
+                }
+                b',' => {
+                    if let Some(frame) = frames.last_mut() {
+                        frame.boundary = i;
+                    }
+                }
+                b'#' => break,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    LineScan {
+        frames,
+        string_start,
+    }
+}
+
+/// Splits the inline table starting at `body` into its `key = value`
+/// entries, skipping the one the cursor is in.
+fn table_entries(line: &str, body: usize, cursor: usize) -> Vec<(String, String)> {
+    let bytes = line.as_bytes();
+    let mut entries = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+    let mut entry_start = body;
+    let mut i = body;
+    while i <= bytes.len() {
+        let b = bytes.get(i).copied();
+        let end_of_entry = match (quote, b) {
+            (_, None) => true,
+            (Some(q), Some(b)) => {
+                if q == b'"' && b == b'\\' {
+                    i += 1;
+                } else if b == q {
+                    quote = None;
+                }
+                false
+            }
+            (None, Some(b'"' | b'\'')) => {
+                quote = b;
+                false
+            }
+            (None, Some(b'{' | b'[')) => {
+                depth += 1;
+                false
+            }
+            (None, Some(b'}')) if depth == 0 => true,
+            (None, Some(b'}' | b']')) => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            (None, Some(b',')) => depth == 0,
+            (None, Some(b'#')) => true,
+            (None, Some(_)) => false,
+        };
+        if end_of_entry {
+            let end = i.min(bytes.len());
+            if !(entry_start..=end).contains(&cursor)
+                && let Some((key, value)) = line[entry_start..end].split_once('=')
+            {
+                let value = value.trim();
+                let unquoted = value
+                    .strip_prefix(['"', '\''])
+                    .and_then(|v| v.strip_suffix(['"', '\'']))
+                    .unwrap_or(value);
+                entries.push((key.trim().to_string(), unquoted.to_string()));
+            }
+            if b != Some(b',') {
+                break;
+            }
+            entry_start = i + 1;
+        }
+        i += 1;
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: TableSchema = TableSchema {
+        keys: &[
+            TableKey {
+                name: "version",
+                value: ValueKind::String,
+                description: "Version requirement",
+            },
+            TableKey {
+                name: "default-features",
+                value: ValueKind::Boolean,
+                description: "Enable the default features",
+            },
+            TableKey {
+                name: "git",
+                value: ValueKind::String,
+                description: "Git repository URL",
+            },
+            TableKey {
+                name: "branch",
+                value: ValueKind::GitBranch,
+                description: "Git branch",
+            },
+            TableKey {
+                name: "tag",
+                value: ValueKind::GitTag,
+                description: "Git tag",
+            },
+        ],
+    };
+
+    fn detect(content: &str) -> Option<TableContext> {
+        let cursor = content.find('|').unwrap();
+        let content = content.replace('|', "");
+        detect_inline_table(&content, Position::new(0, cursor as u32)).map(|(context, _)| context)
+    }
+
+    fn new_texts(items: &[CompletionItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match &item.text_edit {
+                Some(CompletionTextEdit::Edit(edit)) => edit.new_text.clone(),
+                _ => panic!("expected a text edit"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_key() {
+        let context = detect(r#"serde = { version = "1", def| }"#).unwrap();
+        assert_eq!(context.target, TableTarget::Key);
+        assert_eq!(context.prefix, "def");
+        assert_eq!(context.range.start, Position::new(0, 25));
+        assert_eq!(context.entries, vec![("version".into(), "1".into())]);
+
+        let context = detect("serde = { |}").unwrap();
+        assert_eq!(context.target, TableTarget::Key);
+        assert_eq!(context.prefix, "");
+    }
+
+    #[test]
+    fn test_detect_value() {
+        let context = detect(r#"serde = { version = "1", default-features = f| }"#).unwrap();
+        assert_eq!(
+            context.target,
+            TableTarget::Value {
+                key: "default-features".into(),
+                quoted: false
+            }
+        );
+        assert_eq!(context.prefix, "f");
+
+        let context = detect(r#"dep = { git = "../dep", branch = "ma|" }"#).unwrap();
+        assert_eq!(
+            context.target,
+            TableTarget::Value {
+                key: "branch".into(),
+                quoted: true
+            }
+        );
+        assert_eq!(context.prefix, "ma");
+        assert_eq!(context.range.start, Position::new(0, 34));
+        assert_eq!(context.value_of("git"), Some("../dep"));
+    }
+
+    #[test]
+    fn test_detect_outside_tables() {
+        assert!(detect(r#"serde = "1|""#).is_none());
+        assert!(detect(r#"serde = { features = ["de|"] }"#).is_none());
+        assert!(detect(r#"serde = { version = "1"| }"#).is_none());
+        assert!(detect(r#"serde = { version = "1" } |"#).is_none());
+    }
+
+    #[test]
+    fn test_detect_nested_table_depth() {
+        let content = "a = { b = { c|";
+        let (_, depth) = detect_inline_table(content, Position::new(0, 13)).unwrap();
+        assert_eq!(depth, 2);
+    }
+
+    #[test]
+    fn test_complete_keys_skips_present() {
+        let context = detect(r#"serde = { version = "1", |}"#).unwrap();
+        let items = complete_table(&SCHEMA, &context, None);
+        let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["default-features", "git", "branch", "tag"]);
+        assert_eq!(new_texts(&items)[0], "default-features = ");
+    }
+
+    #[test]
+    fn test_complete_boolean_values() {
+        let context = detect("serde = { default-features = |}").unwrap();
+        let items = complete_table(&SCHEMA, &context, None);
+        assert_eq!(new_texts(&items), vec!["true", "false"]);
+
+        let context = detect(r#"serde = { default-features = "|" }"#).unwrap();
+        assert!(complete_table(&SCHEMA, &context, None).is_empty());
+    }
+
+    #[test]
+    fn test_complete_git_refs() {
+        let refs = GitRefs {
+            branches: vec!["main".into(), "release/1.x".into()],
+            tags: vec!["v1.0.0".into()],
+        };
+
+        let context = detect(r#"dep = { git = "../dep", branch = "re|" }"#).unwrap();
+        let items = complete_table(&SCHEMA, &context, Some(&refs));
+        assert_eq!(new_texts(&items), vec!["release/1.x"]);
+
+        let context = detect(r#"dep = { git = "../dep", tag = |"#).unwrap();
+        let items = complete_table(&SCHEMA, &context, Some(&refs));
+        assert_eq!(new_texts(&items), vec!["\"v1.0.0\""]);
+
+        assert!(complete_table(&SCHEMA, &context, None).is_empty());
+    }
+
+    #[test]
+    fn test_local_repository() {
+        let base = Path::new("/work/app");
+        assert_eq!(
+            local_repository("../lib", base),
+            Some(PathBuf::from("/work/app/../lib"))
+        );
+        assert_eq!(
+            local_repository("file:///srv/git/lib", base),
+            Some(PathBuf::from("/srv/git/lib"))
+        );
+        assert!(local_repository("https://github.com/serde-rs/serde", base).is_none());
+        assert!(local_repository("git@github.com:serde-rs/serde.git", base).is_none());
+    }
+
+    #[test]
+    fn test_local_git_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = dir.path().join(".git");
+        std::fs::create_dir_all(git.join("refs/heads/feature")).unwrap();
+        std::fs::create_dir_all(git.join("refs/tags")).unwrap();
+        std::fs::write(git.join("refs/heads/main"), "0000\n").unwrap();
+        std::fs::write(git.join("refs/heads/feature/x"), "0000\n").unwrap();
+        std::fs::write(
+            git.join("packed-refs"),
+            "# pack-refs with: peeled fully-peeled sorted\n\
+             1111 refs/heads/main\n\
+             2222 refs/tags/v1.0.0\n\
+             ^3333\n\
+             4444 refs/remotes/origin/main\n",
+        )
+        .unwrap();
+
+        let refs = local_git_refs(dir.path()).unwrap();
+        assert_eq!(refs.branches, vec!["feature/x", "main"]);
+        assert_eq!(refs.tags, vec!["v1.0.0"]);
+    }
+
+    #[test]
+    fn test_local_git_refs_bare_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(local_git_refs(dir.path()).is_none());
+
+        std::fs::create_dir_all(dir.path().join("refs/tags")).unwrap();
+        std::fs::write(dir.path().join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(dir.path().join("refs/tags/v2"), "0000\n").unwrap();
+        let refs = local_git_refs(dir.path()).unwrap();
+        assert!(refs.branches.is_empty());
+        assert_eq!(refs.tags, vec!["v2"]);
+    }
+}
@@ -1,4 +1,4 @@
-//! Cross-platform test URI helper shared across ecosystem crates.
+//! Test helpers shared across ecosystem crates.
 //!
 //! Test fixtures throughout the workspace write absolute paths in Unix
 //! style (e.g. `/project/Cargo.toml`) for readability. `Uri::from_file_path`
@@ -6,8 +6,11 @@
 //! recognized as absolute on Windows (no drive letter), so calling it
 //! directly with such a literal panics on Windows only. [`test_uri`]
 //! normalizes the path per host platform before constructing the [`Uri`].
+//!
+//! [`apply_edits`] applies the text edits of a code action or command to a
+//! fixture so tests can assert on the resulting document.
 
-use tower_lsp_server::ls_types::Uri;
+use tower_lsp_server::ls_types::{TextEdit, Uri};
 
 /// Builds a [`Uri`] from a Unix-style absolute test path.
 ///
@@ -41,3 +44,22 @@ pub fn test_uri(unix_path: &str) -> Uri {
 
     Uri::from_file_path(path).expect("test_uri: fixture path must be a valid file URI")
 }
+
+/// Applies `edits` to `content` and returns the edited document.
+///
+/// Edits are applied from the end of the document backwards, so their
+/// ranges all refer to the original `content`.
+#[must_use]
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+    let table = crate::LineOffsetTable::new(content);
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+
+    let mut result = content.to_string();
+    for edit in edits.into_iter().rev() {
+        let start = table.position_to_byte_offset(content, edit.range.start);
+        let end = table.position_to_byte_offset(content, edit.range.end);
+        result.replace_range(start..end, &edit.new_text);
+    }
+    result
+}
@@ -0,0 +1,333 @@
+//! Workspace member discovery shared by monorepo-aware ecosystems.
+//!
+//! Cargo (`workspace.members`), npm/yarn (`workspaces`) and pnpm
+//! (`pnpm-workspace.yaml`) all declare members as path globs relative to the
+//! workspace root. This module expands those globs against the filesystem
+//! without pulling in a glob crate: `*` and `?` match within one path
+//! segment, `**` matches any number of directories.
+//!
+//! Loading a workspace reads every member manifest, so [`WorkspaceCache`]
+//! keeps loaded workspaces by root until one of their files changes.
+
+use dashmap::DashMap;
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Maximum directory depth explored while expanding `**` patterns.
+const MAX_GLOB_DEPTH: usize = 8;
+
+/// Directories never descended into while expanding `**` patterns.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", ".git"];
+
+/// Expands workspace member globs into existing directories under `root`.
+///
+/// Patterns in `exclude` are matched against the same relative paths and
+/// remove members. Results are sorted and deduplicated.
+///
+/// # Examples
+///
+/// ```no_run
+/// use deps_core::workspace::expand_member_globs;
+/// use std::path::Path;
+///
+/// let members = expand_member_globs(
+///     Path::new("/repo"),
+///     &["crates/*".to_string()],
+///     &["crates/legacy".to_string()],
+/// );
+/// ```
+pub fn expand_member_globs(root: &Path, members: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+
+    for pattern in members {
+        let segments: Vec<&str> = pattern
+            .trim_start_matches("./")
+            .split('/')
+            .filter(|s| !s.is_empty() && *s != ".")
+            .collect();
+        expand_segments(root, &segments, 0, &mut result);
+    }
+
+    result.retain(|path| {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        !exclude
+            .iter()
+            .any(|pattern| path_matches(pattern.trim_start_matches("./"), &relative))
+    });
+    result.sort();
+    result.dedup();
+    result
+}
+
+fn expand_segments(dir: &Path, segments: &[&str], depth: usize, out: &mut Vec<PathBuf>) {
+    let Some((first, rest)) = segments.split_first() else {
+        if dir.is_dir() {
+            out.push(dir.to_path_buf());
+        }
+        return;
+    };
+
+    if *first == "**" {
+        expand_segments(dir, rest, depth, out);
+        if depth < MAX_GLOB_DEPTH {
+            for child in child_dirs(dir) {
+                expand_segments(&child, segments, depth + 1, out);
+            }
+        }
+    } else if first.contains(['*', '?']) {
+        for child in child_dirs(dir) {
+            let matches = child
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| segment_matches(first, name));
+            if matches {
+                expand_segments(&child, rest, depth + 1, out);
+            }
+        }
+    } else {
+        expand_segments(&dir.join(first), rest, depth + 1, out);
+    }
+}
+
+fn child_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|n| !SKIPPED_DIRS.contains(&n))
+        })
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Matches a `/`-separated relative path against a glob pattern.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    segments_match(&pattern, &path)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            segments_match(rest, path) || (!path.is_empty() && segments_match(pattern, &path[1..]))
+        }
+        (Some((p, prest)), Some((s, srest))) => {
+            segment_matches(p, s) && segments_match(prest, srest)
+        }
+        _ => false,
+    }
+}
+
+/// Matches a single path segment against a pattern with `*` and `?` wildcards.
+///
+/// Also used for package name patterns, where `/` has no special meaning.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::workspace::segment_matches;
+///
+/// assert!(segment_matches("aws-*", "aws-sdk-s3"));
+/// assert!(segment_matches("@types/*", "@types/node"));
+/// assert!(!segment_matches("aws-*", "tokio"));
+/// ```
+pub fn segment_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Loaded workspaces by root directory.
+///
+/// Entries are type-erased so every ecosystem stores its own workspace
+/// model. They are kept until [`invalidate`](Self::invalidate) is called
+/// with a path under their root, e.g. when a member manifest is saved.
+///
+/// # Examples
+///
+/// ```
+/// use deps_core::workspace::WorkspaceCache;
+/// use std::path::Path;
+/// use std::sync::Arc;
+///
+/// let cache = WorkspaceCache::new();
+/// cache.insert(Path::new("/repo"), Arc::new(vec!["crates/core".to_string()]));
+/// assert!(cache.get::<Vec<String>>(Path::new("/repo")).is_some());
+///
+/// cache.invalidate(Path::new("/repo/crates/core/Cargo.toml"));
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Default)]
+pub struct WorkspaceCache {
+    entries: DashMap<PathBuf, Arc<dyn Any + Send + Sync>>,
+}
+
+impl WorkspaceCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the workspace loaded for `root`, if it is a `T`.
+    pub fn get<T: Any + Send + Sync>(&self, root: &Path) -> Option<Arc<T>> {
+        let entry = Arc::clone(self.entries.get(root)?.value());
+        entry.downcast().ok()
+    }
+
+    /// Stores the workspace loaded for `root`.
+    pub fn insert<T: Any + Send + Sync>(&self, root: &Path, workspace: Arc<T>) {
+        self.entries.insert(root.to_path_buf(), workspace);
+    }
+
+    /// Drops every workspace whose root contains `path`.
+    pub fn invalidate(&self, path: &Path) {
+        self.entries.retain(|root, _| !path.starts_with(root));
+    }
+
+    /// Returns the number of cached workspaces.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no workspace is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl std::fmt::Debug for WorkspaceCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkspaceCache")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_cache_invalidate() {
+        let cache = WorkspaceCache::new();
+        cache.insert(Path::new("/repo"), Arc::new(1_u32));
+        cache.insert(Path::new("/other"), Arc::new(2_u32));
+        assert_eq!(cache.get::<u32>(Path::new("/repo")).as_deref(), Some(&1));
+        assert!(cache.get::<String>(Path::new("/repo")).is_none());
+
+        cache.invalidate(Path::new("/repository/Cargo.toml"));
+        assert_eq!(cache.len(), 2);
+        cache.invalidate(Path::new("/repo/crates/a/Cargo.toml"));
+        assert!(cache.get::<u32>(Path::new("/repo")).is_none());
+        assert_eq!(cache.get::<u32>(Path::new("/other")).as_deref(), Some(&2));
+    }
+
+    fn setup() -> tempfile::TempDir {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for dir in [
+            "crates/core",
+            "crates/cli",
+            "crates/legacy",
+            "packages/web/app",
+            "tools",
+        ] {
+            std::fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        temp_dir
+    }
+
+    #[test]
+    fn test_segment_matches() {
+        assert!(segment_matches("*", "core"));
+        assert!(segment_matches("deps-*", "deps-core"));
+        assert!(segment_matches("c?re", "core"));
+        assert!(segment_matches("*-*", "a-b"));
+        assert!(!segment_matches("deps-*", "core"));
+        assert!(!segment_matches("c?re", "cooore"));
+    }
+
+    #[test]
+    fn test_expand_single_star() {
+        let temp_dir = setup();
+        let members = expand_member_globs(temp_dir.path(), &["crates/*".into()], &[]);
+        assert_eq!(
+            members,
+            vec![
+                temp_dir.path().join("crates/cli"),
+                temp_dir.path().join("crates/core"),
+                temp_dir.path().join("crates/legacy"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_with_exclude() {
+        let temp_dir = setup();
+        let members = expand_member_globs(
+            temp_dir.path(),
+            &["crates/*".into(), "tools".into()],
+            &["crates/legacy".into()],
+        );
+        assert_eq!(
+            members,
+            vec![
+                temp_dir.path().join("crates/cli"),
+                temp_dir.path().join("crates/core"),
+                temp_dir.path().join("tools"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_double_star() {
+        let temp_dir = setup();
+        let members = expand_member_globs(temp_dir.path(), &["packages/**".into()], &[]);
+        assert_eq!(
+            members,
+            vec![
+                temp_dir.path().join("packages"),
+                temp_dir.path().join("packages/web"),
+                temp_dir.path().join("packages/web/app"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_missing_literal() {
+        let temp_dir = setup();
+        let members = expand_member_globs(temp_dir.path(), &["./missing".into()], &[]);
+        assert!(members.is_empty());
+    }
+}
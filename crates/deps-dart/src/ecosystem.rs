@@ -2,14 +2,18 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::DartFormatter;
 use crate::registry::PubDevRegistry;
+use crate::types::DependencySection;
 
 pub struct DartEcosystem {
     registry: Arc<PubDevRegistry>,
@@ -74,6 +78,13 @@ impl Ecosystem for DartEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: DartFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::PubspecLockParser))
     }
@@ -106,11 +117,94 @@ impl Ecosystem for DartEcosystem {
         })
     }
 
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        _content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::DartParseResult>()
+        else {
+            return vec![];
+        };
+        let entries = result.dependencies.iter().map(|dep| {
+            (
+                section_label(&dep.section).to_string(),
+                SortableEntry::new(dep.name.clone(), dep.name_range.start.line),
+            )
+        });
+        SortableSection::group(SectionStyle::YAML_MAPPING, entries)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let section = match &dependency.kind {
+                DependencyKind::Normal => DependencySection::Dependencies,
+                DependencyKind::Dev | DependencyKind::Test => DependencySection::DevDependencies,
+                DependencyKind::Other(name) if name == "dependency_overrides" => {
+                    DependencySection::DependencyOverrides
+                }
+                _ => return None,
+            };
+            let key = section_label(&section);
+            let version = if add_dependency::is_bare_version(&dependency.version) {
+                self.formatter
+                    .format_version_for_text_edit(&dependency.version)
+            } else {
+                dependency.version.clone()
+            };
+            let entry = format!("{}: {version}", dependency.name);
+
+            let sections = self.sortable_sections(parse_result, content);
+            let edits = add_dependency::insert_into_section(
+                content,
+                &sections,
+                key,
+                &dependency.name,
+                &entry,
+            )
+            .unwrap_or_else(|| {
+                let header = format!("{key}:");
+                let indent = add_dependency::indent_unit(content);
+                // Top-level keys only; nested mappings may reuse the name
+                match content.lines().position(|line| line.trim_end() == header) {
+                    Some(line) => vec![add_dependency::insert_after_line(
+                        content,
+                        line as u32,
+                        &format!("{indent}{entry}"),
+                    )],
+                    None => vec![add_dependency::append_block(
+                        content,
+                        &format!(
+                            "{header}{}{indent}{entry}",
+                            add_dependency::newline(content)
+                        ),
+                    )],
+                }
+            });
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+const fn section_label(section: &DependencySection) -> &'static str {
+    match section {
+        DependencySection::Dependencies => "dependencies",
+        DependencySection::DevDependencies => "dev_dependencies",
+        DependencySection::DependencyOverrides => "dependency_overrides",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,4 +282,63 @@ mod tests {
         let result = eco.parse_manifest(yaml, &uri).await.unwrap();
         assert_eq!(result.dependencies().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let eco = DartEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pubspec.yaml");
+        let yaml = "name: app\ndependencies:\n  http: ^1.0.0\n  # state management\n  provider:\n    git:\n      url: https://github.com/rrousselGit/provider.git\n  collection: ^1.18.0\ndev_dependencies:\n  test: ^1.24.0\n";
+        let result = eco.parse_manifest(yaml, &uri).await.unwrap();
+
+        let sections = eco.sortable_sections(result.as_ref(), yaml);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "dependencies");
+
+        let runs = deps_core::sort::sort_section(yaml, &sections[0]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "  collection: ^1.18.0\n  http: ^1.0.0\n  # state management\n  provider:\n    git:\n      url: https://github.com/rrousselGit/provider.git"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let eco = DartEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pubspec.yaml");
+        let content = "name: app\n\ndependencies:\n  collection: ^1.18.0\n  provider: ^6.0.0\n\ndev_dependencies:\n";
+        let parse_result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "1.2.0".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = eco
+            .add_dependency(parse_result.as_ref(), content, &add("http", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "name: app\n\ndependencies:\n  collection: ^1.18.0\n  http: ^1.2.0\n  provider: ^6.0.0\n\ndev_dependencies:\n"
+        );
+
+        let edit = eco
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("test", Some("dev_dependencies")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "name: app\n\ndependencies:\n  collection: ^1.18.0\n  provider: ^6.0.0\n\ndev_dependencies:\n  test: ^1.2.0\n"
+        );
+    }
 }
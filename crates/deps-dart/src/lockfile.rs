@@ -20,6 +20,11 @@ impl LockFileProvider for PubspecLockParser {
         locate_lockfile_for_manifest(manifest_uri, Self::LOCKFILE_NAMES)
     }
 
+    fn records_dependencies(&self) -> bool {
+        // pubspec.lock only records whether a package is direct or transitive
+        false
+    }
+
     fn parse_lockfile<'a>(
         &'a self,
         lockfile_path: &'a Path,
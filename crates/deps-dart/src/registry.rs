@@ -5,6 +5,7 @@ use crate::version::compare_versions;
 use deps_core::{HttpCache, Result};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 const PUB_DEV_API_BASE: &str = "https://pub.dev/api";
@@ -19,15 +20,29 @@ pub fn package_url(name: &str) -> String {
 #[derive(Clone)]
 pub struct PubDevRegistry {
     cache: Arc<HttpCache>,
+    api_base: Cow<'static, str>,
 }
 
 impl PubDevRegistry {
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            api_base: Cow::Borrowed(PUB_DEV_API_BASE),
+        }
+    }
+
+    /// Returns a client for the package repository at `url` (as in
+    /// `PUB_HOSTED_URL`), sharing this client's cache.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            api_base: Cow::Owned(format!("{}/api", url.trim_end_matches('/'))),
+        }
     }
 
     pub async fn get_versions(&self, name: &str) -> Result<Vec<DartVersion>> {
-        let url = format!("{PUB_DEV_API_BASE}/packages/{name}");
+        let url = format!("{}/packages/{name}", self.api_base);
         let data = self.cache.get_cached(&url).await?;
         parse_versions_response(&data)
     }
@@ -44,14 +59,14 @@ impl PubDevRegistry {
     }
 
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<PackageInfo>> {
-        let url = format!("{PUB_DEV_API_BASE}/search?q={}", urlencoding::encode(query));
+        let url = format!("{}/search?q={}", self.api_base, urlencoding::encode(query));
         let data = self.cache.get_cached(&url).await?;
         let search_result: SearchResponse = serde_json::from_slice(&data)?;
 
         let mut results = Vec::new();
         for entry in search_result.packages.into_iter().take(limit) {
             // Fetch metadata for each package
-            let pkg_url = format!("{PUB_DEV_API_BASE}/packages/{}", entry.package);
+            let pkg_url = format!("{}/packages/{}", self.api_base, entry.package);
             if let Ok(pkg_data) = self.cache.get_cached(&pkg_url).await
                 && let Ok(info) = parse_package_info(&pkg_data)
             {
@@ -63,7 +78,7 @@ impl PubDevRegistry {
     }
 
     pub async fn get_package_info(&self, name: &str) -> Result<PackageInfo> {
-        let url = format!("{PUB_DEV_API_BASE}/packages/{name}");
+        let url = format!("{}/packages/{name}", self.api_base);
         let data = self.cache.get_cached(&url).await?;
         parse_package_info(&data)
     }
@@ -158,6 +173,10 @@ impl deps_core::Version for DartVersion {
         self.retracted
     }
 
+    fn published_at(&self) -> Option<&str> {
+        self.published.as_deref()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -233,6 +252,10 @@ impl deps_core::Registry for PubDevRegistry {
         package_url(name)
     }
 
+    fn release_url(&self, name: &str, _version: &str) -> Option<String> {
+        Some(format!("{}/changelog", package_url(name)))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -5,14 +5,18 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{EntryExtent, SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::GoFormatter;
 use crate::registry::GoRegistry;
+use crate::types::GoDirective;
 
 /// Go modules ecosystem implementation.
 ///
@@ -107,6 +111,13 @@ impl Ecosystem for GoEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: GoFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::GoSumParser))
     }
@@ -143,11 +154,119 @@ impl Ecosystem for GoEcosystem {
         })
     }
 
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        _content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::GoParseResult>()
+        else {
+            return vec![];
+        };
+        let entries = result
+            .dependencies
+            .iter()
+            .filter(|dep| dep.directive == GoDirective::Require)
+            .map(|dep| {
+                (
+                    "require".to_string(),
+                    SortableEntry::new(dep.module_path.clone(), dep.module_path_range.start.line),
+                )
+            });
+        SortableSection::group(GO_MOD_STYLE, entries)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            if dependency.kind != DependencyKind::Normal {
+                return None;
+            }
+            let result = parse_result
+                .as_any()
+                .downcast_ref::<crate::parser::GoParseResult>()?;
+            let version = if dependency.version.starts_with('v') {
+                dependency.version.clone()
+            } else {
+                format!("v{}", dependency.version)
+            };
+            let entry = format!("{} {version}", dependency.name);
+            let lines: Vec<&str> = content.lines().collect();
+
+            // Direct requirements of a `require (...)` block; indirect ones
+            // conventionally live in a block of their own
+            let block_entries = result
+                .dependencies
+                .iter()
+                .filter(|dep| dep.directive == GoDirective::Require && !dep.indirect)
+                .filter(|dep| {
+                    lines
+                        .get(dep.module_path_range.start.line as usize)
+                        .is_some_and(|line| !line.trim_start().starts_with("require"))
+                })
+                .map(|dep| {
+                    (
+                        "require".to_string(),
+                        SortableEntry::new(
+                            dep.module_path.clone(),
+                            dep.module_path_range.start.line,
+                        ),
+                    )
+                });
+            let sections = SortableSection::group(GO_MOD_STYLE, block_entries);
+
+            let edits = match add_dependency::insert_into_section(
+                content,
+                &sections,
+                "require",
+                &dependency.name,
+                &entry,
+            ) {
+                Some(edits) => edits,
+                None => {
+                    let single = lines.iter().rposition(|line| {
+                        line.starts_with("require ") && !line.trim_end().ends_with('(')
+                    });
+                    let block = lines.iter().position(|line| line.trim_end() == "require (");
+                    let edit = match (single, block) {
+                        (Some(line), _) => add_dependency::insert_after_line(
+                            content,
+                            line as u32,
+                            &format!("require {entry}"),
+                        ),
+                        (None, Some(line)) => add_dependency::insert_after_line(
+                            content,
+                            line as u32,
+                            &format!("\t{entry}"),
+                        ),
+                        (None, None) => {
+                            add_dependency::append_block(content, &format!("require {entry}"))
+                        }
+                    };
+                    vec![edit]
+                }
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+const GO_MOD_STYLE: SectionStyle = SectionStyle {
+    comment_prefix: "//",
+    separator: None,
+    extent: EntryExtent::Brackets,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,6 +370,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Lock file has the latest version
@@ -294,6 +414,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         let resolved_versions = HashMap::new();
@@ -335,6 +456,7 @@ mod tests {
             show_up_to_date_hints: false,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         // Lock file has the latest version - but show_up_to_date_hints is false
@@ -373,6 +495,7 @@ mod tests {
             show_up_to_date_hints: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked_versions: false,
         };
 
         let resolved_versions = HashMap::new();
@@ -571,6 +694,7 @@ mod tests {
                 &parse_result,
                 VersionData::new(&cached_versions, &resolved_versions),
                 parse_result.uri(),
+                20,
             ),
         )
         .await;
@@ -700,4 +824,84 @@ require github.com/gin-gonic/gin v1.9.1
         assert!(dep.indirect);
         assert_eq!(dep.name(), "github.com/example/pkg");
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = GoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/go.mod");
+        let content = r"module example.com/myapp
+
+go 1.21
+
+require (
+	github.com/stretchr/testify v1.8.4
+	// web framework
+	github.com/gin-gonic/gin v1.9.1
+)
+
+require golang.org/x/sys v0.15.0 // indirect
+
+replace github.com/old/pkg => github.com/new/pkg v1.0.0
+";
+        let result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(result.as_ref(), content);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entries.len(), 3);
+
+        let runs = deps_core::sort::sort_section(content, &sections[0]);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "\t// web framework\n\tgithub.com/gin-gonic/gin v1.9.1\n\tgithub.com/stretchr/testify v1.8.4"
+        );
+        assert!(runs[1].edit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = GoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/go.mod");
+        let content = "module example.com/app\n\ngo 1.22\n\nrequire (\n\tgithub.com/gin-gonic/gin v1.9.1\n\tgithub.com/stretchr/testify v1.8.4\n)\n\nrequire (\n\tgolang.org/x/net v0.20.0 // indirect\n)\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "github.com/google/uuid".into(),
+            version: "1.6.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "module example.com/app\n\ngo 1.22\n\nrequire (\n\tgithub.com/gin-gonic/gin v1.9.1\n\tgithub.com/google/uuid v1.6.0\n\tgithub.com/stretchr/testify v1.8.4\n)\n\nrequire (\n\tgolang.org/x/net v0.20.0 // indirect\n)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_without_require() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = GoEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/go.mod");
+        let content = "module example.com/app\n\ngo 1.22\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "github.com/google/uuid".into(),
+            version: "v1.6.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "module example.com/app\n\ngo 1.22\n\nrequire github.com/google/uuid v1.6.0\n"
+        );
+    }
 }
@@ -74,6 +74,11 @@ impl LockFileProvider for GoSumParser {
         locate_lockfile_for_manifest(manifest_uri, Self::LOCKFILE_NAMES)
     }
 
+    fn records_dependencies(&self) -> bool {
+        // go.sum lists module checksums without their requirements
+        false
+    }
+
     fn parse_lockfile<'a>(
         &'a self,
         lockfile_path: &'a Path,
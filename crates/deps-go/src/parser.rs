@@ -189,7 +189,7 @@ fn parse_require_line(
     content: &str,
     line_table: &LineOffsetTable,
 ) -> Option<GoDependency> {
-    let parts: Vec<&str> = line.split_whitespace().collect();
+    let parts: Vec<&str> = strip_line_comment(line).split_whitespace().collect();
     if parts.is_empty() {
         return None;
     }
@@ -376,6 +376,21 @@ require github.com/gin-gonic/gin v1.9.1
         assert!(result.dependencies[1].indirect);
     }
 
+    #[test]
+    fn test_parse_require_block_skips_comment_lines() {
+        let content = r"require (
+    // web framework
+    github.com/gin-gonic/gin v1.9.1
+)
+";
+        let result = parse_go_mod(content, &test_uri()).unwrap();
+        assert_eq!(result.dependencies.len(), 1);
+        assert_eq!(
+            result.dependencies[0].module_path,
+            "github.com/gin-gonic/gin"
+        );
+    }
+
     #[test]
     fn test_parse_replace_directive() {
         let content = "replace github.com/old/module => github.com/new/module v1.2.3\n";
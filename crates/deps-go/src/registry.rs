@@ -31,6 +31,7 @@ use crate::version::{escape_module_path, is_pseudo_version};
 use deps_core::{DepsError, HttpCache, Result};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 const PROXY_BASE: &str = "https://proxy.golang.org";
@@ -141,12 +142,26 @@ fn not_found_or(err: DepsError, module_path: &str) -> DepsError {
 #[derive(Clone)]
 pub struct GoRegistry {
     cache: Arc<HttpCache>,
+    proxy_url: Cow<'static, str>,
 }
 
 impl GoRegistry {
     /// Creates a new Go registry client with the given HTTP cache.
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            proxy_url: Cow::Borrowed(PROXY_BASE),
+        }
+    }
+
+    /// Returns a client for the module proxy at `url` (a `GOPROXY` entry),
+    /// sharing this client's cache.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            proxy_url: Cow::Owned(url.trim_end_matches('/').to_string()),
+        }
     }
 
     /// Fetches all versions for a module from the `/@v/list` endpoint.
@@ -180,7 +195,7 @@ impl GoRegistry {
         validate_module_path(module_path)?;
 
         let escaped = escape_module_path(module_path);
-        let url = format!("{PROXY_BASE}/{escaped}/@v/list");
+        let url = format!("{}/{escaped}/@v/list", self.proxy_url);
 
         let data = self
             .cache
@@ -222,7 +237,7 @@ impl GoRegistry {
         validate_version_string(version)?;
 
         let escaped = escape_module_path(module_path);
-        let url = format!("{PROXY_BASE}/{escaped}/@v/{version}.info");
+        let url = format!("{}/{escaped}/@v/{version}.info", self.proxy_url);
 
         let data = self
             .cache
@@ -263,7 +278,7 @@ impl GoRegistry {
         validate_module_path(module_path)?;
 
         let escaped = escape_module_path(module_path);
-        let url = format!("{PROXY_BASE}/{escaped}/@latest");
+        let url = format!("{}/{escaped}/@latest", self.proxy_url);
 
         let data = self
             .cache
@@ -305,7 +320,7 @@ impl GoRegistry {
         validate_version_string(version)?;
 
         let escaped = escape_module_path(module_path);
-        let url = format!("{PROXY_BASE}/{escaped}/@v/{version}.mod");
+        let url = format!("{}/{escaped}/@v/{version}.mod", self.proxy_url);
 
         let data = self
             .cache
@@ -447,6 +462,14 @@ impl deps_core::Registry for GoRegistry {
         package_url(name)
     }
 
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        Some(format!(
+            "{}@{}",
+            package_url(name),
+            urlencoding::encode(version)
+        ))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
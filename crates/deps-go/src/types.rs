@@ -143,6 +143,10 @@ impl deps_core::registry::Version for GoVersion {
         vec![]
     }
 
+    fn published_at(&self) -> Option<&str> {
+        self.time.as_deref()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
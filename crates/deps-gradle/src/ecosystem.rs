@@ -1,12 +1,18 @@
 //! Gradle ecosystem implementation for deps-lsp.
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{
+    CompletionItem, Location, Position, TextEdit, Uri, WorkspaceEdit,
+};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
     position_in_range,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 use deps_maven::MavenCentralRegistry;
 
@@ -68,6 +74,49 @@ impl GradleEcosystem {
             ("", "")
         }
     }
+
+    /// Adds `dependency` to the `[libraries]` table of a version catalog.
+    ///
+    /// Returns the alias of the library and the catalog edits, which are
+    /// empty when the catalog already declares the module.
+    fn add_catalog_library(
+        &self,
+        catalog: &dyn ParseResultTrait,
+        content: &str,
+        dependency: &NewDependency,
+    ) -> Option<(String, Vec<TextEdit>)> {
+        let lines: Vec<&str> = content.lines().collect();
+        if let Some(existing) = catalog
+            .dependencies()
+            .into_iter()
+            .find(|dep| dep.name() == dependency.name)
+        {
+            let line = lines.get(existing.name_range().start.line as usize)?.trim();
+            let alias = match line.strip_prefix("[libraries.") {
+                Some(table) => table.trim_end_matches(']'),
+                None => line.split_once('=')?.0.trim(),
+            };
+            return Some((alias.trim_matches(['"', '\'']).to_string(), vec![]));
+        }
+
+        let (_, artifact_id) = dependency.name.split_once(':')?;
+        let alias = artifact_id.to_lowercase().replace(['.', '_'], "-");
+        let entry = format!(
+            "{alias} = {{ module = \"{}\", version = \"{}\" }}",
+            dependency.name, dependency.version
+        );
+        let sections = self.sortable_sections(catalog, content);
+        let edits =
+            add_dependency::insert_into_section(content, &sections, "[libraries]", &alias, &entry)
+                .unwrap_or_else(|| {
+                    vec![add_dependency::insert_toml_table_entry(
+                        content,
+                        "[libraries]",
+                        &entry,
+                    )]
+                });
+        Some((alias, edits))
+    }
 }
 
 /// Detects completion context in version catalog files.
@@ -187,6 +236,13 @@ impl Ecosystem for GradleEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: GradleFormatter,
+        }))
+    }
+
     fn formatter(&self) -> &dyn EcosystemFormatter {
         &self.formatter
     }
@@ -220,11 +276,204 @@ impl Ecosystem for GradleEcosystem {
         })
     }
 
+    fn generate_definition<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        content: &'a str,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            let dep = deps_core::lsp_helpers::find_dependency_at(parse_result, position)?;
+            let version_range = dep.version_range()?;
+            let line_table = deps_core::LineOffsetTable::new(content);
+            let raw = line_table.slice(content, version_range);
+            let uri = parse_result.uri();
+
+            if uri.path().as_str().ends_with("libs.versions.toml") {
+                // `version.ref` ranges cover the alias; inline versions have no alias to find
+                let range = crate::parser::catalog::find_version_alias(content, raw)?;
+                return Some(Location::new(uri.clone(), range));
+            }
+
+            let name = crate::parser::variable_name(raw)?;
+            let manifest_path = uri.to_file_path()?;
+            let (props_path, range) =
+                crate::parser::properties::find_property(manifest_path.parent()?, name)?;
+            Some(Location::new(Uri::from_file_path(&props_path)?, range))
+        })
+    }
+
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        content: &str,
+    ) -> Vec<SortableSection> {
+        // Only version catalogs have a table of dependencies to sort
+        if !parse_result
+            .uri()
+            .path()
+            .as_str()
+            .ends_with("libs.versions.toml")
+        {
+            return vec![];
+        }
+        let lines: Vec<&str> = content.lines().collect();
+
+        let entries = parse_result.dependencies().into_iter().filter_map(|dep| {
+            let range = dep.name_range();
+            let line = lines.get(range.start.line as usize)?;
+            // Only `alias = { module = "..." }` entries; `[libraries.alias]`
+            // tables are not entries of `[libraries]`
+            let (alias, table) = line.split_once('=')?;
+            if !table.contains('{') {
+                return None;
+            }
+            let alias = alias.trim().trim_matches(['"', '\'']);
+            Some((
+                "[libraries]".to_string(),
+                SortableEntry::new(alias, range.start.line),
+            ))
+        });
+        SortableSection::group(SectionStyle::TOML_TABLE, entries)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let (group_id, artifact_id) = dependency.name.split_once(':')?;
+            let uri = parse_result.uri();
+            let path = uri.path().as_str();
+
+            if path.ends_with("libs.versions.toml") {
+                let (_, edits) = self.add_catalog_library(parse_result, content, dependency)?;
+                return Some(add_dependency::workspace_edit(uri, edits));
+            }
+            if !(path.ends_with("build.gradle.kts") || path.ends_with("build.gradle")) {
+                return None;
+            }
+            let configuration = match &dependency.kind {
+                DependencyKind::Normal => "implementation",
+                DependencyKind::Dev | DependencyKind::Test => "testImplementation",
+                DependencyKind::Other(configuration) => configuration.as_str(),
+                _ => return None,
+            };
+            let kotlin = path.ends_with(".kts");
+
+            // Projects with a version catalog reference the library through it
+            let catalog = match uri.to_file_path() {
+                Some(build_path) => find_version_catalog(&build_path).await,
+                None => None,
+            };
+            if let Some((catalog_uri, catalog_content)) = catalog {
+                let catalog_result =
+                    crate::parser::catalog::parse_version_catalog(&catalog_content, &catalog_uri)
+                        .ok()?;
+                let (alias, catalog_edits) =
+                    self.add_catalog_library(&catalog_result, &catalog_content, dependency)?;
+                let accessor = format!("libs.{}", alias.replace(['-', '_'], "."));
+                let notation = if kotlin {
+                    format!("{configuration}({accessor})")
+                } else {
+                    format!("{configuration} {accessor}")
+                };
+                let mut changes = HashMap::from([(
+                    uri.clone(),
+                    vec![insert_build_dependency(content, configuration, &notation)],
+                )]);
+                if !catalog_edits.is_empty() {
+                    changes.insert(catalog_uri, catalog_edits);
+                }
+                return Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                });
+            }
+
+            let coordinates = format!("{group_id}:{artifact_id}:{}", dependency.version);
+            let notation = if kotlin {
+                format!("{configuration}(\"{coordinates}\")")
+            } else {
+                format!("{configuration} '{coordinates}'")
+            };
+            Some(add_dependency::workspace_edit(
+                uri,
+                vec![insert_build_dependency(content, configuration, &notation)],
+            ))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Finds `gradle/libs.versions.toml` in the directory of `build_path` or
+/// above, returning its URI and content.
+async fn find_version_catalog(build_path: &std::path::Path) -> Option<(Uri, String)> {
+    for dir in build_path.parent()?.ancestors() {
+        let catalog_path = dir.join("gradle").join("libs.versions.toml");
+        if let Ok(content) = tokio::fs::read_to_string(&catalog_path).await {
+            return Some((Uri::from_file_path(&catalog_path)?, content));
+        }
+    }
+    None
+}
+
+/// Inserts `notation` into the top-level `dependencies {}` block, after the
+/// last declaration of the same configuration, creating the block if needed.
+fn insert_build_dependency(content: &str, configuration: &str, notation: &str) -> TextEdit {
+    let lines: Vec<&str> = content.lines().collect();
+    let newline = add_dependency::newline(content);
+    let unit = add_dependency::indent_unit(content);
+
+    // `buildscript { dependencies { ... } }` is indented; the project block is not
+    let Some(open) = lines
+        .iter()
+        .position(|line| line.trim_end() == "dependencies {")
+    else {
+        return add_dependency::append_block(
+            content,
+            &format!("dependencies {{{newline}{unit}{notation}{newline}}}"),
+        );
+    };
+    let close = (open + 1..lines.len())
+        .find(|&i| lines[i].trim_end() == "}")
+        .unwrap_or(lines.len());
+
+    let same = |line: &&str| {
+        line.trim_start()
+            .strip_prefix(configuration)
+            .is_some_and(|rest| rest.starts_with(['(', ' ']))
+    };
+    let anchor = (open + 1..close).rev().find(|&i| same(&lines[i]));
+    let indent = anchor.map_or_else(
+        || {
+            (open + 1..close)
+                .map(|i| add_dependency::indentation_of(lines[i]))
+                .find(|indent| !indent.is_empty())
+                .unwrap_or(unit)
+        },
+        |i| add_dependency::indentation_of(lines[i]),
+    );
+    match anchor {
+        Some(line) => {
+            add_dependency::insert_after_line(content, line as u32, &format!("{indent}{notation}"))
+        }
+        None if close < lines.len() => add_dependency::insert_before_line(
+            content,
+            close as u32,
+            &format!("{indent}{notation}"),
+        ),
+        None => {
+            add_dependency::insert_after_line(content, open as u32, &format!("{indent}{notation}"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,4 +631,217 @@ mod tests {
         let result = eco.parse_manifest(content, &uri).await.unwrap();
         assert_eq!(result.dependencies().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_generate_definition_catalog_version_ref() {
+        let eco = GradleEcosystem::new(make_cache());
+        let content = r#"[versions]
+guava = "33.0.0-jre"
+
+[libraries]
+guava = { module = "com.google.guava:guava", version.ref = "guava" }
+junit = { module = "junit:junit", version = "4.13.2" }
+"#;
+        let uri = deps_core::test_util::test_uri("/project/gradle/libs.versions.toml");
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let location = eco
+            .generate_definition(result.as_ref(), Position::new(4, 62), content)
+            .await
+            .unwrap();
+        assert_eq!(location.uri, uri);
+        assert_eq!(location.range.start, Position::new(1, 0));
+        assert_eq!(location.range.end, Position::new(1, 5));
+
+        assert!(
+            eco.generate_definition(result.as_ref(), Position::new(5, 48), content)
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_definition_gradle_property() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("gradle.properties"),
+            "junitVersion=4.13.2\n",
+        )
+        .unwrap();
+        let build_path = temp_dir.path().join("build.gradle.kts");
+        let content = "dependencies {\n    implementation(\"junit:junit:$junitVersion\")\n}\n";
+        std::fs::write(&build_path, content).unwrap();
+
+        let eco = GradleEcosystem::new(make_cache());
+        let uri = Uri::from_file_path(&build_path).unwrap();
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+        let version_range = result.dependencies()[0].version_range().unwrap();
+
+        let location = eco
+            .generate_definition(result.as_ref(), version_range.start, content)
+            .await
+            .unwrap();
+        assert_eq!(
+            location.uri,
+            Uri::from_file_path(temp_dir.path().join("gradle.properties")).unwrap()
+        );
+        assert_eq!(location.range.start, Position::new(0, 13));
+    }
+
+    #[tokio::test]
+    async fn test_sortable_sections_version_catalog() {
+        let eco = GradleEcosystem::new(make_cache());
+        let content = r#"[versions]
+okhttp = "4.12.0"
+
+[libraries]
+okhttp = { module = "com.squareup.okhttp3:okhttp", version.ref = "okhttp" }
+# JSON
+gson = { module = "com.google.code.gson:gson", version = "2.10.1" }
+
+[libraries.junit]
+module = "junit:junit"
+version = "4.13.2"
+"#;
+        let uri = deps_core::test_util::test_uri("/project/gradle/libs.versions.toml");
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = eco.sortable_sections(result.as_ref(), content);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entries.len(), 2);
+
+        let runs = deps_core::sort::sort_section(content, &sections[0]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "# JSON\ngson = { module = \"com.google.code.gson:gson\", version = \"2.10.1\" }\nokhttp = { module = \"com.squareup.okhttp3:okhttp\", version.ref = \"okhttp\" }"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sortable_sections_build_script() {
+        let eco = GradleEcosystem::new(make_cache());
+        let content = "dependencies {\n    implementation(\"junit:junit:4.13.2\")\n}\n";
+        let uri = deps_core::test_util::test_uri("/project/build.gradle.kts");
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+        assert!(eco.sortable_sections(result.as_ref(), content).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_build_file() {
+        let eco = GradleEcosystem::new(make_cache());
+        let uri = deps_core::test_util::test_uri("/nonexistent/app/build.gradle.kts");
+        let content = "plugins {\n    java\n}\n\ndependencies {\n    implementation(\"com.google.guava:guava:33.0.0-jre\")\n    testImplementation(\"junit:junit:4.13.2\")\n}\n";
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "com.squareup.okhttp3:okhttp".into(),
+            version: "4.12.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = eco
+            .add_dependency(result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "plugins {\n    java\n}\n\ndependencies {\n    implementation(\"com.google.guava:guava:33.0.0-jre\")\n    implementation(\"com.squareup.okhttp3:okhttp:4.12.0\")\n    testImplementation(\"junit:junit:4.13.2\")\n}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_groovy_new_configuration() {
+        let eco = GradleEcosystem::new(make_cache());
+        let uri = deps_core::test_util::test_uri("/nonexistent/app/build.gradle");
+        let content = "dependencies {\n    implementation 'com.google.guava:guava:33.0.0-jre'\n}\n";
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "org.projectlombok:lombok".into(),
+            version: "1.18.30".into(),
+            kind: DependencyKind::parse(Some("compileOnly")),
+        };
+        let edit = eco
+            .add_dependency(result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "dependencies {\n    implementation 'com.google.guava:guava:33.0.0-jre'\n    compileOnly 'org.projectlombok:lombok:1.18.30'\n}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_with_version_catalog() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let catalog_path = temp_dir.path().join("gradle/libs.versions.toml");
+        std::fs::create_dir_all(catalog_path.parent().unwrap()).unwrap();
+        let catalog = "[libraries]\nguava = { module = \"com.google.guava:guava\", version = \"33.0.0-jre\" }\n";
+        std::fs::write(&catalog_path, catalog).unwrap();
+        let build_path = temp_dir.path().join("app/build.gradle.kts");
+        std::fs::create_dir_all(build_path.parent().unwrap()).unwrap();
+        let content = "dependencies {\n    implementation(libs.guava)\n}\n";
+
+        let eco = GradleEcosystem::new(make_cache());
+        let uri = Uri::from_file_path(&build_path).unwrap();
+        let catalog_uri = Uri::from_file_path(&catalog_path).unwrap();
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "com.squareup.okhttp3:logging-interceptor".into(),
+            version: "4.12.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = eco
+            .add_dependency(result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &changes[&uri]),
+            "dependencies {\n    implementation(libs.guava)\n    implementation(libs.logging.interceptor)\n}\n"
+        );
+        assert_eq!(
+            deps_core::test_util::apply_edits(catalog, &changes[&catalog_uri]),
+            "[libraries]\nguava = { module = \"com.google.guava:guava\", version = \"33.0.0-jre\" }\nlogging-interceptor = { module = \"com.squareup.okhttp3:logging-interceptor\", version = \"4.12.0\" }\n"
+        );
+
+        // Modules already in the catalog are only referenced
+        let dependency = NewDependency {
+            name: "com.google.guava:guava".into(),
+            version: "33.0.0-jre".into(),
+            kind: DependencyKind::Test,
+        };
+        let edit = eco
+            .add_dependency(result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert!(!changes.contains_key(&catalog_uri));
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &changes[&uri]),
+            "dependencies {\n    implementation(libs.guava)\n    testImplementation(libs.guava)\n}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_version_catalog() {
+        let eco = GradleEcosystem::new(make_cache());
+        let uri = deps_core::test_util::test_uri("/project/gradle/libs.versions.toml");
+        let content = "[libraries]\nguava = { module = \"com.google.guava:guava\", version = \"33.0.0-jre\" }\nokhttp = { module = \"com.squareup.okhttp3:okhttp\", version = \"4.12.0\" }\n";
+        let result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "junit:junit".into(),
+            version: "4.13.2".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = eco
+            .add_dependency(result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "[libraries]\nguava = { module = \"com.google.guava:guava\", version = \"33.0.0-jre\" }\njunit = { module = \"junit:junit\", version = \"4.13.2\" }\nokhttp = { module = \"com.squareup.okhttp3:okhttp\", version = \"4.12.0\" }\n"
+        );
+    }
 }
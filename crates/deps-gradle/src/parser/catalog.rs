@@ -65,6 +65,18 @@ pub fn parse_version_catalog(content: &str, uri: &Uri) -> Result<GradleParseResu
     })
 }
 
+/// Finds the range of the `[versions]` key named `alias`.
+///
+/// Used to resolve `version.ref = "alias"` to its declaration.
+pub fn find_version_alias(content: &str, alias: &str) -> Option<Range> {
+    deps_core::check_toml_nesting_depth(content, deps_core::MAX_TOML_NESTING_DEPTH).ok()?;
+    let doc = toml_span::parse(content).ok()?;
+    let versions = get_table_val(doc.as_table()?, "versions")?.as_table()?;
+    let (key, _) = versions.iter().find(|(key, _)| key.name == alias)?;
+    let line_table = LineOffsetTable::new(content);
+    Some(span_to_range(content, &line_table, key.span))
+}
+
 fn get_table_val<'a>(table: &'a Table<'a>, key: &str) -> Option<&'a Value<'a>> {
     table.get(key)
 }
@@ -192,6 +204,20 @@ guava = { module = "com.google.guava:guava", version.ref = "guava" }
         assert_eq!(spring.artifact_id, "spring-boot-starter");
     }
 
+    #[test]
+    fn test_find_version_alias() {
+        let content = "[versions]\nspring = \"3.2.0\"\nguava = \"33.0.0-jre\"\n";
+        assert_eq!(
+            find_version_alias(content, "guava"),
+            Some(Range::new(
+                tower_lsp_server::ls_types::Position::new(2, 0),
+                tower_lsp_server::ls_types::Position::new(2, 5)
+            ))
+        );
+        assert!(find_version_alias(content, "missing").is_none());
+        assert!(find_version_alias("[libraries]\n", "spring").is_none());
+    }
+
     #[test]
     fn test_parse_inline_version() {
         let content = "[libraries]\njunit = { module = \"junit:junit\", version = \"4.13.2\" }\n";
@@ -38,13 +38,16 @@ pub fn resolve_variables(deps: &mut [GradleDependency], properties: &HashMap<Str
 
 /// Returns the resolved value if `value` is a `$name` or `${name}` reference. Returns `None` otherwise.
 fn resolve_variable_ref(value: &str, properties: &HashMap<String, String>) -> Option<String> {
+    properties.get(variable_name(value)?).cloned()
+}
+
+/// Returns `name` if `value` is a `$name` or `${name}` reference.
+pub fn variable_name(value: &str) -> Option<&str> {
     let trimmed = value.trim();
     if let Some(name) = trimmed.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
-        properties.get(name).cloned()
-    } else if let Some(name) = trimmed.strip_prefix('$') {
-        properties.get(name).cloned()
+        Some(name)
     } else {
-        None
+        trimmed.strip_prefix('$')
     }
 }
 
@@ -3,7 +3,8 @@
 //! Provides key-value parsing and directory-walking lookup.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tower_lsp_server::ls_types::{Position, Range};
 
 /// Parses a gradle.properties content into key-value pairs.
 ///
@@ -44,6 +45,40 @@ pub fn load_gradle_properties(start_dir: &Path) -> HashMap<String, String> {
     result
 }
 
+/// Finds the `gradle.properties` line declaring `key`, walking up from `start_dir`.
+///
+/// The nearest file wins, mirroring the override order of
+/// [`load_gradle_properties`]. Returns the file path and the range of the value.
+pub fn find_property(start_dir: &Path, key: &str) -> Option<(PathBuf, Range)> {
+    start_dir.ancestors().find_map(|dir| {
+        let path = dir.join("gradle.properties");
+        let content = std::fs::read_to_string(&path).ok()?;
+        let range = find_property_in(&content, key)?;
+        Some((path, range))
+    })
+}
+
+/// Finds the range of the value of `key` within gradle.properties `content`.
+fn find_property_in(content: &str, key: &str) -> Option<Range> {
+    content.lines().enumerate().find_map(|(idx, line)| {
+        if line.trim_start().starts_with('#') {
+            return None;
+        }
+        let (k, v) = line.split_once('=')?;
+        if k.trim() != key {
+            return None;
+        }
+        let value = v.trim();
+        let start = line.len() - v.len() + (v.len() - v.trim_start().len());
+        let col = crate::parser::utf16_len(&line[..start]) as u32;
+        let end = col + crate::parser::utf16_len(value) as u32;
+        Some(Range::new(
+            Position::new(idx as u32, col),
+            Position::new(idx as u32, end),
+        ))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +135,38 @@ mod tests {
         let props = parse_properties("");
         assert!(props.is_empty());
     }
+
+    #[test]
+    fn test_find_property_in() {
+        let content = "# comment\nkotlinVersion = 2.1.10\nspringVersion=3.2.0\n";
+        assert_eq!(
+            find_property_in(content, "kotlinVersion"),
+            Some(Range::new(Position::new(1, 16), Position::new(1, 22)))
+        );
+        assert_eq!(
+            find_property_in(content, "springVersion"),
+            Some(Range::new(Position::new(2, 14), Position::new(2, 19)))
+        );
+        assert!(find_property_in(content, "comment").is_none());
+    }
+
+    #[test]
+    fn test_find_property_nearest_file_wins() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sub = temp_dir.path().join("app");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::write(
+            temp_dir.path().join("gradle.properties"),
+            "guavaVersion=32.0.0\nroot=1\n",
+        )
+        .unwrap();
+        std::fs::write(sub.join("gradle.properties"), "guavaVersion=33.0.0\n").unwrap();
+
+        let (path, _) = find_property(&sub, "guavaVersion").unwrap();
+        assert_eq!(path, sub.join("gradle.properties"));
+        let (path, range) = find_property(&sub, "root").unwrap();
+        assert_eq!(path, temp_dir.path().join("gradle.properties"));
+        assert_eq!(range.start, Position::new(1, 5));
+        assert!(find_property(&sub, "missing").is_none());
+    }
 }
@@ -0,0 +1,893 @@
+//! Headless dependency check (`deps-lsp check`).
+//!
+//! Runs the same analysis as the editor diagnostics without an LSP client:
+//! every manifest under a directory is discovered with
+//! [`discover_manifests`](crate::workspace_index::discover_manifests), parsed
+//! through the [`EcosystemRegistry`], and each registry dependency is checked
+//! against the versions published on its registry.
+//!
+//! Three kinds of findings are reported:
+//!
+//! - **outdated**: the requirement excludes the latest stable version; a
+//!   finding is *major* when the latest version is semver-incompatible with
+//!   the required one (`1.x` → `2.x`, `0.3` → `0.4`)
+//! - **unknown**: the registry does not know the package
+//! - **yanked**: every published version matching the requirement is yanked
+//! - **checksum**: with `--verify-checksums`, the lock file records another
+//!   checksum than the registry publishes for the locked version
+//!
+//! The report renders as human readable text or JSON, and
+//! [`CheckReport::exit_code`] turns it into a process exit code according to
+//! the `--fail-on` conditions.
+
+use crate::handlers::checksums;
+use crate::sarif::{self, SarifDocument};
+use crate::workspace_index::discover_manifests;
+use deps_core::lockfile::ResolvedPackages;
+use deps_core::lsp_helpers::EcosystemFormatter;
+use deps_core::{
+    Dependency, Ecosystem, EcosystemRegistry, ParseResult, Registry, Version, VersionData,
+};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_lsp_server::ls_types::{Diagnostic, Uri};
+
+/// Exit code when no `--fail-on` condition matched.
+pub const EXIT_OK: i32 = 0;
+
+/// Exit code when at least one finding matched a `--fail-on` condition.
+pub const EXIT_FINDINGS: i32 = 1;
+
+/// Exit code for invalid arguments or an unreadable directory.
+pub const EXIT_ERROR: i32 = 2;
+
+/// Output format of the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// One line per finding, grouped by manifest.
+    #[default]
+    Text,
+    /// A single JSON document.
+    Json,
+    /// A SARIF 2.1.0 log of the diagnostics an editor would show.
+    Sarif,
+}
+
+/// Condition making `deps-lsp check` exit with [`EXIT_FINDINGS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    /// Any finding.
+    Any,
+    /// Any outdated dependency.
+    Outdated,
+    /// Dependencies a semver-incompatible release behind.
+    Major,
+    /// Packages unknown to their registry.
+    Unknown,
+    /// Requirements only matching yanked versions.
+    Yanked,
+    /// Lock file checksums differing from the registry.
+    Checksum,
+}
+
+impl FailOn {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "any" => Some(Self::Any),
+            "outdated" => Some(Self::Outdated),
+            "major" => Some(Self::Major),
+            "unknown" => Some(Self::Unknown),
+            "yanked" => Some(Self::Yanked),
+            "checksum" => Some(Self::Checksum),
+            _ => None,
+        }
+    }
+
+    fn matches(self, finding: &Finding) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Outdated => finding.kind == FindingKind::Outdated,
+            Self::Major => finding.kind == FindingKind::Outdated && finding.major,
+            Self::Unknown => finding.kind == FindingKind::Unknown,
+            Self::Yanked => finding.kind == FindingKind::Yanked,
+            Self::Checksum => finding.kind == FindingKind::Checksum,
+        }
+    }
+}
+
+/// Options of `deps-lsp check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// Directory to scan.
+    pub root: PathBuf,
+    /// Output format.
+    pub format: OutputFormat,
+    /// Conditions making the check fail.
+    pub fail_on: Vec<FailOn>,
+    /// Timeout for fetching the versions of one package.
+    pub timeout_secs: u64,
+    /// Maximum number of concurrent registry requests.
+    pub max_concurrent: usize,
+    /// Whether to compare lock file checksums with the registry.
+    pub verify_checksums: bool,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        let cache = crate::config::CacheConfig::default();
+        Self {
+            root: PathBuf::from("."),
+            format: OutputFormat::Text,
+            fail_on: vec![FailOn::Yanked],
+            timeout_secs: cache.fetch_timeout_secs,
+            max_concurrent: cache.max_concurrent_fetches,
+            verify_checksums: false,
+        }
+    }
+}
+
+impl CheckOptions {
+    /// Parses the arguments following `check`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_lsp::check::{CheckOptions, FailOn, OutputFormat};
+    ///
+    /// let args = ["crates", "--format", "json", "--fail-on", "yanked,major"];
+    /// let options = CheckOptions::parse(args.iter().map(|s| s.to_string())).unwrap();
+    /// assert_eq!(options.root.to_str(), Some("crates"));
+    /// assert_eq!(options.format, OutputFormat::Json);
+    /// assert_eq!(options.fail_on, vec![FailOn::Yanked, FailOn::Major]);
+    /// ```
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut root = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .map(str::to_string)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {name}"))
+            };
+
+            match flag.as_str() {
+                "--format" => {
+                    options.format = match value("--format")?.as_str() {
+                        "text" => OutputFormat::Text,
+                        "json" => OutputFormat::Json,
+                        "sarif" => OutputFormat::Sarif,
+                        other => return Err(format!("Unknown format: {other}")),
+                    };
+                }
+                "--fail-on" => {
+                    let value = value("--fail-on")?;
+                    options.fail_on = if value == "never" {
+                        vec![]
+                    } else {
+                        value
+                            .split(',')
+                            .map(|v| {
+                                FailOn::parse(v).ok_or_else(|| format!("Unknown --fail-on: {v}"))
+                            })
+                            .collect::<Result<_, _>>()?
+                    };
+                }
+                "--timeout" => {
+                    options.timeout_secs = value("--timeout")?
+                        .parse()
+                        .map_err(|_| "Invalid --timeout".to_string())?;
+                }
+                "--verify-checksums" => options.verify_checksums = true,
+                flag if flag.starts_with('-') => return Err(format!("Unknown option: {flag}")),
+                _ if root.is_none() => root = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument: {arg}")),
+            }
+        }
+
+        if let Some(root) = root {
+            options.root = root;
+        }
+        Ok(options)
+    }
+}
+
+/// Kind of a [`Finding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingKind {
+    /// A newer stable version is excluded by the requirement.
+    Outdated,
+    /// The registry does not know the package.
+    Unknown,
+    /// Every version matching the requirement is yanked.
+    Yanked,
+    /// The lock file checksum differs from the registry.
+    Checksum,
+}
+
+/// A problem with one dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Manifest declaring the dependency.
+    pub manifest: PathBuf,
+    /// Ecosystem identifier (e.g. `cargo`).
+    pub ecosystem: String,
+    /// Package name as written in the manifest.
+    pub package: String,
+    /// 1-based line of the declaration.
+    pub line: u32,
+    /// Version requirement as written, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+    /// Latest stable version, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    /// Locked version whose checksum differs, for checksum findings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<String>,
+    /// Kind of problem.
+    pub kind: FindingKind,
+    /// Whether an outdated dependency is a semver-incompatible release behind.
+    pub major: bool,
+}
+
+/// Result of a check run.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckReport {
+    /// Number of manifests parsed.
+    pub manifests: usize,
+    /// Number of registry dependencies checked.
+    pub dependencies: usize,
+    /// Findings, ordered by manifest and line.
+    pub findings: Vec<Finding>,
+    /// Manifests that could not be read or parsed.
+    pub errors: Vec<String>,
+    /// Diagnostics per manifest, collected for [`OutputFormat::Sarif`].
+    #[serde(skip)]
+    pub sarif_documents: Vec<SarifDocument>,
+}
+
+impl CheckReport {
+    /// Returns [`EXIT_FINDINGS`] when a finding matches one of `fail_on`,
+    /// [`EXIT_OK`] otherwise.
+    pub fn exit_code(&self, fail_on: &[FailOn]) -> i32 {
+        let failed = self
+            .findings
+            .iter()
+            .any(|finding| fail_on.iter().any(|condition| condition.matches(finding)));
+        if failed { EXIT_FINDINGS } else { EXIT_OK }
+    }
+
+    /// Renders the report in `format`.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.render_text(),
+            OutputFormat::Json => to_json(self),
+            OutputFormat::Sarif => to_json(&sarif::build_log(&self.sarif_documents)),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        let mut current: Option<&Path> = None;
+        for finding in &self.findings {
+            if current != Some(finding.manifest.as_path()) {
+                if current.is_some() {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "{}", finding.manifest.display());
+                current = Some(&finding.manifest);
+            }
+            let requirement = finding.requirement.as_deref().unwrap_or("*");
+            let detail = match finding.kind {
+                FindingKind::Outdated => format!(
+                    "{} {requirement} -> {}",
+                    if finding.major { "major" } else { "outdated" },
+                    finding.latest.as_deref().unwrap_or("?")
+                ),
+                FindingKind::Unknown => "unknown package".to_string(),
+                FindingKind::Yanked => format!("{requirement} only matches yanked versions"),
+                FindingKind::Checksum => format!(
+                    "{} checksum differs from the registry",
+                    finding.locked.as_deref().unwrap_or("?")
+                ),
+            };
+            let _ = writeln!(out, "  {}: {} {detail}", finding.line, finding.package);
+        }
+        for error in &self.errors {
+            let _ = writeln!(out, "error: {error}");
+        }
+
+        let count = |kind: FindingKind| self.findings.iter().filter(|f| f.kind == kind).count();
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "{} manifests, {} dependencies: {} outdated, {} unknown, {} yanked",
+            self.manifests,
+            self.dependencies,
+            count(FindingKind::Outdated),
+            count(FindingKind::Unknown),
+            count(FindingKind::Yanked),
+        );
+        let mismatches = count(FindingKind::Checksum);
+        if mismatches > 0 {
+            // Keep the summary on one line
+            out.pop();
+            let _ = writeln!(out, ", {mismatches} checksum mismatches");
+        }
+        out
+    }
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    let mut json = serde_json::to_string_pretty(value).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+/// Checks every manifest under `options.root`.
+///
+/// With [`OutputFormat::Sarif`], the ecosystem diagnostics and their fixes
+/// are collected as well.
+pub async fn run_check(registry: &EcosystemRegistry, options: &CheckOptions) -> CheckReport {
+    let mut report = CheckReport::default();
+
+    for path in discover_manifests(&options.root, registry) {
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) => {
+                report.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        let absolute = std::path::absolute(&path).unwrap_or_else(|_| path.clone());
+        let Some(uri) = Uri::from_file_path(&absolute) else {
+            continue;
+        };
+        let Some(ecosystem) = registry.get_for_uri(&uri) else {
+            continue;
+        };
+        let parse_result = match ecosystem.parse_manifest(&content, &uri).await {
+            Ok(parse_result) => parse_result,
+            Err(e) => {
+                report.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+
+        report.manifests += 1;
+        let dependencies: Vec<&dyn Dependency> = parse_result
+            .dependencies()
+            .into_iter()
+            .filter(|dep| dep.source().is_registry())
+            .collect();
+        report.dependencies += dependencies.len();
+        let locked = if options.verify_checksums {
+            load_lockfile(ecosystem.as_ref(), &uri).await
+        } else {
+            None
+        };
+        let (findings, latest, mismatches) = check_dependencies(
+            Arc::clone(&ecosystem),
+            ecosystem.registry_for(parse_result.as_ref()),
+            &path,
+            &dependencies,
+            locked.as_ref(),
+            options,
+        )
+        .await;
+        report.findings.extend(findings);
+
+        if options.format == OutputFormat::Sarif {
+            let mut diagnostics = collect_diagnostics(
+                ecosystem.as_ref(),
+                parse_result.as_ref(),
+                &uri,
+                &latest,
+                options.max_concurrent,
+            )
+            .await;
+            diagnostics.extend(mismatches.into_iter().map(|diagnostic| (diagnostic, None)));
+            report.sarif_documents.push(SarifDocument {
+                uri,
+                relative_path: relative_path(&absolute),
+                content,
+                diagnostics,
+            });
+        }
+    }
+
+    report
+}
+
+/// Classifies `dependencies` and, given the `locked` packages, compares
+/// their checksums with the registry. Returns the findings, the latest
+/// version of each package and the checksum mismatch diagnostics.
+async fn check_dependencies(
+    ecosystem: Arc<dyn Ecosystem>,
+    registry: Arc<dyn Registry>,
+    manifest: &Path,
+    dependencies: &[&dyn Dependency],
+    locked: Option<&ResolvedPackages>,
+    options: &CheckOptions,
+) -> (Vec<Finding>, HashMap<String, String>, Vec<Diagnostic>) {
+    let mut names: Vec<&str> = dependencies.iter().map(|dep| dep.name()).collect();
+    names.sort_unstable();
+    names.dedup();
+    let fetched = fetch_versions(
+        registry,
+        names,
+        options.timeout_secs,
+        options.max_concurrent,
+    )
+    .await;
+
+    let mut findings: Vec<Finding> = dependencies
+        .iter()
+        .filter_map(|dep| {
+            let versions = fetched.get(dep.name()).map(Vec::as_slice);
+            let (kind, latest, major) = classify(*dep, versions, ecosystem.formatter())?;
+            Some(Finding {
+                manifest: manifest.to_path_buf(),
+                ecosystem: ecosystem.id().to_string(),
+                package: dep.name().to_string(),
+                line: dep.name_range().start.line + 1,
+                requirement: dep.version_requirement().map(str::to_string),
+                latest,
+                locked: None,
+                kind,
+                major,
+            })
+        })
+        .collect();
+
+    let mismatches = locked.map_or_else(Vec::new, |packages| {
+        checksums::checksum_mismatches(dependencies, packages, ecosystem.formatter(), &fetched)
+    });
+    findings.extend(mismatches.iter().map(|(dep, mismatch)| Finding {
+        manifest: manifest.to_path_buf(),
+        ecosystem: ecosystem.id().to_string(),
+        package: dep.name().to_string(),
+        line: dep.name_range().start.line + 1,
+        requirement: dep.version_requirement().map(str::to_string),
+        latest: None,
+        locked: Some(mismatch.version.clone()),
+        kind: FindingKind::Checksum,
+        major: false,
+    }));
+    let mismatches = mismatches
+        .iter()
+        .map(|(dep, mismatch)| checksums::mismatch_diagnostic(*dep, mismatch))
+        .collect();
+    findings.sort_by_key(|finding| finding.line);
+
+    let latest = fetched
+        .iter()
+        .filter_map(|(name, versions)| {
+            let latest = deps_core::find_latest_stable(versions)?;
+            Some(((*name).to_string(), latest.version_string().to_string()))
+        })
+        .collect();
+    (findings, latest, mismatches)
+}
+
+/// Fetches the versions of `names` in parallel, leaving out packages whose
+/// lookup failed or timed out.
+pub(crate) async fn fetch_versions(
+    registry: Arc<dyn Registry>,
+    names: Vec<&str>,
+    timeout_secs: u64,
+    max_concurrent: usize,
+) -> HashMap<&str, Vec<Box<dyn Version>>> {
+    let timeout = Duration::from_secs(timeout_secs);
+    stream::iter(names)
+        .map(|name| {
+            let registry = Arc::clone(&registry);
+            async move {
+                match tokio::time::timeout(timeout, registry.get_versions(name)).await {
+                    Ok(Ok(versions)) => Some((name, versions)),
+                    Ok(Err(e)) => {
+                        tracing::debug!(package = %name, error = %e, "fetch failed");
+                        None
+                    }
+                    Err(_) => {
+                        tracing::warn!(package = %name, "fetch timed out ({}s)", timeout.as_secs());
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(max_concurrent.max(1))
+        .filter_map(std::future::ready)
+        .collect()
+        .await
+}
+
+/// Runs the ecosystem's diagnostics the way the server does, with `latest`
+/// standing in for the fetched versions, and looks up a fix for each.
+///
+/// Diagnostics on path, git and workspace dependencies are dropped: they
+/// were never fetched, so they would all read "Unknown package".
+async fn collect_diagnostics(
+    ecosystem: &dyn Ecosystem,
+    parse_result: &dyn ParseResult,
+    uri: &Uri,
+    latest: &HashMap<String, String>,
+    max_concurrent: usize,
+) -> Vec<(
+    tower_lsp_server::ls_types::Diagnostic,
+    Option<tower_lsp_server::ls_types::CodeAction>,
+)> {
+    let resolved = load_resolved_versions(ecosystem, uri).await;
+    let dependencies = parse_result.dependencies();
+    let diagnostics = ecosystem
+        .generate_diagnostics(
+            parse_result,
+            VersionData::new(latest, &resolved),
+            uri,
+            max_concurrent,
+        )
+        .await;
+
+    let mut collected = Vec::with_capacity(diagnostics.len());
+    for diagnostic in diagnostics {
+        let on_unfetched = dependencies.iter().any(|dep| {
+            !dep.source().is_registry()
+                && (dep.name_range() == diagnostic.range
+                    || dep.version_range() == Some(diagnostic.range))
+        });
+        if on_unfetched {
+            continue;
+        }
+        let fix = sarif::find_fix(ecosystem, parse_result, uri, &diagnostic).await;
+        collected.push((diagnostic, fix));
+    }
+    collected
+}
+
+async fn load_resolved_versions(ecosystem: &dyn Ecosystem, uri: &Uri) -> HashMap<String, String> {
+    load_lockfile(ecosystem, uri)
+        .await
+        .map(|resolved| {
+            resolved
+                .iter()
+                .map(|(name, pkg)| (name.clone(), pkg.version.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn load_lockfile(ecosystem: &dyn Ecosystem, uri: &Uri) -> Option<ResolvedPackages> {
+    let provider = ecosystem.lockfile_provider()?;
+    let lockfile = provider.locate_lockfile(uri)?;
+    match provider.parse_lockfile(&lockfile).await {
+        Ok(resolved) => Some(resolved),
+        Err(e) => {
+            tracing::warn!("Failed to parse lock file {}: {e}", lockfile.display());
+            None
+        }
+    }
+}
+
+/// Path of `manifest` relative to the working directory, with `/`
+/// separators; `None` outside of it.
+fn relative_path(manifest: &Path) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    let relative = manifest.strip_prefix(cwd).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Classifies one dependency given the versions published for it (`None`
+/// when the registry lookup failed).
+fn classify(
+    dep: &dyn Dependency,
+    versions: Option<&[Box<dyn Version>]>,
+    formatter: &dyn EcosystemFormatter,
+) -> Option<(FindingKind, Option<String>, bool)> {
+    let versions = match versions {
+        Some(versions) if !versions.is_empty() => versions,
+        _ => return Some((FindingKind::Unknown, None, false)),
+    };
+    let latest = deps_core::find_latest_stable(versions).map(|v| v.version_string().to_string());
+    let requirement = dep.version_requirement()?;
+
+    let matching: Vec<&dyn Version> = versions
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|v| formatter.version_satisfies_requirement(v.version_string(), requirement))
+        .collect();
+    if !matching.is_empty() && matching.iter().all(|v| v.is_yanked()) {
+        return Some((FindingKind::Yanked, latest, false));
+    }
+
+    let latest_version = latest.as_deref()?;
+    if formatter.is_requirement_up_to_date(requirement, latest_version) {
+        return None;
+    }
+    let current = matching.first().map_or(requirement, |v| v.version_string());
+    let major = compatibility_key(current) != compatibility_key(latest_version);
+    Some((FindingKind::Outdated, latest, major))
+}
+
+/// Returns the semver component whose change breaks compatibility: the major
+/// version, or the minor version for `0.x` releases.
+pub(crate) fn compatibility_key(version: &str) -> (u64, u64) {
+    let (major, minor) = major_minor(version);
+    if major == 0 { (0, minor) } else { (major, 0) }
+}
+
+/// Returns the first two numeric components of a version or requirement,
+/// ignoring operators and prefixes such as `^`, `>=` or `v`.
+pub(crate) fn major_minor(version: &str) -> (u64, u64) {
+    let start = version.find(|c: char| c.is_ascii_digit()).unwrap_or(0);
+    let mut parts = version[start..]
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse::<u64>().unwrap_or(0));
+    (parts.next().unwrap_or(0), parts.next().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deps_core::parser::DependencySource;
+    use std::any::Any;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    struct TestVersion(&'static str, bool);
+
+    impl Version for TestVersion {
+        fn version_string(&self) -> &str {
+            self.0
+        }
+        fn is_yanked(&self) -> bool {
+            self.1
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    struct TestDependency(Option<&'static str>);
+
+    impl Dependency for TestDependency {
+        fn name(&self) -> &'static str {
+            "pkg"
+        }
+        fn name_range(&self) -> Range {
+            Range::new(Position::new(3, 0), Position::new(3, 3))
+        }
+        fn version_requirement(&self) -> Option<&str> {
+            self.0
+        }
+        fn version_range(&self) -> Option<Range> {
+            None
+        }
+        fn source(&self) -> DependencySource {
+            DependencySource::Registry
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    /// Compatibility matching keeps the tests independent of ecosystems.
+    struct ExactFormatter;
+
+    impl EcosystemFormatter for ExactFormatter {
+        fn format_version_for_text_edit(&self, version: &str) -> String {
+            version.to_string()
+        }
+        fn version_satisfies_requirement(&self, version: &str, requirement: &str) -> bool {
+            compatibility_key(version) == compatibility_key(requirement)
+        }
+        fn package_url(&self, name: &str) -> String {
+            name.to_string()
+        }
+    }
+
+    fn versions(list: &[(&'static str, bool)]) -> Vec<Box<dyn Version>> {
+        list.iter()
+            .map(|&(v, yanked)| Box::new(TestVersion(v, yanked)) as Box<dyn Version>)
+            .collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let published = versions(&[("2.1.0", false), ("1.4.0", false), ("1.3.0", true)]);
+        let formatter = ExactFormatter;
+
+        assert_eq!(
+            classify(&TestDependency(Some("1.0")), Some(&published), &formatter),
+            Some((FindingKind::Outdated, Some("2.1.0".into()), true))
+        );
+        assert_eq!(
+            classify(&TestDependency(Some("2.0")), Some(&published), &formatter),
+            None
+        );
+        assert_eq!(
+            classify(&TestDependency(Some("1.0")), None, &formatter),
+            Some((FindingKind::Unknown, None, false))
+        );
+        assert_eq!(
+            classify(&TestDependency(None), Some(&published), &formatter),
+            None
+        );
+
+        let only_yanked = versions(&[("2.0.0", false), ("1.0.0", true)]);
+        assert_eq!(
+            classify(&TestDependency(Some("1.0")), Some(&only_yanked), &formatter),
+            Some((FindingKind::Yanked, Some("2.0.0".into()), false))
+        );
+    }
+
+    #[test]
+    fn test_compatibility_key() {
+        assert_eq!(compatibility_key("^1.2.3"), (1, 0));
+        assert_eq!(compatibility_key("v2.0.0"), (2, 0));
+        assert_eq!(compatibility_key("0.4.1"), (0, 4));
+        assert_eq!(compatibility_key(">=0.27,<1"), (0, 27));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parse = |args: &[&str]| CheckOptions::parse(args.iter().map(|s| s.to_string()));
+
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.root, PathBuf::from("."));
+        assert_eq!(options.format, OutputFormat::Text);
+        assert_eq!(options.fail_on, vec![FailOn::Yanked]);
+
+        let options = parse(&["--format=json", "--fail-on", "never", "/repo"]).unwrap();
+        assert_eq!(options.format, OutputFormat::Json);
+        assert!(options.fail_on.is_empty());
+        assert_eq!(options.root, PathBuf::from("/repo"));
+
+        assert_eq!(
+            parse(&["--format", "sarif"]).unwrap().format,
+            OutputFormat::Sarif
+        );
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--fail-on", "sometimes"]).is_err());
+        assert!(parse(&["--fail-on"]).is_err());
+        assert!(parse(&["a", "b"]).is_err());
+
+        let options = parse(&["--verify-checksums", "--fail-on", "checksum"]).unwrap();
+        assert!(options.verify_checksums);
+        assert_eq!(options.fail_on, vec![FailOn::Checksum]);
+        assert!(!parse(&[]).unwrap().verify_checksums);
+    }
+
+    fn finding(kind: FindingKind, major: bool) -> Finding {
+        Finding {
+            manifest: PathBuf::from("Cargo.toml"),
+            ecosystem: "cargo".into(),
+            package: "serde".into(),
+            line: 7,
+            requirement: Some("1.0".into()),
+            latest: Some("2.0.0".into()),
+            locked: None,
+            kind,
+            major,
+        }
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let report = CheckReport {
+            findings: vec![finding(FindingKind::Outdated, false)],
+            ..Default::default()
+        };
+        assert_eq!(report.exit_code(&[FailOn::Yanked]), EXIT_OK);
+        assert_eq!(report.exit_code(&[FailOn::Major]), EXIT_OK);
+        assert_eq!(report.exit_code(&[FailOn::Outdated]), EXIT_FINDINGS);
+        assert_eq!(report.exit_code(&[FailOn::Any]), EXIT_FINDINGS);
+        assert_eq!(report.exit_code(&[]), EXIT_OK);
+
+        let report = CheckReport {
+            findings: vec![finding(FindingKind::Outdated, true)],
+            ..Default::default()
+        };
+        assert_eq!(
+            report.exit_code(&[FailOn::Yanked, FailOn::Major]),
+            EXIT_FINDINGS
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let report = CheckReport {
+            manifests: 1,
+            dependencies: 3,
+            findings: vec![
+                finding(FindingKind::Outdated, true),
+                Finding {
+                    line: 9,
+                    package: "left-pad".into(),
+                    ..finding(FindingKind::Unknown, false)
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            report.render(OutputFormat::Text),
+            "Cargo.toml\n  7: serde major 1.0 -> 2.0.0\n  9: left-pad unknown package\n\n1 manifests, 3 dependencies: 1 outdated, 1 unknown, 0 yanked\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["findings"][0]["kind"], "outdated");
+        assert_eq!(json["findings"][0]["major"], true);
+        assert_eq!(json["findings"][1]["kind"], "unknown");
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&report.render(OutputFormat::Sarif)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        assert!(sarif.get("findings").is_none());
+    }
+
+    #[test]
+    fn test_render_checksum_mismatch() {
+        let report = CheckReport {
+            manifests: 1,
+            dependencies: 1,
+            findings: vec![Finding {
+                latest: None,
+                locked: Some("1.0.195".into()),
+                ..finding(FindingKind::Checksum, false)
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            report.render(OutputFormat::Text),
+            "Cargo.toml\n  7: serde 1.0.195 checksum differs from the registry\n\n1 manifests, 1 dependencies: 0 outdated, 0 unknown, 0 yanked, 1 checksum mismatches\n"
+        );
+        assert_eq!(report.exit_code(&[FailOn::Yanked]), EXIT_OK);
+        assert_eq!(report.exit_code(&[FailOn::Checksum]), EXIT_FINDINGS);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["findings"][0]["kind"], "checksum");
+        assert_eq!(json["findings"][0]["locked"], "1.0.195");
+    }
+
+    #[cfg(feature = "cargo")]
+    #[tokio::test]
+    async fn test_run_check_reports_parse_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("Cargo.toml"), "[dependencies\n").unwrap();
+
+        let registry = EcosystemRegistry::new();
+        crate::register_ecosystems(&registry, Arc::new(deps_core::HttpCache::new()));
+        let options = CheckOptions {
+            root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let report = run_check(&registry, &options).await;
+        assert_eq!(report.manifests, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.exit_code(&[FailOn::Any]), EXIT_OK);
+    }
+}
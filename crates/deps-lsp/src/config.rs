@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use deps_core::hint_template::uses_release_history;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tower_lsp_server::ls_types::DiagnosticSeverity;
 
 /// Root configuration for the deps-lsp server.
@@ -22,7 +24,7 @@ use tower_lsp_server::ls_types::DiagnosticSeverity;
 /// let config: DepsConfig = serde_json::from_str(json).unwrap();
 /// assert!(config.inlay_hints.enabled);
 /// ```
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DepsConfig {
     #[serde(default)]
     pub inlay_hints: InlayHintsConfig,
@@ -34,6 +36,188 @@ pub struct DepsConfig {
     pub cold_start: ColdStartConfig,
     #[serde(default)]
     pub loading_indicator: LoadingIndicatorConfig,
+    #[serde(default)]
+    pub update: UpdateConfig,
+    /// Per-ecosystem settings, keyed by ecosystem ID (`cargo`, `npm`, ...).
+    #[serde(default)]
+    pub ecosystems: HashMap<String, EcosystemSettings>,
+    /// Registry URLs replacing the public registry, keyed by ecosystem ID.
+    #[serde(default, deserialize_with = "deserialize_registries")]
+    pub registries: HashMap<String, String>,
+}
+
+impl DepsConfig {
+    /// Whether manifests of the ecosystem `id` are handled at all.
+    pub fn is_ecosystem_enabled(&self, id: &str) -> bool {
+        self.ecosystems
+            .get(id)
+            .is_none_or(|settings| settings.enabled)
+    }
+
+    /// Whether pre-releases count as the latest version of packages of the
+    /// ecosystem `id`.
+    pub fn include_prereleases(&self, id: &str) -> bool {
+        self.ecosystems
+            .get(id)
+            .is_some_and(|settings| settings.include_prereleases)
+    }
+
+    /// Returns the settings for manifests of the ecosystem `id`, with its
+    /// overrides applied to the global sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_lsp::config::DepsConfig;
+    ///
+    /// let config: DepsConfig = serde_json::from_str(r#"{
+    ///     "inlay_hints": { "up_to_date_text": "ok" },
+    ///     "ecosystems": { "npm": { "inlay_hints": { "up_to_date_text": "npm ok" } } }
+    /// }"#).unwrap();
+    ///
+    /// assert_eq!(config.for_ecosystem("npm").inlay_hints.up_to_date_text, "npm ok");
+    /// assert_eq!(config.for_ecosystem("cargo").inlay_hints.up_to_date_text, "ok");
+    /// ```
+    #[must_use]
+    pub fn for_ecosystem(&self, id: &str) -> Self {
+        let mut config = self.clone();
+        if let Some(settings) = self.ecosystems.get(id) {
+            settings.apply(&mut config);
+        }
+        config
+    }
+}
+
+/// Settings of a single ecosystem.
+///
+/// Besides switching the ecosystem off, inlay hint texts, the loading text
+/// and diagnostic severities can be overridden; unset fields fall back to
+/// the global sections.
+///
+/// # Examples
+///
+/// ```
+/// use deps_lsp::config::DepsConfig;
+/// use tower_lsp_server::ls_types::DiagnosticSeverity;
+///
+/// let config: DepsConfig = serde_json::from_str(r#"{
+///     "ecosystems": {
+///         "gradle": { "enabled": false },
+///         "npm": { "diagnostics": { "outdated_severity": "warning" } }
+///     }
+/// }"#).unwrap();
+///
+/// assert!(!config.is_ecosystem_enabled("gradle"));
+/// assert!(config.is_ecosystem_enabled("cargo"));
+/// assert_eq!(
+///     config.for_ecosystem("npm").diagnostics.outdated_severity,
+///     DiagnosticSeverity::WARNING
+/// );
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcosystemSettings {
+    /// Handle manifests of this ecosystem (default: true)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Treat the newest pre-release as the latest version (default: false)
+    #[serde(default)]
+    pub include_prereleases: bool,
+    #[serde(default)]
+    pub inlay_hints: InlayHintsOverrides,
+    #[serde(default)]
+    pub loading_indicator: LoadingIndicatorOverrides,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsOverrides,
+}
+
+impl Default for EcosystemSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            include_prereleases: false,
+            inlay_hints: InlayHintsOverrides::default(),
+            loading_indicator: LoadingIndicatorOverrides::default(),
+            diagnostics: DiagnosticsOverrides::default(),
+        }
+    }
+}
+
+impl EcosystemSettings {
+    fn apply(&self, config: &mut DepsConfig) {
+        let hints = &self.inlay_hints;
+        if let Some(enabled) = hints.enabled {
+            config.inlay_hints.enabled = enabled;
+        }
+        if let Some(text) = &hints.up_to_date_text {
+            config.inlay_hints.up_to_date_text.clone_from(text);
+        }
+        if let Some(text) = &hints.needs_update_text {
+            config.inlay_hints.needs_update_text.clone_from(text);
+        }
+        if let Some(show_locked) = hints.show_locked {
+            config.inlay_hints.show_locked = show_locked;
+        }
+        if let Some(text) = &self.loading_indicator.loading_text {
+            config.loading_indicator.loading_text.clone_from(text);
+        }
+        let diagnostics = &self.diagnostics;
+        if let Some(severity) = diagnostics.outdated_severity {
+            config.diagnostics.outdated_severity = severity;
+        }
+        if let Some(severity) = diagnostics.unknown_severity {
+            config.diagnostics.unknown_severity = severity;
+        }
+        if let Some(severity) = diagnostics.yanked_severity {
+            config.diagnostics.yanked_severity = severity;
+        }
+    }
+}
+
+/// Per-ecosystem overrides of [`InlayHintsConfig`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InlayHintsOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up_to_date_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub needs_update_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_locked: Option<bool>,
+}
+
+/// Per-ecosystem overrides of [`LoadingIndicatorConfig`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoadingIndicatorOverrides {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_loading_text"
+    )]
+    pub loading_text: Option<String>,
+}
+
+/// Per-ecosystem overrides of the [`DiagnosticsConfig`] severities.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiagnosticsOverrides {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_severity"
+    )]
+    pub outdated_severity: Option<DiagnosticSeverity>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_severity"
+    )]
+    pub unknown_severity: Option<DiagnosticSeverity>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_severity"
+    )]
+    pub yanked_severity: Option<DiagnosticSeverity>,
 }
 
 /// Configuration for inlay hints (inline version annotations).
@@ -46,6 +230,11 @@ pub struct DepsConfig {
 /// - `enabled`: `true`
 /// - `up_to_date_text`: `"✅"`
 /// - `needs_update_text`: `"❌ {}"` (where `{}` is replaced with the latest version)
+/// - `show_locked`: `false`
+///
+/// Both texts are templates: `{latest}`, `{compatible}`, `{locked}`, `{kind}`,
+/// `{age}` and `{count}` are replaced as described in
+/// [`deps_core::hint_template`].
 ///
 /// # Examples
 ///
@@ -56,11 +245,12 @@ pub struct DepsConfig {
 ///     enabled: true,
 ///     up_to_date_text: "OK".into(),
 ///     needs_update_text: "UPDATE {}".into(),
+///     show_locked: false,
 /// };
 ///
 /// assert_eq!(config.up_to_date_text, "OK");
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InlayHintsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -68,6 +258,9 @@ pub struct InlayHintsConfig {
     pub up_to_date_text: String,
     #[serde(default = "default_needs_update")]
     pub needs_update_text: String,
+    /// Show the lock file version next to loose requirements
+    #[serde(default)]
+    pub show_locked: bool,
 }
 
 impl Default for InlayHintsConfig {
@@ -76,10 +269,21 @@ impl Default for InlayHintsConfig {
             enabled: true,
             up_to_date_text: default_up_to_date(),
             needs_update_text: default_needs_update(),
+            show_locked: false,
         }
     }
 }
 
+impl InlayHintsConfig {
+    /// Whether the texts use placeholders that need each package's release
+    /// history, which is then fetched along with the latest versions.
+    pub fn uses_release_history(&self) -> bool {
+        self.enabled
+            && (uses_release_history(&self.up_to_date_text)
+                || uses_release_history(&self.needs_update_text))
+    }
+}
+
 /// Configuration for diagnostic severity levels.
 ///
 /// Controls the severity level reported for different types of dependency issues.
@@ -90,6 +294,14 @@ impl Default for InlayHintsConfig {
 /// - `outdated_severity`: `HINT` - Dependencies with available updates
 /// - `unknown_severity`: `WARNING` - Dependencies not found in registry
 /// - `yanked_severity`: `WARNING` - Dependencies using yanked versions
+/// - `duplicate_versions`: `false` - Report direct dependencies that pull in an
+///   older copy of a package the lock file also resolves at a newer version
+/// - `verify_checksums`: `false` - Report direct dependencies whose lock file
+///   checksum differs from the one published by the registry
+/// - `ignore`: empty - Packages (`*`/`?` wildcards allowed) never reported
+///
+/// Severities are LSP severity numbers (`1` = error ... `4` = hint) or their
+/// names (`"error"`, `"warning"`, `"information"`, `"hint"`).
 ///
 /// # Examples
 ///
@@ -101,18 +313,46 @@ impl Default for InlayHintsConfig {
 ///     outdated_severity: DiagnosticSeverity::INFORMATION,
 ///     unknown_severity: DiagnosticSeverity::ERROR,
 ///     yanked_severity: DiagnosticSeverity::ERROR,
+///     duplicate_versions: true,
+///     verify_checksums: false,
+///     ignore: vec!["internal-*".into()],
 /// };
 ///
 /// assert_eq!(config.unknown_severity, DiagnosticSeverity::ERROR);
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiagnosticsConfig {
-    #[serde(default = "default_outdated_severity")]
+    #[serde(
+        default = "default_outdated_severity",
+        deserialize_with = "deserialize_severity"
+    )]
     pub outdated_severity: DiagnosticSeverity,
-    #[serde(default = "default_unknown_severity")]
+    #[serde(
+        default = "default_unknown_severity",
+        deserialize_with = "deserialize_severity"
+    )]
     pub unknown_severity: DiagnosticSeverity,
-    #[serde(default = "default_yanked_severity")]
+    #[serde(
+        default = "default_yanked_severity",
+        deserialize_with = "deserialize_severity"
+    )]
     pub yanked_severity: DiagnosticSeverity,
+    #[serde(default)]
+    pub duplicate_versions: bool,
+    #[serde(default)]
+    pub verify_checksums: bool,
+    /// Packages whose diagnostics are suppressed
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl DiagnosticsConfig {
+    /// Whether diagnostics for `name` are suppressed.
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| deps_core::workspace::segment_matches(pattern, name))
+    }
 }
 
 impl Default for DiagnosticsConfig {
@@ -121,6 +361,9 @@ impl Default for DiagnosticsConfig {
             outdated_severity: default_outdated_severity(),
             unknown_severity: default_unknown_severity(),
             yanked_severity: default_yanked_severity(),
+            duplicate_versions: false,
+            verify_checksums: false,
+            ignore: Vec::new(),
         }
     }
 }
@@ -151,7 +394,7 @@ impl Default for DiagnosticsConfig {
 ///
 /// assert_eq!(config.refresh_interval_secs, 600);
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheConfig {
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval_secs: u64,
@@ -191,7 +434,7 @@ impl Default for CacheConfig {
 /// - `enabled`: `true`
 /// - `fallback_to_hints`: `true`
 /// - `loading_text`: `"⏳"`
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoadingIndicatorConfig {
     /// Enable loading indicators (default: true)
     #[serde(default = "default_true")]
@@ -220,6 +463,48 @@ impl Default for LoadingIndicatorConfig {
     }
 }
 
+/// Packages `deps-lsp update` leaves alone or keeps within a range.
+///
+/// Ignore patterns and pin keys are package names as written in manifests;
+/// ignore patterns may use `*` and `?` wildcards.
+///
+/// # Examples
+///
+/// ```
+/// use deps_lsp::config::UpdateConfig;
+///
+/// let config: UpdateConfig = serde_json::from_str(r#"{
+///     "ignore": ["aws-*"],
+///     "pin": { "tokio": "1.38" }
+/// }"#).unwrap();
+///
+/// assert!(config.is_ignored("aws-sdk-s3"));
+/// assert_eq!(config.pin("tokio"), Some("1.38"));
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// Packages never updated.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Requirements updated versions must satisfy, per package.
+    #[serde(default)]
+    pub pin: HashMap<String, String>,
+}
+
+impl UpdateConfig {
+    /// Whether `name` matches one of the ignore patterns.
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| deps_core::workspace::segment_matches(pattern, name))
+    }
+
+    /// Returns the requirement `name` is pinned to.
+    pub fn pin(&self, name: &str) -> Option<&str> {
+        self.pin.get(name).map(String::as_str)
+    }
+}
+
 // Default value functions
 const fn default_true() -> bool {
     true
@@ -264,6 +549,57 @@ where
     Ok(validate_loading_text(text))
 }
 
+fn deserialize_optional_loading_text<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.map(validate_loading_text))
+}
+
+/// A severity as its LSP number or its lowercase name
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeverityRepr {
+    Number(DiagnosticSeverity),
+    Name(String),
+}
+
+impl SeverityRepr {
+    fn into_severity<E: serde::de::Error>(self) -> Result<DiagnosticSeverity, E> {
+        match self {
+            Self::Number(severity) => Ok(severity),
+            Self::Name(name) => match name.to_ascii_lowercase().as_str() {
+                "error" => Ok(DiagnosticSeverity::ERROR),
+                "warning" => Ok(DiagnosticSeverity::WARNING),
+                "information" | "info" => Ok(DiagnosticSeverity::INFORMATION),
+                "hint" => Ok(DiagnosticSeverity::HINT),
+                _ => Err(E::custom(format!(
+                    "unknown severity \"{name}\", expected error, warning, information or hint"
+                ))),
+            },
+        }
+    }
+}
+
+/// Accepts a severity as its LSP number or its lowercase name
+fn deserialize_severity<'de, D>(deserializer: D) -> Result<DiagnosticSeverity, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    SeverityRepr::deserialize(deserializer)?.into_severity()
+}
+
+fn deserialize_optional_severity<'de, D>(
+    deserializer: D,
+) -> Result<Option<DiagnosticSeverity>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<SeverityRepr>::deserialize(deserializer)?
+        .map(SeverityRepr::into_severity)
+        .transpose()
+}
+
 const fn default_outdated_severity() -> DiagnosticSeverity {
     DiagnosticSeverity::HINT
 }
@@ -288,6 +624,24 @@ const fn default_max_concurrent_fetches() -> usize {
     20
 }
 
+/// Accepts `https://` registry URLs only, so lookups can't be pointed at
+/// `file://` paths or sent in clear text.
+fn deserialize_registries<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let registries = HashMap::<String, String>::deserialize(deserializer)?;
+    for (id, url) in &registries {
+        let url = url.strip_prefix("sparse+").unwrap_or(url);
+        if !url.starts_with("https://") {
+            return Err(serde::de::Error::custom(format!(
+                "registry URL for \"{id}\" must start with https://"
+            )));
+        }
+    }
+    Ok(registries)
+}
+
 /// Minimum timeout (seconds) to prevent zero-timeout edge case
 const MIN_FETCH_TIMEOUT_SECS: u64 = 1;
 /// Maximum timeout (seconds) - 5 minutes is generous
@@ -363,7 +717,7 @@ where
 ///
 /// assert_eq!(config.rate_limit_ms, 200);
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColdStartConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -422,6 +776,135 @@ mod tests {
         assert_eq!(config.outdated_severity, DiagnosticSeverity::ERROR);
         assert_eq!(config.unknown_severity, DiagnosticSeverity::WARNING);
         assert_eq!(config.yanked_severity, DiagnosticSeverity::WARNING);
+        assert!(!config.duplicate_versions);
+
+        let config: DiagnosticsConfig =
+            serde_json::from_str(r#"{ "duplicate_versions": true }"#).unwrap();
+        assert!(config.duplicate_versions);
+        assert!(!config.verify_checksums);
+
+        let config: DiagnosticsConfig =
+            serde_json::from_str(r#"{ "verify_checksums": true }"#).unwrap();
+        assert!(config.verify_checksums);
+    }
+
+    #[test]
+    fn test_diagnostics_config_severity_names() {
+        let config: DiagnosticsConfig = serde_json::from_str(
+            r#"{ "outdated_severity": "information", "yanked_severity": "Error" }"#,
+        )
+        .unwrap();
+        assert_eq!(config.outdated_severity, DiagnosticSeverity::INFORMATION);
+        assert_eq!(config.yanked_severity, DiagnosticSeverity::ERROR);
+        assert_eq!(config.unknown_severity, DiagnosticSeverity::WARNING);
+
+        assert!(
+            serde_json::from_str::<DiagnosticsConfig>(r#"{ "outdated_severity": "loud" }"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_config_ignore() {
+        let config: DiagnosticsConfig =
+            serde_json::from_str(r#"{ "ignore": ["internal-*", "left-pad"] }"#).unwrap();
+        assert!(config.is_ignored("internal-utils"));
+        assert!(config.is_ignored("left-pad"));
+        assert!(!config.is_ignored("serde"));
+    }
+
+    #[test]
+    fn test_config_serialization_round_trip() {
+        let json = r#"{
+            "diagnostics": { "outdated_severity": 2, "ignore": ["a*"] },
+            "cache": { "fetch_timeout_secs": 30 },
+            "ecosystems": { "npm": { "enabled": false } }
+        }"#;
+        let config: DepsConfig = serde_json::from_str(json).unwrap();
+
+        let value = serde_json::to_value(&config).unwrap();
+        let config: DepsConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            config.diagnostics.outdated_severity,
+            DiagnosticSeverity::WARNING
+        );
+        assert!(config.diagnostics.is_ignored("abc"));
+        assert_eq!(config.cache.fetch_timeout_secs, 30);
+        assert!(!config.is_ecosystem_enabled("npm"));
+    }
+
+    #[test]
+    fn test_registries() {
+        let config: DepsConfig = serde_json::from_str(
+            r#"{ "registries": { "npm": "https://npm.example.com/", "cargo": "sparse+https://index.example.com/" } }"#,
+        )
+        .unwrap();
+        assert_eq!(config.registries["npm"], "https://npm.example.com/");
+
+        let invalid = r#"{ "registries": { "pypi": "file:///etc" } }"#;
+        assert!(serde_json::from_str::<DepsConfig>(invalid).is_err());
+        let plain = r#"{ "registries": { "npm": "http://npm.example.com/" } }"#;
+        assert!(serde_json::from_str::<DepsConfig>(plain).is_err());
+    }
+
+    #[test]
+    fn test_ecosystem_overrides() {
+        let json = r#"{
+            "inlay_hints": { "up_to_date_text": "ok", "needs_update_text": "new {}" },
+            "diagnostics": { "outdated_severity": "hint" },
+            "ecosystems": {
+                "npm": {
+                    "include_prereleases": true,
+                    "inlay_hints": { "enabled": false, "needs_update_text": "npm {}" },
+                    "loading_indicator": { "loading_text": "..." },
+                    "diagnostics": { "outdated_severity": "warning", "yanked_severity": 1 }
+                }
+            }
+        }"#;
+        let config: DepsConfig = serde_json::from_str(json).unwrap();
+
+        let npm = config.for_ecosystem("npm");
+        assert!(!npm.inlay_hints.enabled);
+        assert_eq!(npm.inlay_hints.up_to_date_text, "ok");
+        assert_eq!(npm.inlay_hints.needs_update_text, "npm {}");
+        assert_eq!(npm.loading_indicator.loading_text, "...");
+        assert_eq!(
+            npm.diagnostics.outdated_severity,
+            DiagnosticSeverity::WARNING
+        );
+        assert_eq!(npm.diagnostics.yanked_severity, DiagnosticSeverity::ERROR);
+        assert_eq!(
+            npm.diagnostics.unknown_severity,
+            DiagnosticSeverity::WARNING
+        );
+        assert!(config.include_prereleases("npm"));
+
+        let cargo = config.for_ecosystem("cargo");
+        assert!(cargo.inlay_hints.enabled);
+        assert_eq!(cargo.inlay_hints.needs_update_text, "new {}");
+        assert_eq!(
+            cargo.diagnostics.outdated_severity,
+            DiagnosticSeverity::HINT
+        );
+        assert!(!config.include_prereleases("cargo"));
+        assert!(config.is_ecosystem_enabled("npm"));
+    }
+
+    #[test]
+    fn test_ecosystem_overrides_round_trip() {
+        let json =
+            r#"{ "ecosystems": { "pypi": { "diagnostics": { "unknown_severity": "error" } } } }"#;
+        let config: DepsConfig = serde_json::from_str(json).unwrap();
+        let config: DepsConfig =
+            serde_json::from_value(serde_json::to_value(&config).unwrap()).unwrap();
+        assert_eq!(
+            config.for_ecosystem("pypi").diagnostics.unknown_severity,
+            DiagnosticSeverity::ERROR
+        );
+
+        let invalid =
+            r#"{ "ecosystems": { "pypi": { "diagnostics": { "unknown_severity": "loud" } } } }"#;
+        assert!(serde_json::from_str::<DepsConfig>(invalid).is_err());
     }
 
     #[test]
@@ -516,6 +999,22 @@ mod tests {
         assert!(config.cache.enabled);
     }
 
+    #[test]
+    fn test_update_config_deserialization() {
+        let json =
+            r#"{ "update": { "ignore": ["@types/*", "left-pad"], "pin": { "react": "^17" } } }"#;
+        let config: DepsConfig = serde_json::from_str(json).unwrap();
+
+        assert!(config.update.is_ignored("@types/node"));
+        assert!(config.update.is_ignored("left-pad"));
+        assert!(!config.update.is_ignored("react"));
+        assert_eq!(config.update.pin("react"), Some("^17"));
+        assert_eq!(config.update.pin("react-dom"), None);
+
+        let config: DepsConfig = serde_json::from_str("{}").unwrap();
+        assert!(config.update.ignore.is_empty());
+    }
+
     #[test]
     fn test_cold_start_config_defaults() {
         let config = ColdStartConfig::default();
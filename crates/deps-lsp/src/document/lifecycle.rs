@@ -6,12 +6,14 @@
 use super::loader::load_document_from_disk;
 use super::state::{DocumentState, ServerState};
 use crate::config::DepsConfig;
-use crate::handlers::diagnostics;
+use crate::handlers::{diagnostics, status};
+use crate::metrics::MeteredRegistry;
 use crate::progress::{ProgressSender, RegistryProgress};
 use deps_core::Ecosystem;
 use deps_core::EcosystemId;
 use deps_core::Registry;
 use deps_core::Result;
+use deps_core::hint_template::ReleaseInfo;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -79,6 +81,23 @@ struct FetchResult {
     first_error: Option<String>,
 }
 
+/// Fetches the newest version of a package.
+///
+/// Registries return versions newest first, so with `include_prereleases`
+/// the first non-yanked entry wins; otherwise the registry's own
+/// stable-only `*` match is used.
+async fn fetch_latest_version(
+    registry: &dyn Registry,
+    name: &str,
+    include_prereleases: bool,
+) -> Result<Option<Box<dyn deps_core::Version>>> {
+    if !include_prereleases {
+        return registry.get_latest_matching(name, "*").await;
+    }
+    let versions = registry.get_versions(name).await?;
+    Ok(versions.into_iter().find(|v| !v.is_yanked()))
+}
+
 /// Fetches latest versions for multiple packages in parallel with progress reporting.
 ///
 /// Returns a [`FetchResult`] containing successfully fetched versions and failure count.
@@ -94,6 +113,7 @@ struct FetchResult {
 /// * `progress` - Optional progress tracker (will be updated after each fetch)
 /// * `timeout_secs` - Timeout for each individual package fetch (default: 10s)
 /// * `max_concurrent` - Maximum concurrent fetches (default: 20)
+/// * `include_prereleases` - Report the newest release even if it is a pre-release
 ///
 /// # Timeout Behavior
 ///
@@ -113,6 +133,7 @@ async fn fetch_latest_versions_parallel(
     progress_sender: Option<ProgressSender>,
     timeout_secs: u64,
     max_concurrent: usize,
+    include_prereleases: bool,
 ) -> FetchResult {
     use futures::stream::{self, StreamExt};
     use std::time::Duration;
@@ -130,8 +151,11 @@ async fn fetch_latest_versions_parallel(
             let first_error = Arc::clone(&first_error);
             let progress_sender = progress_sender.clone();
             async move {
-                let result =
-                    tokio::time::timeout(timeout, registry.get_latest_matching(&name, "*")).await;
+                let result = tokio::time::timeout(
+                    timeout,
+                    fetch_latest_version(registry.as_ref(), &name, include_prereleases),
+                )
+                .await;
 
                 let version = match result {
                     Ok(Ok(Some(v))) => {
@@ -177,6 +201,49 @@ async fn fetch_latest_versions_parallel(
     }
 }
 
+/// Fetches the release history of each package in parallel, for hint
+/// templates using `{compatible}`, `{age}` or `{count}`.
+///
+/// Packages that fail or time out are left out; their placeholders render
+/// empty.
+async fn fetch_release_history_parallel(
+    registry: Arc<dyn Registry>,
+    package_names: Vec<String>,
+    timeout_secs: u64,
+    max_concurrent: usize,
+) -> HashMap<String, Vec<ReleaseInfo>> {
+    use futures::stream::{self, StreamExt};
+
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    stream::iter(package_names)
+        .map(|name| {
+            let registry = Arc::clone(&registry);
+            async move {
+                match tokio::time::timeout(timeout, registry.get_versions(&name)).await {
+                    Ok(Ok(versions)) => {
+                        let releases = versions
+                            .iter()
+                            .map(|v| ReleaseInfo::from_version(v.as_ref()))
+                            .collect();
+                        Some((name, releases))
+                    }
+                    Ok(Err(e)) => {
+                        tracing::debug!(package = %name, error = %e, "release history fetch failed");
+                        None
+                    }
+                    Err(_) => {
+                        tracing::debug!(package = %name, "release history fetch timed out");
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(max_concurrent)
+        .filter_map(std::future::ready)
+        .collect()
+        .await
+}
+
 /// Generic document open handler using ecosystem registry.
 ///
 /// Parses manifest using the ecosystem's parser, creates document state,
@@ -188,8 +255,10 @@ pub async fn handle_document_open(
     client: Client,
     config: Arc<RwLock<DepsConfig>>,
 ) -> Result<JoinHandle<()>> {
+    let effective_config = state.config_for(&config, &uri).await;
+
     // Find appropriate ecosystem for this URI
-    let ecosystem = match state.ecosystem_registry.get_for_uri(&uri) {
+    let ecosystem = match state.enabled_ecosystem_for_uri(&uri, &effective_config) {
         Some(e) => e,
         None => {
             tracing::debug!("No ecosystem handler for {:?}", uri);
@@ -208,18 +277,30 @@ pub async fn handle_document_open(
     // Try to parse manifest (may fail for incomplete syntax)
     let parse_result = ecosystem.parse_manifest(&content, &uri).await.ok();
 
+    if let Some(pr) = &parse_result {
+        state.workspace_index.record(
+            &uri,
+            resolve_ecosystem_id(&*ecosystem),
+            &*ecosystem,
+            pr.as_ref(),
+        );
+    }
+
     // Create document state (parse_result may be None)
-    let doc_state = if let Some(pr) = parse_result {
+    let mut doc_state = if let Some(pr) = parse_result {
         DocumentState::new_from_parse_result(resolve_ecosystem_id(&*ecosystem), content, pr)
     } else {
         tracing::debug!("Failed to parse manifest, storing document without parse result");
         DocumentState::new_without_parse_result(resolve_ecosystem_id(&*ecosystem), content)
     };
+    doc_state.registry_url = effective_config.registries.get(ecosystem.id()).cloned();
 
     state.update_document(uri.clone(), doc_state);
 
     // Clone cache config before spawning background task
-    let cache_config = { config.read().await.cache.clone() };
+    let include_prereleases = effective_config.include_prereleases(ecosystem.id());
+    let with_releases = effective_config.inlay_hints.uses_release_history();
+    let (cache_config, diagnostics_config) = (effective_config.cache, effective_config.diagnostics);
 
     // Spawn background task to fetch versions
     let uri_clone = uri.clone();
@@ -244,7 +325,7 @@ pub async fn handle_document_open(
         }
 
         // Collect dependency names while holding reference (can't hold across await)
-        let dep_names: Vec<String> = {
+        let (dep_names, registry): (Vec<String>, _) = {
             let doc = match state_clone.get_document(&uri_clone) {
                 Some(d) => d,
                 None => {
@@ -259,11 +340,12 @@ pub async fn handle_document_open(
                     return;
                 }
             };
-            parse_result
+            let names = parse_result
                 .dependencies()
                 .into_iter()
                 .map(|d| d.name().to_string())
-                .collect()
+                .collect();
+            (names, ecosystem_clone.registry_for(parse_result))
         };
 
         tracing::debug!(count = dep_names.len(), "starting registry fetch");
@@ -286,13 +368,19 @@ pub async fn handle_document_open(
         tracing::debug!("progress started, fetching versions");
 
         // Fetch latest versions from registry in parallel (for update hints)
-        let registry = ecosystem_clone.registry();
-        let fetch_result = fetch_latest_versions_parallel(
+        let registry = MeteredRegistry::wrap(
             registry,
+            ecosystem_clone.id(),
+            Arc::clone(&state_clone.fetch_metrics),
+        );
+        let history_names = with_releases.then(|| dep_names.clone());
+        let fetch_result = fetch_latest_versions_parallel(
+            Arc::clone(&registry),
             dep_names,
             progress_sender,
             cache_config.fetch_timeout_secs,
             cache_config.max_concurrent_fetches,
+            include_prereleases,
         )
         .await;
 
@@ -313,6 +401,19 @@ pub async fn handle_document_open(
             }
         }
 
+        if let Some(names) = history_names {
+            let history = fetch_release_history_parallel(
+                registry,
+                names,
+                cache_config.fetch_timeout_secs,
+                cache_config.max_concurrent_fetches,
+            )
+            .await;
+            if let Some(mut doc) = state_clone.documents.get_mut(&uri_clone) {
+                doc.update_release_history(history);
+            }
+        }
+
         // End progress
         if let Some(progress) = progress {
             progress.end(success).await;
@@ -340,12 +441,19 @@ pub async fn handle_document_open(
         }
 
         // Publish diagnostics (may be slower, runs after hints are already visible)
-        let diags =
-            diagnostics::generate_diagnostics_internal(Arc::clone(&state_clone), &uri_clone).await;
+        let diags = diagnostics::generate_diagnostics_internal(
+            Arc::clone(&state_clone),
+            &uri_clone,
+            &diagnostics_config,
+            &cache_config,
+        )
+        .await;
 
         client_clone
             .publish_diagnostics(uri_clone.clone(), diags, None)
             .await;
+
+        status::notify_status(&state_clone, &client_clone).await;
     });
 
     Ok(task)
@@ -362,8 +470,10 @@ pub async fn handle_document_change(
     client: Client,
     config: Arc<RwLock<DepsConfig>>,
 ) -> Result<JoinHandle<()>> {
+    let effective_config = state.config_for(&config, &uri).await;
+
     // Find appropriate ecosystem for this URI
-    let ecosystem = match state.ecosystem_registry.get_for_uri(&uri) {
+    let ecosystem = match state.enabled_ecosystem_for_uri(&uri, &effective_config) {
         Some(e) => e,
         None => {
             tracing::debug!("No ecosystem handler for {:?}", uri);
@@ -389,6 +499,16 @@ pub async fn handle_document_change(
     // Try to parse manifest (may fail for incomplete syntax)
     let parse_result = ecosystem.parse_manifest(&content, &uri).await.ok();
 
+    // Keep the last good index entries while the manifest is mid-edit
+    if let Some(pr) = &parse_result {
+        state.workspace_index.record(
+            &uri,
+            resolve_ecosystem_id(&*ecosystem),
+            &*ecosystem,
+            pr.as_ref(),
+        );
+    }
+
     // Extract new dependency names for diff
     let new_dep_names: HashSet<String> = parse_result
         .as_ref()
@@ -400,6 +520,11 @@ pub async fn handle_document_change(
         })
         .unwrap_or_default();
 
+    let registry = parse_result.as_ref().map_or_else(
+        || ecosystem.registry(),
+        |pr| ecosystem.registry_for(pr.as_ref()),
+    );
+
     // Compute dependency diff
     let diff = DependencyDiff::compute(&old_dep_names, &new_dep_names);
     tracing::debug!(
@@ -414,6 +539,7 @@ pub async fn handle_document_change(
         tracing::debug!("Failed to parse manifest, storing document without parse result");
         DocumentState::new_without_parse_result(resolve_ecosystem_id(&*ecosystem), content)
     };
+    doc_state.registry_url = effective_config.registries.get(ecosystem.id()).cloned();
 
     if let Some(old_doc) = state.get_document(&uri) {
         preserve_cache(&mut doc_state, &old_doc);
@@ -428,7 +554,9 @@ pub async fn handle_document_change(
     state.update_document(uri.clone(), doc_state);
 
     // Clone cache config before spawning background task
-    let cache_config = { config.read().await.cache.clone() };
+    let include_prereleases = effective_config.include_prereleases(ecosystem.id());
+    let with_releases = effective_config.inlay_hints.uses_release_history();
+    let (cache_config, diagnostics_config) = (effective_config.cache, effective_config.diagnostics);
 
     // Spawn background task to update diagnostics
     let uri_clone = uri.clone();
@@ -465,9 +593,13 @@ pub async fn handle_document_change(
                 tracing::debug!("inlay_hint_refresh not supported: {:?}", e);
             }
 
-            let diags =
-                diagnostics::generate_diagnostics_internal(Arc::clone(&state_clone), &uri_clone)
-                    .await;
+            let diags = diagnostics::generate_diagnostics_internal(
+                Arc::clone(&state_clone),
+                &uri_clone,
+                &diagnostics_config,
+                &cache_config,
+            )
+            .await;
             client_clone
                 .publish_diagnostics(uri_clone.clone(), diags, None)
                 .await;
@@ -499,13 +631,19 @@ pub async fn handle_document_change(
         };
 
         // Fetch latest versions only for NEW dependencies
-        let registry = ecosystem_clone.registry();
-        let fetch_result = fetch_latest_versions_parallel(
+        let registry = MeteredRegistry::wrap(
             registry,
+            ecosystem_clone.id(),
+            Arc::clone(&state_clone.fetch_metrics),
+        );
+        let history_names = with_releases.then(|| deps_to_fetch.clone());
+        let fetch_result = fetch_latest_versions_parallel(
+            Arc::clone(&registry),
             deps_to_fetch,
             progress_sender,
             cache_config.fetch_timeout_secs,
             cache_config.max_concurrent_fetches,
+            include_prereleases,
         )
         .await;
 
@@ -523,6 +661,19 @@ pub async fn handle_document_change(
             }
         }
 
+        if let Some(names) = history_names {
+            let history = fetch_release_history_parallel(
+                registry,
+                names,
+                cache_config.fetch_timeout_secs,
+                cache_config.max_concurrent_fetches,
+            )
+            .await;
+            if let Some(mut doc) = state_clone.documents.get_mut(&uri_clone) {
+                doc.release_history.extend(history);
+            }
+        }
+
         if let Some(progress) = progress {
             progress.end(success).await;
         }
@@ -546,12 +697,19 @@ pub async fn handle_document_change(
             tracing::debug!("inlay_hint_refresh not supported: {:?}", e);
         }
 
-        let diags =
-            diagnostics::generate_diagnostics_internal(Arc::clone(&state_clone), &uri_clone).await;
+        let diags = diagnostics::generate_diagnostics_internal(
+            Arc::clone(&state_clone),
+            &uri_clone,
+            &diagnostics_config,
+            &cache_config,
+        )
+        .await;
 
         client_clone
             .publish_diagnostics(uri_clone.clone(), diags, None)
             .await;
+
+        status::notify_status(&state_clone, &client_clone).await;
     });
 
     Ok(task)
@@ -664,8 +822,8 @@ pub async fn ensure_document_loaded(
         return true;
     }
 
-    // Clone cold start config before async operations to release lock
-    let cold_start_config = { config.read().await.cold_start.clone() };
+    let effective_config = state.config_for(&config, uri).await;
+    let cold_start_config = effective_config.cold_start.clone();
 
     // Check if cold start is enabled
     if !cold_start_config.enabled {
@@ -680,7 +838,10 @@ pub async fn ensure_document_loaded(
     }
 
     // Check if we support this file type
-    if state.ecosystem_registry.get_for_uri(uri).is_none() {
+    if state
+        .enabled_ecosystem_for_uri(uri, &effective_config)
+        .is_none()
+    {
         tracing::debug!("Unsupported file type: {:?}", uri);
         return false;
     }
@@ -819,13 +980,121 @@ mod tests {
         let packages = vec!["slow-package".to_string()];
 
         // Use 1 second timeout for test speed
-        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10).await;
+        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10, false).await;
 
         // Should return empty (timeout, not success)
         assert!(result.versions.is_empty(), "Slow package should timeout");
         assert_eq!(result.failed_count, 1, "Should track 1 failed package");
     }
 
+    #[tokio::test]
+    async fn test_fetch_latest_versions_parallel_include_prereleases() {
+        use deps_core::{Metadata, Registry, Version};
+        use std::any::Any;
+
+        struct MockVersion {
+            version: String,
+            prerelease: bool,
+        }
+
+        impl Version for MockVersion {
+            fn version_string(&self) -> &str {
+                &self.version
+            }
+
+            fn is_prerelease(&self) -> bool {
+                self.prerelease
+            }
+
+            fn is_yanked(&self) -> bool {
+                false
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        }
+
+        // Newest version is a pre-release; newest stable is 1.0.0
+        struct PrereleaseRegistry;
+
+        impl Registry for PrereleaseRegistry {
+            fn get_versions<'a>(
+                &'a self,
+                _name: &'a str,
+            ) -> deps_core::ecosystem::BoxFuture<'a, deps_core::Result<Vec<Box<dyn Version>>>>
+            {
+                Box::pin(async move {
+                    Ok(vec![
+                        Box::new(MockVersion {
+                            version: "2.0.0-beta.1".to_string(),
+                            prerelease: true,
+                        }) as Box<dyn Version>,
+                        Box::new(MockVersion {
+                            version: "1.0.0".to_string(),
+                            prerelease: false,
+                        }),
+                    ])
+                })
+            }
+
+            fn get_latest_matching<'a>(
+                &'a self,
+                _name: &'a str,
+                _req: &'a str,
+            ) -> deps_core::ecosystem::BoxFuture<'a, deps_core::Result<Option<Box<dyn Version>>>>
+            {
+                Box::pin(async move {
+                    Ok(Some(Box::new(MockVersion {
+                        version: "1.0.0".to_string(),
+                        prerelease: false,
+                    }) as Box<dyn Version>))
+                })
+            }
+
+            fn search<'a>(
+                &'a self,
+                _query: &'a str,
+                _limit: usize,
+            ) -> deps_core::ecosystem::BoxFuture<'a, deps_core::Result<Vec<Box<dyn Metadata>>>>
+            {
+                Box::pin(async move { Ok(vec![]) })
+            }
+
+            fn package_url(&self, name: &str) -> String {
+                format!("https://example.com/{}", name)
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        }
+
+        let registry: Arc<dyn Registry> = Arc::new(PrereleaseRegistry);
+        let packages = vec!["pkg".to_string()];
+
+        let stable = fetch_latest_versions_parallel(
+            Arc::clone(&registry),
+            packages.clone(),
+            None,
+            1,
+            10,
+            false,
+        )
+        .await;
+        assert_eq!(
+            stable.versions.get("pkg").map(String::as_str),
+            Some("1.0.0")
+        );
+
+        let prerelease =
+            fetch_latest_versions_parallel(registry, packages, None, 1, 10, true).await;
+        assert_eq!(
+            prerelease.versions.get("pkg").map(String::as_str),
+            Some("2.0.0-beta.1")
+        );
+    }
+
     #[tokio::test]
     async fn test_fetch_latest_versions_parallel_fast_packages_not_blocked() {
         use deps_core::{Metadata, Registry, Version};
@@ -889,7 +1158,7 @@ mod tests {
         let packages = vec!["slow-package".to_string(), "fast-package".to_string()];
 
         let start = std::time::Instant::now();
-        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10).await;
+        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10, false).await;
         let elapsed = start.elapsed();
 
         // Should complete in ~1s (timeout), not 10s (slow package duration)
@@ -998,7 +1267,7 @@ mod tests {
         // Create 50 packages, limit concurrency to 20
         let packages: Vec<String> = (0..50).map(|i| format!("package-{}", i)).collect();
 
-        fetch_latest_versions_parallel(registry, packages, None, 5, 20).await;
+        fetch_latest_versions_parallel(registry, packages, None, 5, 20, false).await;
 
         // Max concurrent should not exceed limit (allow small margin for timing)
         let max = max_seen.load(Ordering::SeqCst);
@@ -1124,7 +1393,7 @@ mod tests {
         ];
 
         // Use 1 second timeout for test speed
-        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10).await;
+        let result = fetch_latest_versions_parallel(registry, packages, None, 1, 10, false).await;
 
         // Only the fast package should be in results
         assert_eq!(
@@ -1209,7 +1478,7 @@ mod tests {
         ];
 
         // Should not panic, just return empty result
-        let result = fetch_latest_versions_parallel(registry, packages, None, 5, 10).await;
+        let result = fetch_latest_versions_parallel(registry, packages, None, 5, 10, false).await;
 
         // All packages failed, result should be empty
         assert!(
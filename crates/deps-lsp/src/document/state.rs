@@ -1,7 +1,14 @@
+use crate::config::DepsConfig;
+use crate::metrics::FetchMetrics;
+use crate::project_config::ProjectConfigs;
+use crate::workspace_index::WorkspaceIndex;
 use dashmap::DashMap;
 use deps_core::HttpCache;
+use deps_core::dependency_tree::DuplicatePackage;
+use deps_core::hint_template::ReleaseInfo;
 use deps_core::lockfile::LockFileCache;
-use deps_core::{EcosystemId, EcosystemRegistry, ParseResult};
+use deps_core::workspace::WorkspaceCache;
+use deps_core::{Ecosystem, EcosystemId, EcosystemRegistry, ParseResult};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -49,12 +56,17 @@ pub struct DocumentState {
     pub cached_versions: HashMap<String, String>,
     /// Resolved versions from lock file
     pub resolved_versions: HashMap<String, String>,
+    /// Release histories for hint templates (only fetched when a template needs them)
+    pub release_history: HashMap<String, Vec<ReleaseInfo>>,
     /// Last successful parse time
     pub parsed_at: Instant,
     /// Current loading state for registry data
     pub loading_state: LoadingState,
     /// When the current loading operation started (for timeout/metrics)
     pub loading_started_at: Option<Instant>,
+    /// Registry configured for this document's ecosystem (`registries`),
+    /// replacing the public one
+    pub registry_url: Option<String>,
 }
 
 impl Clone for DocumentState {
@@ -66,10 +78,12 @@ impl Clone for DocumentState {
             parse_result: None, // Don't clone trait object
             cached_versions: self.cached_versions.clone(),
             resolved_versions: self.resolved_versions.clone(),
+            release_history: self.release_history.clone(),
             parsed_at: self.parsed_at,
             loading_state: self.loading_state,
             // Note: Instant is Copy. Clones share the same loading start time.
             loading_started_at: self.loading_started_at,
+            registry_url: self.registry_url.clone(),
         }
     }
 }
@@ -162,9 +176,11 @@ impl std::fmt::Debug for DocumentState {
             .field("has_parse_result", &self.parse_result.is_some())
             .field("cached_versions_count", &self.cached_versions.len())
             .field("resolved_versions_count", &self.resolved_versions.len())
+            .field("release_history_count", &self.release_history.len())
             .field("parsed_at", &self.parsed_at)
             .field("loading_state", &self.loading_state)
             .field("loading_started_at", &self.loading_started_at)
+            .field("registry_url", &self.registry_url)
             .finish()
     }
 }
@@ -187,9 +203,11 @@ impl DocumentState {
             parse_result: Some(parse_result),
             cached_versions: HashMap::new(),
             resolved_versions: HashMap::new(),
+            release_history: HashMap::new(),
             parsed_at: Instant::now(),
             loading_state: LoadingState::Idle,
             loading_started_at: None,
+            registry_url: None,
         }
     }
 
@@ -207,9 +225,11 @@ impl DocumentState {
             parse_result: None,
             cached_versions: HashMap::new(),
             resolved_versions: HashMap::new(),
+            release_history: HashMap::new(),
             parsed_at: Instant::now(),
             loading_state: LoadingState::Idle,
             loading_started_at: None,
+            registry_url: None,
         }
     }
 
@@ -223,6 +243,11 @@ impl DocumentState {
         self.cached_versions = versions;
     }
 
+    /// Updates the release histories used by hint templates.
+    pub fn update_release_history(&mut self, history: HashMap<String, Vec<ReleaseInfo>>) {
+        self.release_history = history;
+    }
+
     /// Updates the resolved versions from lock file.
     pub fn update_resolved_versions(&mut self, versions: HashMap<String, String>) {
         self.resolved_versions = versions;
@@ -340,6 +365,19 @@ pub struct ServerState {
     pub ecosystem_registry: Arc<EcosystemRegistry>,
     /// Cold start rate limiter
     pub cold_start_limiter: ColdStartLimiter,
+    /// Workspace-wide index of dependency declarations
+    pub workspace_index: Arc<WorkspaceIndex>,
+    /// `.deps-lsp.toml` / `[tool.deps-lsp]` files by manifest directory
+    pub project_configs: ProjectConfigs,
+    /// Registry fetch counts and latencies by ecosystem
+    pub fetch_metrics: Arc<FetchMetrics>,
+    /// Workspaces loaded by the ecosystems, by root directory
+    pub workspaces: Arc<WorkspaceCache>,
+    /// Ecosystems fetching from a configured registry, by ecosystem ID and
+    /// registry URL
+    relocated_ecosystems: DashMap<(&'static str, String), Arc<dyn Ecosystem>>,
+    /// Duplicated packages of each lock file, by lock file URI
+    pub duplicates: DashMap<Uri, Arc<Vec<DuplicatePackage>>>,
     /// Background task handles
     tasks: tokio::sync::RwLock<HashMap<Uri, JoinHandle<()>>>,
 }
@@ -347,12 +385,24 @@ pub struct ServerState {
 impl ServerState {
     /// Creates a new server state with default configuration.
     pub fn new() -> Self {
-        let cache = Arc::new(HttpCache::new());
+        Self::with_cache(Arc::new(HttpCache::new()))
+    }
+
+    /// Creates a server state whose registries share an existing HTTP cache.
+    ///
+    /// Used when one process serves several clients, so that every session
+    /// benefits from responses already fetched by the others.
+    pub fn with_cache(cache: Arc<HttpCache>) -> Self {
         let lockfile_cache = Arc::new(LockFileCache::new());
         let ecosystem_registry = Arc::new(EcosystemRegistry::new());
+        let workspaces = Arc::new(WorkspaceCache::new());
 
         // Register ecosystems based on enabled features
-        crate::register_ecosystems(&ecosystem_registry, Arc::clone(&cache));
+        crate::register_ecosystems_with_workspaces(
+            &ecosystem_registry,
+            Arc::clone(&cache),
+            &workspaces,
+        );
 
         // Create cold start limiter with default 100ms interval (10 req/sec per URI)
         let cold_start_limiter = ColdStartLimiter::new(Duration::from_millis(100));
@@ -363,10 +413,80 @@ impl ServerState {
             lockfile_cache,
             ecosystem_registry,
             cold_start_limiter,
+            workspace_index: Arc::new(WorkspaceIndex::new()),
+            project_configs: ProjectConfigs::new(),
+            fetch_metrics: Arc::new(FetchMetrics::new()),
+            workspaces,
+            relocated_ecosystems: DashMap::new(),
+            duplicates: DashMap::new(),
             tasks: tokio::sync::RwLock::new(HashMap::new()),
         }
     }
 
+    /// Returns the settings in effect for `uri`: the client's `config` with
+    /// the project configuration of the manifest's directory and the
+    /// overrides of the manifest's ecosystem applied.
+    pub async fn config_for(
+        &self,
+        config: &tokio::sync::RwLock<DepsConfig>,
+        uri: &Uri,
+    ) -> DepsConfig {
+        let resolved = {
+            let base = config.read().await;
+            self.project_configs.resolve(&base, uri)
+        };
+        match self.ecosystem_registry.get_for_uri(uri) {
+            Some(ecosystem) => resolved.for_ecosystem(ecosystem.id()),
+            None => resolved,
+        }
+    }
+
+    /// Returns the ecosystem handling `uri`, unless `config` disables it,
+    /// fetching from the registry `config` sets for it.
+    pub fn enabled_ecosystem_for_uri(
+        &self,
+        uri: &Uri,
+        config: &DepsConfig,
+    ) -> Option<Arc<dyn Ecosystem>> {
+        let ecosystem = self
+            .ecosystem_registry
+            .get_for_uri(uri)
+            .filter(|ecosystem| config.is_ecosystem_enabled(ecosystem.id()))?;
+        let url = config.registries.get(ecosystem.id()).map(String::as_str);
+        Some(self.relocate(ecosystem, url))
+    }
+
+    /// Returns the ecosystem handling `doc`, fetching from the registry
+    /// configured when it was parsed.
+    pub fn ecosystem_for(&self, doc: &DocumentState) -> Option<Arc<dyn Ecosystem>> {
+        let ecosystem = self.ecosystem_registry.get(doc.ecosystem_id)?;
+        Some(self.relocate(ecosystem, doc.registry_url.as_deref()))
+    }
+
+    /// Returns `ecosystem` fetching from `url`, reusing the copy made for
+    /// an earlier document so its registry client keeps its state.
+    fn relocate(&self, ecosystem: Arc<dyn Ecosystem>, url: Option<&str>) -> Arc<dyn Ecosystem> {
+        let Some(url) = url else {
+            return ecosystem;
+        };
+        let key = (ecosystem.id(), url.to_string());
+        if let Some(relocated) = self.relocated_ecosystems.get(&key) {
+            return Arc::clone(&relocated);
+        }
+        match ecosystem.with_registry_url(url) {
+            Some(relocated) => {
+                Arc::clone(&self.relocated_ecosystems.entry(key).or_insert(relocated))
+            }
+            None => {
+                tracing::warn!(
+                    ecosystem = ecosystem.id(),
+                    "registry URL is not configurable, ignoring it"
+                );
+                ecosystem
+            }
+        }
+    }
+
     /// Retrieves document state by URI.
     ///
     /// Returns a read-only reference to the document state if it exists.
@@ -446,6 +566,18 @@ impl ServerState {
         tasks.insert(uri, task);
     }
 
+    /// Aborts the background tasks of every document.
+    ///
+    /// Called when the session ends. If the task map is locked at that
+    /// moment, the tasks are left to finish on their own.
+    pub fn abort_background_tasks(&self) {
+        if let Ok(mut tasks) = self.tasks.try_write() {
+            for (_, task) in tasks.drain() {
+                task.abort();
+            }
+        }
+    }
+
     /// Cancels the background task for a document.
     ///
     /// If no task exists, this is a no-op.
@@ -456,6 +588,12 @@ impl ServerState {
         }
     }
 
+    /// Returns the number of background tasks still running.
+    pub async fn active_task_count(&self) -> usize {
+        let tasks = self.tasks.read().await;
+        tasks.values().filter(|task| !task.is_finished()).count()
+    }
+
     /// Returns the number of open documents.
     pub fn document_count(&self) -> usize {
         self.documents.len()
@@ -734,7 +872,9 @@ mod tests {
         });
 
         state.spawn_background_task(uri.clone(), task).await;
+        assert_eq!(state.active_task_count().await, 1);
         state.cancel_background_task(&uri).await;
+        assert_eq!(state.active_task_count().await, 0);
     }
 
     #[tokio::test]
@@ -956,6 +1096,61 @@ mod tests {
     mod cargo_tests {
         use super::*;
 
+        #[tokio::test]
+        async fn test_project_config_re_enables_ecosystem() {
+            let dir = tempfile::tempdir().unwrap();
+            let manifest = Uri::from_file_path(dir.path().join("Cargo.toml")).unwrap();
+            let state = ServerState::new();
+            let config = tokio::sync::RwLock::new(
+                serde_json::from_str::<DepsConfig>(
+                    r#"{"ecosystems": {"cargo": {"enabled": false}}}"#,
+                )
+                .unwrap(),
+            );
+
+            let effective = state.config_for(&config, &manifest).await;
+            assert!(
+                state
+                    .enabled_ecosystem_for_uri(&manifest, &effective)
+                    .is_none()
+            );
+
+            std::fs::write(
+                dir.path().join(crate::project_config::PROJECT_CONFIG_FILE),
+                "[ecosystems.cargo]\nenabled = true\n",
+            )
+            .unwrap();
+            state.project_configs.invalidate();
+            let effective = state.config_for(&config, &manifest).await;
+            assert!(
+                state
+                    .enabled_ecosystem_for_uri(&manifest, &effective)
+                    .is_some()
+            );
+        }
+
+        #[test]
+        fn test_registries_relocate_ecosystem() {
+            let state = ServerState::new();
+            let manifest = Uri::from_file_path("/project/Cargo.toml").unwrap();
+            let config: DepsConfig = serde_json::from_str(
+                r#"{"registries": {"cargo": "https://mirror.example.com/index/"}}"#,
+            )
+            .unwrap();
+            let default = state.ecosystem_registry.get("cargo").unwrap();
+
+            let relocated = state.enabled_ecosystem_for_uri(&manifest, &config).unwrap();
+            assert!(!Arc::ptr_eq(&relocated, &default));
+            let again = state.enabled_ecosystem_for_uri(&manifest, &config).unwrap();
+            assert!(Arc::ptr_eq(&relocated, &again));
+
+            let mut doc =
+                DocumentState::new_without_parse_result(EcosystemId::Cargo, String::new());
+            assert!(Arc::ptr_eq(&state.ecosystem_for(&doc).unwrap(), &default));
+            doc.registry_url = config.registries.get("cargo").cloned();
+            assert!(Arc::ptr_eq(&state.ecosystem_for(&doc).unwrap(), &relocated));
+        }
+
         #[test]
         fn test_document_state_creation() {
             let state =
@@ -1,7 +1,8 @@
-//! Lock file watching infrastructure.
+//! Lock file, manifest and project configuration watching infrastructure.
 //!
-//! Provides file system watcher registration for lock files.
-//! Lock file patterns are provided by individual ecosystem implementations.
+//! Provides file system watcher registration for lock files, manifests and
+//! `.deps-lsp.toml` files. Lock file and manifest patterns are provided by
+//! individual ecosystem implementations.
 
 use std::path::Path;
 use tower_lsp_server::Client;
@@ -27,9 +28,57 @@ use tower_lsp_server::ls_types::{
 pub async fn register_lock_file_watchers(
     client: &Client,
     patterns: &[String],
+) -> Result<(), String> {
+    register_watchers(client, "deps-lsp-lockfile-watcher", patterns, "lock file").await
+}
+
+/// Registers file system watchers for manifests from all registered ecosystems.
+///
+/// Keeps the workspace index current for manifests that are not open in the
+/// editor. Patterns come from `EcosystemRegistry::all_manifest_patterns()`.
+///
+/// # Errors
+///
+/// Returns an error if the client doesn't support dynamic registration
+/// or if the registration request fails.
+pub async fn register_manifest_watchers(
+    client: &Client,
+    patterns: &[String],
+) -> Result<(), String> {
+    register_watchers(client, "deps-lsp-manifest-watcher", patterns, "manifest").await
+}
+
+/// Registers file system watchers for project configuration files.
+///
+/// Changes to `.deps-lsp.toml` or `pyproject.toml` re-resolve the settings
+/// of open manifests (see [`crate::project_config`]).
+///
+/// # Errors
+///
+/// Returns an error if the client doesn't support dynamic registration
+/// or if the registration request fails.
+pub async fn register_project_config_watchers(client: &Client) -> Result<(), String> {
+    let patterns = [
+        format!("**/{}", crate::project_config::PROJECT_CONFIG_FILE),
+        "**/pyproject.toml".to_string(),
+    ];
+    register_watchers(
+        client,
+        "deps-lsp-config-watcher",
+        &patterns,
+        "project config",
+    )
+    .await
+}
+
+async fn register_watchers(
+    client: &Client,
+    id: &str,
+    patterns: &[String],
+    label: &str,
 ) -> Result<(), String> {
     if patterns.is_empty() {
-        tracing::debug!("No lock file patterns to watch");
+        tracing::debug!("No {} patterns to watch", label);
         return Ok(());
     }
 
@@ -44,7 +93,7 @@ pub async fn register_lock_file_watchers(
     let options = DidChangeWatchedFilesRegistrationOptions { watchers };
 
     let registration = Registration {
-        id: "deps-lsp-lockfile-watcher".to_string(),
+        id: id.to_string(),
         method: "workspace/didChangeWatchedFiles".to_string(),
         register_options: Some(serde_json::to_value(options).map_err(|e| e.to_string())?),
     };
@@ -54,7 +103,7 @@ pub async fn register_lock_file_watchers(
         .await
         .map_err(|e| format!("Failed to register file watchers: {e}"))?;
 
-    tracing::info!("Registered {} lock file watchers", patterns.len());
+    tracing::info!("Registered {} {} watchers", patterns.len(), label);
     Ok(())
 }
 
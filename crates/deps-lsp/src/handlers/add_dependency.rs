@@ -0,0 +1,264 @@
+//! The `deps.addDependency` command.
+//!
+//! Resolves the version to add (the latest stable release unless one is
+//! given) and asks the ecosystem for the edit inserting the new entry. How
+//! entries are placed and formatted is described in
+//! [`deps_core::add_dependency`].
+
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::add_dependency::{DependencyKind, NewDependency};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{Uri, WorkspaceEdit};
+
+/// Arguments of `deps.addDependency`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddDependencyArgs {
+    /// Manifest to add the dependency to.
+    pub uri: Uri,
+    /// Package name as the ecosystem spells it (`group:artifact` for Maven
+    /// and Gradle, `owner/repo` for Swift).
+    pub name: String,
+    /// Version or requirement; defaults to the latest stable version.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Section such as `dev`, `build`, `test` or an ecosystem-specific
+    /// group; defaults to regular dependencies.
+    #[serde(default)]
+    pub section: Option<String>,
+}
+
+/// Builds the edit adding a dependency as described by `args`.
+///
+/// Returns a message suitable for the user when the dependency cannot be
+/// added.
+pub async fn handle_add_dependency(
+    state: Arc<ServerState>,
+    args: AddDependencyArgs,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Result<WorkspaceEdit, String> {
+    let uri = &args.uri;
+    let name = args.name.trim();
+    if name.is_empty() {
+        return Err("Package name is empty".into());
+    }
+
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for add dependency: {:?}", uri);
+        return Err(format!("Could not load {}", uri.as_str()));
+    }
+
+    let kind = DependencyKind::parse(args.section.as_deref());
+    let (ecosystem, ecosystem_id) = {
+        let doc = state
+            .get_document(uri)
+            .ok_or_else(|| format!("Could not load {}", uri.as_str()))?;
+        let ecosystem = state
+            .ecosystem_registry
+            .get(doc.ecosystem_id)
+            .ok_or_else(|| format!("No ecosystem handles {}", uri.as_str()))?;
+        let parse_result = doc
+            .parse_result()
+            .ok_or_else(|| format!("Could not parse {}", uri.as_str()))?;
+        if ecosystem.has_dependency(parse_result, &doc.content, name, &kind) {
+            return Err(
+                match args
+                    .section
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                {
+                    Some(section) => format!("{name} is already in \"{section}\""),
+                    None => format!("{name} is already a dependency"),
+                },
+            );
+        }
+        (ecosystem, doc.ecosystem_id)
+    };
+
+    let version = match args.version.as_deref().map(str::trim) {
+        Some(version) if !version.is_empty() => version.to_string(),
+        _ => {
+            let versions = ecosystem
+                .registry()
+                .get_versions(name)
+                .await
+                .map_err(|e| format!("Could not fetch versions of {name}: {e}"))?;
+            deps_core::find_latest_stable(&versions)
+                .map(|v| v.version_string().to_string())
+                .ok_or_else(|| format!("No stable version of {name} found"))?
+        }
+    };
+
+    let dependency = NewDependency {
+        name: name.to_string(),
+        version,
+        kind,
+    };
+
+    // The document may have changed while the registry was queried
+    let doc = state
+        .get_document(uri)
+        .filter(|doc| doc.ecosystem_id == ecosystem_id)
+        .ok_or_else(|| format!("Could not load {}", uri.as_str()))?;
+    let parse_result = doc
+        .parse_result()
+        .ok_or_else(|| format!("Could not parse {}", uri.as_str()))?;
+    ecosystem
+        .add_dependency(parse_result, &doc.content, &dependency)
+        .await
+        .ok_or_else(|| {
+            let section = args.section.as_deref().unwrap_or("dependencies");
+            format!(
+                "Cannot add {name} to \"{section}\" in a {} manifest",
+                ecosystem.display_name()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+
+    #[test]
+    fn test_args_deserialization() {
+        let json = serde_json::json!({
+            "uri": "file:///test/Cargo.toml",
+            "name": "serde",
+            "section": "dev"
+        });
+
+        let args: AddDependencyArgs = serde_json::from_value(json).unwrap();
+        assert_eq!(args.name, "serde");
+        assert_eq!(args.version, None);
+        assert_eq!(args.section.as_deref(), Some("dev"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_add_dependency_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let (client, config) = create_test_client_and_config();
+        let args = AddDependencyArgs {
+            uri: deps_core::test_util::test_uri("/nonexistent/Cargo.toml"),
+            name: "serde".into(),
+            version: Some("1.0.0".into()),
+            section: None,
+        };
+
+        assert!(
+            handle_add_dependency(state, args, client, config)
+                .await
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        const MANIFEST: &str =
+            "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\ntokio = \"1\"\n";
+
+        async fn setup() -> (Arc<ServerState>, Uri) {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(MANIFEST, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    MANIFEST.to_string(),
+                    parse_result,
+                ),
+            );
+            (state, uri)
+        }
+
+        #[tokio::test]
+        async fn test_handle_add_dependency_with_version() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+            let args = AddDependencyArgs {
+                uri: uri.clone(),
+                name: "serde".into(),
+                version: Some("1.0.210".into()),
+                section: None,
+            };
+
+            let edit = handle_add_dependency(state, args, client, config)
+                .await
+                .unwrap();
+            let edits = &edit.changes.unwrap()[&uri];
+            assert_eq!(
+                deps_core::test_util::apply_edits(MANIFEST, edits),
+                "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\nserde = \"1.0.210\"\ntokio = \"1\"\n"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_handle_add_dependency_already_present() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+            let args = AddDependencyArgs {
+                uri,
+                name: "tokio".into(),
+                version: Some("1.40.0".into()),
+                section: None,
+            };
+
+            let error = handle_add_dependency(state, args, client, config)
+                .await
+                .unwrap_err();
+            assert_eq!(error, "tokio is already a dependency");
+        }
+
+        #[tokio::test]
+        async fn test_handle_add_dependency_present_in_other_section() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+            let args = AddDependencyArgs {
+                uri: uri.clone(),
+                name: "tokio".into(),
+                version: Some("1.40.0".into()),
+                section: Some("dev".into()),
+            };
+
+            let edit = handle_add_dependency(state, args, client, config)
+                .await
+                .unwrap();
+            let edits = &edit.changes.unwrap()[&uri];
+            assert_eq!(
+                deps_core::test_util::apply_edits(MANIFEST, edits),
+                "[package]\nname = \"app\"\n\n[dependencies]\nanyhow = \"1\"\ntokio = \"1\"\n\n[dev-dependencies]\ntokio = \"1.40.0\"\n"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_handle_add_dependency_unsupported_section() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+            let args = AddDependencyArgs {
+                uri,
+                name: "serde".into(),
+                version: Some("1.0.210".into()),
+                section: Some("peer".into()),
+            };
+
+            assert!(
+                handle_add_dependency(state, args, client, config)
+                    .await
+                    .is_err()
+            );
+        }
+    }
+}
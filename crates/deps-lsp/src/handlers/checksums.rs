@@ -0,0 +1,283 @@
+//! Lock file checksums verified against the registry.
+//!
+//! Cargo.lock records the checksum of every registry package and the sparse
+//! index publishes the `cksum` of every release. A direct dependency locked
+//! with another checksum than its registry publishes points at a tampered
+//! lock file, or at a registry mirror serving other content than the one the
+//! lock file was created from.
+//!
+//! The check fetches the versions of every direct dependency, so it is
+//! opt-in: `diagnostics.verify_checksums` in the editor and
+//! `deps-lsp check --verify-checksums` headless.
+
+use super::dependency_tree::{lock_name, locked_manifest};
+use crate::config::CacheConfig;
+use crate::document::ServerState;
+use deps_core::lockfile::{self, ChecksumMismatch, ResolvedPackages};
+use deps_core::lsp_helpers::EcosystemFormatter;
+use deps_core::{Dependency, Version};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Uri};
+
+/// Diagnostic code of checksum mismatch diagnostics.
+pub const CHECKSUM_MISMATCH_CODE: &str = "checksum-mismatch";
+
+/// Length checksums are shortened to in messages.
+const SHORT_CHECKSUM_LEN: usize = 12;
+
+/// Compares every locked copy of the registry dependencies in
+/// `dependencies` with the checksums `fetched` from their registry.
+pub(crate) fn checksum_mismatches<'a>(
+    dependencies: &[&'a dyn Dependency],
+    packages: &ResolvedPackages,
+    formatter: &dyn EcosystemFormatter,
+    fetched: &HashMap<&str, Vec<Box<dyn Version>>>,
+) -> Vec<(&'a dyn Dependency, ChecksumMismatch)> {
+    dependencies
+        .iter()
+        .filter(|dep| dep.source().is_registry())
+        .flat_map(|dep| {
+            let locked = lock_name(packages, formatter, dep.name())
+                .and_then(|name| packages.get_all(&name))
+                .unwrap_or_default();
+            let versions = fetched.get(dep.name()).map_or(&[][..], Vec::as_slice);
+            locked
+                .iter()
+                .filter_map(|package| lockfile::verify_checksum(package, versions))
+                .map(|mismatch| (*dep, mismatch))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Builds the warning reporting `mismatch` on `dep`.
+pub(crate) fn mismatch_diagnostic(dep: &dyn Dependency, mismatch: &ChecksumMismatch) -> Diagnostic {
+    Diagnostic {
+        range: dep.version_range().unwrap_or_else(|| dep.name_range()),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(CHECKSUM_MISMATCH_CODE.into())),
+        source: Some("deps-lsp".into()),
+        message: format!(
+            "Lock file checksum of {} {} does not match the registry (locked {}, registry {})",
+            dep.name(),
+            mismatch.version,
+            short(&mismatch.locked),
+            short(&mismatch.registry)
+        ),
+        ..Default::default()
+    }
+}
+
+/// Builds warnings for direct dependencies locked with another checksum
+/// than their registry publishes.
+///
+/// Versions the registry client kept from fetching the document are
+/// reused; the others are fetched in parallel within the limits of `cache`.
+pub(crate) async fn checksum_diagnostics(
+    state: &ServerState,
+    uri: &Uri,
+    cache: &CacheConfig,
+) -> Vec<Diagnostic> {
+    let Some(locked) = locked_manifest(state, uri).await else {
+        return vec![];
+    };
+    let (names, registry) = {
+        let Some(doc) = state.get_document(uri) else {
+            return vec![];
+        };
+        let Some(parse_result) = doc.parse_result() else {
+            return vec![];
+        };
+        let mut names: Vec<String> = parse_result
+            .dependencies()
+            .into_iter()
+            .filter(|dep| dep.source().is_registry())
+            .map(|dep| dep.name().to_string())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        (names, locked.ecosystem.registry_for(parse_result))
+    };
+
+    let mut fetched: HashMap<&str, Vec<Box<dyn Version>>> = HashMap::new();
+    let mut missing = Vec::new();
+    for name in &names {
+        match registry.cached_versions(name) {
+            Some(versions) => {
+                fetched.insert(name, versions);
+            }
+            None => missing.push(name.clone()),
+        }
+    }
+
+    let timeout = Duration::from_secs(cache.fetch_timeout_secs);
+    let results: Vec<(String, Vec<Box<dyn Version>>)> = stream::iter(missing)
+        .map(|name| {
+            let registry = Arc::clone(&registry);
+            async move {
+                match tokio::time::timeout(timeout, registry.get_versions(&name)).await {
+                    Ok(Ok(versions)) => Some((name, versions)),
+                    _ => None,
+                }
+            }
+        })
+        .buffer_unordered(cache.max_concurrent_fetches.max(1))
+        .filter_map(std::future::ready)
+        .collect()
+        .await;
+    for (name, versions) in results {
+        if let Some(name) = names.iter().find(|n| **n == name) {
+            fetched.insert(name, versions);
+        }
+    }
+
+    let Some(doc) = state.get_document(uri) else {
+        return vec![];
+    };
+    let Some(parse_result) = doc.parse_result() else {
+        return vec![];
+    };
+    checksum_mismatches(
+        &parse_result.dependencies(),
+        &locked.packages,
+        locked.ecosystem.formatter(),
+        &fetched,
+    )
+    .iter()
+    .map(|(dep, mismatch)| mismatch_diagnostic(*dep, mismatch))
+    .collect()
+}
+
+fn short(checksum: &str) -> &str {
+    checksum.get(..SHORT_CHECKSUM_LEN).unwrap_or(checksum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deps_core::lockfile::{ResolvedPackage, ResolvedSource};
+    use deps_core::parser::DependencySource;
+    use std::any::Any;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    struct TestVersion(&'static str, &'static str);
+
+    impl Version for TestVersion {
+        fn version_string(&self) -> &str {
+            self.0
+        }
+        fn is_yanked(&self) -> bool {
+            false
+        }
+        fn checksum(&self) -> Option<&str> {
+            Some(self.1)
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    struct TestDependency(&'static str, DependencySource);
+
+    impl Dependency for TestDependency {
+        fn name(&self) -> &str {
+            self.0
+        }
+        fn name_range(&self) -> Range {
+            Range::new(Position::new(5, 0), Position::new(5, 5))
+        }
+        fn version_requirement(&self) -> Option<&str> {
+            Some("1")
+        }
+        fn version_range(&self) -> Option<Range> {
+            Some(Range::new(Position::new(5, 9), Position::new(5, 10)))
+        }
+        fn source(&self) -> DependencySource {
+            self.1.clone()
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    struct TestFormatter;
+
+    impl EcosystemFormatter for TestFormatter {
+        fn format_version_for_text_edit(&self, version: &str) -> String {
+            version.to_string()
+        }
+        fn package_url(&self, name: &str) -> String {
+            name.to_string()
+        }
+    }
+
+    fn locked(name: &str, version: &str, checksum: &str) -> ResolvedPackage {
+        ResolvedPackage {
+            name: name.into(),
+            version: version.into(),
+            source: ResolvedSource::Registry {
+                url: "https://github.com/rust-lang/crates.io-index".into(),
+                checksum: checksum.into(),
+            },
+            dependencies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_checksum_mismatches() {
+        let mut packages = ResolvedPackages::new();
+        packages.insert(locked("serde", "1.0.195", "0123456789abcdef0123"));
+        packages.insert(locked("log", "0.4.22", "7a7a7a"));
+        packages.insert(locked("local", "0.1.0", "ffff"));
+
+        let fetched: HashMap<&str, Vec<Box<dyn Version>>> = HashMap::from([
+            (
+                "serde",
+                vec![Box::new(TestVersion("1.0.195", "fedcba9876543210fedc")) as Box<dyn Version>],
+            ),
+            (
+                "log",
+                vec![Box::new(TestVersion("0.4.22", "7a7a7a")) as Box<dyn Version>],
+            ),
+            (
+                "local",
+                vec![Box::new(TestVersion("0.1.0", "0000")) as Box<dyn Version>],
+            ),
+        ]);
+        let serde = TestDependency("serde", DependencySource::Registry);
+        let log = TestDependency("log", DependencySource::Registry);
+        let local = TestDependency(
+            "local",
+            DependencySource::Path {
+                path: "../local".into(),
+            },
+        );
+        let dependencies: Vec<&dyn Dependency> = vec![&serde, &log, &local];
+
+        let mismatches = checksum_mismatches(&dependencies, &packages, &TestFormatter, &fetched);
+        assert_eq!(mismatches.len(), 1);
+        let (dep, mismatch) = &mismatches[0];
+        assert_eq!(dep.name(), "serde");
+
+        let diagnostic = mismatch_diagnostic(*dep, mismatch);
+        assert_eq!(
+            diagnostic.message,
+            "Lock file checksum of serde 1.0.195 does not match the registry (locked 0123456789ab, registry fedcba987654)"
+        );
+        assert_eq!(diagnostic.range.start, Position::new(5, 9));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[test]
+    fn test_checksum_mismatches_without_fetched_versions() {
+        let mut packages = ResolvedPackages::new();
+        packages.insert(locked("serde", "1.0.195", "0123"));
+        let serde = TestDependency("serde", DependencySource::Registry);
+
+        let mismatches = checksum_mismatches(&[&serde], &packages, &TestFormatter, &HashMap::new());
+        assert!(mismatches.is_empty());
+    }
+}
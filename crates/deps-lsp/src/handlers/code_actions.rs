@@ -29,7 +29,7 @@ pub async fn handle_code_actions(
         None => return vec![],
     };
 
-    let ecosystem = match state.ecosystem_registry.get(doc.ecosystem_id) {
+    let ecosystem = match state.ecosystem_for(&doc) {
         Some(e) => e,
         None => return vec![],
     };
@@ -40,9 +40,16 @@ pub async fn handle_code_actions(
     };
 
     // Generate code actions while holding the lock
-    let actions = ecosystem
+    let mut actions = ecosystem
         .generate_code_actions(parse_result, position, uri)
         .await;
+    actions.extend(super::formatting::sort_code_actions(
+        ecosystem.as_ref(),
+        parse_result,
+        &doc.content,
+        uri,
+        params.range,
+    ));
 
     actions
         .into_iter()
@@ -71,6 +71,7 @@ pub async fn handle_completion(
         }
     };
     let ecosystem_id = doc.ecosystem_id;
+    let ecosystem = state.ecosystem_for(&doc)?;
     let content = doc.content.clone();
     let has_parse_result = doc.parse_result().is_some();
     drop(doc);
@@ -86,7 +87,6 @@ pub async fn handle_completion(
         // Re-acquire document to get parse_result
         let doc = state.get_document(uri)?;
         let parse_result = doc.parse_result()?;
-        let ecosystem = state.ecosystem_registry.get(ecosystem_id)?;
         let completion_result = tokio::time::timeout(
             std::time::Duration::from_secs(COMPLETION_SEARCH_TIMEOUT_SECS),
             ecosystem.generate_completions(parse_result, position, &content),
@@ -99,7 +99,13 @@ pub async fn handle_completion(
             // case where the user is typing a NEW package name.
             Ok(completions) if completions.is_empty() => {
                 tracing::info!("completion: ecosystem returned empty, trying fallback");
-                fallback_completion(&state, ecosystem_id, position, &content).await
+                fallback_completion(
+                    ecosystem.registry().as_ref(),
+                    ecosystem_id,
+                    position,
+                    &content,
+                )
+                .await
             }
             Ok(completions) => completions,
             // Timed out, not genuinely empty: the registry is slow right now, so a
@@ -114,8 +120,20 @@ pub async fn handle_completion(
             }
         }
     } else {
-        // Fallback: detect context from raw text
-        fallback_completion(&state, ecosystem_id, position, &content).await
+        // Fallback: detect context from raw text, first what the ecosystem
+        // recognizes (e.g. an unclosed inline table), then package names
+        let items = ecosystem.generate_unparsed_completions(&content, position, uri);
+        if items.is_empty() {
+            fallback_completion(
+                ecosystem.registry().as_ref(),
+                ecosystem_id,
+                position,
+                &content,
+            )
+            .await
+        } else {
+            items
+        }
     };
 
     tracing::info!("completion: returning {} items", items.len());
@@ -131,7 +149,7 @@ pub async fn handle_completion(
 ///
 /// Detects dependencies sections from raw text and provides package name suggestions.
 async fn fallback_completion(
-    state: &ServerState,
+    registry: &dyn deps_core::Registry,
     ecosystem_id: &str,
     position: tower_lsp_server::ls_types::Position,
     content: &str,
@@ -152,9 +170,8 @@ async fn fallback_completion(
 
     tracing::info!("fallback_completion: line content = {:?}", line);
 
-    // Check if we're in a dependencies section. An ecosystem id that doesn't parse is
-    // not registered, so there is no fallback to offer (mirrors the `ecosystem_registry
-    // .get` lookup below, which fails the same way for the same reason).
+    // Check if we're in a dependencies section. An ecosystem id that doesn't parse has
+    // no section layout to detect, so there is no fallback to offer.
     let Ok(ecosystem_kind) = ecosystem_id.parse::<EcosystemId>() else {
         tracing::warn!("fallback_completion: unknown ecosystem id {ecosystem_id:?}");
         return vec![];
@@ -175,16 +192,8 @@ async fn fallback_completion(
         return vec![];
     }
 
-    // Get ecosystem and search for packages
-    let ecosystem = match state.ecosystem_registry.get(ecosystem_id) {
-        Some(e) => e,
-        None => return vec![],
-    };
-
-    let registry = ecosystem.registry();
-
     // Search for packages matching the prefix
-    search_packages(registry.as_ref(), ecosystem_kind, prefix).await
+    search_packages(registry, ecosystem_kind, prefix).await
 }
 
 /// Extracts what the user has typed on `line` up to the cursor (`character`), trimmed
@@ -1057,6 +1066,36 @@ requests
         assert_eq!(item.insert_text, Some("requests = \"2.31.0\"".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_table_completion_when_parse_fails() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+
+        // Unclosed inline table, so the manifest does not parse
+        let content = "[dependencies]\nserde = { version = \"1\", default-features = ".to_string();
+        let doc = DocumentState::new_without_parse_result(EcosystemId::Cargo, content);
+        state.update_document(uri.clone(), doc);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(1, 44),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+
+        let (client, config) = create_test_client_and_config();
+        let Some(CompletionResponse::Array(items)) =
+            handle_completion(state, params, client, config).await
+        else {
+            panic!("expected completions");
+        };
+        let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["true", "false"]);
+    }
+
     #[tokio::test]
     async fn test_fallback_triggered_when_parse_fails() {
         let state = Arc::new(ServerState::new());
@@ -1092,9 +1131,11 @@ ser"
     #[tokio::test]
     async fn test_fallback_completion_unknown_ecosystem_id_returns_empty() {
         let state = ServerState::new();
+        let registry = state.ecosystem_registry.get("cargo").unwrap().registry();
         let content = "[dependencies]\nserde\n".to_string();
 
-        let items = fallback_completion(&state, "unknown", Position::new(1, 5), &content).await;
+        let items =
+            fallback_completion(registry.as_ref(), "unknown", Position::new(1, 5), &content).await;
         assert!(items.is_empty());
     }
 
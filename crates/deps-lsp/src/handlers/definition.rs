@@ -0,0 +1,199 @@
+//! Go-to-definition handler using ecosystem trait delegation.
+//!
+//! Jumps from an inherited dependency to the place its version is declared:
+//! Cargo `workspace = true` entries, Maven `${property}` versions, Gradle
+//! catalog aliases and `gradle.properties` variables, npm `workspace:`
+//! references, and NuGet central package management.
+
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{GotoDefinitionParams, GotoDefinitionResponse};
+
+/// Handles definition requests using trait-based delegation.
+pub async fn handle_definition(
+    state: Arc<ServerState>,
+    params: GotoDefinitionParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Option<GotoDefinitionResponse> {
+    let uri = &params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for definition: {:?}", uri);
+        return None;
+    }
+
+    let doc = state.get_document(uri)?;
+    let ecosystem = state.ecosystem_for(&doc)?;
+    let parse_result = doc.parse_result()?;
+
+    ecosystem
+        .generate_definition(parse_result, position, &doc.content)
+        .await
+        .map(GotoDefinitionResponse::Scalar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::ServerState;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+    use tower_lsp_server::ls_types::{
+        Position, TextDocumentIdentifier, TextDocumentPositionParams,
+    };
+
+    fn definition_params(
+        uri: tower_lsp_server::ls_types::Uri,
+        position: Position,
+    ) -> GotoDefinitionParams {
+        GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_definition_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let (client, config) = create_test_client_and_config();
+
+        let result = handle_definition(
+            state,
+            definition_params(uri, Position::new(0, 0)),
+            client,
+            config,
+        )
+        .await;
+        assert!(result.is_none());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        #[tokio::test]
+        async fn test_handle_definition_workspace_dependency() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            std::fs::write(
+                temp_dir.path().join("Cargo.toml"),
+                "[workspace]\nmembers = [\"app\"]\n\n[workspace.dependencies]\nserde = \"1.0\"\n",
+            )
+            .unwrap();
+            let member_dir = temp_dir.path().join("app");
+            std::fs::create_dir(&member_dir).unwrap();
+            let member_path = member_dir.join("Cargo.toml");
+            let content = "[dependencies]\nserde = { workspace = true }\n".to_string();
+            std::fs::write(&member_path, &content).unwrap();
+
+            let state = Arc::new(ServerState::new());
+            let uri = tower_lsp_server::ls_types::Uri::from_file_path(&member_path).unwrap();
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result),
+            );
+
+            let (client, config) = create_test_client_and_config();
+            let result = handle_definition(
+                state,
+                definition_params(uri, Position::new(1, 1)),
+                client,
+                config,
+            )
+            .await;
+
+            let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+                panic!("expected a single location, got {result:?}");
+            };
+            assert_eq!(
+                location.uri,
+                tower_lsp_server::ls_types::Uri::from_file_path(temp_dir.path().join("Cargo.toml"))
+                    .unwrap()
+            );
+            assert_eq!(location.range.start.line, 4);
+        }
+
+        #[tokio::test]
+        async fn test_handle_definition_registry_dependency() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let content = "[dependencies]\nserde = \"1.0\"\n".to_string();
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result),
+            );
+
+            let (client, config) = create_test_client_and_config();
+            let result = handle_definition(
+                state,
+                definition_params(uri, Position::new(1, 1)),
+                client,
+                config,
+            )
+            .await;
+            assert!(result.is_none());
+        }
+    }
+
+    #[cfg(feature = "maven")]
+    mod maven_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        #[tokio::test]
+        async fn test_handle_definition_property_version() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/pom.xml");
+            let content = r"<project>
+  <properties>
+    <junit.version>4.13.2</junit.version>
+  </properties>
+  <dependencies>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>${junit.version}</version>
+    </dependency>
+  </dependencies>
+</project>"
+                .to_string();
+            let ecosystem = state.ecosystem_registry.get("maven").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(EcosystemId::Maven, content, parse_result),
+            );
+
+            let (client, config) = create_test_client_and_config();
+            let result = handle_definition(
+                state,
+                definition_params(uri.clone(), Position::new(8, 20)),
+                client,
+                config,
+            )
+            .await;
+
+            let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+                panic!("expected a single location, got {result:?}");
+            };
+            assert_eq!(location.uri, uri);
+            assert_eq!(location.range.start, Position::new(2, 19));
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! Custom requests exposing the resolved dependency graph.
+//!
+//! - `deps/dependencyTree` returns the transitive tree below a manifest's
+//!   direct dependencies, as resolved in its lock file.
+//! - `deps/whyDependency` returns every path from a direct dependency to a
+//!   given (usually transitive) package.
+//!
+//! Both work for any ecosystem with a `LockFileProvider` that records package
+//! dependencies, and return `null` when the manifest has no lock file. Lock
+//! formats without a dependency graph fail with a `RequestFailed` error.
+
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::Ecosystem;
+use deps_core::dependency_tree::{self, DependencyNode, WhyDependency};
+use deps_core::lockfile::ResolvedPackages;
+use deps_core::lsp_helpers::EcosystemFormatter;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp_server::ls_types::{TextDocumentIdentifier, Uri};
+
+/// Method name of the dependency tree request.
+pub const DEPENDENCY_TREE_METHOD: &str = "deps/dependencyTree";
+
+/// Method name of the why-dependency request.
+pub const WHY_DEPENDENCY_METHOD: &str = "deps/whyDependency";
+
+/// Parameters of `deps/dependencyTree`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyTreeParams {
+    /// Manifest to resolve.
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Result of `deps/dependencyTree`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyTreeResult {
+    /// Lock file the tree was read from.
+    pub lockfile: Uri,
+    /// One node per direct dependency found in the lock file.
+    pub dependencies: Vec<DependencyNode>,
+}
+
+/// Parameters of `deps/whyDependency`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhyDependencyParams {
+    /// Manifest whose direct dependencies start the paths.
+    pub text_document: TextDocumentIdentifier,
+    /// Package to explain.
+    pub package: String,
+}
+
+/// Result of `deps/whyDependency`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhyDependencyResult {
+    /// Lock file the paths were read from.
+    pub lockfile: Uri,
+    /// Package name as written in the lock file.
+    pub package: String,
+    /// Paths from direct dependencies to the package.
+    #[serde(flatten)]
+    pub why: WhyDependency,
+}
+
+/// Lock file contents and lock file names of a manifest's direct dependencies.
+pub(crate) struct LockedManifest {
+    pub(crate) lockfile: Uri,
+    pub(crate) packages: ResolvedPackages,
+    pub(crate) roots: Vec<String>,
+    pub(crate) ecosystem: Arc<dyn Ecosystem>,
+}
+
+/// Handles `deps/dependencyTree`.
+pub async fn handle_dependency_tree(
+    state: Arc<ServerState>,
+    params: DependencyTreeParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Result<Option<DependencyTreeResult>> {
+    let Some(locked) =
+        load_locked_manifest(state, &params.text_document.uri, client, config).await?
+    else {
+        return Ok(None);
+    };
+    let roots: Vec<&str> = locked.roots.iter().map(String::as_str).collect();
+
+    Ok(Some(DependencyTreeResult {
+        dependencies: dependency_tree::build_tree(&locked.packages, &roots),
+        lockfile: locked.lockfile,
+    }))
+}
+
+/// Handles `deps/whyDependency`.
+pub async fn handle_why_dependency(
+    state: Arc<ServerState>,
+    params: WhyDependencyParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Result<Option<WhyDependencyResult>> {
+    let Some(locked) =
+        load_locked_manifest(state, &params.text_document.uri, client, config).await?
+    else {
+        return Ok(None);
+    };
+    let Some(package) = lock_name(
+        &locked.packages,
+        locked.ecosystem.formatter(),
+        &params.package,
+    ) else {
+        return Ok(None);
+    };
+    let roots: Vec<&str> = locked.roots.iter().map(String::as_str).collect();
+
+    Ok(Some(WhyDependencyResult {
+        why: dependency_tree::why_dependency(&locked.packages, &roots, &package),
+        package,
+        lockfile: locked.lockfile,
+    }))
+}
+
+async fn load_locked_manifest(
+    state: Arc<ServerState>,
+    uri: &Uri,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Result<Option<LockedManifest>> {
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for dependency tree: {:?}", uri);
+        return Ok(None);
+    }
+
+    let ecosystem = state
+        .get_document(uri)
+        .and_then(|doc| state.ecosystem_for(&doc));
+    if let Some(ecosystem) = ecosystem
+        && ecosystem
+            .lockfile_provider()
+            .is_some_and(|provider| !provider.records_dependencies())
+    {
+        return Err(Error {
+            // LSP `RequestFailed`
+            code: ErrorCode::ServerError(-32803),
+            message: format!(
+                "Dependency graphs are not supported for {}: its lock file does not record package dependencies",
+                ecosystem.display_name()
+            )
+            .into(),
+            data: None,
+        });
+    }
+
+    Ok(locked_manifest(&state, uri).await)
+}
+
+/// Reads the lock file of an already loaded manifest.
+///
+/// Returns `None` when the document is not loaded, failed to parse, or its
+/// ecosystem has no lock file.
+pub(crate) async fn locked_manifest(state: &ServerState, uri: &Uri) -> Option<LockedManifest> {
+    let (ecosystem, direct) = {
+        let doc = state.get_document(uri)?;
+        let ecosystem = state.ecosystem_for(&doc)?;
+        let direct: Vec<String> = doc
+            .parse_result()?
+            .dependencies()
+            .into_iter()
+            .map(|d| d.name().to_string())
+            .collect();
+        (ecosystem, direct)
+    };
+
+    let provider = ecosystem.lockfile_provider()?;
+    let lockfile_path = provider.locate_lockfile(uri)?;
+    let packages = match state
+        .lockfile_cache
+        .get_or_parse(provider.as_ref(), &lockfile_path)
+        .await
+    {
+        Ok(packages) => packages,
+        Err(e) => {
+            tracing::warn!("Failed to parse lock file: {}", e);
+            return None;
+        }
+    };
+
+    let formatter = ecosystem.formatter();
+    let mut roots: Vec<String> = Vec::with_capacity(direct.len());
+    for name in &direct {
+        if let Some(locked) = lock_name(&packages, formatter, name)
+            && !roots.contains(&locked)
+        {
+            roots.push(locked);
+        }
+    }
+
+    Some(LockedManifest {
+        lockfile: Uri::from_file_path(&lockfile_path)?,
+        packages,
+        roots,
+        ecosystem,
+    })
+}
+
+/// Maps a manifest name to the name the lock file uses for it.
+///
+/// Exact matches win; otherwise names are compared after ecosystem
+/// normalization (e.g. PyPI's `Django` vs `django`).
+pub(crate) fn lock_name(
+    packages: &ResolvedPackages,
+    formatter: &dyn EcosystemFormatter,
+    name: &str,
+) -> Option<String> {
+    if packages.get(name).is_some() {
+        return Some(name.to_string());
+    }
+    let normalized = formatter.normalize_package_name(name);
+    packages
+        .iter()
+        .find(|(locked, _)| formatter.normalize_package_name(locked) == normalized)
+        .map(|(locked, _)| locked.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+
+    #[tokio::test]
+    async fn test_handle_dependency_tree_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let (client, config) = create_test_client_and_config();
+
+        let result = handle_dependency_tree(
+            state,
+            DependencyTreeParams {
+                text_document: TextDocumentIdentifier { uri },
+            },
+            client,
+            config,
+        )
+        .await;
+        assert!(result.unwrap().is_none());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        const MANIFEST: &str = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nhyper = \"1\"\ntokio = \"1\"\n";
+
+        const LOCKFILE: &str = r#"version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["hyper", "tokio"]
+
+[[package]]
+name = "bytes"
+version = "1.5.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "hyper"
+version = "1.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["bytes", "tokio"]
+
+[[package]]
+name = "tokio"
+version = "1.35.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["bytes"]
+"#;
+
+        async fn setup() -> (tempfile::TempDir, Arc<ServerState>, Uri) {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let manifest_path = temp_dir.path().join("Cargo.toml");
+            std::fs::write(&manifest_path, MANIFEST).unwrap();
+            std::fs::write(temp_dir.path().join("Cargo.lock"), LOCKFILE).unwrap();
+
+            let state = Arc::new(ServerState::new());
+            let uri = Uri::from_file_path(&manifest_path).unwrap();
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(MANIFEST, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    MANIFEST.to_string(),
+                    parse_result,
+                ),
+            );
+            (temp_dir, state, uri)
+        }
+
+        #[tokio::test]
+        async fn test_handle_dependency_tree() {
+            let (_temp_dir, state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let result = handle_dependency_tree(
+                state,
+                DependencyTreeParams {
+                    text_document: TextDocumentIdentifier { uri },
+                },
+                client,
+                config,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+            assert!(result.lockfile.as_str().ends_with("Cargo.lock"));
+            let names: Vec<&str> = result
+                .dependencies
+                .iter()
+                .map(|n| n.name.as_str())
+                .collect();
+            assert_eq!(names, vec!["hyper", "tokio"]);
+            let hyper = &result.dependencies[0];
+            assert_eq!(hyper.version, "1.1.0");
+            assert_eq!(hyper.children.len(), 2);
+            // tokio is expanded below hyper, so the top-level entry is deduplicated
+            assert!(result.dependencies[1].deduplicated);
+        }
+
+        #[tokio::test]
+        async fn test_handle_why_dependency() {
+            let (_temp_dir, state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let result = handle_why_dependency(
+                state,
+                WhyDependencyParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    package: "bytes".into(),
+                },
+                client,
+                config,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+            assert_eq!(result.package, "bytes");
+            assert_eq!(result.why.paths.len(), 3);
+            assert!(!result.why.truncated);
+
+            let json = serde_json::to_value(&result).unwrap();
+            assert_eq!(json["paths"][0][0]["name"], "hyper");
+            assert_eq!(json["truncated"], false);
+        }
+
+        #[tokio::test]
+        async fn test_handle_why_dependency_unknown_package() {
+            let (_temp_dir, state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let result = handle_why_dependency(
+                state,
+                WhyDependencyParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    package: "serde".into(),
+                },
+                client,
+                config,
+            )
+            .await;
+            assert!(result.unwrap().is_none());
+        }
+    }
+
+    #[cfg(feature = "go")]
+    #[tokio::test]
+    async fn test_handle_dependency_tree_unsupported_lockfile() {
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        let manifest =
+            "module example.com/app\n\ngo 1.21\n\nrequire github.com/gin-gonic/gin v1.9.1\n";
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest_path = temp_dir.path().join("go.mod");
+        std::fs::write(&manifest_path, manifest).unwrap();
+        std::fs::write(
+            temp_dir.path().join("go.sum"),
+            "github.com/gin-gonic/gin v1.9.1 h1:4idEAncQnU5cB7BeOkPtxjfCSye0AAm1R0RVIqJ+Jmg=\n",
+        )
+        .unwrap();
+
+        let state = Arc::new(ServerState::new());
+        let uri = Uri::from_file_path(&manifest_path).unwrap();
+        let ecosystem = state.ecosystem_registry.get("go").unwrap();
+        let parse_result = ecosystem.parse_manifest(manifest, &uri).await.unwrap();
+        state.update_document(
+            uri.clone(),
+            DocumentState::new_from_parse_result(
+                EcosystemId::Go,
+                manifest.to_string(),
+                parse_result,
+            ),
+        );
+        let (client, config) = create_test_client_and_config();
+
+        let error = handle_dependency_tree(
+            state,
+            DependencyTreeParams {
+                text_document: TextDocumentIdentifier { uri },
+            },
+            client,
+            config,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(-32803));
+        assert!(error.message.contains("Go"));
+    }
+}
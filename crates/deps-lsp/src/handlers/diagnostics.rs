@@ -1,36 +1,43 @@
 //! Diagnostics handler using ecosystem trait delegation.
 
-use crate::config::{DepsConfig, DiagnosticsConfig};
+use super::{checksums, duplicates};
+use crate::config::{CacheConfig, DepsConfig, DiagnosticsConfig};
 use crate::document::{ServerState, ensure_document_loaded};
 use deps_core::VersionData;
+use deps_core::lsp_helpers::{OUTDATED_CODE, UNKNOWN_PACKAGE_CODE, YANKED_CODE};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_lsp_server::Client;
-use tower_lsp_server::ls_types::{Diagnostic, Uri};
+use tower_lsp_server::ls_types::{Diagnostic, NumberOrString, Range, Uri};
 
 /// Handles diagnostic requests using trait-based delegation.
 pub async fn handle_diagnostics(
     state: Arc<ServerState>,
     uri: &Uri,
-    _config: &DiagnosticsConfig,
+    config: &DiagnosticsConfig,
     client: Client,
     full_config: Arc<RwLock<DepsConfig>>,
 ) -> Vec<Diagnostic> {
+    let cache = state.config_for(&full_config, uri).await.cache;
+
     // Ensure document is loaded (cold start support)
     if !ensure_document_loaded(uri, Arc::clone(&state), client, full_config).await {
         tracing::warn!("Could not load document for diagnostics: {:?}", uri);
         return vec![];
     }
 
-    generate_diagnostics_internal(state, uri).await
+    generate_diagnostics_internal(state, uri, config, &cache).await
 }
 
 /// Internal diagnostic generation without cold start support.
 ///
 /// This is used when we know the document is already loaded (e.g., from background tasks).
+/// `cache` bounds the registry requests of the opt-in checks.
 pub(crate) async fn generate_diagnostics_internal(
     state: Arc<ServerState>,
     uri: &Uri,
+    config: &DiagnosticsConfig,
+    cache: &CacheConfig,
 ) -> Vec<Diagnostic> {
     // Single document lookup: extract all needed data at once
     let doc = match state.get_document(uri) {
@@ -41,7 +48,7 @@ pub(crate) async fn generate_diagnostics_internal(
         }
     };
 
-    let ecosystem = match state.ecosystem_registry.get(doc.ecosystem_id) {
+    let ecosystem = match state.ecosystem_for(&doc) {
         Some(e) => e,
         None => {
             tracing::warn!("Ecosystem not found for diagnostics: {}", doc.ecosystem_id);
@@ -61,19 +68,60 @@ pub(crate) async fn generate_diagnostics_internal(
     };
 
     // Generate diagnostics while holding the lock
-    ecosystem
+    let mut diagnostics = ecosystem
         .generate_diagnostics(
             parse_result,
             VersionData::new(&doc.cached_versions, &doc.resolved_versions),
             uri,
+            cache.max_concurrent_fetches,
         )
-        .await
+        .await;
+
+    // Ranges of dependencies whose diagnostics are suppressed
+    let ignored: Vec<Range> = parse_result
+        .dependencies()
+        .into_iter()
+        .filter(|dep| config.is_ignored(dep.name()))
+        .flat_map(|dep| [Some(dep.name_range()), dep.version_range()])
+        .flatten()
+        .collect();
+    drop(doc);
+
+    if config.duplicate_versions {
+        diagnostics.extend(duplicates::duplicate_diagnostics(&state, uri).await);
+    }
+    if config.verify_checksums {
+        diagnostics.extend(checksums::checksum_diagnostics(&state, uri, cache).await);
+    }
+
+    if !ignored.is_empty() {
+        diagnostics.retain(|diagnostic| !ignored.contains(&diagnostic.range));
+    }
+    apply_severities(&mut diagnostics, config);
+
+    diagnostics
+}
+
+/// Sets the configured severity on outdated, unknown and yanked diagnostics.
+fn apply_severities(diagnostics: &mut [Diagnostic], config: &DiagnosticsConfig) {
+    for diagnostic in diagnostics {
+        let Some(NumberOrString::String(code)) = &diagnostic.code else {
+            continue;
+        };
+        let severity = match code.as_str() {
+            OUTDATED_CODE => config.outdated_severity,
+            UNKNOWN_PACKAGE_CODE => config.unknown_severity,
+            YANKED_CODE => config.yanked_severity,
+            _ => continue,
+        };
+        diagnostic.severity = Some(severity);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::DiagnosticsConfig;
+    use crate::config::{CacheConfig, DiagnosticsConfig};
     use crate::document::ServerState;
     use crate::test_utils::test_helpers::create_test_client_and_config;
     use deps_core::EcosystemId;
@@ -123,6 +171,148 @@ serde = "1.0.0"
             // Test passes if no panic occurs
         }
 
+        #[tokio::test]
+        async fn test_generate_diagnostics_ignored_packages() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let content = "[dependencies]\nserde = \"0.1\"\nnot-a-crate = \"1\"\n".to_string();
+
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            let mut doc =
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result);
+            doc.update_cached_versions(std::collections::HashMap::from([(
+                "serde".into(),
+                "1.0.228".into(),
+            )]));
+            state.update_document(uri.clone(), doc);
+
+            let config = DiagnosticsConfig::default();
+            let all = generate_diagnostics_internal(
+                Arc::clone(&state),
+                &uri,
+                &config,
+                &CacheConfig::default(),
+            )
+            .await;
+            assert_eq!(all.len(), 2);
+
+            let config = DiagnosticsConfig {
+                ignore: vec!["ser*".into(), "not-a-crate".into()],
+                ..DiagnosticsConfig::default()
+            };
+            let remaining =
+                generate_diagnostics_internal(state, &uri, &config, &CacheConfig::default()).await;
+            assert!(remaining.is_empty(), "{remaining:?}");
+        }
+
+        #[tokio::test]
+        async fn test_generate_diagnostics_configured_severities() {
+            use tower_lsp_server::ls_types::DiagnosticSeverity;
+
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let content = "[dependencies]\nserde = \"0.1\"\nnot-a-crate = \"1\"\n".to_string();
+
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            let mut doc =
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result);
+            doc.update_cached_versions(std::collections::HashMap::from([(
+                "serde".into(),
+                "1.0.228".into(),
+            )]));
+            state.update_document(uri.clone(), doc);
+
+            let config = DiagnosticsConfig {
+                outdated_severity: DiagnosticSeverity::WARNING,
+                unknown_severity: DiagnosticSeverity::ERROR,
+                ..DiagnosticsConfig::default()
+            };
+            let diagnostics =
+                generate_diagnostics_internal(state, &uri, &config, &CacheConfig::default()).await;
+            let severity_of = |code: &str| {
+                diagnostics
+                    .iter()
+                    .find(|d| d.code == Some(NumberOrString::String(code.into())))
+                    .and_then(|d| d.severity)
+            };
+            assert_eq!(
+                severity_of(OUTDATED_CODE),
+                Some(DiagnosticSeverity::WARNING)
+            );
+            assert_eq!(
+                severity_of(UNKNOWN_PACKAGE_CODE),
+                Some(DiagnosticSeverity::ERROR)
+            );
+        }
+
+        #[tokio::test]
+        async fn test_handle_diagnostics_duplicate_versions_opt_in() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let manifest_path = temp_dir.path().join("Cargo.toml");
+            let content = "[dependencies]\nold-crate = \"1\"\n".to_string();
+            std::fs::write(&manifest_path, &content).unwrap();
+            std::fs::write(
+                temp_dir.path().join("Cargo.lock"),
+                r#"version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["old-crate", "rand 0.8.5"]
+
+[[package]]
+name = "old-crate"
+version = "1.0.0"
+dependencies = ["rand 0.7.3"]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+"#,
+            )
+            .unwrap();
+
+            let state = Arc::new(ServerState::new());
+            let uri = Uri::from_file_path(&manifest_path).unwrap();
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result),
+            );
+
+            let is_duplicate = |d: &Diagnostic| {
+                d.code
+                    == Some(tower_lsp_server::ls_types::NumberOrString::String(
+                        duplicates::DUPLICATE_VERSION_CODE.into(),
+                    ))
+            };
+
+            let default_config = DiagnosticsConfig::default();
+            let result = generate_diagnostics_internal(
+                Arc::clone(&state),
+                &uri,
+                &default_config,
+                &CacheConfig::default(),
+            )
+            .await;
+            assert!(!result.iter().any(is_duplicate));
+
+            let config = DiagnosticsConfig {
+                duplicate_versions: true,
+                ..Default::default()
+            };
+            let result =
+                generate_diagnostics_internal(state, &uri, &config, &CacheConfig::default()).await;
+            assert_eq!(result.iter().filter(|d| is_duplicate(d)).count(), 1);
+        }
+
         #[tokio::test]
         async fn test_handle_diagnostics_no_parse_result() {
             let state = Arc::new(ServerState::new());
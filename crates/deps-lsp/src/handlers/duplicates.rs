@@ -0,0 +1,419 @@
+//! Duplicate transitive versions reported from lock files.
+//!
+//! Lock files routinely resolve the same package at two or three versions,
+//! each copy adding to build times and bundle size. This module surfaces them
+//! in three places:
+//!
+//! - an opt-in informational diagnostic on each direct dependency that pulls
+//!   in an older copy (`diagnostics.duplicate_versions`)
+//! - a hover section listing every resolved version and who requires it,
+//!   shown alongside the diagnostic
+//! - the `deps/duplicates` request, summarizing the whole lock file
+//!
+//! Duplicates are computed once per lock file and kept in
+//! [`ServerState::duplicates`] until the file watcher reports a change.
+
+use super::dependency_tree::{LockedManifest, lock_name, locked_manifest};
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::dependency_tree::{self, DuplicatePackage};
+use deps_core::lsp_helpers::{find_dependency_at, markdown_code_span};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{
+    Diagnostic, DiagnosticSeverity, NumberOrString, Position, TextDocumentIdentifier, Uri,
+};
+
+/// Method name of the duplicates request.
+pub const DUPLICATES_METHOD: &str = "deps/duplicates";
+
+/// Diagnostic code of duplicate version diagnostics.
+pub const DUPLICATE_VERSION_CODE: &str = "duplicate-version";
+
+/// Parameters of `deps/duplicates`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesParams {
+    /// Manifest whose lock file to inspect.
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// A duplicated package and the direct dependencies responsible for it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateSummary {
+    /// Package and its resolved versions.
+    #[serde(flatten)]
+    pub package: DuplicatePackage,
+    /// Direct dependencies of the manifest that pull in an older copy.
+    pub introduced_by: Vec<String>,
+}
+
+/// Result of `deps/duplicates`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesResult {
+    /// Lock file the duplicates were read from.
+    pub lockfile: Uri,
+    /// Duplicated packages, sorted by name.
+    pub duplicates: Vec<DuplicateSummary>,
+}
+
+/// Handles `deps/duplicates`.
+pub async fn handle_duplicates(
+    state: Arc<ServerState>,
+    params: DuplicatesParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Option<DuplicatesResult> {
+    let uri = &params.text_document.uri;
+
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for duplicates: {:?}", uri);
+        return None;
+    }
+
+    let locked = locked_manifest(&state, uri).await?;
+    let duplicates = lock_duplicates(&state, &locked);
+    let introducers: Vec<(String, Vec<String>)> = locked
+        .roots
+        .iter()
+        .map(|root| {
+            let names =
+                dependency_tree::duplicates_introduced_by(&locked.packages, root, &duplicates)
+                    .into_iter()
+                    .map(|p| p.name)
+                    .collect();
+            (root.clone(), names)
+        })
+        .collect();
+
+    Some(DuplicatesResult {
+        duplicates: duplicates
+            .iter()
+            .cloned()
+            .map(|package| DuplicateSummary {
+                introduced_by: introducers
+                    .iter()
+                    .filter(|(_, names)| names.contains(&package.name))
+                    .map(|(root, _)| root.clone())
+                    .collect(),
+                package,
+            })
+            .collect(),
+        lockfile: locked.lockfile,
+    })
+}
+
+/// Builds informational diagnostics for direct dependencies that pull in an
+/// older copy of a duplicated package.
+pub(crate) async fn duplicate_diagnostics(state: &ServerState, uri: &Uri) -> Vec<Diagnostic> {
+    let Some(locked) = locked_manifest(state, uri).await else {
+        return vec![];
+    };
+    let duplicates = lock_duplicates(state, &locked);
+    if duplicates.is_empty() {
+        return vec![];
+    }
+
+    let Some(doc) = state.get_document(uri) else {
+        return vec![];
+    };
+    let Some(parse_result) = doc.parse_result() else {
+        return vec![];
+    };
+
+    let formatter = locked.ecosystem.formatter();
+    parse_result
+        .dependencies()
+        .into_iter()
+        .filter_map(|dep| {
+            let root = lock_name(&locked.packages, formatter, dep.name())?;
+            let introduced =
+                dependency_tree::duplicates_introduced_by(&locked.packages, &root, &duplicates);
+            if introduced.is_empty() {
+                return None;
+            }
+
+            let copies: Vec<String> = introduced
+                .iter()
+                .map(|p| {
+                    let latest = duplicates
+                        .iter()
+                        .find(|d| d.name == p.name)
+                        .and_then(DuplicatePackage::latest)
+                        .unwrap_or_default();
+                    format!("{} {} (also {})", p.name, p.version, latest)
+                })
+                .collect();
+            Some(Diagnostic {
+                range: dep.name_range(),
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                code: Some(NumberOrString::String(DUPLICATE_VERSION_CODE.into())),
+                source: Some("deps-lsp".into()),
+                message: format!("Pulls in duplicate versions: {}", copies.join(", ")),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Renders the duplicate versions relevant to the dependency at `position`
+/// as a Markdown hover section.
+///
+/// Covers the dependency itself when it is resolved more than once, and the
+/// duplicated packages it pulls in.
+pub(crate) async fn duplicate_hover_section(
+    state: &ServerState,
+    uri: &Uri,
+    position: Position,
+) -> Option<String> {
+    let locked = locked_manifest(state, uri).await?;
+    let root = {
+        let doc = state.get_document(uri)?;
+        let dep = find_dependency_at(doc.parse_result()?, position)?;
+        lock_name(&locked.packages, locked.ecosystem.formatter(), dep.name())?
+    };
+
+    let duplicates = lock_duplicates(state, &locked);
+    if duplicates.is_empty() {
+        return None;
+    }
+    let introduced =
+        dependency_tree::duplicates_introduced_by(&locked.packages, &root, &duplicates);
+    let relevant: Vec<&DuplicatePackage> = duplicates
+        .iter()
+        .filter(|d| d.name == root || introduced.iter().any(|p| p.name == d.name))
+        .collect();
+    if relevant.is_empty() {
+        return None;
+    }
+
+    Some(render_duplicates(&relevant))
+}
+
+/// Duplicated packages of the manifest's lock file, computed on first use.
+fn lock_duplicates(state: &ServerState, locked: &LockedManifest) -> Arc<Vec<DuplicatePackage>> {
+    Arc::clone(
+        &state
+            .duplicates
+            .entry(locked.lockfile.clone())
+            .or_insert_with(|| Arc::new(dependency_tree::find_duplicates(&locked.packages))),
+    )
+}
+
+fn render_duplicates(duplicates: &[&DuplicatePackage]) -> String {
+    let mut markdown = String::from("**Duplicate versions in lock file**\n");
+    for package in duplicates {
+        let _ = write!(markdown, "\n{}\n", markdown_code_span(&package.name));
+        for version in &package.versions {
+            let resolved = markdown_code_span(&version.version);
+            if version.required_by.is_empty() {
+                let _ = writeln!(markdown, "- {resolved} — direct dependency");
+                continue;
+            }
+            let required_by: Vec<String> = version
+                .required_by
+                .iter()
+                .map(|p| markdown_code_span(&format!("{} {}", p.name, p.version)))
+                .collect();
+            let _ = writeln!(
+                markdown,
+                "- {resolved} — required by {}",
+                required_by.join(", ")
+            );
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+
+    #[tokio::test]
+    async fn test_handle_duplicates_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let (client, config) = create_test_client_and_config();
+
+        let result = handle_duplicates(
+            state,
+            DuplicatesParams {
+                text_document: TextDocumentIdentifier { uri },
+            },
+            client,
+            config,
+        )
+        .await;
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_render_duplicates_escapes_lock_file_text() {
+        use deps_core::dependency_tree::{DuplicateVersion, PackageRef};
+
+        let package = DuplicatePackage {
+            name: "evil`pkg".into(),
+            versions: vec![
+                DuplicateVersion {
+                    version: "1.0.0\n# heading".into(),
+                    required_by: vec![],
+                },
+                DuplicateVersion {
+                    version: "2.0.0".into(),
+                    required_by: vec![PackageRef {
+                        name: "[link](https://example.com)".into(),
+                        version: "1.0.0".into(),
+                    }],
+                },
+            ],
+        };
+
+        let markdown = render_duplicates(&[&package]);
+        assert!(markdown.contains("``evil`pkg``"));
+        assert!(markdown.contains("- `1.0.0 # heading` — direct dependency"));
+        assert!(markdown.contains("required by `[link](https://example.com) 1.0.0`"));
+        assert!(!markdown.contains("\n# heading"));
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        const MANIFEST: &str = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nrand = \"0.8\"\nold-crate = \"1\"\n";
+
+        const LOCKFILE: &str = r#"version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["old-crate", "rand 0.8.5"]
+
+[[package]]
+name = "old-crate"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["rand 0.7.3"]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+        async fn setup() -> (tempfile::TempDir, Arc<ServerState>, Uri) {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let manifest_path = temp_dir.path().join("Cargo.toml");
+            std::fs::write(&manifest_path, MANIFEST).unwrap();
+            std::fs::write(temp_dir.path().join("Cargo.lock"), LOCKFILE).unwrap();
+
+            let state = Arc::new(ServerState::new());
+            let uri = Uri::from_file_path(&manifest_path).unwrap();
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(MANIFEST, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    MANIFEST.to_string(),
+                    parse_result,
+                ),
+            );
+            (temp_dir, state, uri)
+        }
+
+        #[tokio::test]
+        async fn test_handle_duplicates() {
+            let (_temp_dir, state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let result = handle_duplicates(
+                state,
+                DuplicatesParams {
+                    text_document: TextDocumentIdentifier { uri },
+                },
+                client,
+                config,
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(result.duplicates.len(), 1);
+            let rand = &result.duplicates[0];
+            assert_eq!(rand.package.name, "rand");
+            assert_eq!(rand.introduced_by, vec!["old-crate"]);
+
+            let json = serde_json::to_value(&result).unwrap();
+            assert_eq!(json["duplicates"][0]["name"], "rand");
+            assert_eq!(
+                json["duplicates"][0]["versions"][0]["requiredBy"][0]["name"],
+                "old-crate"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_duplicate_diagnostics() {
+            let (_temp_dir, state, uri) = setup().await;
+
+            let diagnostics = duplicate_diagnostics(&state, &uri).await;
+            assert_eq!(diagnostics.len(), 1);
+            let diagnostic = &diagnostics[0];
+            assert_eq!(diagnostic.range.start, Position::new(6, 0));
+            assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+            assert_eq!(
+                diagnostic.message,
+                "Pulls in duplicate versions: rand 0.7.3 (also 0.8.5)"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_duplicate_hover_section() {
+            let (_temp_dir, state, uri) = setup().await;
+
+            let section = duplicate_hover_section(&state, &uri, Position::new(5, 1))
+                .await
+                .unwrap();
+            assert!(section.contains("`rand`"));
+            assert!(section.contains("- `0.7.3` — required by `old-crate 1.0.0`"));
+            assert!(section.contains("- `0.8.5` — required by `app 0.1.0`"));
+
+            // Not on a dependency
+            assert!(
+                duplicate_hover_section(&state, &uri, Position::new(0, 1))
+                    .await
+                    .is_none()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_duplicate_diagnostics_without_lockfile() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/nonexistent/Cargo.toml");
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(MANIFEST, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    MANIFEST.to_string(),
+                    parse_result,
+                ),
+            );
+
+            assert!(duplicate_diagnostics(&state, &uri).await.is_empty());
+        }
+    }
+}
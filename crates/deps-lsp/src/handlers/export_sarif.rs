@@ -0,0 +1,168 @@
+//! The `deps/exportSarif` request.
+//!
+//! Exports the diagnostics the server currently publishes, for one manifest
+//! or for every open one, as a SARIF 2.1.0 log (see [`crate::sarif`]).
+
+use super::diagnostics::generate_diagnostics_internal;
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use crate::sarif::{self, SarifDocument, SarifLog};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{TextDocumentIdentifier, Uri};
+
+/// Method name of the SARIF export request.
+pub const EXPORT_SARIF_METHOD: &str = "deps/exportSarif";
+
+/// Parameters of `deps/exportSarif`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSarifParams {
+    /// Manifest to export; every open manifest when omitted.
+    #[serde(default)]
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+/// Builds a SARIF log from the diagnostics of the requested manifests.
+///
+/// Artifact locations are the absolute document URIs.
+pub async fn handle_export_sarif(
+    state: Arc<ServerState>,
+    params: ExportSarifParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> SarifLog {
+    let uris: Vec<Uri> = match params.text_document {
+        Some(document) => {
+            if !ensure_document_loaded(
+                &document.uri,
+                Arc::clone(&state),
+                client,
+                Arc::clone(&config),
+            )
+            .await
+            {
+                tracing::warn!(
+                    "Could not load document for SARIF export: {:?}",
+                    document.uri
+                );
+                return sarif::build_log(&[]);
+            }
+            vec![document.uri]
+        }
+        None => {
+            let mut uris: Vec<Uri> = state.documents.iter().map(|e| e.key().clone()).collect();
+            uris.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            uris
+        }
+    };
+
+    let mut documents = Vec::with_capacity(uris.len());
+    for uri in uris {
+        let effective = state.config_for(&config, &uri).await;
+        let diagnostics = generate_diagnostics_internal(
+            Arc::clone(&state),
+            &uri,
+            &effective.diagnostics,
+            &effective.cache,
+        )
+        .await;
+        if let Some(document) = sarif_document(&state, uri, diagnostics).await {
+            documents.push(document);
+        }
+    }
+
+    sarif::build_log(&documents)
+}
+
+async fn sarif_document(
+    state: &ServerState,
+    uri: Uri,
+    diagnostics: Vec<tower_lsp_server::ls_types::Diagnostic>,
+) -> Option<SarifDocument> {
+    let doc = state.get_document(&uri)?;
+    let ecosystem = state.ecosystem_for(&doc)?;
+
+    let mut with_fixes = Vec::with_capacity(diagnostics.len());
+    for diagnostic in diagnostics {
+        let fix = match doc.parse_result() {
+            Some(parse_result) => {
+                sarif::find_fix(ecosystem.as_ref(), parse_result, &uri, &diagnostic).await
+            }
+            None => None,
+        };
+        with_fixes.push((diagnostic, fix));
+    }
+
+    Some(SarifDocument {
+        content: doc.content.clone(),
+        uri,
+        relative_path: None,
+        diagnostics: with_fixes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+
+    #[test]
+    fn test_params_deserialization() {
+        let params: ExportSarifParams = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(params.text_document.is_none());
+
+        let params: ExportSarifParams = serde_json::from_value(serde_json::json!({
+            "textDocument": { "uri": "file:///test/Cargo.toml" }
+        }))
+        .unwrap();
+        assert!(params.text_document.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handle_export_sarif_no_documents() {
+        let state = Arc::new(ServerState::new());
+        let (client, config) = create_test_client_and_config();
+
+        let log = handle_export_sarif(state, ExportSarifParams::default(), client, config).await;
+        assert_eq!(log.version, "2.1.0");
+        assert!(log.runs[0].results.is_empty());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+        use std::collections::HashMap;
+
+        #[tokio::test]
+        async fn test_handle_export_sarif_unknown_package() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let content = "[dependencies]\nnot-a-crate = \"1\"\nserde = \"1\"\n".to_string();
+
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+            let mut doc =
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result);
+            doc.update_cached_versions(HashMap::from([("serde".into(), "1.0.228".into())]));
+            state.update_document(uri.clone(), doc);
+
+            let (client, config) = create_test_client_and_config();
+            let params = ExportSarifParams {
+                text_document: Some(TextDocumentIdentifier { uri }),
+            };
+            let log = handle_export_sarif(state, params, client, config).await;
+
+            let results = &log.runs[0].results;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].rule_id, "unknown-package");
+            let region = results[0].locations[0].physical_location.region;
+            assert_eq!((region.start_line, region.start_column), (2, 1));
+            assert!(results[0].fixes.is_empty());
+        }
+    }
+}
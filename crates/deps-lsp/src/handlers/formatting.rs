@@ -0,0 +1,193 @@
+//! Alphabetical sorting of dependency sections.
+//!
+//! Two entry points share the ecosystem's
+//! [`sortable_sections`](deps_core::Ecosystem::sortable_sections):
+//!
+//! - a `refactor.rewrite` code action sorting the section under the cursor
+//! - `textDocument/rangeFormatting`, sorting the runs touched by the selection
+//!
+//! Runs, comment handling and separators are described in [`deps_core::sort`].
+
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::sort::{self, SortedRun};
+use deps_core::{Ecosystem, ParseResult};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, DocumentRangeFormattingParams, Range, TextEdit, Uri, WorkspaceEdit,
+};
+
+/// Handles `textDocument/rangeFormatting` by sorting every dependency run
+/// that overlaps the selection.
+pub async fn handle_range_formatting(
+    state: Arc<ServerState>,
+    params: DocumentRangeFormattingParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Option<Vec<TextEdit>> {
+    let uri = &params.text_document.uri;
+
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for range formatting: {:?}", uri);
+        return None;
+    }
+
+    let doc = state.get_document(uri)?;
+    let ecosystem = state.ecosystem_for(&doc)?;
+    let parse_result = doc.parse_result()?;
+
+    Some(
+        ecosystem
+            .sortable_sections(parse_result, &doc.content)
+            .iter()
+            .flat_map(|section| sort::sort_section(&doc.content, section))
+            .filter(|run| overlaps(run, params.range))
+            .filter_map(|run| run.edit)
+            .collect(),
+    )
+}
+
+/// Builds a "Sort ..." action for each unsorted section overlapping `range`.
+///
+/// The action sorts the whole section, not just the run under the cursor.
+pub(crate) fn sort_code_actions(
+    ecosystem: &dyn Ecosystem,
+    parse_result: &dyn ParseResult,
+    content: &str,
+    uri: &Uri,
+    range: Range,
+) -> Vec<CodeAction> {
+    ecosystem
+        .sortable_sections(parse_result, content)
+        .iter()
+        .filter_map(|section| {
+            let runs = sort::sort_section(content, section);
+            if !runs.iter().any(|run| overlaps(run, range)) {
+                return None;
+            }
+            let edits: Vec<TextEdit> = runs.into_iter().filter_map(|run| run.edit).collect();
+            if edits.is_empty() {
+                return None;
+            }
+
+            Some(CodeAction {
+                title: format!("Sort {}", section.label),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), edits)])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+fn overlaps(run: &SortedRun, range: Range) -> bool {
+    run.range.start.line <= range.end.line && range.start.line <= run.range.end.line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+    use tower_lsp_server::ls_types::{FormattingOptions, Position, TextDocumentIdentifier};
+
+    fn params(uri: Uri, range: Range) -> DocumentRangeFormattingParams {
+        DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier { uri },
+            range,
+            options: FormattingOptions::default(),
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_range_formatting_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let (client, config) = create_test_client_and_config();
+
+        let range = Range::new(Position::new(0, 0), Position::new(0, 0));
+        let result = handle_range_formatting(state, params(uri, range), client, config).await;
+        assert!(result.is_none());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        const MANIFEST: &str = "[dependencies]\ntokio = \"1\"\nanyhow = \"1\"\n\n[dev-dependencies]\ninsta = \"1\"\ncriterion = \"0.5\"\n";
+
+        async fn setup() -> (Arc<ServerState>, Uri) {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(MANIFEST, &uri).await.unwrap();
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    MANIFEST.to_string(),
+                    parse_result,
+                ),
+            );
+            (state, uri)
+        }
+
+        #[tokio::test]
+        async fn test_handle_range_formatting_sorts_selected_sections() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+            let edits = handle_range_formatting(state, params(uri, range), client, config)
+                .await
+                .unwrap();
+            assert_eq!(edits.len(), 1);
+            assert_eq!(edits[0].range.start, Position::new(1, 0));
+            assert_eq!(edits[0].new_text, "anyhow = \"1\"\ntokio = \"1\"");
+        }
+
+        #[tokio::test]
+        async fn test_handle_range_formatting_outside_sections() {
+            let (state, uri) = setup().await;
+            let (client, config) = create_test_client_and_config();
+
+            let range = Range::new(Position::new(3, 0), Position::new(3, 0));
+            let edits = handle_range_formatting(state, params(uri, range), client, config)
+                .await
+                .unwrap();
+            assert!(edits.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_sort_code_actions() {
+            let (state, uri) = setup().await;
+            let doc = state.get_document(&uri).unwrap();
+            let ecosystem = state.ecosystem_registry.get(doc.ecosystem_id).unwrap();
+
+            let cursor = Range::new(Position::new(6, 2), Position::new(6, 2));
+            let actions = sort_code_actions(
+                ecosystem.as_ref(),
+                doc.parse_result().unwrap(),
+                &doc.content,
+                &uri,
+                cursor,
+            );
+            assert_eq!(actions.len(), 1);
+            assert_eq!(actions[0].title, "Sort [dev-dependencies]");
+            let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+            assert_eq!(
+                changes[&uri][0].new_text,
+                "criterion = \"0.5\"\ninsta = \"1\""
+            );
+        }
+    }
+}
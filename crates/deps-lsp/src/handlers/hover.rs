@@ -1,12 +1,13 @@
 //! Hover handler using ecosystem trait delegation.
 
+use super::duplicates;
 use crate::config::DepsConfig;
 use crate::document::{ServerState, ensure_document_loaded};
 use deps_core::VersionData;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_lsp_server::Client;
-use tower_lsp_server::ls_types::{Hover, HoverParams};
+use tower_lsp_server::ls_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
 
 /// Handles hover requests using trait-based delegation.
 pub async fn handle_hover(
@@ -17,6 +18,11 @@ pub async fn handle_hover(
 ) -> Option<Hover> {
     let uri = &params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
+    let show_duplicates = state
+        .config_for(&config, uri)
+        .await
+        .diagnostics
+        .duplicate_versions;
 
     // Ensure document is loaded (cold start support)
     if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
@@ -24,19 +30,55 @@ pub async fn handle_hover(
         return None;
     }
 
-    // Single document lookup: extract all needed data at once
-    let doc = state.get_document(uri)?;
-    let ecosystem = state.ecosystem_registry.get(doc.ecosystem_id)?;
-    let parse_result = doc.parse_result()?;
-
-    // Generate hover while holding the lock
-    ecosystem
-        .generate_hover(
-            parse_result,
-            position,
-            VersionData::new(&doc.cached_versions, &doc.resolved_versions),
-        )
-        .await
+    let hover = {
+        // Single document lookup: extract all needed data at once
+        let doc = state.get_document(uri)?;
+        let ecosystem = state.ecosystem_for(&doc)?;
+        let parse_result = doc.parse_result()?;
+
+        // Generate hover while holding the lock
+        ecosystem
+            .generate_hover(
+                parse_result,
+                position,
+                VersionData::new(&doc.cached_versions, &doc.resolved_versions),
+            )
+            .await
+    };
+
+    // Shown alongside the opt-in diagnostic
+    if !show_duplicates {
+        return hover;
+    }
+    let Some(section) = duplicates::duplicate_hover_section(&state, uri, position).await else {
+        return hover;
+    };
+    Some(append_section(hover, &section))
+}
+
+/// Appends a Markdown section to a hover, or creates one if there is none.
+fn append_section(hover: Option<Hover>, section: &str) -> Hover {
+    match hover {
+        Some(Hover {
+            contents: HoverContents::Markup(mut markup),
+            range,
+        }) => {
+            markup.value.push_str("\n\n---\n\n");
+            markup.value.push_str(section);
+            Hover {
+                contents: HoverContents::Markup(markup),
+                range,
+            }
+        }
+        Some(other) => other,
+        None => Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: section.to_string(),
+            }),
+            range: None,
+        },
+    }
 }
 
 #[cfg(test)]
@@ -69,6 +111,26 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_append_section() {
+        let hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "# serde".into(),
+            }),
+            range: None,
+        };
+        let HoverContents::Markup(markup) = append_section(Some(hover), "extra").contents else {
+            panic!("expected markup");
+        };
+        assert_eq!(markup.value, "# serde\n\n---\n\nextra");
+
+        let HoverContents::Markup(markup) = append_section(None, "extra").contents else {
+            panic!("expected markup");
+        };
+        assert_eq!(markup.value, "extra");
+    }
+
     // Cargo-specific tests
     #[cfg(feature = "cargo")]
     mod cargo_tests {
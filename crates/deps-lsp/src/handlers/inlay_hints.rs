@@ -1,15 +1,36 @@
 //! Inlay hints handler using ecosystem trait delegation.
 //!
 //! This handler uses the ecosystem registry to delegate inlay hint generation
-//! to the appropriate ecosystem implementation.
+//! to the appropriate ecosystem implementation. `inlayHint/resolve` then adds
+//! a release tooltip and a click-to-update command to a single hint.
 
 use crate::config::{DepsConfig, InlayHintsConfig};
 use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::hint_template::{HintValues, ReleaseInfo};
+use deps_core::lsp_helpers::markdown_code_span;
 use deps_core::{EcosystemConfig, VersionData};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tower_lsp_server::Client;
-use tower_lsp_server::ls_types::{InlayHint, InlayHintParams};
+use tower_lsp_server::ls_types::{
+    Command, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintParams, InlayHintTooltip,
+    MarkupContent, MarkupKind, Range, Uri,
+};
+
+/// Command applying a version update; arguments are `{ uri, range, version }`
+/// with `version` the replacement text of `range`.
+pub const UPDATE_VERSION_COMMAND: &str = "deps-lsp.updateVersion";
+
+/// Data attached to version hints so `inlayHint/resolve` can find the
+/// dependency again.
+#[derive(Debug, Serialize, Deserialize)]
+struct HintData {
+    uri: Uri,
+    name: String,
+}
 
 /// Handles inlay hint requests using trait-based delegation.
 ///
@@ -43,7 +64,7 @@ pub async fn handle_inlay_hints(
         }
     };
 
-    let ecosystem = match state.ecosystem_registry.get(doc.ecosystem_id) {
+    let ecosystem = match state.ecosystem_for(&doc) {
         Some(e) => e,
         None => {
             tracing::warn!("Ecosystem not found: {}", doc.ecosystem_id);
@@ -57,25 +78,243 @@ pub async fn handle_inlay_hints(
     };
 
     // Get loading indicator config
-    let loading_config = { full_config.read().await.loading_indicator.clone() };
+    let loading_config = state.config_for(&full_config, uri).await.loading_indicator;
 
     let ecosystem_config = EcosystemConfig {
         show_up_to_date_hints: true,
         up_to_date_text: config.up_to_date_text.clone(),
         needs_update_text: config.needs_update_text.clone(),
+        show_locked_versions: config.show_locked,
         loading_text: loading_config.loading_text,
         show_loading_hints: loading_config.enabled && loading_config.fallback_to_hints,
     };
 
+    let mut versions = VersionData::new(&doc.cached_versions, &doc.resolved_versions);
+    if !doc.release_history.is_empty() {
+        versions = versions.with_releases(&doc.release_history);
+    }
+
     // Generate hints while holding the lock
-    ecosystem
-        .generate_inlay_hints(
-            parse_result,
-            VersionData::new(&doc.cached_versions, &doc.resolved_versions),
-            doc.loading_state,
-            &ecosystem_config,
-        )
-        .await
+    let mut hints = ecosystem
+        .generate_inlay_hints(parse_result, versions, doc.loading_state, &ecosystem_config)
+        .await;
+
+    // Remember the document so resolve can look the dependency up again
+    for hint in &mut hints {
+        hint.data = hint
+            .data
+            .take()
+            .and_then(|data| data.get("name")?.as_str().map(str::to_string))
+            .and_then(|name| {
+                serde_json::to_value(HintData {
+                    uri: uri.clone(),
+                    name,
+                })
+                .ok()
+            });
+    }
+    hints
+}
+
+/// What resolve needs to know about the hinted dependency.
+struct HintTarget {
+    ecosystem: Arc<dyn deps_core::Ecosystem>,
+    registry: Arc<dyn deps_core::Registry>,
+    requirement: String,
+    version_range: Range,
+    latest: Option<String>,
+    locked: Option<String>,
+    releases: Option<Vec<ReleaseInfo>>,
+}
+
+/// Handles `inlayHint/resolve`.
+///
+/// Adds a markdown tooltip with the release date of the latest version, the
+/// locked and compatible versions and a link to the release, and, when the
+/// dependency is outdated, turns the label into a part that runs
+/// [`UPDATE_VERSION_COMMAND`]. Hints that are not version hints, or whose
+/// dependency is gone, are returned unchanged.
+pub async fn handle_inlay_hint_resolve(
+    state: Arc<ServerState>,
+    mut hint: InlayHint,
+    full_config: Arc<RwLock<DepsConfig>>,
+) -> InlayHint {
+    let Some(data) = hint
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<HintData>(data).ok())
+    else {
+        return hint;
+    };
+    let Some(target) = find_target(&state, &data) else {
+        return hint;
+    };
+
+    let registry = Arc::clone(&target.registry);
+    let releases = match target.releases {
+        Some(releases) => releases,
+        None => {
+            let timeout = state
+                .config_for(&full_config, &data.uri)
+                .await
+                .cache
+                .fetch_timeout_secs;
+            match tokio::time::timeout(
+                Duration::from_secs(timeout),
+                registry.get_versions(&data.name),
+            )
+            .await
+            {
+                Ok(Ok(versions)) => versions
+                    .iter()
+                    .map(|v| ReleaseInfo::from_version(v.as_ref()))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        }
+    };
+
+    let formatter = target.ecosystem.formatter();
+    let values = HintValues::compute(
+        &target.requirement,
+        target.latest.as_deref(),
+        target.locked.as_deref(),
+        Some(&releases),
+        formatter,
+    );
+    let published = target.latest.as_deref().and_then(|latest| {
+        releases
+            .iter()
+            .find(|r| r.version == latest)
+            .and_then(|r| r.published_at.as_deref())
+    });
+    let link = match target
+        .latest
+        .as_deref()
+        .and_then(|latest| registry.release_url(&data.name, latest))
+    {
+        Some(url) => ("Release notes", url),
+        None => ("Package page", registry.package_url(&data.name)),
+    };
+
+    let mut tooltip = resolve_tooltip(&data.name, &values, published, (link.0, &link.1));
+    // Keep the explanation of locked-version hints in front
+    if let Some(InlayHintTooltip::String(lead)) = &hint.tooltip {
+        tooltip = format!("{lead}\n\n{tooltip}");
+    }
+    hint.tooltip = Some(InlayHintTooltip::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: tooltip,
+    }));
+
+    let Some(latest) = target.latest else {
+        return hint;
+    };
+    let up_to_date = match &target.locked {
+        Some(locked) => *locked == latest,
+        None => formatter.is_requirement_up_to_date(&target.requirement, &latest),
+    };
+    if up_to_date {
+        return hint;
+    }
+
+    let label = match hint.label {
+        InlayHintLabel::String(text) => text,
+        InlayHintLabel::LabelParts(parts) => parts.into_iter().map(|p| p.value).collect(),
+    };
+    hint.label = InlayHintLabel::LabelParts(vec![InlayHintLabelPart {
+        value: label,
+        tooltip: None,
+        location: None,
+        command: Some(Command {
+            title: format!("Update {} to {latest}", data.name),
+            command: UPDATE_VERSION_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::json!({
+                "uri": data.uri,
+                "range": target.version_range,
+                "version": formatter.format_version_for_text_edit(&latest),
+            })]),
+        }),
+    }]);
+    hint
+}
+
+fn find_target(state: &ServerState, data: &HintData) -> Option<HintTarget> {
+    let doc = state.get_document(&data.uri)?;
+    let ecosystem = state.ecosystem_for(&doc)?;
+    let parse_result = doc.parse_result()?;
+    let registry = ecosystem.registry_for(parse_result);
+    let dep = parse_result
+        .dependencies()
+        .into_iter()
+        .find(|d| d.name() == data.name)?;
+
+    let normalized = ecosystem.formatter().normalize_package_name(&data.name);
+    let lookup = |map: &std::collections::HashMap<String, String>| {
+        map.get(&normalized)
+            .or_else(|| map.get(&data.name))
+            .cloned()
+    };
+
+    Some(HintTarget {
+        requirement: dep.version_requirement().unwrap_or("").to_string(),
+        version_range: dep.version_range()?,
+        latest: lookup(&doc.cached_versions),
+        locked: lookup(&doc.resolved_versions),
+        releases: doc
+            .release_history
+            .get(&normalized)
+            .or_else(|| doc.release_history.get(&data.name))
+            .cloned(),
+        ecosystem,
+        registry,
+    })
+}
+
+/// Builds the markdown tooltip of a resolved hint.
+fn resolve_tooltip(
+    name: &str,
+    values: &HintValues<'_>,
+    published: Option<&str>,
+    (link_text, url): (&str, &str),
+) -> String {
+    let mut tooltip = format!("**{}**", markdown_code_span(name));
+
+    if let Some(latest) = values.latest {
+        let _ = write!(tooltip, "\n\nLatest: {}", markdown_code_span(latest));
+        if let Some(kind) = values.kind {
+            let _ = write!(tooltip, " ({} update)", kind.as_str());
+        }
+        if let Some(published) = published {
+            let date = published.get(..10).unwrap_or(published);
+            let _ = write!(tooltip, ", released {date}");
+            match values.age.as_deref() {
+                Some("today") => tooltip.push_str(" (today)"),
+                Some(age) => {
+                    let _ = write!(tooltip, " ({age} ago)");
+                }
+                None => {}
+            }
+        }
+    }
+
+    let mut details = Vec::new();
+    if let Some(locked) = values.locked {
+        details.push(format!("Locked: {}", markdown_code_span(locked)));
+    }
+    if let Some(compatible) = values.compatible {
+        details.push(format!("Compatible: {}", markdown_code_span(compatible)));
+    }
+    if let Some(count) = values.count.filter(|&c| c > 0) {
+        let plural = if count == 1 { "" } else { "s" };
+        details.push(format!("{count} newer stable release{plural}"));
+    }
+    if !details.is_empty() {
+        let _ = write!(tooltip, "\n\n{}", details.join(" · "));
+    }
+
+    let _ = write!(tooltip, "\n\n[{link_text}]({url})");
+    tooltip
 }
 
 #[cfg(test)]
@@ -94,6 +333,7 @@ mod tests {
             enabled: false,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked: false,
         };
 
         assert!(!config.enabled);
@@ -107,6 +347,7 @@ mod tests {
             enabled: false,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked: false,
         };
 
         let params = InlayHintParams {
@@ -131,6 +372,7 @@ mod tests {
             enabled: true,
             up_to_date_text: "✅".to_string(),
             needs_update_text: "❌ {}".to_string(),
+            show_locked: false,
         };
 
         let params = InlayHintParams {
@@ -147,12 +389,141 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_resolve_tooltip() {
+        let values = HintValues {
+            latest: Some("1.2.0"),
+            locked: Some("1.0.0"),
+            compatible: Some("1.2.0"),
+            kind: Some(deps_core::hint_template::UpdateKind::Minor),
+            age: Some("3w".to_string()),
+            count: Some(1),
+        };
+        let tooltip = resolve_tooltip(
+            "serde",
+            &values,
+            Some("2024-02-26T10:00:00Z"),
+            ("Release notes", "https://example.com/serde/1.2.0"),
+        );
+
+        assert_eq!(
+            tooltip,
+            "**`serde`**\n\nLatest: `1.2.0` (minor update), released 2024-02-26 (3w ago)\n\n\
+             Locked: `1.0.0` · Compatible: `1.2.0` · 1 newer stable release\n\n\
+             [Release notes](https://example.com/serde/1.2.0)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hint_resolve_without_data() {
+        let state = Arc::new(ServerState::new());
+        let hint = InlayHint {
+            position: tower_lsp_server::ls_types::Position::new(0, 0),
+            label: InlayHintLabel::String("⏳".to_string()),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        };
+
+        let (_, full_config) = create_test_client_and_config();
+        let resolved = handle_inlay_hint_resolve(state, hint, full_config).await;
+        assert!(resolved.tooltip.is_none());
+        assert!(matches!(resolved.label, InlayHintLabel::String(ref s) if s == "⏳"));
+    }
+
     // Cargo-specific tests
     #[cfg(feature = "cargo")]
     mod cargo_tests {
         use super::*;
         use crate::document::DocumentState;
 
+        #[tokio::test]
+        async fn test_inlay_hint_templates_and_resolve() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+            let config = InlayHintsConfig {
+                enabled: true,
+                up_to_date_text: "✅".to_string(),
+                needs_update_text: "{locked} → {latest} ({kind}, {count} newer)".to_string(),
+                show_locked: false,
+            };
+
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let content = r#"[dependencies]
+serde = "1.0.0"
+"#
+            .to_string();
+            let parse_result = ecosystem.parse_manifest(&content, &uri).await.unwrap();
+
+            let mut doc_state =
+                DocumentState::new_from_parse_result(EcosystemId::Cargo, content, parse_result);
+            doc_state.set_loaded();
+            doc_state
+                .cached_versions
+                .insert("serde".to_string(), "1.1.0".to_string());
+            doc_state
+                .resolved_versions
+                .insert("serde".to_string(), "1.0.0".to_string());
+            let release = |version: &str| ReleaseInfo {
+                version: version.to_string(),
+                yanked: false,
+                prerelease: false,
+                published_at: None,
+            };
+            doc_state.update_release_history(std::collections::HashMap::from([(
+                "serde".to_string(),
+                vec![release("1.1.0"), release("1.0.1"), release("1.0.0")],
+            )]));
+            state.update_document(uri.clone(), doc_state);
+
+            let params = InlayHintParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                work_done_progress_params: Default::default(),
+                range: Range::new(
+                    tower_lsp_server::ls_types::Position::new(0, 0),
+                    tower_lsp_server::ls_types::Position::new(100, 0),
+                ),
+            };
+
+            let (client, full_config) = create_test_client_and_config();
+            let hints = handle_inlay_hints(
+                Arc::clone(&state),
+                params,
+                &config,
+                client,
+                Arc::clone(&full_config),
+            )
+            .await;
+            assert_eq!(hints.len(), 1);
+            assert!(matches!(
+                &hints[0].label,
+                InlayHintLabel::String(label) if label == "1.0.0 → 1.1.0 (minor, 2 newer)"
+            ));
+
+            let resolved = handle_inlay_hint_resolve(state, hints[0].clone(), full_config).await;
+            let Some(InlayHintTooltip::MarkupContent(tooltip)) = resolved.tooltip else {
+                panic!("expected a markdown tooltip");
+            };
+            assert!(tooltip.value.contains("Locked: `1.0.0`"));
+            assert!(
+                tooltip
+                    .value
+                    .contains("https://crates.io/crates/serde/1.1.0")
+            );
+
+            let InlayHintLabel::LabelParts(parts) = resolved.label else {
+                panic!("expected label parts");
+            };
+            let command = parts[0].command.as_ref().unwrap();
+            assert_eq!(command.command, UPDATE_VERSION_COMMAND);
+            let args = &command.arguments.as_ref().unwrap()[0];
+            assert_eq!(args["version"], "1.1.0");
+            assert_eq!(args["uri"], uri.as_str());
+        }
+
         #[tokio::test]
         async fn test_handle_inlay_hints() {
             let state = Arc::new(ServerState::new());
@@ -161,6 +532,7 @@ mod tests {
                 enabled: true,
                 up_to_date_text: "✅".to_string(),
                 needs_update_text: "❌ {}".to_string(),
+                show_locked: false,
             };
 
             let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
@@ -200,6 +572,7 @@ serde = "1.0.0"
                 enabled: true,
                 up_to_date_text: "✅".to_string(),
                 needs_update_text: "❌ {}".to_string(),
+                show_locked: false,
             };
 
             let doc_state =
@@ -228,6 +601,7 @@ serde = "1.0.0"
                 enabled: true,
                 up_to_date_text: "OK".to_string(),
                 needs_update_text: "UPDATE: {}".to_string(),
+                show_locked: false,
             };
 
             let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
@@ -274,6 +648,7 @@ serde = "1.0.0"
                 enabled: true,
                 up_to_date_text: "✅".to_string(),
                 needs_update_text: "❌ {}".to_string(),
+                show_locked: false,
             };
 
             let ecosystem = state.ecosystem_registry.get("npm").unwrap();
@@ -317,6 +692,7 @@ serde = "1.0.0"
                 enabled: true,
                 up_to_date_text: "✅".to_string(),
                 needs_update_text: "❌ {}".to_string(),
+                show_locked: false,
             };
 
             let ecosystem = state.ecosystem_registry.get("pypi").unwrap();
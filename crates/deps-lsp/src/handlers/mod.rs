@@ -4,10 +4,19 @@
 //! deps-lsp. Each handler is responsible for a specific LSP feature:
 //!
 //! - [`completion`]: Package name and version completions
+//! - [`dependency_tree`]: Resolved dependency graph and "why is this here?" paths
+//! - [`definition`]: Jump from inherited dependencies to their declaration
 //! - [`hover`]: Hover documentation with crate metadata
 //! - [`inlay_hints`]: Inline version annotations
+//! - [`references`]: Manifests declaring the dependency under the cursor
+//! - [`workspace_symbol`]: Package search across all workspace manifests
+//! - [`duplicates`]: Packages resolved at several versions in the lock file
+//! - [`checksums`]: Lock file checksums verified against the registry
 //! - [`diagnostics`]: Outdated/yanked version warnings
+//! - [`export_sarif`]: Diagnostics exported as a SARIF log
 //! - [`code_actions`]: Quick fixes (e.g., "Update to latest version")
+//! - [`formatting`]: Alphabetical sorting of dependency sections
+//! - [`add_dependency`]: The `deps.addDependency` command
 //!
 //! # Handler Architecture
 //!
@@ -31,8 +40,18 @@
 //! // Handlers use state.get_document() and ecosystem_registry
 //! ```
 
+pub mod add_dependency;
+pub mod checksums;
 pub mod code_actions;
 pub mod completion;
+pub mod definition;
+pub mod dependency_tree;
 pub mod diagnostics;
+pub mod duplicates;
+pub mod export_sarif;
+pub mod formatting;
 pub mod hover;
 pub mod inlay_hints;
+pub mod references;
+pub mod status;
+pub mod workspace_symbol;
@@ -0,0 +1,175 @@
+//! Find-references handler backed by the workspace index.
+//!
+//! Lists every manifest in the workspace that declares the dependency under
+//! the cursor. Names are compared after ecosystem normalization, so PyPI's
+//! `Django` and `django` are the same package.
+
+use crate::config::DepsConfig;
+use crate::document::{ServerState, ensure_document_loaded};
+use deps_core::lsp_helpers::find_dependency_at;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{Location, ReferenceParams};
+
+/// Handles references requests using the workspace index.
+///
+/// When the client sets `includeDeclaration` to `false`, the declaration under
+/// the cursor is left out of the result.
+pub async fn handle_references(
+    state: Arc<ServerState>,
+    params: ReferenceParams,
+    client: Client,
+    config: Arc<RwLock<DepsConfig>>,
+) -> Option<Vec<Location>> {
+    let uri = &params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+
+    // Ensure document is loaded (cold start support)
+    if !ensure_document_loaded(uri, Arc::clone(&state), client, config).await {
+        tracing::warn!("Could not load document for references: {:?}", uri);
+        return None;
+    }
+
+    let doc = state.get_document(uri)?;
+    let ecosystem = state.ecosystem_for(&doc)?;
+    let parse_result = doc.parse_result()?;
+    let dep = find_dependency_at(parse_result, position)?;
+
+    let normalized = ecosystem.formatter().normalize_package_name(dep.name());
+    let own_range = dep.name_range();
+    let mut locations = state.workspace_index.references(doc.ecosystem, &normalized);
+
+    if !params.context.include_declaration {
+        locations.retain(|loc| !(loc.uri == *uri && loc.range == own_range));
+    }
+
+    Some(locations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_client_and_config;
+    use tower_lsp_server::ls_types::{
+        Position, ReferenceContext, TextDocumentIdentifier, TextDocumentPositionParams, Uri,
+    };
+
+    fn reference_params(
+        uri: Uri,
+        position: Position,
+        include_declaration: bool,
+    ) -> ReferenceParams {
+        ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_references_missing_document() {
+        let state = Arc::new(ServerState::new());
+        let uri = deps_core::test_util::test_uri("/test/Cargo.toml");
+        let (client, config) = create_test_client_and_config();
+
+        let result = handle_references(
+            state,
+            reference_params(uri, Position::new(0, 0), true),
+            client,
+            config,
+        )
+        .await;
+        assert!(result.is_none());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+        use crate::document::DocumentState;
+        use deps_core::EcosystemId;
+
+        async fn open(state: &ServerState, uri: &Uri, content: &str) {
+            let ecosystem = state.ecosystem_registry.get("cargo").unwrap();
+            let parse_result = ecosystem.parse_manifest(content, uri).await.unwrap();
+            state.workspace_index.record(
+                uri,
+                EcosystemId::Cargo,
+                ecosystem.as_ref(),
+                parse_result.as_ref(),
+            );
+            state.update_document(
+                uri.clone(),
+                DocumentState::new_from_parse_result(
+                    EcosystemId::Cargo,
+                    content.to_string(),
+                    parse_result,
+                ),
+            );
+        }
+
+        #[tokio::test]
+        async fn test_handle_references_across_manifests() {
+            let state = Arc::new(ServerState::new());
+            let a = deps_core::test_util::test_uri("/ws/a/Cargo.toml");
+            let b = deps_core::test_util::test_uri("/ws/b/Cargo.toml");
+            open(&state, &a, "[dependencies]\nserde = \"1.0\"\n").await;
+            state
+                .workspace_index
+                .index_manifest(
+                    &state.ecosystem_registry,
+                    &b,
+                    "[dependencies]\ntokio = \"1\"\nserde = \"1.0\"\n",
+                )
+                .await;
+
+            let (client, config) = create_test_client_and_config();
+            let result = handle_references(
+                Arc::clone(&state),
+                reference_params(a.clone(), Position::new(1, 2), true),
+                client.clone(),
+                Arc::clone(&config),
+            )
+            .await
+            .unwrap();
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0].uri, a);
+            assert_eq!(result[1].uri, b);
+            assert_eq!(result[1].range.start, Position::new(2, 0));
+
+            let result = handle_references(
+                state,
+                reference_params(a, Position::new(1, 2), false),
+                client,
+                config,
+            )
+            .await
+            .unwrap();
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].uri, b);
+        }
+
+        #[tokio::test]
+        async fn test_handle_references_outside_dependency() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/ws/Cargo.toml");
+            open(&state, &uri, "[dependencies]\nserde = \"1.0\"\n").await;
+
+            let (client, config) = create_test_client_and_config();
+            let result = handle_references(
+                state,
+                reference_params(uri, Position::new(0, 3), true),
+                client,
+                config,
+            )
+            .await;
+            assert!(result.is_none());
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Server health reported through `deps/status`.
+//!
+//! The same snapshot is answered to the `deps/status` request and pushed as
+//! a `$/deps/statusNotification` after every background fetch, so clients
+//! can show a status bar item or debug slow registries without enabling
+//! trace logs.
+
+use crate::document::{LoadingState, ServerState};
+use crate::metrics::EcosystemFetchStats;
+use deps_core::CacheStats;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::notification::Notification;
+
+/// Method name of the status request.
+pub const STATUS_METHOD: &str = "deps/status";
+
+/// Notification carrying a [`StatusResult`], sent after background fetches.
+#[derive(Debug)]
+pub enum StatusNotification {}
+
+impl Notification for StatusNotification {
+    type Params = StatusResult;
+    const METHOD: &'static str = "$/deps/statusNotification";
+}
+
+/// Open documents by loading state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentCounts {
+    pub open: usize,
+    pub idle: usize,
+    pub loading: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+/// Lock file cache statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockFileCacheStats {
+    /// Parsed lock files currently cached.
+    pub entries: usize,
+}
+
+/// Result of `deps/status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusResult {
+    /// Registry fetch statistics, sorted by ecosystem.
+    pub ecosystems: Vec<EcosystemFetchStats>,
+    pub http_cache: CacheStats,
+    pub lockfile_cache: LockFileCacheStats,
+    pub documents: DocumentCounts,
+    /// Background fetch tasks still running.
+    pub background_tasks: usize,
+}
+
+/// Collects the current status of the server.
+pub async fn collect_status(state: &ServerState) -> StatusResult {
+    let mut documents = DocumentCounts::default();
+    for doc in &state.documents {
+        documents.open += 1;
+        match doc.loading_state {
+            LoadingState::Idle => documents.idle += 1,
+            LoadingState::Loading => documents.loading += 1,
+            LoadingState::Loaded => documents.loaded += 1,
+            LoadingState::Failed => documents.failed += 1,
+        }
+    }
+
+    StatusResult {
+        ecosystems: state.fetch_metrics.snapshot(),
+        http_cache: state.cache.stats(),
+        lockfile_cache: LockFileCacheStats {
+            entries: state.lockfile_cache.len(),
+        },
+        documents,
+        background_tasks: state.active_task_count().await,
+    }
+}
+
+/// Handles `deps/status`.
+pub async fn handle_status(state: Arc<ServerState>) -> StatusResult {
+    collect_status(&state).await
+}
+
+/// Sends a `$/deps/statusNotification` with the current status.
+pub async fn notify_status(state: &ServerState, client: &Client) {
+    let status = collect_status(state).await;
+    client.send_notification::<StatusNotification>(status).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::DocumentState;
+    use deps_core::EcosystemId;
+    use deps_core::test_util::test_uri;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_collect_status() {
+        let state = ServerState::new();
+        let mut loading =
+            DocumentState::new_without_parse_result(EcosystemId::Cargo, String::new());
+        loading.set_loading();
+        state.documents.insert(test_uri("/a/Cargo.toml"), loading);
+        state.documents.insert(
+            test_uri("/b/Cargo.toml"),
+            DocumentState::new_without_parse_result(EcosystemId::Cargo, String::new()),
+        );
+        state
+            .fetch_metrics
+            .record("cargo", Duration::from_millis(12), true);
+
+        let status = collect_status(&state).await;
+        assert_eq!(
+            status.documents,
+            DocumentCounts {
+                open: 2,
+                idle: 1,
+                loading: 1,
+                loaded: 0,
+                failed: 0,
+            }
+        );
+        assert_eq!(status.ecosystems.len(), 1);
+        assert_eq!(status.ecosystems[0].p50_ms, Some(12));
+        assert_eq!(status.http_cache, CacheStats::default());
+        assert_eq!(status.lockfile_cache.entries, 0);
+        assert_eq!(status.background_tasks, 0);
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let status = StatusResult {
+            ecosystems: vec![],
+            http_cache: CacheStats::default(),
+            lockfile_cache: LockFileCacheStats::default(),
+            documents: DocumentCounts::default(),
+            background_tasks: 3,
+        };
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["backgroundTasks"], 3);
+        assert_eq!(json["httpCache"]["hits"], 0);
+        assert_eq!(json["lockfileCache"]["entries"], 0);
+        assert_eq!(json["documents"]["open"], 0);
+    }
+}
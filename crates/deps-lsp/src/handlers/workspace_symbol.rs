@@ -0,0 +1,110 @@
+//! Workspace symbol handler backed by the workspace index.
+//!
+//! Every dependency declaration in an indexed manifest is a `Package` symbol,
+//! so "Go to Symbol in Workspace" can jump to any place a package is used.
+
+use crate::config::DepsConfig;
+use crate::document::ServerState;
+use std::sync::Arc;
+use tower_lsp_server::ls_types::{
+    Location, OneOf, SymbolKind, WorkspaceSymbol, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+
+/// Maximum number of symbols returned for a single query.
+const MAX_SYMBOLS: usize = 256;
+
+/// Handles workspace symbol requests.
+///
+/// Matches the query as a case-insensitive substring of the package name. The
+/// symbol's container is the ecosystem and version requirement, which tells
+/// apart the same package declared in several manifests. Manifests whose
+/// ecosystem is disabled for them, in `config` or a project file, are left
+/// out.
+pub fn handle_workspace_symbol(
+    state: &Arc<ServerState>,
+    config: &DepsConfig,
+    params: &WorkspaceSymbolParams,
+) -> Option<WorkspaceSymbolResponse> {
+    let symbols = state
+        .workspace_index
+        .search(&params.query, MAX_SYMBOLS)
+        .into_iter()
+        .filter(|(uri, dep)| {
+            state
+                .project_configs
+                .resolve(config, uri)
+                .is_ecosystem_enabled(dep.ecosystem.id())
+        })
+        .map(|(uri, dep)| WorkspaceSymbol {
+            name: dep.name,
+            kind: SymbolKind::PACKAGE,
+            tags: None,
+            container_name: Some(match dep.version_requirement {
+                Some(req) => format!("{} {}", dep.ecosystem, req),
+                None => dep.ecosystem.to_string(),
+            }),
+            location: OneOf::Left(Location::new(uri, dep.name_range)),
+            data: None,
+        })
+        .collect();
+
+    Some(WorkspaceSymbolResponse::Nested(symbols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol_params(query: &str) -> WorkspaceSymbolParams {
+        WorkspaceSymbolParams {
+            query: query.into(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_handle_workspace_symbol_empty_index() {
+        let state = Arc::new(ServerState::new());
+        let Some(WorkspaceSymbolResponse::Nested(symbols)) =
+            handle_workspace_symbol(&state, &DepsConfig::default(), &symbol_params("serde"))
+        else {
+            panic!("expected nested symbols");
+        };
+        assert!(symbols.is_empty());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_handle_workspace_symbol_matches_packages() {
+            let state = Arc::new(ServerState::new());
+            let uri = deps_core::test_util::test_uri("/ws/Cargo.toml");
+            state
+                .workspace_index
+                .index_manifest(
+                    &state.ecosystem_registry,
+                    &uri,
+                    "[dependencies]\nserde = \"1.0\"\nserde_json = \"1.0\"\ntokio = \"1\"\n",
+                )
+                .await;
+
+            let Some(WorkspaceSymbolResponse::Nested(symbols)) =
+                handle_workspace_symbol(&state, &DepsConfig::default(), &symbol_params("serde"))
+            else {
+                panic!("expected nested symbols");
+            };
+            assert_eq!(symbols.len(), 2);
+            assert_eq!(symbols[0].name, "serde");
+            assert_eq!(symbols[0].kind, SymbolKind::PACKAGE);
+            assert_eq!(symbols[0].container_name.as_deref(), Some("cargo 1.0"));
+            let OneOf::Left(location) = &symbols[0].location else {
+                panic!("expected a full location");
+            };
+            assert_eq!(location.uri, uri);
+            assert_eq!(location.range.start.line, 1);
+        }
+    }
+}
@@ -1,13 +1,21 @@
+pub mod check;
 pub mod config;
 pub mod document;
 pub mod file_watcher;
 pub mod handlers;
+pub mod metrics;
 pub mod progress;
+pub mod project_config;
+pub mod sarif;
 pub mod server;
+pub mod transport;
+pub mod update;
+pub mod workspace_index;
 
 #[cfg(test)]
 mod test_utils;
 
+use deps_core::workspace::WorkspaceCache;
 use std::sync::Arc;
 
 pub use deps_core::{DepsError, EcosystemRegistry, HttpCache, Result};
@@ -203,7 +211,21 @@ ecosystem!(
 
 /// Registers all enabled ecosystems.
 pub fn register_ecosystems(registry: &EcosystemRegistry, cache: Arc<HttpCache>) {
-    register!("cargo", CargoEcosystem, registry, &cache);
+    register_ecosystems_with_workspaces(registry, cache, &Arc::default());
+}
+
+/// Registers all enabled ecosystems, keeping the workspaces they load in
+/// `workspaces`.
+#[cfg_attr(not(feature = "cargo"), allow(unused_variables))]
+pub fn register_ecosystems_with_workspaces(
+    registry: &EcosystemRegistry,
+    cache: Arc<HttpCache>,
+    workspaces: &Arc<WorkspaceCache>,
+) {
+    #[cfg(feature = "cargo")]
+    registry.register(Arc::new(
+        CargoEcosystem::new(Arc::clone(&cache)).with_workspace_cache(Arc::clone(workspaces)),
+    ));
     register!("npm", NpmEcosystem, registry, &cache);
     register!("pypi", PypiEcosystem, registry, &cache);
     register!("go", GoEcosystem, registry, &cache);
@@ -1,6 +1,9 @@
-use deps_lsp::server::Backend;
+use deps_lsp::check::{self, CheckOptions};
+use deps_lsp::transport::{self, ServeOptions};
+use deps_lsp::update::{self, UpdateOptions};
+use deps_lsp::{EcosystemRegistry, HttpCache};
 use std::env;
-use tower_lsp_server::{LspService, Server};
+use std::sync::Arc;
 use tracing_subscriber::EnvFilter;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -22,16 +25,59 @@ fn print_help() {
     eprintln!("deps-lsp {VERSION} - Language Server for dependency management");
     eprintln!();
     eprintln!("Usage: deps-lsp [OPTIONS]");
+    eprintln!("       deps-lsp check [PATH] [CHECK OPTIONS]");
+    eprintln!("       deps-lsp update [PATH] [UPDATE OPTIONS]");
     eprintln!();
     eprintln!("Options:");
-    eprintln!("  --stdio     Use stdio transport (default)");
-    eprintln!("  --version   Print version information");
-    eprintln!("  --help      Print this help message");
+    eprintln!("  --stdio                Use stdio transport (default)");
+    eprintln!("  --listen <[ADDR:]PORT> Accept unauthenticated TCP connections");
+    eprintln!("                         (ADDR defaults to 127.0.0.1)");
+    eprintln!("  --socket <PATH>        Accept connections on a Unix domain socket");
+    eprintln!("  --connect <TARGET>     Connect to a client at ADDR:PORT or a socket PATH");
+    eprintln!("  --idle-timeout <SECS>  Seconds to wait for a new client after the last");
+    eprintln!("                         one disconnects (default: 10)");
+    eprintln!("  --version              Print version information");
+    eprintln!("  --help                 Print this help message");
+    eprintln!();
+    eprintln!("Check options:");
+    eprintln!("  --format <FORMAT>      text, json, or sarif (default: text)");
+    eprintln!("  --fail-on <LIST>       Comma-separated conditions exiting with 1:");
+    eprintln!("                         any, outdated, major, unknown, yanked, checksum,");
+    eprintln!("                         never (default: yanked)");
+    eprintln!("  --timeout <SECS>       Per-package fetch timeout (default: 5)");
+    eprintln!("  --verify-checksums     Compare lock file checksums with the registry");
+    eprintln!();
+    eprintln!("Update options:");
+    eprintln!("  --mode <MODE>          compatible, minor, or latest (default: compatible)");
+    eprintln!("  --ecosystem <IDS>      Only update these ecosystems (e.g. cargo,npm)");
+    eprintln!("  --ignore <PATTERN>     Never update matching packages (repeatable)");
+    eprintln!("  --config <PATH>        JSON settings file; its \"update\" section applies");
+    eprintln!("  --dry-run              Print the diff without writing files");
+    eprintln!("  --timeout <SECS>       Per-package fetch timeout (default: 5)");
 }
 
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
 
+    if let Some(command @ ("check" | "update")) = args.first().map(String::as_str) {
+        if args[1..].iter().any(|arg| arg == "--help" || arg == "-h") {
+            print_help();
+            return;
+        }
+        let rest = args[1..].iter().cloned();
+        let code = match command {
+            "check" => {
+                CheckOptions::parse(rest).map(|options| runtime().block_on(run_check(options)))
+            }
+            _ => UpdateOptions::parse(rest).map(|options| runtime().block_on(run_update(options))),
+        };
+        std::process::exit(code.unwrap_or_else(|e| {
+            eprintln!("{e}");
+            eprintln!("Run 'deps-lsp --help' for usage information.");
+            check::EXIT_ERROR
+        }));
+    }
+
     // Handle CLI flags
     for arg in &args {
         match arg.as_str() {
@@ -43,27 +89,102 @@ fn main() {
                 print_help();
                 return;
             }
-            "--stdio" => {
-                // Default mode, continue
-            }
-            arg if arg.starts_with('-') => {
-                eprintln!("Unknown option: {arg}");
-                eprintln!("Run 'deps-lsp --help' for usage information.");
-                std::process::exit(1);
-            }
             _ => {}
         }
     }
 
+    let options = ServeOptions::parse(args).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("Run 'deps-lsp --help' for usage information.");
+        std::process::exit(1);
+    });
+    if let Err(e) = runtime().block_on(serve(options)) {
+        eprintln!("deps-lsp: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn runtime() -> tokio::runtime::Runtime {
     tokio::runtime::Builder::new_multi_thread()
         .thread_stack_size(WORKER_THREAD_STACK_SIZE)
         .enable_all()
         .build()
         .expect("failed to build tokio runtime")
-        .block_on(serve());
 }
 
-async fn serve() {
+/// Logs warnings to stderr for the headless subcommands.
+fn init_cli_tracing() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+}
+
+fn cli_registry() -> EcosystemRegistry {
+    let registry = EcosystemRegistry::new();
+    deps_lsp::register_ecosystems(&registry, Arc::new(HttpCache::new()));
+    registry
+}
+
+async fn run_check(options: CheckOptions) -> i32 {
+    init_cli_tracing();
+
+    if !options.root.is_dir() {
+        eprintln!("Not a directory: {}", options.root.display());
+        return check::EXIT_ERROR;
+    }
+
+    let registry = cli_registry();
+    let report = check::run_check(&registry, &options).await;
+    print!("{}", report.render(options.format));
+    report.exit_code(&options.fail_on)
+}
+
+async fn run_update(options: UpdateOptions) -> i32 {
+    init_cli_tracing();
+
+    if !options.path.exists() {
+        eprintln!("No such file or directory: {}", options.path.display());
+        return check::EXIT_ERROR;
+    }
+    let config = match options.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return check::EXIT_ERROR;
+        }
+    };
+
+    let registry = cli_registry();
+    let plan = update::plan_updates(&registry, &options, &config).await;
+    for manifest in &plan.manifests {
+        print!("{}", manifest.diff());
+    }
+    for error in &plan.errors {
+        eprintln!("error: {error}");
+    }
+
+    if options.dry_run {
+        println!("{} (dry run, nothing written)", update::summary(&plan));
+        return check::EXIT_OK;
+    }
+
+    let failures = update::write_updates(&registry, &plan);
+    for (path, error) in &failures {
+        eprintln!("error: {}: {error}", path.display());
+    }
+    println!("{}", update::summary(&plan));
+    if failures.is_empty() {
+        check::EXIT_OK
+    } else {
+        check::EXIT_ERROR
+    }
+}
+
+async fn serve(options: ServeOptions) -> std::io::Result<()> {
     // Initialize tracing - write to stderr to avoid interfering with LSP on stdout
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -75,10 +196,5 @@ async fn serve() {
 
     tracing::info!("Starting deps-lsp v{VERSION}");
 
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
-    let (service, socket) = LspService::new(Backend::new);
-
-    Server::new(stdin, stdout, socket).serve(service).await;
+    transport::serve(&options).await
 }
@@ -0,0 +1,258 @@
+//! Registry fetch metrics reported by `deps/status`.
+//!
+//! Background fetches go through a [`MeteredRegistry`], which records for
+//! every `get_versions` / `get_latest_matching` call how long it took and
+//! whether it succeeded. Calls dropped before completing (a fetch timeout)
+//! count as failures with the time spent until then.
+
+use dashmap::DashMap;
+use deps_core::ecosystem::BoxFuture;
+use deps_core::{Metadata, Registry, Version};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Latency samples kept per ecosystem for percentiles.
+const MAX_SAMPLES: usize = 1024;
+
+#[derive(Debug, Default)]
+struct EcosystemMetrics {
+    fetches: u64,
+    failures: u64,
+    latencies_ms: VecDeque<u64>,
+}
+
+/// Fetch counters and latency samples by ecosystem.
+#[derive(Debug, Default)]
+pub struct FetchMetrics {
+    by_ecosystem: DashMap<&'static str, EcosystemMetrics>,
+}
+
+/// Fetch statistics of one ecosystem.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcosystemFetchStats {
+    pub ecosystem: String,
+    /// Registry calls made.
+    pub fetches: u64,
+    /// Calls that failed or timed out.
+    pub failures: u64,
+    /// Median latency of the most recent calls, in milliseconds.
+    pub p50_ms: Option<u64>,
+    /// 95th percentile latency of the most recent calls, in milliseconds.
+    pub p95_ms: Option<u64>,
+}
+
+impl FetchMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one registry call.
+    pub fn record(&self, ecosystem: &'static str, elapsed: Duration, success: bool) {
+        let mut metrics = self.by_ecosystem.entry(ecosystem).or_default();
+        metrics.fetches += 1;
+        if !success {
+            metrics.failures += 1;
+        }
+        if metrics.latencies_ms.len() == MAX_SAMPLES {
+            metrics.latencies_ms.pop_front();
+        }
+        let millis = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        metrics.latencies_ms.push_back(millis);
+    }
+
+    /// Returns the statistics of every ecosystem that fetched, sorted by id.
+    pub fn snapshot(&self) -> Vec<EcosystemFetchStats> {
+        let mut stats: Vec<_> = self
+            .by_ecosystem
+            .iter()
+            .map(|entry| {
+                let mut latencies: Vec<u64> = entry.latencies_ms.iter().copied().collect();
+                latencies.sort_unstable();
+                EcosystemFetchStats {
+                    ecosystem: (*entry.key()).to_string(),
+                    fetches: entry.fetches,
+                    failures: entry.failures,
+                    p50_ms: percentile(&latencies, 50),
+                    p95_ms: percentile(&latencies, 95),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.ecosystem.cmp(&b.ecosystem));
+        stats
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[u64], percent: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// Records a call when dropped: as a success once [`Self::finish`]ed with
+/// `true`, as a failure otherwise (including cancellation).
+struct FetchTimer<'a> {
+    metrics: &'a FetchMetrics,
+    ecosystem: &'static str,
+    started: Instant,
+    success: bool,
+}
+
+impl<'a> FetchTimer<'a> {
+    fn start(metrics: &'a FetchMetrics, ecosystem: &'static str) -> Self {
+        Self {
+            metrics,
+            ecosystem,
+            started: Instant::now(),
+            success: false,
+        }
+    }
+
+    const fn finish(&mut self, success: bool) {
+        self.success = success;
+    }
+}
+
+impl Drop for FetchTimer<'_> {
+    fn drop(&mut self) {
+        self.metrics
+            .record(self.ecosystem, self.started.elapsed(), self.success);
+    }
+}
+
+/// Registry wrapper recording version fetches into [`FetchMetrics`].
+pub struct MeteredRegistry {
+    inner: Arc<dyn Registry>,
+    ecosystem: &'static str,
+    metrics: Arc<FetchMetrics>,
+}
+
+impl MeteredRegistry {
+    /// Wraps `inner`, attributing its fetches to `ecosystem`.
+    pub fn wrap(
+        inner: Arc<dyn Registry>,
+        ecosystem: &'static str,
+        metrics: Arc<FetchMetrics>,
+    ) -> Arc<dyn Registry> {
+        Arc::new(Self {
+            inner,
+            ecosystem,
+            metrics,
+        })
+    }
+}
+
+impl Registry for MeteredRegistry {
+    fn get_versions<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> BoxFuture<'a, deps_core::Result<Vec<Box<dyn Version>>>> {
+        Box::pin(async move {
+            let mut timer = FetchTimer::start(&self.metrics, self.ecosystem);
+            let result = self.inner.get_versions(name).await;
+            timer.finish(result.is_ok());
+            result
+        })
+    }
+
+    fn get_latest_matching<'a>(
+        &'a self,
+        name: &'a str,
+        req: &'a str,
+    ) -> BoxFuture<'a, deps_core::Result<Option<Box<dyn Version>>>> {
+        Box::pin(async move {
+            let mut timer = FetchTimer::start(&self.metrics, self.ecosystem);
+            let result = self.inner.get_latest_matching(name, req).await;
+            timer.finish(result.is_ok());
+            result
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        limit: usize,
+    ) -> BoxFuture<'a, deps_core::Result<Vec<Box<dyn Metadata>>>> {
+        self.inner.search(query, limit)
+    }
+
+    fn package_url(&self, name: &str) -> String {
+        self.inner.package_url(name)
+    }
+
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        self.inner.release_url(name, version)
+    }
+
+    fn cached_versions(&self, name: &str) -> Option<Vec<Box<dyn Version>>> {
+        self.inner.cached_versions(name)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self.inner.as_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50), Some(50));
+        assert_eq!(percentile(&samples, 95), Some(95));
+        assert_eq!(percentile(&[7], 95), Some(7));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[test]
+    fn test_record_and_snapshot() {
+        let metrics = FetchMetrics::new();
+        for ms in [10, 20, 30, 400] {
+            metrics.record("npm", Duration::from_millis(ms), true);
+        }
+        metrics.record("cargo", Duration::from_millis(5), false);
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].ecosystem, "cargo");
+        assert_eq!(stats[0].failures, 1);
+        assert_eq!(stats[1].fetches, 4);
+        assert_eq!(stats[1].p50_ms, Some(20));
+        assert_eq!(stats[1].p95_ms, Some(400));
+    }
+
+    #[test]
+    fn test_samples_are_bounded() {
+        let metrics = FetchMetrics::new();
+        for _ in 0..MAX_SAMPLES + 10 {
+            metrics.record("go", Duration::from_millis(1), true);
+        }
+        let entry = metrics.by_ecosystem.get("go").unwrap();
+        assert_eq!(entry.latencies_ms.len(), MAX_SAMPLES);
+        assert_eq!(entry.fetches, (MAX_SAMPLES + 10) as u64);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_fetch_counts_as_failure() {
+        let metrics = FetchMetrics::new();
+        {
+            let _timer = FetchTimer::start(&metrics, "pypi");
+        }
+        {
+            let mut timer = FetchTimer::start(&metrics, "pypi");
+            timer.finish(true);
+        }
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats[0].fetches, 2);
+        assert_eq!(stats[0].failures, 1);
+    }
+}
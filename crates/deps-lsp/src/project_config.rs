@@ -0,0 +1,394 @@
+//! Project-level configuration files.
+//!
+//! A `.deps-lsp.toml` file, or a `[tool.deps-lsp]` table in `pyproject.toml`,
+//! holds the same settings as the initialization options, so a team can
+//! commit one configuration instead of every member setting up their editor:
+//!
+//! ```toml
+//! [diagnostics]
+//! outdated_severity = "information"
+//! ignore = ["internal-*"]
+//!
+//! [ecosystems.gradle]
+//! enabled = false
+//! ```
+//!
+//! The nearest file walking up from a manifest applies to it. Its tables are
+//! merged key by key over the client's settings and the result is
+//! deserialized as a [`DepsConfig`] again, so the file goes through the same
+//! defaults, bounds and validation as the initialization options. A file that
+//! fails to parse or validate is ignored with a warning.
+//!
+//! `registries` is refused: a checked-out repository must not be able to
+//! send package lookups, and the credentials some registries attach to them,
+//! to a server of its choosing. It can only come from the editor.
+
+use crate::config::DepsConfig;
+use dashmap::DashMap;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp_server::ls_types::Uri;
+
+/// Name of the dedicated project configuration file.
+pub const PROJECT_CONFIG_FILE: &str = ".deps-lsp.toml";
+
+/// `pyproject.toml`, whose `[tool.deps-lsp]` table is read as well.
+const PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// Maximum size of a configuration file (1MB).
+const MAX_CONFIG_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Maximum TOML nesting depth accepted in a configuration file.
+const MAX_CONFIG_NESTING_DEPTH: usize = 32;
+
+/// Whether `path` names a file that can hold project configuration.
+///
+/// # Examples
+///
+/// ```
+/// use deps_lsp::project_config::is_project_config_file;
+/// use std::path::Path;
+///
+/// assert!(is_project_config_file(Path::new("/repo/.deps-lsp.toml")));
+/// assert!(is_project_config_file(Path::new("/repo/pyproject.toml")));
+/// assert!(!is_project_config_file(Path::new("/repo/Cargo.toml")));
+/// ```
+pub fn is_project_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == PROJECT_CONFIG_FILE || name == PYPROJECT_FILE)
+}
+
+/// Finds the configuration applying to files in `dir`.
+///
+/// Walks up from `dir`; in each directory `.deps-lsp.toml` wins over a
+/// `pyproject.toml` with a `[tool.deps-lsp]` table. Returns the file and its
+/// settings as JSON.
+pub fn find_project_config(dir: &Path) -> Option<(PathBuf, Value)> {
+    dir.ancestors().find_map(|dir| {
+        [PROJECT_CONFIG_FILE, PYPROJECT_FILE]
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .find_map(|path| match load_project_config(&path) {
+                Ok(Some(settings)) => Some((path, settings)),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::warn!("ignoring {}: {e}", path.display());
+                    None
+                }
+            })
+    })
+}
+
+/// Reads the settings of a configuration file as JSON.
+///
+/// Returns `Ok(None)` for a `pyproject.toml` without a `[tool.deps-lsp]`
+/// table.
+pub fn load_project_config(path: &Path) -> Result<Option<Value>, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_CONFIG_FILE_SIZE {
+        return Err(format!("file too large ({size} bytes)"));
+    }
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if deps_core::check_toml_nesting_depth(&content, MAX_CONFIG_NESTING_DEPTH).is_err() {
+        return Err("TOML nesting too deep".to_string());
+    }
+
+    let mut document = toml_span::parse(&content).map_err(|e| e.to_string())?;
+    let settings = if path.file_name().is_some_and(|name| name == PYPROJECT_FILE) {
+        match document.pointer_mut("/tool/deps-lsp") {
+            Some(table) => toml_to_json(table),
+            None => return Ok(None),
+        }
+    } else {
+        toml_to_json(&mut document)
+    };
+
+    if !settings.is_object() {
+        return Err("[tool.deps-lsp] must be a table".to_string());
+    }
+    if settings.get("registries").is_some() {
+        return Err("registries can only be set in the editor settings".to_string());
+    }
+    Ok(Some(settings))
+}
+
+/// Applies project `settings` over `base`.
+///
+/// Tables are merged recursively; any other value, arrays included, replaces
+/// the base value.
+///
+/// # Errors
+///
+/// Returns the deserialization error if the merged settings are not a valid
+/// [`DepsConfig`].
+///
+/// # Examples
+///
+/// ```
+/// use deps_lsp::config::DepsConfig;
+/// use deps_lsp::project_config::merge;
+///
+/// let base: DepsConfig =
+///     serde_json::from_str(r#"{ "inlay_hints": { "up_to_date_text": "ok" } }"#).unwrap();
+/// let settings = serde_json::json!({ "inlay_hints": { "enabled": false } });
+///
+/// let config = merge(&base, &settings).unwrap();
+/// assert!(!config.inlay_hints.enabled);
+/// assert_eq!(config.inlay_hints.up_to_date_text, "ok");
+/// ```
+pub fn merge(base: &DepsConfig, settings: &Value) -> Result<DepsConfig, serde_json::Error> {
+    let mut merged = serde_json::to_value(base)?;
+    merge_values(&mut merged, settings);
+    serde_json::from_value(merged)
+}
+
+fn merge_values(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
+
+fn toml_to_json(value: &mut toml_span::Value<'_>) -> Value {
+    use toml_span::value::ValueInner;
+
+    match value.take() {
+        ValueInner::String(s) => Value::String(s.into_owned()),
+        ValueInner::Integer(i) => Value::from(i),
+        ValueInner::Float(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+        ValueInner::Boolean(b) => Value::Bool(b),
+        ValueInner::Array(mut array) => Value::Array(array.iter_mut().map(toml_to_json).collect()),
+        ValueInner::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, mut value)| (key.name.into_owned(), toml_to_json(&mut value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// Project configuration files found so far, by manifest directory.
+///
+/// Lookups are cached until [`ProjectConfigs::invalidate`] is called, which
+/// the server does whenever a watched configuration file changes.
+#[derive(Debug, Default)]
+pub struct ProjectConfigs {
+    by_dir: DashMap<PathBuf, Option<Arc<(PathBuf, Value)>>>,
+}
+
+impl ProjectConfigs {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the configuration file applying to `uri` and its settings.
+    pub fn lookup(&self, uri: &Uri) -> Option<Arc<(PathBuf, Value)>> {
+        let path = uri.to_file_path()?;
+        let dir = path.parent()?;
+        if let Some(found) = self.by_dir.get(dir) {
+            return found.clone();
+        }
+
+        let found = find_project_config(dir).map(Arc::new);
+        if let Some(found) = &found {
+            tracing::debug!("{} configured by {}", path.display(), found.0.display());
+        }
+        self.by_dir.insert(dir.to_path_buf(), found.clone());
+        found
+    }
+
+    /// Returns the effective configuration for `uri`: `base` with the
+    /// nearest project configuration applied.
+    pub fn resolve(&self, base: &DepsConfig, uri: &Uri) -> DepsConfig {
+        let Some(found) = self.lookup(uri) else {
+            return base.clone();
+        };
+        let (path, settings) = found.as_ref();
+        match merge(base, settings) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("ignoring invalid settings in {}: {e}", path.display());
+                base.clone()
+            }
+        }
+    }
+
+    /// Forgets all cached lookups.
+    pub fn invalidate(&self) {
+        self.by_dir.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::DiagnosticSeverity;
+
+    fn uri(path: &Path) -> Uri {
+        Uri::from_file_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_load_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "[diagnostics]\noutdated_severity = \"warning\"\nignore = [\"a*\"]\n\n[cache]\nfetch_timeout_secs = 2\n",
+        )
+        .unwrap();
+
+        let settings = load_project_config(&path).unwrap().unwrap();
+        assert_eq!(
+            settings,
+            serde_json::json!({
+                "diagnostics": { "outdated_severity": "warning", "ignore": ["a*"] },
+                "cache": { "fetch_timeout_secs": 2 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_pyproject_tool_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PYPROJECT_FILE);
+
+        std::fs::write(&path, "[project]\nname = \"app\"\n").unwrap();
+        assert!(load_project_config(&path).unwrap().is_none());
+
+        std::fs::write(
+            &path,
+            "[project]\nname = \"app\"\n\n[tool.deps-lsp.inlay_hints]\nenabled = false\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_project_config(&path).unwrap().unwrap(),
+            serde_json::json!({ "inlay_hints": { "enabled": false } })
+        );
+    }
+
+    #[test]
+    fn test_load_project_config_invalid_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_FILE);
+        std::fs::write(&path, "[diagnostics\n").unwrap();
+        assert!(load_project_config(&path).is_err());
+    }
+
+    #[test]
+    fn test_load_project_config_rejects_registries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_FILE);
+        std::fs::write(&path, "[registries]\nnpm = \"http://npm.example.com/\"\n").unwrap();
+        assert!(load_project_config(&path).is_err());
+        // The file is skipped, so the editor's registries stay in effect
+        assert!(find_project_config(dir.path()).is_none());
+
+        let pyproject = dir.path().join(PYPROJECT_FILE);
+        std::fs::write(
+            &pyproject,
+            "[tool.deps-lsp.registries]\npypi = \"https://pypi.example.com/simple\"\n",
+        )
+        .unwrap();
+        assert!(load_project_config(&pyproject).is_err());
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("crates/app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[inlay_hints]\nenabled = false\n",
+        )
+        .unwrap();
+        // A pyproject.toml without a tool table does not stop the walk
+        std::fs::write(nested.join(PYPROJECT_FILE), "[project]\nname = \"app\"\n").unwrap();
+
+        let (path, _) = find_project_config(&nested).unwrap();
+        assert_eq!(path, dir.path().join(PROJECT_CONFIG_FILE));
+    }
+
+    #[test]
+    fn test_dedicated_file_wins_over_pyproject() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(PROJECT_CONFIG_FILE), "").unwrap();
+        std::fs::write(
+            dir.path().join(PYPROJECT_FILE),
+            "[tool.deps-lsp.inlay_hints]\nenabled = false\n",
+        )
+        .unwrap();
+
+        let (path, _) = find_project_config(dir.path()).unwrap();
+        assert_eq!(path, dir.path().join(PROJECT_CONFIG_FILE));
+    }
+
+    #[test]
+    fn test_merge_over_base() {
+        let base: DepsConfig = serde_json::from_value(serde_json::json!({
+            "diagnostics": { "unknown_severity": 1, "duplicate_versions": true },
+            "update": { "ignore": ["x"] }
+        }))
+        .unwrap();
+        let settings = serde_json::json!({
+            "diagnostics": { "outdated_severity": "warning" },
+            "update": { "ignore": ["y"] },
+            "cache": { "fetch_timeout_secs": 0 }
+        });
+
+        let config = merge(&base, &settings).unwrap();
+        assert_eq!(
+            config.diagnostics.outdated_severity,
+            DiagnosticSeverity::WARNING
+        );
+        assert_eq!(
+            config.diagnostics.unknown_severity,
+            DiagnosticSeverity::ERROR
+        );
+        assert!(config.diagnostics.duplicate_versions);
+        assert_eq!(config.update.ignore, vec!["y".to_string()]);
+        // Bounds of the initialization options apply to project files too
+        assert_eq!(config.cache.fetch_timeout_secs, 1);
+    }
+
+    #[test]
+    fn test_merge_rejects_invalid_settings() {
+        let settings = serde_json::json!({ "diagnostics": { "outdated_severity": "loud" } });
+        assert!(merge(&DepsConfig::default(), &settings).is_err());
+    }
+
+    #[test]
+    fn test_project_configs_resolve_and_invalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        let config_path = dir.path().join(PROJECT_CONFIG_FILE);
+        let configs = ProjectConfigs::new();
+        let base = DepsConfig::default();
+
+        assert!(configs.resolve(&base, &uri(&manifest)).inlay_hints.enabled);
+
+        std::fs::write(&config_path, "[inlay_hints]\nenabled = false\n").unwrap();
+        // Cached until invalidated
+        assert!(configs.resolve(&base, &uri(&manifest)).inlay_hints.enabled);
+        configs.invalidate();
+        assert!(!configs.resolve(&base, &uri(&manifest)).inlay_hints.enabled);
+
+        std::fs::write(&config_path, "[inlay_hints]\nenabled = \"no\"\n").unwrap();
+        configs.invalidate();
+        assert!(configs.resolve(&base, &uri(&manifest)).inlay_hints.enabled);
+    }
+}
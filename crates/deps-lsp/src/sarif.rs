@@ -0,0 +1,510 @@
+//! SARIF 2.1.0 export of dependency diagnostics.
+//!
+//! Converts the diagnostics produced by [`Ecosystem::generate_diagnostics`]
+//! into a SARIF log that code-scanning services (GitHub, GitLab) ingest.
+//! Every diagnostic becomes a result whose rule ID is its diagnostic code
+//! (`outdated`, `unknown-package`, `yanked`, `duplicate-version`, or
+//! `deps-lsp` for uncoded ones); the preferred update code action, when there
+//! is one, becomes the result's fix.
+//!
+//! LSP positions count UTF-16 code units from 0, SARIF regions count lines
+//! and Unicode code points from 1, so regions are converted against the
+//! document content (the log declares `"columnKind": "unicodeCodePoints"`).
+
+use crate::handlers::checksums::CHECKSUM_MISMATCH_CODE;
+use crate::handlers::duplicates::DUPLICATE_VERSION_CODE;
+use deps_core::lsp_helpers::{OUTDATED_CODE, UNKNOWN_PACKAGE_CODE, YANKED_CODE};
+use deps_core::{Ecosystem, LineOffsetTable, ParseResult};
+use serde::Serialize;
+use tower_lsp_server::ls_types::{
+    CodeAction, Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, TextEdit, Uri,
+};
+
+/// SARIF version written to the log.
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// JSON schema of SARIF 2.1.0 logs.
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// URI base ID that relative artifact locations resolve against; consumers
+/// such as GitHub code scanning map it to the checkout root.
+pub const SRCROOT: &str = "%SRCROOT%";
+
+/// Rule ID of diagnostics without a code.
+const DEFAULT_RULE: &str = "deps-lsp";
+
+/// Diagnostics of one manifest, ready to be exported.
+#[derive(Debug, Clone)]
+pub struct SarifDocument {
+    /// Manifest URI the diagnostics and fixes refer to.
+    pub uri: Uri,
+    /// Location written to the log: a path relative to [`SRCROOT`] when set,
+    /// `uri` otherwise.
+    pub relative_path: Option<String>,
+    /// Manifest content, used to convert UTF-16 positions.
+    pub content: String,
+    /// Diagnostics, each with its fix if there is one.
+    pub diagnostics: Vec<(Diagnostic, Option<CodeAction>)>,
+}
+
+/// A SARIF log.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+/// A single analysis run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub tool: Tool,
+    pub column_kind: &'static str,
+    pub results: Vec<SarifResult>,
+}
+
+/// The tool that produced a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub driver: ToolComponent,
+}
+
+/// Name, version and rules of the tool.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolComponent {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub information_uri: &'static str,
+    pub rules: Vec<ReportingDescriptor>,
+}
+
+/// A rule results refer to by ID.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingDescriptor {
+    pub id: String,
+    pub short_description: Message,
+    pub default_configuration: ReportingConfiguration,
+}
+
+/// Default level of a rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportingConfiguration {
+    pub level: &'static str,
+}
+
+/// A finding.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Message,
+    pub locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<Fix>,
+}
+
+/// Plain text message.
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+/// Where a result was found.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+}
+
+/// A region of a file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+/// A file, absolute or relative to a URI base ID.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactLocation {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_base_id: Option<&'static str>,
+}
+
+/// 1-based line/column region; the end column is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+/// A proposed fix.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fix {
+    pub description: Message,
+    pub artifact_changes: Vec<ArtifactChange>,
+}
+
+/// Replacements within one file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactChange {
+    pub artifact_location: ArtifactLocation,
+    pub replacements: Vec<Replacement>,
+}
+
+/// Replaces a region with new text.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replacement {
+    pub deleted_region: Region,
+    pub inserted_content: ArtifactContent,
+}
+
+/// Text inserted by a replacement.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactContent {
+    pub text: String,
+}
+
+/// Converts an LSP range (0-based, UTF-16 columns) into a SARIF region
+/// (1-based, code point columns).
+///
+/// # Examples
+///
+/// ```
+/// use deps_lsp::sarif::{Region, to_region};
+/// use tower_lsp_server::ls_types::{Position, Range};
+///
+/// // "é" is one UTF-16 unit, "😀" two
+/// let content = "name = \"😀é\"\n";
+/// let range = Range::new(Position::new(0, 8), Position::new(0, 11));
+/// assert_eq!(
+///     to_region(content, range),
+///     Region { start_line: 1, start_column: 9, end_line: 1, end_column: 11 }
+/// );
+/// ```
+pub fn to_region(content: &str, range: Range) -> Region {
+    let table = LineOffsetTable::new(content);
+    let column = |position: Position| {
+        let line_start = table.position_to_byte_offset(content, Position::new(position.line, 0));
+        let offset = table.position_to_byte_offset(content, position);
+        let code_points = content[line_start..offset.max(line_start)].chars().count();
+        u32::try_from(code_points).unwrap_or(u32::MAX - 1) + 1
+    };
+    Region {
+        start_line: range.start.line + 1,
+        start_column: column(range.start),
+        end_line: range.end.line + 1,
+        end_column: column(range.end),
+    }
+}
+
+/// Returns the update action fixing `diagnostic`, if it is an outdated or
+/// yanked version.
+///
+/// Among the code actions offered at the diagnostic, the preferred one (the
+/// latest version) wins.
+pub async fn find_fix(
+    ecosystem: &dyn Ecosystem,
+    parse_result: &dyn ParseResult,
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeAction> {
+    let rule = rule_id(diagnostic);
+    if rule != OUTDATED_CODE && rule != YANKED_CODE {
+        return None;
+    }
+
+    let mut actions: Vec<CodeAction> = ecosystem
+        .generate_code_actions(parse_result, diagnostic.range.start, uri)
+        .await
+        .into_iter()
+        .filter(|action| action.edit.is_some())
+        .collect();
+    let index = actions
+        .iter()
+        .position(|action| action.is_preferred == Some(true))
+        .unwrap_or(0);
+    (index < actions.len()).then(|| actions.swap_remove(index))
+}
+
+/// Builds a SARIF log with one run covering `documents`.
+pub fn build_log(documents: &[SarifDocument]) -> SarifLog {
+    let mut rules: Vec<ReportingDescriptor> = Vec::new();
+    let mut results = Vec::new();
+
+    for document in documents {
+        for (diagnostic, fix) in &document.diagnostics {
+            let rule = rule_id(diagnostic);
+            let rule_index = rules.iter().position(|r| r.id == rule).unwrap_or_else(|| {
+                rules.push(rule_descriptor(rule, diagnostic));
+                rules.len() - 1
+            });
+
+            results.push(SarifResult {
+                rule_id: rule.to_string(),
+                rule_index,
+                level: level(diagnostic.severity),
+                message: Message {
+                    text: diagnostic.message.clone(),
+                },
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact_location(document),
+                        region: to_region(&document.content, diagnostic.range),
+                    },
+                }],
+                fixes: fix
+                    .as_ref()
+                    .and_then(|action| to_fix(document, action))
+                    .into_iter()
+                    .collect(),
+            });
+        }
+    }
+
+    SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: ToolComponent {
+                    name: "deps-lsp",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            column_kind: "unicodeCodePoints",
+            results,
+        }],
+    }
+}
+
+fn rule_id(diagnostic: &Diagnostic) -> &str {
+    match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code,
+        _ => DEFAULT_RULE,
+    }
+}
+
+fn rule_descriptor(id: &str, diagnostic: &Diagnostic) -> ReportingDescriptor {
+    let description = match id {
+        OUTDATED_CODE => "A newer version of the dependency is available",
+        UNKNOWN_PACKAGE_CODE => "The package is not known to its registry",
+        YANKED_CODE => "The required version has been yanked or deprecated",
+        DUPLICATE_VERSION_CODE => "The dependency pulls in an older copy of a package",
+        CHECKSUM_MISMATCH_CODE => "The lock file checksum differs from the registry's",
+        _ => "Dependency issue",
+    };
+    ReportingDescriptor {
+        id: id.to_string(),
+        short_description: Message {
+            text: description.to_string(),
+        },
+        default_configuration: ReportingConfiguration {
+            level: level(diagnostic.severity),
+        },
+    }
+}
+
+fn level(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::INFORMATION | DiagnosticSeverity::HINT) => "note",
+        _ => "warning",
+    }
+}
+
+fn artifact_location(document: &SarifDocument) -> ArtifactLocation {
+    match &document.relative_path {
+        Some(path) => ArtifactLocation {
+            uri: path.clone(),
+            uri_base_id: Some(SRCROOT),
+        },
+        None => ArtifactLocation {
+            uri: document.uri.as_str().to_string(),
+            uri_base_id: None,
+        },
+    }
+}
+
+/// Converts the edits an action makes to `document`; edits to other files
+/// are dropped since their content is not at hand.
+fn to_fix(document: &SarifDocument, action: &CodeAction) -> Option<Fix> {
+    let edits: &[TextEdit] = action.edit.as_ref()?.changes.as_ref()?.get(&document.uri)?;
+    if edits.is_empty() {
+        return None;
+    }
+    Some(Fix {
+        description: Message {
+            text: action.title.clone(),
+        },
+        artifact_changes: vec![ArtifactChange {
+            artifact_location: artifact_location(document),
+            replacements: edits
+                .iter()
+                .map(|edit| Replacement {
+                    deleted_region: to_region(&document.content, edit.range),
+                    inserted_content: ArtifactContent {
+                        text: edit.new_text.clone(),
+                    },
+                })
+                .collect(),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tower_lsp_server::ls_types::WorkspaceEdit;
+
+    const CONTENT: &str = "[dependencies]\nserde = \"1.0\"\n# ünïcode\nleft-pad = \"1\"\n";
+
+    fn diagnostic(code: Option<&str>, severity: DiagnosticSeverity, range: Range) -> Diagnostic {
+        Diagnostic {
+            range,
+            severity: Some(severity),
+            code: code.map(|c| NumberOrString::String(c.into())),
+            message: "message".into(),
+            ..Default::default()
+        }
+    }
+
+    fn document(relative_path: Option<&str>) -> SarifDocument {
+        let uri = deps_core::test_util::test_uri("/repo/Cargo.toml");
+        let version_range = Range::new(Position::new(1, 9), Position::new(1, 12));
+        let fix = CodeAction {
+            title: "Update serde to 1.0.228".into(),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: version_range,
+                        new_text: "1.0.228".into(),
+                    }],
+                )])),
+                ..Default::default()
+            }),
+            is_preferred: Some(true),
+            ..Default::default()
+        };
+
+        SarifDocument {
+            uri,
+            relative_path: relative_path.map(str::to_string),
+            content: CONTENT.into(),
+            diagnostics: vec![
+                (
+                    diagnostic(Some(OUTDATED_CODE), DiagnosticSeverity::HINT, version_range),
+                    Some(fix),
+                ),
+                (
+                    diagnostic(
+                        Some(UNKNOWN_PACKAGE_CODE),
+                        DiagnosticSeverity::WARNING,
+                        Range::new(Position::new(3, 0), Position::new(3, 8)),
+                    ),
+                    None,
+                ),
+                (
+                    diagnostic(
+                        None,
+                        DiagnosticSeverity::ERROR,
+                        Range::new(Position::new(2, 2), Position::new(2, 9)),
+                    ),
+                    None,
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_region_multibyte() {
+        // "ü" and "ï" are two bytes but one code point and one UTF-16 unit
+        let region = to_region(
+            CONTENT,
+            Range::new(Position::new(2, 2), Position::new(2, 9)),
+        );
+        assert_eq!(
+            region,
+            Region {
+                start_line: 3,
+                start_column: 3,
+                end_line: 3,
+                end_column: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_log() {
+        let log = build_log(&[document(Some("crates/app/Cargo.toml"))]);
+        let json = serde_json::to_value(&log).unwrap();
+
+        assert_eq!(json["version"], "2.1.0");
+        let run = &json["runs"][0];
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        assert_eq!(run["tool"]["driver"]["name"], "deps-lsp");
+
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| rule["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, ["outdated", "unknown-package", "deps-lsp"]);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["level"], "note");
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[2]["level"], "error");
+        assert_eq!(results[1]["ruleIndex"], 1);
+
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "crates/app/Cargo.toml");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], SRCROOT);
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 10);
+        assert_eq!(location["region"]["endColumn"], 13);
+
+        let fix = &results[0]["fixes"][0];
+        assert_eq!(fix["description"]["text"], "Update serde to 1.0.228");
+        let replacement = &fix["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], "1.0.228");
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 10);
+        assert!(results[1].get("fixes").is_none());
+    }
+
+    #[test]
+    fn test_build_log_absolute_uris() {
+        let log = build_log(&[document(None)]);
+        let location = &log.runs[0].results[0].locations[0].physical_location;
+        assert!(location.artifact_location.uri.starts_with("file://"));
+        assert_eq!(location.artifact_location.uri_base_id, None);
+    }
+
+    #[test]
+    fn test_build_log_empty() {
+        let log = build_log(&[]);
+        assert!(log.runs[0].results.is_empty());
+        assert!(log.runs[0].tool.driver.rules.is_empty());
+    }
+}
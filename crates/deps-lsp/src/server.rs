@@ -1,27 +1,48 @@
 use crate::config::DepsConfig;
 use crate::document::{ServerState, handle_document_change, handle_document_open};
 use crate::file_watcher;
-use crate::handlers::{code_actions, completion, diagnostics, hover, inlay_hints};
+use crate::handlers::dependency_tree::{
+    self, DEPENDENCY_TREE_METHOD, DependencyTreeParams, DependencyTreeResult,
+    WHY_DEPENDENCY_METHOD, WhyDependencyParams, WhyDependencyResult,
+};
+use crate::handlers::duplicates::{self, DUPLICATES_METHOD, DuplicatesParams, DuplicatesResult};
+use crate::handlers::export_sarif::{self, EXPORT_SARIF_METHOD, ExportSarifParams};
+use crate::handlers::status::{self, STATUS_METHOD, StatusResult};
+use crate::handlers::{
+    add_dependency, code_actions, completion, definition, diagnostics, formatting, hover,
+    inlay_hints, references, workspace_symbol,
+};
+use crate::project_config;
+use crate::sarif::SarifLog;
+use crate::workspace_index;
+use deps_core::HttpCache;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tower_lsp_server::ls_types::{
-    CodeActionOptions, CodeActionParams, CodeActionProviderCapability, CompletionOptions,
-    CompletionParams, CompletionResponse, DiagnosticOptions, DiagnosticServerCapabilities,
-    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
-    DidOpenTextDocumentParams, DocumentDiagnosticParams, DocumentDiagnosticReport,
-    DocumentDiagnosticReportResult, ExecuteCommandOptions, ExecuteCommandParams,
-    FullDocumentDiagnosticReport, Hover, HoverParams, HoverProviderCapability, InitializeParams,
-    InitializeResult, InitializedParams, InlayHint, InlayHintParams, MessageType, OneOf, Range,
-    RelatedFullDocumentDiagnosticReport, ServerCapabilities, ServerInfo,
+    CodeActionKind, CodeActionOptions, CodeActionParams, CodeActionProviderCapability,
+    CompletionOptions, CompletionParams, CompletionResponse, DiagnosticOptions,
+    DiagnosticServerCapabilities, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+    DidChangeWatchedFilesParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
+    DocumentRangeFormattingParams, ExecuteCommandOptions, ExecuteCommandParams, FileChangeType,
+    FullDocumentDiagnosticReport, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams,
+    HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, InlayHint,
+    InlayHintOptions, InlayHintParams, InlayHintServerCapabilities, Location, MessageType, OneOf,
+    Range, ReferenceParams, RelatedFullDocumentDiagnosticReport, ServerCapabilities, ServerInfo,
     TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+    WorkspaceSymbolParams, WorkspaceSymbolResponse,
 };
-use tower_lsp_server::{Client, LanguageServer, jsonrpc::Result};
+use tower_lsp_server::{Client, ClientSocket, LanguageServer, LspService, jsonrpc::Result};
 
 /// LSP command identifiers.
 mod commands {
     /// Command to update a dependency version.
-    pub(super) const UPDATE_VERSION: &str = "deps-lsp.updateVersion";
+    pub(super) const UPDATE_VERSION: &str = super::inlay_hints::UPDATE_VERSION_COMMAND;
+    /// Command to add a dependency to a manifest.
+    pub(super) const ADD_DEPENDENCY: &str = "deps.addDependency";
 }
 
 pub struct Backend {
@@ -29,16 +50,61 @@ pub struct Backend {
     state: Arc<ServerState>,
     config: Arc<RwLock<DepsConfig>>,
     client_capabilities: Arc<RwLock<Option<tower_lsp_server::ls_types::ClientCapabilities>>>,
+    workspace_roots: Arc<RwLock<Vec<PathBuf>>>,
+    /// Tasks living as long as the session, aborted when it ends
+    session_tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl Backend {
     pub fn new(client: Client) -> Self {
+        Self::with_cache(client, Arc::new(HttpCache::new()))
+    }
+
+    /// Creates a backend whose registry requests go through a shared cache.
+    pub fn with_cache(client: Client, cache: Arc<HttpCache>) -> Self {
         Self {
             client,
-            state: Arc::new(ServerState::new()),
+            state: Arc::new(ServerState::with_cache(cache)),
             config: Arc::new(RwLock::new(DepsConfig::default())),
             client_capabilities: Arc::new(RwLock::new(None)),
+            workspace_roots: Arc::new(RwLock::new(Vec::new())),
+            session_tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Aborts the session's own tasks and those of its documents.
+    ///
+    /// A listening server outlives its sessions, so nothing spawned for a
+    /// session may keep its state alive once the client has gone.
+    fn abort_session_tasks(&self) {
+        if let Ok(mut tasks) = self.session_tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
         }
+        self.state.abort_background_tasks();
+    }
+
+    /// Weak reference to the session state, to observe when it is dropped.
+    #[cfg(test)]
+    pub(crate) fn state_ref(&self) -> std::sync::Weak<ServerState> {
+        Arc::downgrade(&self.state)
+    }
+
+    /// Builds the LSP service with the custom `deps/*` requests registered.
+    pub fn build_service() -> (LspService<Self>, ClientSocket) {
+        Self::build_service_with_cache(Arc::new(HttpCache::new()))
+    }
+
+    /// Like [`Backend::build_service`], sharing `cache` with other sessions.
+    pub fn build_service_with_cache(cache: Arc<HttpCache>) -> (LspService<Self>, ClientSocket) {
+        LspService::build(move |client| Self::with_cache(client, Arc::clone(&cache)))
+            .custom_method(DEPENDENCY_TREE_METHOD, Self::dependency_tree)
+            .custom_method(WHY_DEPENDENCY_METHOD, Self::why_dependency)
+            .custom_method(DUPLICATES_METHOD, Self::duplicates)
+            .custom_method(EXPORT_SARIF_METHOD, Self::export_sarif)
+            .custom_method(STATUS_METHOD, Self::status)
+            .finish()
     }
 
     /// Get a reference to the LSP client (primarily for testing/benchmarking).
@@ -157,17 +223,16 @@ impl Backend {
             }
         };
 
-        let config = self.config.read().await;
-
         for uri in affected_uris {
             if let Some(mut doc) = self.state.documents.get_mut(&uri) {
                 doc.update_resolved_versions(resolved_versions.clone());
             }
 
+            let diagnostics_config = self.state.config_for(&self.config, &uri).await.diagnostics;
             let items = diagnostics::handle_diagnostics(
                 Arc::clone(&self.state),
                 &uri,
-                &config.diagnostics,
+                &diagnostics_config,
                 self.client.clone(),
                 Arc::clone(&self.config),
             )
@@ -181,6 +246,105 @@ impl Backend {
         }
     }
 
+    /// Installs a new client configuration.
+    ///
+    /// Ecosystem switches are not applied here: whether an ecosystem handles
+    /// a manifest is resolved per URI, once project files are merged in.
+    async fn apply_config(&self, config: DepsConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Re-publishes diagnostics and inlay hints of every open manifest after
+    /// its settings changed. Manifests of a now disabled ecosystem are
+    /// dropped and their diagnostics cleared.
+    async fn refresh_open_documents(&self) {
+        let uris: Vec<Uri> = self
+            .state
+            .documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for uri in uris {
+            let config = self.state.config_for(&self.config, &uri).await;
+            if self
+                .state
+                .enabled_ecosystem_for_uri(&uri, &config)
+                .is_none()
+            {
+                tracing::info!("ecosystem disabled, dropping {:?}", uri);
+                self.state.remove_document(&uri);
+                self.state.cancel_background_task(&uri).await;
+                self.client.publish_diagnostics(uri, vec![], None).await;
+                continue;
+            }
+
+            let items = diagnostics::handle_diagnostics(
+                Arc::clone(&self.state),
+                &uri,
+                &config.diagnostics,
+                self.client.clone(),
+                Arc::clone(&self.config),
+            )
+            .await;
+            self.client.publish_diagnostics(uri, items, None).await;
+        }
+
+        if let Err(e) = self.client.inlay_hint_refresh().await {
+            tracing::debug!("inlay_hint_refresh not supported: {:?}", e);
+        }
+    }
+
+    async fn dependency_tree(
+        &self,
+        params: DependencyTreeParams,
+    ) -> Result<Option<DependencyTreeResult>> {
+        dependency_tree::handle_dependency_tree(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await
+    }
+
+    async fn why_dependency(
+        &self,
+        params: WhyDependencyParams,
+    ) -> Result<Option<WhyDependencyResult>> {
+        dependency_tree::handle_why_dependency(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await
+    }
+
+    async fn duplicates(&self, params: DuplicatesParams) -> Result<Option<DuplicatesResult>> {
+        Ok(duplicates::handle_duplicates(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
+    async fn export_sarif(&self, params: ExportSarifParams) -> Result<SarifLog> {
+        Ok(export_sarif::handle_export_sarif(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
+    async fn status(&self) -> Result<StatusResult> {
+        Ok(status::handle_status(Arc::clone(&self.state)).await)
+    }
+
     /// Check if client supports work done progress.
     #[allow(dead_code)]
     async fn supports_progress(&self) -> bool {
@@ -200,9 +364,22 @@ impl Backend {
                 ..Default::default()
             }),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
-            inlay_hint_provider: Some(OneOf::Left(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            inlay_hint_provider: Some(OneOf::Right(InlayHintServerCapabilities::Options(
+                InlayHintOptions {
+                    resolve_provider: Some(true),
+                    ..Default::default()
+                },
+            ))),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
             code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
-                code_action_kinds: Some(vec![tower_lsp_server::ls_types::CodeActionKind::REFACTOR]),
+                code_action_kinds: Some(vec![
+                    CodeActionKind::QUICKFIX,
+                    CodeActionKind::REFACTOR,
+                    CodeActionKind::REFACTOR_REWRITE,
+                ]),
                 ..Default::default()
             })),
             diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
@@ -212,7 +389,10 @@ impl Backend {
                 ..Default::default()
             })),
             execute_command_provider: Some(ExecuteCommandOptions {
-                commands: vec![commands::UPDATE_VERSION.into()],
+                commands: vec![
+                    commands::UPDATE_VERSION.into(),
+                    commands::ADD_DEPENDENCY.into(),
+                ],
                 ..Default::default()
             }),
             ..Default::default()
@@ -220,6 +400,13 @@ impl Backend {
     }
 }
 
+impl Drop for Backend {
+    fn drop(&mut self) {
+        // Clients may disconnect without `shutdown`
+        self.abort_session_tasks();
+    }
+}
+
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::info!("initializing deps-lsp server");
@@ -227,12 +414,30 @@ impl LanguageServer for Backend {
         // Store client capabilities
         *self.client_capabilities.write().await = Some(params.capabilities.clone());
 
+        // Remember workspace folders for the background manifest index
+        #[allow(deprecated)]
+        let roots: Vec<PathBuf> = match params.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|f| f.uri.to_file_path())
+                .map(std::borrow::Cow::into_owned)
+                .collect(),
+            None => params
+                .root_uri
+                .as_ref()
+                .and_then(Uri::to_file_path)
+                .map(std::borrow::Cow::into_owned)
+                .into_iter()
+                .collect(),
+        };
+        *self.workspace_roots.write().await = roots;
+
         // Parse initialization options
         if let Some(init_options) = params.initialization_options
             && let Ok(config) = serde_json::from_value::<DepsConfig>(init_options)
         {
             tracing::debug!("loaded configuration: {:?}", config);
-            *self.config.write().await = config;
+            self.apply_config(config).await;
         }
 
         Ok(InitializeResult {
@@ -247,6 +452,38 @@ impl LanguageServer for Backend {
 
     async fn initialized(&self, _: InitializedParams) {
         tracing::info!("deps-lsp server initialized");
+
+        let mut session_tasks = Vec::new();
+
+        // Build the workspace index in the background
+        let roots = self.workspace_roots.read().await.clone();
+        if !roots.is_empty() {
+            session_tasks.push(tokio::spawn(workspace_index::index_workspace(
+                Arc::clone(&self.state.workspace_index),
+                Arc::clone(&self.state.ecosystem_registry),
+                roots,
+            )));
+        }
+
+        // Spawn background cleanup task for cold start rate limiter
+        let state = Arc::downgrade(&self.state);
+        session_tasks.push(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_mins(1));
+            loop {
+                interval.tick().await;
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                state
+                    .cold_start_limiter
+                    .cleanup_old_entries(std::time::Duration::from_mins(5));
+                tracing::trace!("Cleaned up old cold start rate limit entries");
+            }
+        }));
+        if let Ok(mut tasks) = self.session_tasks.lock() {
+            tasks.extend(session_tasks);
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -268,22 +505,49 @@ impl LanguageServer for Backend {
                 .await;
         }
 
-        // Spawn background cleanup task for cold start rate limiter
-        let state_clone = Arc::clone(&self.state);
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_mins(1));
-            loop {
-                interval.tick().await;
-                state_clone
-                    .cold_start_limiter
-                    .cleanup_old_entries(std::time::Duration::from_mins(5));
-                tracing::trace!("Cleaned up old cold start rate limit entries");
+        // Watch manifests so the workspace index follows edits made outside the editor
+        let manifest_patterns = self.state.ecosystem_registry.all_manifest_patterns();
+        if let Err(e) =
+            file_watcher::register_manifest_watchers(&self.client, &manifest_patterns).await
+        {
+            tracing::warn!("Failed to register manifest watchers: {}", e);
+        }
+
+        if let Err(e) = file_watcher::register_project_config_watchers(&self.client).await {
+            tracing::warn!("Failed to register project config watchers: {}", e);
+        }
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // Clients either send the `deps-lsp` section or the settings object itself
+        let settings = match params.settings {
+            serde_json::Value::Object(mut map) if map.contains_key("deps-lsp") => {
+                map.remove("deps-lsp").unwrap_or_default()
             }
-        });
+            settings => settings,
+        };
+
+        match serde_json::from_value::<DepsConfig>(settings) {
+            Ok(config) => {
+                tracing::info!("configuration changed");
+                self.apply_config(config).await;
+                self.refresh_open_documents().await;
+            }
+            Err(e) => {
+                tracing::warn!("ignoring invalid configuration: {}", e);
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("Invalid deps-lsp settings: {e}"),
+                    )
+                    .await;
+            }
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
         tracing::info!("shutting down deps-lsp server");
+        self.abort_session_tasks();
         Ok(())
     }
 
@@ -294,7 +558,12 @@ impl LanguageServer for Backend {
         tracing::info!("document opened: {:?}", uri);
 
         // Use ecosystem registry to check if we support this file type
-        if self.state.ecosystem_registry.get_for_uri(&uri).is_none() {
+        let config = self.state.config_for(&self.config, &uri).await;
+        if self
+            .state
+            .enabled_ecosystem_for_uri(&uri, &config)
+            .is_none()
+        {
             tracing::debug!("unsupported file type: {:?}", uri);
             return;
         }
@@ -309,7 +578,12 @@ impl LanguageServer for Backend {
             let content = change.text.clone();
 
             // Use ecosystem registry to check if we support this file type
-            if self.state.ecosystem_registry.get_for_uri(&uri).is_none() {
+            let config = self.state.config_for(&self.config, &uri).await;
+            if self
+                .state
+                .enabled_ecosystem_for_uri(&uri, &config)
+                .is_none()
+            {
                 tracing::debug!("unsupported file type: {:?}", uri);
                 return;
             }
@@ -329,12 +603,46 @@ impl LanguageServer for Backend {
     async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
         tracing::debug!("Received {} file change events", params.changes.len());
 
+        let mut config_changed = false;
         for change in params.changes {
             let Some(path) = change.uri.to_file_path() else {
                 tracing::warn!("Invalid file path in change event: {:?}", change.uri);
                 continue;
             };
 
+            // pyproject.toml is a manifest as well, so keep going for it
+            if project_config::is_project_config_file(&path) {
+                config_changed = true;
+                if self
+                    .state
+                    .ecosystem_registry
+                    .get_for_uri(&change.uri)
+                    .is_none()
+                {
+                    continue;
+                }
+            }
+
+            // Manifests: keep the workspace index current. Open documents are
+            // indexed from their in-memory content by did_open/did_change.
+            if self
+                .state
+                .ecosystem_registry
+                .get_for_uri(&change.uri)
+                .is_some()
+            {
+                self.state.workspaces.invalidate(&path);
+                if change.typ == FileChangeType::DELETED {
+                    self.state.workspace_index.remove_manifest(&change.uri);
+                } else if !self.state.documents.contains_key(&change.uri) {
+                    self.state
+                        .workspace_index
+                        .index_file(&self.state.ecosystem_registry, &path)
+                        .await;
+                }
+                continue;
+            }
+
             let Some(filename) = file_watcher::extract_lockfile_name(&path) else {
                 continue;
             };
@@ -351,8 +659,19 @@ impl LanguageServer for Backend {
             );
 
             self.state.lockfile_cache.invalidate(&path);
+            // Keyed like `LockedManifest::lockfile`, which may spell the URI
+            // differently from the client
+            if let Some(lockfile) = Uri::from_file_path(&path) {
+                self.state.duplicates.remove(&lockfile);
+            }
             self.handle_lockfile_change(&path, ecosystem.id()).await;
         }
+
+        if config_changed {
+            tracing::info!("Project configuration changed, refreshing open manifests");
+            self.state.project_configs.invalidate();
+            self.refresh_open_documents().await;
+        }
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -365,6 +684,41 @@ impl LanguageServer for Backend {
         .await)
     }
 
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        Ok(definition::handle_definition(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        Ok(references::handle_references(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<WorkspaceSymbolResponse>> {
+        let config = self.config.read().await.clone();
+        Ok(workspace_symbol::handle_workspace_symbol(
+            &self.state,
+            &config,
+            &params,
+        ))
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         Ok(completion::handle_completion(
             Arc::clone(&self.state),
@@ -377,7 +731,11 @@ impl LanguageServer for Backend {
 
     async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
         // Clone config before async call to release lock early
-        let inlay_config = { self.config.read().await.inlay_hints.clone() };
+        let inlay_config = self
+            .state
+            .config_for(&self.config, &params.text_document.uri)
+            .await
+            .inlay_hints;
         let range = params.range;
 
         let hints: Vec<_> = inlay_hints::handle_inlay_hints(
@@ -395,6 +753,28 @@ impl LanguageServer for Backend {
         Ok(Some(hints))
     }
 
+    async fn inlay_hint_resolve(&self, hint: InlayHint) -> Result<InlayHint> {
+        Ok(inlay_hints::handle_inlay_hint_resolve(
+            Arc::clone(&self.state),
+            hint,
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        Ok(formatting::handle_range_formatting(
+            Arc::clone(&self.state),
+            params,
+            self.client.clone(),
+            Arc::clone(&self.config),
+        )
+        .await)
+    }
+
     async fn code_action(
         &self,
         params: CodeActionParams,
@@ -422,8 +802,7 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri;
         tracing::info!("diagnostic request for: {:?}", uri);
 
-        // Clone config before async call to release lock early
-        let diagnostics_config = { self.config.read().await.diagnostics.clone() };
+        let diagnostics_config = self.state.config_for(&self.config, &uri).await.diagnostics;
 
         let items = diagnostics::handle_diagnostics(
             Arc::clone(&self.state),
@@ -462,7 +841,7 @@ impl LanguageServer for Backend {
                 update_args.uri.clone(),
                 vec![TextEdit {
                     range: update_args.range,
-                    new_text: format!("\"{}\"", update_args.version),
+                    new_text: update_args.version,
                 }],
             );
 
@@ -476,6 +855,40 @@ impl LanguageServer for Backend {
             }
         }
 
+        if params.command == commands::ADD_DEPENDENCY {
+            let args = params
+                .arguments
+                .into_iter()
+                .next()
+                .map(serde_json::from_value::<add_dependency::AddDependencyArgs>);
+            let Some(Ok(args)) = args else {
+                tracing::warn!("Invalid arguments for {}", commands::ADD_DEPENDENCY);
+                return Ok(None);
+            };
+
+            let result = add_dependency::handle_add_dependency(
+                Arc::clone(&self.state),
+                args,
+                self.client.clone(),
+                Arc::clone(&self.config),
+            )
+            .await;
+            match result {
+                Ok(edit) => {
+                    let value = serde_json::to_value(&edit).ok();
+                    if let Err(e) = self.client.apply_edit(edit).await {
+                        tracing::error!("Failed to apply edit: {:?}", e);
+                    }
+                    return Ok(value);
+                }
+                Err(message) => {
+                    self.client
+                        .show_message(MessageType::WARNING, message)
+                        .await;
+                }
+            }
+        }
+
         Ok(None)
     }
 }
@@ -506,6 +919,12 @@ mod tests {
         // Verify hover provider
         assert!(caps.hover_provider.is_some());
 
+        // Verify definition provider
+        assert!(caps.definition_provider.is_some());
+        assert!(caps.references_provider.is_some());
+        assert!(caps.document_range_formatting_provider.is_some());
+        assert!(caps.workspace_symbol_provider.is_some());
+
         // Verify inlay hints
         assert!(caps.inlay_hint_provider.is_some());
 
@@ -565,7 +984,14 @@ mod tests {
                 let kinds = opts
                     .code_action_kinds
                     .expect("code action kinds should exist");
-                assert!(kinds.contains(&tower_lsp_server::ls_types::CodeActionKind::REFACTOR));
+                assert_eq!(
+                    kinds,
+                    vec![
+                        CodeActionKind::QUICKFIX,
+                        CodeActionKind::REFACTOR,
+                        CodeActionKind::REFACTOR_REWRITE,
+                    ]
+                );
             }
             _ => panic!("Expected code action provider options"),
         }
@@ -597,11 +1023,17 @@ mod tests {
                 .commands
                 .contains(&commands::UPDATE_VERSION.to_string())
         );
+        assert!(
+            execute
+                .commands
+                .contains(&commands::ADD_DEPENDENCY.to_string())
+        );
     }
 
     #[test]
     fn test_commands_constants() {
         assert_eq!(commands::UPDATE_VERSION, "deps-lsp.updateVersion");
+        assert_eq!(commands::ADD_DEPENDENCY, "deps.addDependency");
     }
 
     #[tokio::test]
@@ -0,0 +1,516 @@
+//! Transports the language server can run over.
+//!
+//! Besides the default stdio transport the server can listen on a TCP port
+//! (`--listen`) or a Unix domain socket (`--socket`), or connect out to a
+//! client that is already listening (`--connect`).
+//!
+//! A listening server accepts any number of clients, one after another or at
+//! the same time. Every connection gets its own [`Backend`] with its own
+//! documents and settings, but all of them share one [`HttpCache`], so a
+//! long-lived server answers most registry lookups from memory. Once the last
+//! client has disconnected the server waits [`ServeOptions::idle_timeout`]
+//! for another one and then exits.
+//!
+//! Connections are not authenticated: anyone who can reach the port or the
+//! socket can read the manifests the server has access to. `--listen` with
+//! a bare port therefore binds to loopback (`127.0.0.1`); only pass another
+//! address on a trusted network.
+
+use crate::server::Backend;
+use deps_core::HttpCache;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tower_lsp_server::Server;
+
+/// How long a listening server waits for a new client after the last one
+/// disconnected.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the server reads requests from and writes responses to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Transport {
+    /// Standard input and output (the default).
+    #[default]
+    Stdio,
+    /// Accept TCP connections on `host:port`. Not authenticated.
+    Listen(String),
+    /// Accept connections on a Unix domain socket.
+    Socket(PathBuf),
+    /// Connect to a client listening on `host:port`.
+    Connect(String),
+    /// Connect to a client listening on a Unix domain socket.
+    ConnectSocket(PathBuf),
+}
+
+impl Transport {
+    /// Interprets a `--connect` target: a value containing a path separator
+    /// or lacking a numeric port is a socket path, anything else `host:port`.
+    fn connect_target(value: String) -> Self {
+        let is_tcp = !value.contains('/')
+            && value
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if is_tcp {
+            Self::Connect(value)
+        } else {
+            Self::ConnectSocket(PathBuf::from(value))
+        }
+    }
+}
+
+/// Transport options of the language server itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeOptions {
+    /// Transport to serve over.
+    pub transport: Transport,
+    /// Grace period for new clients once the last one has disconnected.
+    pub idle_timeout: Duration,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            transport: Transport::Stdio,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+}
+
+impl ServeOptions {
+    /// Parses the server's command line arguments.
+    ///
+    /// Positional arguments are ignored, as some editors append their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_lsp::transport::{ServeOptions, Transport};
+    ///
+    /// let options = ServeOptions::parse(["--listen".to_string(), "127.0.0.1:9257".to_string()])
+    ///     .unwrap();
+    /// assert_eq!(options.transport, Transport::Listen("127.0.0.1:9257".into()));
+    /// ```
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut transports = 0;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .map(str::to_string)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {name}"))
+            };
+
+            match flag.as_str() {
+                "--stdio" => {
+                    options.transport = Transport::Stdio;
+                    transports += 1;
+                }
+                "--listen" => {
+                    let addr = value("--listen")?;
+                    // A bare port stays on loopback
+                    let addr = if addr.parse::<u16>().is_ok() {
+                        format!("127.0.0.1:{addr}")
+                    } else {
+                        addr
+                    };
+                    options.transport = Transport::Listen(addr);
+                    transports += 1;
+                }
+                "--socket" => {
+                    options.transport = Transport::Socket(PathBuf::from(value("--socket")?));
+                    transports += 1;
+                }
+                "--connect" => {
+                    options.transport = Transport::connect_target(value("--connect")?);
+                    transports += 1;
+                }
+                "--idle-timeout" => {
+                    let secs = value("--idle-timeout")?
+                        .parse()
+                        .map_err(|_| "Invalid --idle-timeout".to_string())?;
+                    options.idle_timeout = Duration::from_secs(secs);
+                }
+                flag if flag.starts_with('-') => return Err(format!("Unknown option: {flag}")),
+                _ => {}
+            }
+        }
+
+        if transports > 1 {
+            return Err(
+                "Only one of --stdio, --listen, --socket and --connect may be given".to_string(),
+            );
+        }
+        Ok(options)
+    }
+}
+
+/// Runs the language server until its client(s) are gone.
+///
+/// # Errors
+///
+/// Returns an error if the listener cannot be bound or the outgoing
+/// connection cannot be established.
+pub async fn serve(options: &ServeOptions) -> io::Result<()> {
+    let cache = Arc::new(HttpCache::new());
+
+    match &options.transport {
+        Transport::Stdio => {
+            run_session(tokio::io::stdin(), tokio::io::stdout(), cache).await;
+            Ok(())
+        }
+        Transport::Listen(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            tracing::info!("listening on {}", listener.local_addr()?);
+            serve_listener(Listener::Tcp(listener), cache, options.idle_timeout).await;
+            Ok(())
+        }
+        Transport::Connect(addr) => {
+            let stream = TcpStream::connect(addr).await?;
+            tracing::info!("connected to {addr}");
+            let (read, write) = stream.into_split();
+            run_session(read, write, cache).await;
+            Ok(())
+        }
+        #[cfg(unix)]
+        Transport::Socket(path) => {
+            let listener = bind_unix_socket(path).await?;
+            tracing::info!("listening on {}", path.display());
+            serve_listener(Listener::Unix(listener), cache, options.idle_timeout).await;
+            let _ = std::fs::remove_file(path);
+            Ok(())
+        }
+        #[cfg(unix)]
+        Transport::ConnectSocket(path) => {
+            let stream = tokio::net::UnixStream::connect(path).await?;
+            tracing::info!("connected to {}", path.display());
+            let (read, write) = stream.into_split();
+            run_session(read, write, cache).await;
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        Transport::Socket(_) | Transport::ConnectSocket(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Unix domain sockets are not supported on this platform",
+        )),
+    }
+}
+
+/// Binds a Unix domain socket at `path` that only the current user can
+/// connect to.
+///
+/// A socket file left behind by a crashed server is replaced; anything else
+/// at `path`, and sockets another server still listens on, are left alone.
+#[cfg(unix)]
+async fn bind_unix_socket(path: &std::path::Path) -> io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if !metadata.file_type().is_socket() => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        Ok(_) => {
+            if tokio::net::UnixStream::connect(path).await.is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("{} is in use by another server", path.display()),
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+type Connection = (
+    Box<dyn AsyncRead + Send + Unpin>,
+    Box<dyn AsyncWrite + Send + Unpin>,
+    String,
+);
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+}
+
+impl Listener {
+    async fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                let (read, write) = stream.into_split();
+                Ok((Box::new(read), Box::new(write), peer.to_string()))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let (read, write) = stream.into_split();
+                Ok((Box::new(read), Box::new(write), "unix socket".to_string()))
+            }
+        }
+    }
+}
+
+/// Accepts clients until none has been connected for `idle_timeout`.
+///
+/// The timeout only starts once the first client has come and gone, so a
+/// freshly started server waits for its first client indefinitely.
+async fn serve_listener(listener: Listener, cache: Arc<HttpCache>, idle_timeout: Duration) {
+    let mut sessions = JoinSet::new();
+    let mut served = false;
+
+    loop {
+        let idle = sessions.is_empty();
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((read, write, peer)) => {
+                    tracing::info!("client connected: {peer}");
+                    served = true;
+                    sessions.spawn(run_session(read, write, Arc::clone(&cache)));
+                }
+                Err(e) => tracing::warn!("failed to accept connection: {e}"),
+            },
+            Some(_) = sessions.join_next(), if !idle => {
+                tracing::info!("client disconnected, {} remaining", sessions.len());
+            }
+            () = tokio::time::sleep(idle_timeout), if idle && served => {
+                tracing::info!("no clients left, exiting");
+                break;
+            }
+        }
+    }
+}
+
+async fn run_session<R, W>(read: R, write: W, cache: Arc<HttpCache>)
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite,
+{
+    let (service, socket) = Backend::build_service_with_cache(cache);
+    Server::new(read, write, socket).serve(service).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn parse(args: &[&str]) -> Result<ServeOptions, String> {
+        ServeOptions::parse(args.iter().map(|s| (*s).to_string()))
+    }
+
+    #[test]
+    fn test_parse_transports() {
+        assert_eq!(parse(&[]).unwrap(), ServeOptions::default());
+        assert_eq!(parse(&["--stdio"]).unwrap().transport, Transport::Stdio);
+        assert_eq!(
+            parse(&["--listen", "127.0.0.1:9257"]).unwrap().transport,
+            Transport::Listen("127.0.0.1:9257".into())
+        );
+        assert_eq!(
+            parse(&["--listen", "9257"]).unwrap().transport,
+            Transport::Listen("127.0.0.1:9257".into())
+        );
+        assert_eq!(
+            parse(&["--socket=/tmp/deps.sock"]).unwrap().transport,
+            Transport::Socket("/tmp/deps.sock".into())
+        );
+        assert_eq!(
+            parse(&["--connect", "localhost:9257"]).unwrap().transport,
+            Transport::Connect("localhost:9257".into())
+        );
+        assert_eq!(
+            parse(&["--connect", "/run/user/1000/deps.sock"])
+                .unwrap()
+                .transport,
+            Transport::ConnectSocket("/run/user/1000/deps.sock".into())
+        );
+        assert_eq!(
+            parse(&["--connect", "deps.sock"]).unwrap().transport,
+            Transport::ConnectSocket("deps.sock".into())
+        );
+        assert_eq!(
+            parse(&["--idle-timeout", "0"]).unwrap().idle_timeout,
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--listen"]).is_err());
+        assert!(parse(&["--listen", ":1", "--socket", "a"]).is_err());
+        assert!(parse(&["--idle-timeout", "soon"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["positional"]).is_ok());
+    }
+
+    async fn request<S>(stream: &mut S, body: &str) -> Option<String>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let message = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        stream.write_all(message.as_bytes()).await.unwrap();
+        if !body.contains("\"id\"") {
+            return None;
+        }
+        Some(read_message(stream).await)
+    }
+
+    /// Reads one message without buffering past it, so that messages sent
+    /// back to back are not lost between calls.
+    async fn read_message<S: AsyncRead + Unpin>(stream: &mut S) -> String {
+        let mut header = Vec::new();
+        while !header.ends_with(b"\r\n\r\n") {
+            header.push(stream.read_u8().await.unwrap());
+        }
+        let length = String::from_utf8(header)
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: ")?.trim().parse().ok())
+            .unwrap();
+        let mut buf = vec![0; length];
+        stream.read_exact(&mut buf).await.unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    async fn run_client(addr: std::net::SocketAddr) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let response = request(
+            &mut stream,
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+        )
+        .await
+        .unwrap();
+        assert!(response.contains("\"capabilities\""), "{response}");
+        request(
+            &mut stream,
+            r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+        )
+        .await;
+        request(&mut stream, r#"{"jsonrpc":"2.0","method":"exit"}"#).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("deps-lsp.sock");
+
+        let listener = bind_unix_socket(&path).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Still accepting connections
+        let error = bind_unix_socket(&path).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        // Left behind by a server that is gone
+        drop(listener);
+        bind_unix_socket(&path).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_socket_keeps_other_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        std::fs::write(&path, "keep me").unwrap();
+
+        let error = bind_unix_socket(&path).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_listener_serves_sequential_clients_then_exits() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve_listener(
+            Listener::Tcp(listener),
+            Arc::new(HttpCache::new()),
+            Duration::from_millis(500),
+        ));
+
+        run_client(addr).await;
+        run_client(addr).await;
+
+        tokio::time::timeout(Duration::from_secs(10), server)
+            .await
+            .expect("server should exit after the last client")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_state_dropped_after_disconnect() {
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        let (service, socket) = Backend::build_service_with_cache(Arc::new(HttpCache::new()));
+        let state = service.inner().state_ref();
+        let (read, write) = tokio::io::split(server);
+        let session = tokio::spawn(Server::new(read, write, socket).serve(service));
+
+        request(
+            &mut client,
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+        )
+        .await
+        .unwrap();
+        request(
+            &mut client,
+            r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+        )
+        .await;
+        // Answer the watcher registrations until `initialized` has returned
+        while let Ok(message) =
+            tokio::time::timeout(Duration::from_millis(500), read_message(&mut client)).await
+        {
+            let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+            if let (Some(id), Some(_)) = (message.get("id"), message.get("method")) {
+                let body = serde_json::json!({"jsonrpc": "2.0", "id": id, "result": null});
+                let body = body.to_string();
+                let response = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+                client.write_all(response.as_bytes()).await.unwrap();
+            }
+        }
+
+        // Disconnect without `shutdown`
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(10), session)
+            .await
+            .expect("session should end when the client disconnects")
+            .unwrap();
+
+        // Aborted tasks release the state once the runtime drops them
+        for _ in 0..100 {
+            if state.upgrade().is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(
+            state.upgrade().is_none(),
+            "session state outlived the session"
+        );
+    }
+}
@@ -0,0 +1,669 @@
+//! Headless manifest updates (`deps-lsp update`).
+//!
+//! Rewrites version requirements in place with the same edits the "update
+//! version" code actions make: the dependency's version range is replaced by
+//! [`EcosystemFormatter::format_version_for_text_edit`] of the chosen
+//! version. Which version is chosen depends on the [`UpdateMode`]; packages
+//! listed in [`UpdateConfig::ignore`] are skipped and packages in
+//! [`UpdateConfig::pin`] stay within their pinned requirement. The `[update]`
+//! table of a manifest's [project configuration](crate::project_config)
+//! applies over the `--config` settings.
+//!
+//! Only manifests are written. Lock files are never touched; run the
+//! ecosystem's own tool (`cargo update`, `npm install`, ...) afterwards.
+
+use crate::check::{compatibility_key, fetch_versions, major_minor};
+use crate::config::{DepsConfig, UpdateConfig};
+use crate::project_config::ProjectConfigs;
+use crate::workspace_index::discover_manifests;
+use deps_core::lsp_helpers::EcosystemFormatter;
+use deps_core::{EcosystemRegistry, LineOffsetTable, Version};
+use std::fmt::Write;
+use std::path::PathBuf;
+use tower_lsp_server::ls_types::{TextEdit, Uri};
+
+/// Lines of context around each change in the diff preview.
+const DIFF_CONTEXT: usize = 3;
+
+/// How far `deps-lsp update` moves a requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateMode {
+    /// Newest semver-compatible version (same major, or same minor for `0.x`).
+    #[default]
+    Compatible,
+    /// Newest version with the same major version.
+    Minor,
+    /// Newest stable version.
+    Latest,
+}
+
+impl UpdateMode {
+    fn allows(self, current: &str, candidate: &str) -> bool {
+        match self {
+            Self::Compatible => compatibility_key(current) == compatibility_key(candidate),
+            Self::Minor => major_minor(current).0 == major_minor(candidate).0,
+            Self::Latest => true,
+        }
+    }
+}
+
+/// Options of `deps-lsp update`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateOptions {
+    /// Manifest or directory to update.
+    pub path: PathBuf,
+    /// How far requirements move.
+    pub mode: UpdateMode,
+    /// Ecosystem IDs to update; all when empty.
+    pub ecosystems: Vec<String>,
+    /// Print the diff without writing files.
+    pub dry_run: bool,
+    /// JSON file with the server configuration, whose `update` section applies.
+    pub config: Option<PathBuf>,
+    /// Extra ignore patterns from the command line.
+    pub ignore: Vec<String>,
+    /// Timeout for fetching the versions of one package.
+    pub timeout_secs: u64,
+    /// Maximum number of concurrent registry requests.
+    pub max_concurrent: usize,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        let cache = crate::config::CacheConfig::default();
+        Self {
+            path: PathBuf::from("."),
+            mode: UpdateMode::Compatible,
+            ecosystems: vec![],
+            dry_run: false,
+            config: None,
+            ignore: vec![],
+            timeout_secs: cache.fetch_timeout_secs,
+            max_concurrent: cache.max_concurrent_fetches,
+        }
+    }
+}
+
+impl UpdateOptions {
+    /// Parses the arguments following `update`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deps_lsp::update::{UpdateMode, UpdateOptions};
+    ///
+    /// let args = ["--mode", "latest", "--ecosystem", "cargo", "--dry-run", "crates/app"];
+    /// let options = UpdateOptions::parse(args.iter().map(|s| s.to_string())).unwrap();
+    /// assert_eq!(options.mode, UpdateMode::Latest);
+    /// assert_eq!(options.ecosystems, vec!["cargo"]);
+    /// assert!(options.dry_run);
+    /// ```
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut path = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .map(str::to_string)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {name}"))
+            };
+
+            match flag.as_str() {
+                "--mode" => {
+                    options.mode = match value("--mode")?.as_str() {
+                        "compatible" => UpdateMode::Compatible,
+                        "minor" => UpdateMode::Minor,
+                        "latest" => UpdateMode::Latest,
+                        other => return Err(format!("Unknown mode: {other}")),
+                    };
+                }
+                "--ecosystem" => options.ecosystems.extend(
+                    value("--ecosystem")?
+                        .split(',')
+                        .map(|id| id.trim().to_string()),
+                ),
+                "--ignore" => options.ignore.push(value("--ignore")?),
+                "--config" => options.config = Some(PathBuf::from(value("--config")?)),
+                "--dry-run" => options.dry_run = true,
+                "--timeout" => {
+                    options.timeout_secs = value("--timeout")?
+                        .parse()
+                        .map_err(|_| "Invalid --timeout".to_string())?;
+                }
+                flag if flag.starts_with('-') => return Err(format!("Unknown option: {flag}")),
+                _ if path.is_none() => path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument: {arg}")),
+            }
+        }
+
+        if let Some(path) = path {
+            options.path = path;
+        }
+        Ok(options)
+    }
+
+    /// Reads the `update` section of `--config` and adds `--ignore` patterns.
+    pub fn load_config(&self) -> Result<UpdateConfig, String> {
+        let mut config = match &self.config {
+            Some(path) => {
+                let json = std::fs::read_to_string(path)
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+                serde_json::from_str::<DepsConfig>(&json)
+                    .map_err(|e| format!("{}: {e}", path.display()))?
+                    .update
+            }
+            None => UpdateConfig::default(),
+        };
+        config.ignore.extend(self.ignore.iter().cloned());
+        Ok(config)
+    }
+}
+
+/// A requirement change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedUpdate {
+    /// Package name as written in the manifest.
+    pub package: String,
+    /// Text replaced.
+    pub from: String,
+    /// Replacement text.
+    pub to: String,
+    /// The edit itself.
+    pub edit: TextEdit,
+}
+
+/// The updates planned for one manifest.
+#[derive(Debug, Clone)]
+pub struct ManifestUpdate {
+    /// Manifest path.
+    pub path: PathBuf,
+    /// Content before the updates.
+    pub original: String,
+    /// Content after the updates.
+    pub updated: String,
+    /// Updates in document order.
+    pub updates: Vec<PlannedUpdate>,
+}
+
+impl ManifestUpdate {
+    /// Unified diff from [`Self::original`] to [`Self::updated`].
+    pub fn diff(&self) -> String {
+        let label = self.path.strip_prefix(".").unwrap_or(&self.path);
+        unified_diff(&label.display().to_string(), &self.original, &self.updated)
+    }
+
+    /// Writes the updated content, refusing to write lock files.
+    pub fn write(&self, registry: &EcosystemRegistry) -> std::io::Result<()> {
+        let is_lockfile = self
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| registry.get_for_lockfile(name).is_some());
+        if is_lockfile {
+            return Err(std::io::Error::other(format!(
+                "refusing to write lock file {}",
+                self.path.display()
+            )));
+        }
+        std::fs::write(&self.path, &self.updated)
+    }
+}
+
+/// Result of planning updates.
+#[derive(Debug, Clone, Default)]
+pub struct UpdatePlan {
+    /// Manifests with at least one update.
+    pub manifests: Vec<ManifestUpdate>,
+    /// Manifests that could not be read or parsed.
+    pub errors: Vec<String>,
+}
+
+/// Computes the updates for every manifest under `options.path` (or the
+/// manifest itself when it is a file).
+pub async fn plan_updates(
+    registry: &EcosystemRegistry,
+    options: &UpdateOptions,
+    config: &UpdateConfig,
+) -> UpdatePlan {
+    let mut plan = UpdatePlan::default();
+    let project_configs = ProjectConfigs::new();
+    let base = DepsConfig {
+        update: config.clone(),
+        ..DepsConfig::default()
+    };
+    let paths = if options.path.is_file() {
+        vec![options.path.clone()]
+    } else {
+        discover_manifests(&options.path, registry)
+    };
+
+    for path in paths {
+        let absolute = std::path::absolute(&path).unwrap_or_else(|_| path.clone());
+        let Some(uri) = Uri::from_file_path(&absolute) else {
+            continue;
+        };
+        let Some(ecosystem) = registry.get_for_uri(&uri) else {
+            plan.errors
+                .push(format!("{}: not a supported manifest", path.display()));
+            continue;
+        };
+        // The manifest's `.deps-lsp.toml` may add ignore rules and pins;
+        // patterns given with --ignore always apply
+        let mut config = project_configs.resolve(&base, &uri).update;
+        config.ignore.extend(options.ignore.iter().cloned());
+        if !options.ecosystems.is_empty()
+            && !options.ecosystems.iter().any(|id| id == ecosystem.id())
+        {
+            continue;
+        }
+
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) => {
+                plan.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        let parse_result = match ecosystem.parse_manifest(&content, &uri).await {
+            Ok(parse_result) => parse_result,
+            Err(e) => {
+                plan.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+
+        let candidates: Vec<_> = parse_result
+            .dependencies()
+            .into_iter()
+            .filter(|dep| dep.source().is_registry() && !config.is_ignored(dep.name()))
+            .filter_map(|dep| Some((dep, dep.version_requirement()?, dep.version_range()?)))
+            .collect();
+        let mut names: Vec<&str> = candidates.iter().map(|(dep, ..)| dep.name()).collect();
+        names.sort_unstable();
+        names.dedup();
+        let fetched = fetch_versions(
+            ecosystem.registry_for(parse_result.as_ref()),
+            names,
+            options.timeout_secs,
+            options.max_concurrent,
+        )
+        .await;
+
+        let formatter = ecosystem.formatter();
+        let table = LineOffsetTable::new(&content);
+        let mut updates: Vec<PlannedUpdate> = candidates
+            .into_iter()
+            .filter_map(|(dep, requirement, range)| {
+                let versions = fetched.get(dep.name())?;
+                let pin = config.pin(dep.name());
+                let target = select_target(options.mode, requirement, versions, formatter, pin)?;
+                let from = table.slice(&content, range);
+                let to = formatter.format_version_for_text_edit(target.version_string());
+                (from != to).then(|| PlannedUpdate {
+                    package: dep.name().to_string(),
+                    from: from.to_string(),
+                    edit: TextEdit {
+                        range,
+                        new_text: to.clone(),
+                    },
+                    to,
+                })
+            })
+            .collect();
+        if updates.is_empty() {
+            continue;
+        }
+
+        updates.sort_by_key(|update| {
+            (
+                update.edit.range.start.line,
+                update.edit.range.start.character,
+            )
+        });
+        let edits: Vec<TextEdit> = updates.iter().map(|update| update.edit.clone()).collect();
+        plan.manifests.push(ManifestUpdate {
+            path,
+            updated: apply_edits(&content, &edits),
+            original: content,
+            updates,
+        });
+    }
+
+    plan
+}
+
+/// Picks the version a requirement moves to.
+///
+/// Starting from the newest stable version the requirement accepts, only
+/// newer (or equal) stable, non-yanked versions allowed by `mode` and `pin`
+/// qualify; the newest of them wins. Requirements accepting no published
+/// version are left alone.
+fn select_target<'v>(
+    mode: UpdateMode,
+    requirement: &str,
+    versions: &'v [Box<dyn Version>],
+    formatter: &dyn EcosystemFormatter,
+    pin: Option<&str>,
+) -> Option<&'v dyn Version> {
+    // Versions are sorted newest first; prefer what the requirement resolves
+    // to today over yanked versions it happens to accept
+    let satisfies =
+        |v: &dyn Version| formatter.version_satisfies_requirement(v.version_string(), requirement);
+    let current = versions
+        .iter()
+        .position(|v| v.is_stable() && satisfies(v.as_ref()))
+        .or_else(|| versions.iter().position(|v| satisfies(v.as_ref())))?;
+    let current_version = versions[current].version_string();
+
+    versions[..=current].iter().map(AsRef::as_ref).find(|v| {
+        v.is_stable()
+            && mode.allows(current_version, v.version_string())
+            && pin
+                .is_none_or(|pin| formatter.version_satisfies_requirement(v.version_string(), pin))
+    })
+}
+
+/// Applies non-overlapping edits to `content`.
+fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+    let table = LineOffsetTable::new(content);
+    let mut ranges: Vec<(usize, usize, &str)> = edits
+        .iter()
+        .map(|edit| {
+            (
+                table.position_to_byte_offset(content, edit.range.start),
+                table.position_to_byte_offset(content, edit.range.end),
+                edit.new_text.as_str(),
+            )
+        })
+        .collect();
+    ranges.sort_by_key(|&(start, ..)| std::cmp::Reverse(start));
+
+    let mut result = content.to_string();
+    for (start, end, text) in ranges {
+        result.replace_range(start..end, text);
+    }
+    result
+}
+
+/// Renders a unified diff of two versions of `label`.
+///
+/// Requirement edits never add or remove lines, so lines are compared
+/// pairwise; if the line counts differ the whole file forms one hunk.
+pub fn unified_diff(label: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut out = format!("--- a/{label}\n+++ b/{label}\n");
+
+    if old_lines.len() != new_lines.len() {
+        let _ = writeln!(out, "@@ -1,{} +1,{} @@", old_lines.len(), new_lines.len());
+        for line in &old_lines {
+            let _ = writeln!(out, "-{line}");
+        }
+        for line in &new_lines {
+            let _ = writeln!(out, "+{line}");
+        }
+        return out;
+    }
+
+    let changed: Vec<usize> = (0..old_lines.len())
+        .filter(|&i| old_lines[i] != new_lines[i])
+        .collect();
+
+    // Group changed lines whose context windows touch into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &line in &changed {
+        let start = line.saturating_sub(DIFF_CONTEXT);
+        let end = (line + DIFF_CONTEXT + 1).min(old_lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let len = end - start;
+        let _ = writeln!(out, "@@ -{},{len} +{},{len} @@", start + 1, start + 1);
+        let mut i = start;
+        while i < end {
+            if old_lines[i] == new_lines[i] {
+                let _ = writeln!(out, " {}", old_lines[i]);
+                i += 1;
+                continue;
+            }
+            // A run of changed lines: removals first, then additions
+            let run_end = (i..end)
+                .find(|&j| old_lines[j] == new_lines[j])
+                .unwrap_or(end);
+            for line in &old_lines[i..run_end] {
+                let _ = writeln!(out, "-{line}");
+            }
+            for line in &new_lines[i..run_end] {
+                let _ = writeln!(out, "+{line}");
+            }
+            i = run_end;
+        }
+    }
+    out
+}
+
+/// Writes every planned update, returning the paths that failed.
+pub fn write_updates(registry: &EcosystemRegistry, plan: &UpdatePlan) -> Vec<(PathBuf, String)> {
+    plan.manifests
+        .iter()
+        .filter_map(|manifest| {
+            manifest
+                .write(registry)
+                .err()
+                .map(|e| (manifest.path.clone(), e.to_string()))
+        })
+        .collect()
+}
+
+/// Short summary line of `plan`, e.g. `3 updates in 2 manifests`.
+pub fn summary(plan: &UpdatePlan) -> String {
+    let updates: usize = plan.manifests.iter().map(|m| m.updates.len()).sum();
+    format!(
+        "{updates} update{} in {} manifest{}",
+        if updates == 1 { "" } else { "s" },
+        plan.manifests.len(),
+        if plan.manifests.len() == 1 { "" } else { "s" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::any::Any;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    struct TestVersion(&'static str, bool);
+
+    impl Version for TestVersion {
+        fn version_string(&self) -> &str {
+            self.0
+        }
+        fn is_yanked(&self) -> bool {
+            self.1
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    /// Cargo-like caret semantics for bare requirements.
+    struct CaretFormatter;
+
+    impl EcosystemFormatter for CaretFormatter {
+        fn format_version_for_text_edit(&self, version: &str) -> String {
+            version.to_string()
+        }
+        fn version_satisfies_requirement(&self, version: &str, requirement: &str) -> bool {
+            compatibility_key(version) == compatibility_key(requirement)
+                && major_minor(version) >= major_minor(requirement)
+        }
+        fn package_url(&self, name: &str) -> String {
+            name.to_string()
+        }
+    }
+
+    fn versions() -> Vec<Box<dyn Version>> {
+        [
+            ("3.0.0-beta.1", false),
+            ("2.4.0", false),
+            ("2.1.0", false),
+            ("1.9.0", true),
+            ("1.8.2", false),
+            ("1.2.0", false),
+            ("0.5.0", false),
+        ]
+        .into_iter()
+        .map(|(v, yanked)| Box::new(TestVersion(v, yanked)) as Box<dyn Version>)
+        .collect()
+    }
+
+    fn target(mode: UpdateMode, requirement: &str, pin: Option<&str>) -> Option<String> {
+        let versions = versions();
+        select_target(mode, requirement, &versions, &CaretFormatter, pin)
+            .map(|v| v.version_string().to_string())
+    }
+
+    #[test]
+    fn test_select_target_modes() {
+        assert_eq!(
+            target(UpdateMode::Compatible, "1.2", None),
+            Some("1.8.2".into())
+        );
+        assert_eq!(target(UpdateMode::Minor, "1.2", None), Some("1.8.2".into()));
+        assert_eq!(
+            target(UpdateMode::Latest, "1.2", None),
+            Some("2.4.0".into())
+        );
+        assert_eq!(
+            target(UpdateMode::Compatible, "0.5", None),
+            Some("0.5.0".into())
+        );
+        assert_eq!(
+            target(UpdateMode::Latest, "0.5", None),
+            Some("2.4.0".into())
+        );
+    }
+
+    #[test]
+    fn test_select_target_pin_and_unknown_requirement() {
+        assert_eq!(
+            target(UpdateMode::Latest, "1.2", Some("1")),
+            Some("1.8.2".into())
+        );
+        assert_eq!(target(UpdateMode::Latest, "1.2", Some("0.5")), None);
+        assert_eq!(target(UpdateMode::Latest, "4.0", None), None);
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let content = "[dependencies]\nserde = \"1.0\"\ntokio = \"1\"\n";
+        let edits = [
+            TextEdit {
+                range: Range::new(Position::new(2, 9), Position::new(2, 10)),
+                new_text: "1.40.0".into(),
+            },
+            TextEdit {
+                range: Range::new(Position::new(1, 9), Position::new(1, 12)),
+                new_text: "1.0.228".into(),
+            },
+        ];
+        assert_eq!(
+            apply_edits(content, &edits),
+            "[dependencies]\nserde = \"1.0.228\"\ntokio = \"1.40.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nK\nl\n";
+        assert_eq!(
+            unified_diff("Cargo.toml", old, new),
+            "--- a/Cargo.toml\n+++ b/Cargo.toml\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,5 +8,5 @@\n h\n i\n j\n-k\n+K\n l\n"
+        );
+
+        let merged = unified_diff("x", "a\nb\nc\nd\n", "A\nb\nc\nD\n");
+        assert_eq!(merged.matches("@@ -").count(), 1);
+
+        let run = unified_diff("x", "a\nb\nc\n", "A\nB\nc\n");
+        assert_eq!(
+            run,
+            "--- a/x\n+++ b/x\n@@ -1,3 +1,3 @@\n-a\n-b\n+A\n+B\n c\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parse = |args: &[&str]| UpdateOptions::parse(args.iter().map(|s| s.to_string()));
+
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.mode, UpdateMode::Compatible);
+        assert_eq!(options.path, PathBuf::from("."));
+        assert!(!options.dry_run);
+
+        let options = parse(&["--ecosystem=cargo,npm", "--ignore", "aws-*", "Cargo.toml"]).unwrap();
+        assert_eq!(options.ecosystems, vec!["cargo", "npm"]);
+        assert_eq!(options.ignore, vec!["aws-*"]);
+        assert_eq!(options.path, PathBuf::from("Cargo.toml"));
+
+        assert!(parse(&["--mode", "major"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("deps-lsp.json");
+        std::fs::write(
+            &path,
+            r#"{ "update": { "ignore": ["tokio"], "pin": { "serde": "1" } } }"#,
+        )
+        .unwrap();
+
+        let options = UpdateOptions {
+            config: Some(path),
+            ignore: vec!["aws-*".into()],
+            ..Default::default()
+        };
+        let config = options.load_config().unwrap();
+        assert!(config.is_ignored("tokio"));
+        assert!(config.is_ignored("aws-config"));
+        assert_eq!(config.pin("serde"), Some("1"));
+
+        let options = UpdateOptions {
+            config: Some(temp_dir.path().join("missing.json")),
+            ..Default::default()
+        };
+        assert!(options.load_config().is_err());
+    }
+
+    #[cfg(feature = "cargo")]
+    #[test]
+    fn test_write_refuses_lock_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let registry = EcosystemRegistry::new();
+        crate::register_ecosystems(&registry, std::sync::Arc::new(deps_core::HttpCache::new()));
+
+        let update = ManifestUpdate {
+            path: temp_dir.path().join("Cargo.lock"),
+            original: String::new(),
+            updated: "changed".into(),
+            updates: vec![],
+        };
+        assert!(update.write(&registry).is_err());
+        assert!(!update.path.exists());
+    }
+}
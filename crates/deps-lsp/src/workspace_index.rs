@@ -0,0 +1,427 @@
+//! Workspace-wide dependency index.
+//!
+//! Backs `textDocument/references` and `workspace/symbol`: every manifest
+//! discovered under the workspace folders is parsed through the
+//! `EcosystemRegistry` and its dependency declarations are recorded with their
+//! `name_range`. The index is kept current incrementally — open documents are
+//! re-indexed on every change, and watched manifests on create/change/delete.
+//!
+//! # Discovery
+//!
+//! Directory walks skip VCS metadata, hidden directories and build/vendor
+//! output (`node_modules`, `target`, ...), stop at [`MAX_DISCOVERY_DEPTH`],
+//! and index at most [`MAX_INDEXED_MANIFESTS`] manifests.
+
+use dashmap::DashMap;
+use deps_core::{Ecosystem, EcosystemId, EcosystemRegistry, ParseResult};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp_server::ls_types::{Location, Range, Uri};
+
+/// Maximum directory depth explored below a workspace folder.
+const MAX_DISCOVERY_DEPTH: usize = 16;
+
+/// Maximum number of manifests indexed from a workspace scan.
+const MAX_INDEXED_MANIFESTS: usize = 10_000;
+
+/// Manifests larger than this are not indexed (matches the cold start limit).
+const MAX_MANIFEST_SIZE: u64 = 10_000_000;
+
+/// Directory names never descended into during discovery.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "build",
+    "dist",
+    "bin",
+    "obj",
+    "__pycache__",
+];
+
+/// A single dependency declaration recorded in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedDependency {
+    /// Ecosystem that declared the dependency.
+    pub ecosystem: EcosystemId,
+    /// Name as written in the manifest.
+    pub name: String,
+    /// Name after ecosystem-specific normalization, used for matching.
+    pub normalized_name: String,
+    /// Range of the name in the declaring manifest.
+    pub name_range: Range,
+    /// Version requirement, if any.
+    pub version_requirement: Option<String>,
+}
+
+/// Index of dependency declarations across all workspace manifests.
+#[derive(Default)]
+pub struct WorkspaceIndex {
+    manifests: DashMap<Uri, Vec<IndexedDependency>>,
+}
+
+impl WorkspaceIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `content` and replaces the entries recorded for `uri`.
+    ///
+    /// Returns `false` (leaving previous entries untouched) if no ecosystem
+    /// handles the URI or the manifest fails to parse — a half-typed manifest
+    /// should not make its dependencies vanish from the index.
+    pub async fn index_manifest(
+        &self,
+        registry: &EcosystemRegistry,
+        uri: &Uri,
+        content: &str,
+    ) -> bool {
+        let Some(ecosystem) = registry.get_for_uri(uri) else {
+            return false;
+        };
+        let Ok(ecosystem_id) = ecosystem.id().parse::<EcosystemId>() else {
+            return false;
+        };
+        let parse_result = match ecosystem.parse_manifest(content, uri).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::debug!("Skipping index update for {:?}: {}", uri, e);
+                return false;
+            }
+        };
+
+        self.record(uri, ecosystem_id, ecosystem.as_ref(), parse_result.as_ref());
+        true
+    }
+
+    /// Replaces the entries for `uri` with the declarations in `parse_result`.
+    ///
+    /// Used by the document lifecycle, which has already parsed the content.
+    pub fn record(
+        &self,
+        uri: &Uri,
+        ecosystem_id: EcosystemId,
+        ecosystem: &dyn Ecosystem,
+        parse_result: &dyn ParseResult,
+    ) {
+        let formatter = ecosystem.formatter();
+        let entries = parse_result
+            .dependencies()
+            .into_iter()
+            .map(|dep| IndexedDependency {
+                ecosystem: ecosystem_id,
+                name: dep.name().to_string(),
+                normalized_name: formatter.normalize_package_name(dep.name()),
+                name_range: dep.name_range(),
+                version_requirement: dep.version_requirement().map(String::from),
+            })
+            .collect();
+
+        self.manifests.insert(uri.clone(), entries);
+    }
+
+    /// Reads a manifest from disk and indexes it.
+    pub async fn index_file(&self, registry: &EcosystemRegistry, path: &Path) -> bool {
+        let Some(uri) = Uri::from_file_path(path) else {
+            return false;
+        };
+        match tokio::fs::metadata(path).await {
+            Ok(meta) if meta.len() <= MAX_MANIFEST_SIZE => {}
+            _ => return false,
+        }
+        let Ok(content) = tokio::fs::read_to_string(path).await else {
+            return false;
+        };
+        self.index_manifest(registry, &uri, &content).await
+    }
+
+    /// Removes all entries recorded for `uri`.
+    pub fn remove_manifest(&self, uri: &Uri) {
+        self.manifests.remove(uri);
+    }
+
+    /// Returns whether `uri` has been indexed.
+    pub fn contains(&self, uri: &Uri) -> bool {
+        self.manifests.contains_key(uri)
+    }
+
+    /// Number of indexed manifests.
+    pub fn manifest_count(&self) -> usize {
+        self.manifests.len()
+    }
+
+    /// Returns every declaration of `normalized_name` in `ecosystem`.
+    ///
+    /// Locations are sorted by URI and position for stable output.
+    pub fn references(&self, ecosystem: EcosystemId, normalized_name: &str) -> Vec<Location> {
+        let mut locations: Vec<Location> = self
+            .manifests
+            .iter()
+            .flat_map(|entry| {
+                let uri = entry.key().clone();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|dep| {
+                        dep.ecosystem == ecosystem && dep.normalized_name == normalized_name
+                    })
+                    .map(|dep| Location::new(uri.clone(), dep.name_range))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        locations.sort_by(|a, b| {
+            a.uri
+                .as_str()
+                .cmp(b.uri.as_str())
+                .then(a.range.start.line.cmp(&b.range.start.line))
+                .then(a.range.start.character.cmp(&b.range.start.character))
+        });
+        locations
+    }
+
+    /// Returns declarations whose name contains `query` (case-insensitive).
+    ///
+    /// An empty query matches everything. Results are sorted by name, then URI,
+    /// and truncated to `limit` entries.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(Uri, IndexedDependency)> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<(Uri, IndexedDependency)> = self
+            .manifests
+            .iter()
+            .flat_map(|entry| {
+                let uri = entry.key().clone();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|dep| dep.name.to_lowercase().contains(&query))
+                    .map(|dep| (uri.clone(), dep.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        matches.sort_by(|(a_uri, a), (b_uri, b)| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| a_uri.as_str().cmp(b_uri.as_str()))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// Finds all manifest files under `root` handled by a registered ecosystem.
+pub fn discover_manifests(root: &Path, registry: &EcosystemRegistry) -> Vec<PathBuf> {
+    let mut manifests = Vec::new();
+    let mut stack = vec![(root.to_path_buf(), 0usize)];
+
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                if depth < MAX_DISCOVERY_DEPTH
+                    && !name.starts_with('.')
+                    && !SKIPPED_DIRS.contains(&name)
+                {
+                    stack.push((entry.path(), depth + 1));
+                }
+            } else if file_type.is_file() && registry.get_for_filename(name).is_some() {
+                manifests.push(entry.path());
+                if manifests.len() >= MAX_INDEXED_MANIFESTS {
+                    tracing::warn!(
+                        "Workspace index limit of {} manifests reached under {}",
+                        MAX_INDEXED_MANIFESTS,
+                        root.display()
+                    );
+                    manifests.sort();
+                    return manifests;
+                }
+            }
+        }
+    }
+
+    manifests.sort();
+    manifests
+}
+
+/// Discovers and indexes every manifest under `roots`.
+///
+/// Manifests already present in the index (e.g. open documents indexed from
+/// their in-memory content) are left as they are.
+pub async fn index_workspace(
+    index: Arc<WorkspaceIndex>,
+    registry: Arc<EcosystemRegistry>,
+    roots: Vec<PathBuf>,
+) {
+    let started = std::time::Instant::now();
+    for root in roots {
+        let scan_registry = Arc::clone(&registry);
+        let scan_root = root.clone();
+        let manifests =
+            tokio::task::spawn_blocking(move || discover_manifests(&scan_root, &scan_registry))
+                .await
+                .unwrap_or_default();
+
+        for path in manifests {
+            if Uri::from_file_path(&path).is_some_and(|uri| index.contains(&uri)) {
+                continue;
+            }
+            index.index_file(&registry, &path).await;
+        }
+    }
+    tracing::info!(
+        "Indexed {} manifests in {:?}",
+        index.manifest_count(),
+        started.elapsed()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> Arc<EcosystemRegistry> {
+        let registry = Arc::new(EcosystemRegistry::new());
+        crate::register_ecosystems(&registry, Arc::new(deps_core::HttpCache::new()));
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_index_manifest_unsupported_file() {
+        let index = WorkspaceIndex::new();
+        let uri = deps_core::test_util::test_uri("/test/README.md");
+        assert!(!index.index_manifest(&registry(), &uri, "# readme").await);
+        assert_eq!(index.manifest_count(), 0);
+    }
+
+    #[test]
+    fn test_search_empty_index() {
+        let index = WorkspaceIndex::new();
+        assert!(index.search("", 10).is_empty());
+    }
+
+    #[cfg(feature = "cargo")]
+    mod cargo_tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_references_across_manifests() {
+            let registry = registry();
+            let index = WorkspaceIndex::new();
+            let a = deps_core::test_util::test_uri("/ws/a/Cargo.toml");
+            let b = deps_core::test_util::test_uri("/ws/b/Cargo.toml");
+
+            assert!(
+                index
+                    .index_manifest(&registry, &a, "[dependencies]\nserde = \"1.0\"\n")
+                    .await
+            );
+            assert!(
+                index
+                    .index_manifest(
+                        &registry,
+                        &b,
+                        "[dependencies]\ntokio = \"1\"\n\n[dev-dependencies]\nserde = \"1.0.190\"\n",
+                    )
+                    .await
+            );
+
+            let refs = index.references(EcosystemId::Cargo, "serde");
+            assert_eq!(refs.len(), 2);
+            assert_eq!(refs[0].uri, a);
+            assert_eq!(refs[0].range.start.line, 1);
+            assert_eq!(refs[1].uri, b);
+            assert_eq!(refs[1].range.start.line, 4);
+
+            assert!(index.references(EcosystemId::Npm, "serde").is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_reindex_replaces_entries() {
+            let registry = registry();
+            let index = WorkspaceIndex::new();
+            let uri = deps_core::test_util::test_uri("/ws/Cargo.toml");
+
+            index
+                .index_manifest(&registry, &uri, "[dependencies]\nserde = \"1.0\"\n")
+                .await;
+            index
+                .index_manifest(&registry, &uri, "[dependencies]\nanyhow = \"1.0\"\n")
+                .await;
+
+            assert!(index.references(EcosystemId::Cargo, "serde").is_empty());
+            assert_eq!(index.references(EcosystemId::Cargo, "anyhow").len(), 1);
+
+            // A parse error keeps the last good entries
+            assert!(
+                !index
+                    .index_manifest(&registry, &uri, "[dependencies\n")
+                    .await
+            );
+            assert_eq!(index.references(EcosystemId::Cargo, "anyhow").len(), 1);
+
+            index.remove_manifest(&uri);
+            assert_eq!(index.manifest_count(), 0);
+        }
+
+        #[tokio::test]
+        async fn test_search_is_case_insensitive_and_limited() {
+            let registry = registry();
+            let index = WorkspaceIndex::new();
+            let uri = deps_core::test_util::test_uri("/ws/Cargo.toml");
+            index
+                .index_manifest(
+                    &registry,
+                    &uri,
+                    "[dependencies]\nserde = \"1.0\"\nserde_json = \"1.0\"\ntokio = \"1\"\n",
+                )
+                .await;
+
+            let results = index.search("SERDE", 10);
+            let names: Vec<&str> = results.iter().map(|(_, d)| d.name.as_str()).collect();
+            assert_eq!(names, vec!["serde", "serde_json"]);
+            assert_eq!(index.search("", 2).len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_discover_and_index_workspace() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let root = temp_dir.path();
+            for (path, content) in [
+                ("Cargo.toml", "[workspace]\nmembers = [\"app\"]\n"),
+                ("app/Cargo.toml", "[dependencies]\nserde = \"1.0\"\n"),
+                (
+                    "target/debug/Cargo.toml",
+                    "[dependencies]\nserde = \"1.0\"\n",
+                ),
+                (".hidden/Cargo.toml", "[dependencies]\nserde = \"1.0\"\n"),
+                ("app/README.md", "not a manifest"),
+            ] {
+                let full = root.join(path);
+                std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+                std::fs::write(full, content).unwrap();
+            }
+
+            let registry = registry();
+            let manifests = discover_manifests(root, &registry);
+            assert_eq!(
+                manifests,
+                vec![root.join("Cargo.toml"), root.join("app/Cargo.toml")]
+            );
+
+            let index = Arc::new(WorkspaceIndex::new());
+            index_workspace(Arc::clone(&index), registry, vec![root.to_path_buf()]).await;
+            assert_eq!(index.manifest_count(), 2);
+            assert_eq!(index.references(EcosystemId::Cargo, "serde").len(), 1);
+        }
+    }
+}
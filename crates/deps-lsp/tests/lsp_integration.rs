@@ -351,6 +351,65 @@ fn test_jsonrpc_error_on_invalid_method() {
     assert_eq!(response["error"]["code"], json!(-32601)); // Method not found
 }
 
+#[test]
+fn test_dependency_tree_custom_requests() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let manifest = temp_dir.path().join("Cargo.toml");
+    std::fs::write(
+        &manifest,
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("Cargo.lock"),
+        r#"version = 4
+
+[[package]]
+name = "serde"
+version = "1.0.195"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["serde_derive"]
+
+[[package]]
+name = "serde_derive"
+version = "1.0.195"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+    )
+    .unwrap();
+    let uri = format!("file://{}", manifest.display());
+
+    let mut client = LspClient::spawn();
+    client.initialize();
+
+    client.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 200,
+        "method": "deps/dependencyTree",
+        "params": { "textDocument": { "uri": uri } }
+    }));
+    let response = client.read_response(Some(200));
+    let root = &response["result"]["dependencies"][0];
+    assert_eq!(root["name"], "serde", "unexpected response: {response:?}");
+    assert_eq!(root["source"]["kind"], "registry");
+    assert_eq!(root["children"][0]["name"], "serde_derive");
+
+    client.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 201,
+        "method": "deps/whyDependency",
+        "params": { "textDocument": { "uri": uri }, "package": "serde_derive" }
+    }));
+    let response = client.read_response(Some(201));
+    assert_eq!(
+        response["result"]["paths"],
+        json!([[
+            { "name": "serde", "version": "1.0.195" },
+            { "name": "serde_derive", "version": "1.0.195" }
+        ]])
+    );
+}
+
 // Cold Start Integration Tests
 
 #[test]
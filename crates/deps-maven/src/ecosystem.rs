@@ -2,11 +2,14 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Location, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
     position_in_range,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::MavenFormatter;
@@ -127,6 +130,13 @@ impl Ecosystem for MavenEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: MavenFormatter,
+        }))
+    }
+
     fn formatter(&self) -> &dyn EcosystemFormatter {
         &self.formatter
     }
@@ -159,11 +169,177 @@ impl Ecosystem for MavenEcosystem {
         })
     }
 
+    fn generate_definition<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        _content: &'a str,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            let pom = parse_result
+                .as_any()
+                .downcast_ref::<crate::parser::MavenParseResult>()?;
+            let dep = pom.dependencies.iter().find(|d| {
+                position_in_range(position, d.name_range)
+                    || d.version_range
+                        .is_some_and(|r| position_in_range(position, r))
+            })?;
+            let key = dep.version_property.as_ref()?;
+            let range = pom.property_ranges.get(key)?;
+            Some(Location::new(pom.uri.clone(), *range))
+        })
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let pom = parse_result
+                .as_any()
+                .downcast_ref::<crate::parser::MavenParseResult>()?;
+            let (group_id, artifact_id) = dependency.name.split_once(':')?;
+            let scope = match &dependency.kind {
+                DependencyKind::Normal => None,
+                DependencyKind::Dev | DependencyKind::Test => Some("test"),
+                DependencyKind::Other(scope) => Some(scope.as_str()),
+                _ => return None,
+            };
+
+            let newline = add_dependency::newline(content);
+            let unit = add_dependency::indent_unit(content);
+            let mut entry = format!(
+                "<dependency>{newline}{unit}<groupId>{group_id}</groupId>{newline}{unit}<artifactId>{artifact_id}</artifactId>{newline}{unit}<version>{}</version>",
+                dependency.version
+            );
+            if let Some(scope) = scope {
+                entry.push_str(&format!("{newline}{unit}<scope>{scope}</scope>"));
+            }
+            entry.push_str(&format!("{newline}</dependency>"));
+
+            let lines: Vec<&str> = content.lines().collect();
+            let edits = match project_dependencies(content) {
+                Some((open, close)) => {
+                    let section = dependency_section(pom, &lines, open, close);
+                    match deps_core::sort::insert_sorted(
+                        content,
+                        &section,
+                        &dependency.name,
+                        &entry,
+                    ) {
+                        Some(edits) => edits,
+                        None => {
+                            let indent = format!(
+                                "{}{unit}",
+                                add_dependency::indentation_of(lines[open as usize])
+                            );
+                            vec![add_dependency::insert_after_line(
+                                content,
+                                open,
+                                &add_dependency::indent_lines(&entry, &indent, newline),
+                            )]
+                        }
+                    }
+                }
+                None => {
+                    let close = add_dependency::find_line(content, |l| l == "</project>")?;
+                    let block = format!(
+                        "<dependencies>{newline}{}{newline}</dependencies>",
+                        add_dependency::indent_lines(&entry, unit, newline)
+                    );
+                    vec![add_dependency::insert_before_line(
+                        content,
+                        close,
+                        &add_dependency::indent_lines(&block, unit, newline),
+                    )]
+                }
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Finds the lines of the `<dependencies>` and `</dependencies>` tags that
+/// are direct children of `<project>`, skipping `<dependencyManagement>`,
+/// plugin and profile dependencies.
+fn project_dependencies(content: &str) -> Option<(u32, u32)> {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() as u32;
+    let mut stack: Vec<&str> = Vec::new();
+    let mut open = None;
+
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        let offset = content.len() - rest.len() + start;
+        let tag = &rest[start + 1..];
+        if let Some(comment) = tag.strip_prefix("!--") {
+            let end = comment.find("-->")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end = tag.find('>')?;
+        let body = &tag[..end];
+        rest = &tag[end + 1..];
+        if body.starts_with(['?', '!']) || body.ends_with('/') {
+            continue;
+        }
+
+        if let Some(name) = body.strip_prefix('/') {
+            let name = name.trim();
+            if name == "dependencies" && stack == ["project", "dependencies"] {
+                return Some((open?, line_of(offset)));
+            }
+            if let Some(i) = stack.iter().rposition(|&open| open == name) {
+                stack.truncate(i);
+            }
+        } else {
+            let name = body.split_whitespace().next().unwrap_or(body);
+            stack.push(name);
+            if name == "dependencies" && stack == ["project", "dependencies"] {
+                open = Some(line_of(offset));
+            }
+        }
+    }
+    None
+}
+
+/// Builds the sortable section of the `<dependency>` elements between lines
+/// `open` and `close`.
+fn dependency_section(
+    pom: &crate::parser::MavenParseResult,
+    lines: &[&str],
+    open: u32,
+    close: u32,
+) -> SortableSection {
+    let entries = pom
+        .dependencies
+        .iter()
+        .filter(|dep| (open..close).contains(&dep.name_range.start.line))
+        .filter_map(|dep| {
+            let line = dep.name_range.start.line as usize;
+            let first = (open as usize..=line)
+                .rev()
+                .find(|&i| lines[i].contains("<dependency>"))?;
+            let last = (line..close as usize).find(|&i| lines[i].contains("</dependency>"))?;
+            Some(SortableEntry {
+                key: dep.name.clone(),
+                first_line: first as u32,
+                last_line: last as u32,
+            })
+        })
+        .collect();
+    SortableSection {
+        label: "<dependencies>".into(),
+        style: SectionStyle::XML_ELEMENT,
+        entries,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,4 +495,123 @@ mod tests {
         let result = eco.parse_manifest(xml, &uri).await.unwrap();
         assert_eq!(result.dependencies().len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_generate_definition_property_version() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let eco = MavenEcosystem::new(cache);
+
+        let xml = r"<project>
+  <properties>
+    <junit.version>4.13.2</junit.version>
+  </properties>
+  <dependencies>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>${junit.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+      <version>2.0.16</version>
+    </dependency>
+  </dependencies>
+</project>";
+
+        let uri = deps_core::test_util::test_uri("/test/pom.xml");
+        let result = eco.parse_manifest(xml, &uri).await.unwrap();
+
+        let location = eco
+            .generate_definition(result.as_ref(), make_position(8, 18), xml)
+            .await
+            .unwrap();
+        assert_eq!(location.uri, uri);
+        assert_eq!(location.range.start, make_position(2, 19));
+        assert_eq!(location.range.end, make_position(2, 25));
+
+        // Literal versions have no declaration to jump to
+        assert!(
+            eco.generate_definition(result.as_ref(), make_position(13, 18), xml)
+                .await
+                .is_none()
+        );
+    }
+
+    const POM: &str = r"<project>
+  <modelVersion>4.0.0</modelVersion>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.junit</groupId>
+        <artifactId>junit-bom</artifactId>
+        <version>5.10.0</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.google.guava</groupId>
+      <artifactId>guava</artifactId>
+      <version>33.0.0-jre</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+      <version>2.0.9</version>
+    </dependency>
+  </dependencies>
+</project>
+";
+
+    #[test]
+    fn test_project_dependencies() {
+        assert_eq!(project_dependencies(POM), Some((11, 22)));
+        assert_eq!(project_dependencies("<project>\n</project>\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = MavenEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pom.xml");
+        let parse_result = ecosystem.parse_manifest(POM, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "junit:junit".into(),
+            version: "4.13.2".into(),
+            kind: DependencyKind::Test,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), POM, &dependency)
+            .await
+            .unwrap();
+        let result = deps_core::test_util::apply_edits(POM, &edit.changes.unwrap()[&uri]);
+        assert!(result.contains(
+            "      <version>33.0.0-jre</version>\n    </dependency>\n    <dependency>\n      <groupId>junit</groupId>\n      <artifactId>junit</artifactId>\n      <version>4.13.2</version>\n      <scope>test</scope>\n    </dependency>\n    <dependency>\n      <groupId>org.slf4j</groupId>"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_without_dependencies() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = MavenEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pom.xml");
+        let content = "<project>\n  <modelVersion>4.0.0</modelVersion>\n</project>\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "org.slf4j:slf4j-api".into(),
+            version: "2.0.9".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "<project>\n  <modelVersion>4.0.0</modelVersion>\n  <dependencies>\n    <dependency>\n      <groupId>org.slf4j</groupId>\n      <artifactId>slf4j-api</artifactId>\n      <version>2.0.9</version>\n    </dependency>\n  </dependencies>\n</project>\n"
+        );
+    }
 }
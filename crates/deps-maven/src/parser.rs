@@ -15,6 +15,8 @@ use tower_lsp_server::ls_types::{Range, Uri};
 pub struct MavenParseResult {
     pub dependencies: Vec<MavenDependency>,
     pub properties: HashMap<String, String>,
+    /// Ranges of `<properties>` values, keyed by property name.
+    pub property_ranges: HashMap<String, Range>,
     pub uri: Uri,
 }
 
@@ -47,6 +49,7 @@ pub fn parse_pom_xml(content: &str, doc_uri: &Uri) -> Result<MavenParseResult> {
     let line_table = LineOffsetTable::new(content);
     let mut dependencies = Vec::new();
     let mut properties = HashMap::new();
+    let mut property_ranges = HashMap::new();
 
     let mut reader = Reader::from_str(content);
     reader.config_mut().trim_text(true);
@@ -154,6 +157,14 @@ pub fn parse_pom_xml(content: &str, doc_uri: &Uri) -> Result<MavenParseResult> {
                 } else if ctx == ParseContext::Properties
                     && let Some(key) = current_prop_key.take()
                 {
+                    let range = text_range(
+                        content,
+                        &line_table,
+                        text_start as usize,
+                        text_end as usize,
+                        &text,
+                    );
+                    property_ranges.insert(key.clone(), range);
                     properties.insert(key, text);
                 } else if ctx == ParseContext::Root
                     && let Some(tag) = root_tag.take()
@@ -197,6 +208,7 @@ pub fn parse_pom_xml(content: &str, doc_uri: &Uri) -> Result<MavenParseResult> {
     Ok(MavenParseResult {
         dependencies,
         properties,
+        property_ranges,
         uri: doc_uri.clone(),
     })
 }
@@ -237,6 +249,7 @@ fn finalize_dep(
         .parse::<MavenScope>()
         .unwrap_or_default();
 
+    let version_property = dep.version.as_deref().and_then(property_reference);
     let version_req = dep.version.map(|v| resolve_properties(&v, properties));
 
     Some(MavenDependency {
@@ -246,10 +259,19 @@ fn finalize_dep(
         name_range,
         version_req,
         version_range,
+        version_property,
         scope,
     })
 }
 
+/// Extracts the key of the first `${property}` reference in `input`.
+fn property_reference(input: &str) -> Option<String> {
+    let start = input.find("${")?;
+    let end = input[start..].find('}')?;
+    let key = &input[start + 2..start + end];
+    (!key.is_empty()).then(|| key.to_string())
+}
+
 /// Resolves `${property}` references in a string using the properties map.
 ///
 /// Handles `${project.version}` and similar Maven property expressions.
@@ -331,6 +353,7 @@ impl deps_core::ParseResult for MavenParseResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tower_lsp_server::ls_types::Position;
 
     fn test_uri() -> Uri {
         #[cfg(windows)]
@@ -491,6 +514,14 @@ mod tests {
         let result = parse_pom_xml(xml, &test_uri()).unwrap();
         assert_eq!(result.dependencies.len(), 1);
         assert_eq!(result.dependencies[0].version_req, Some("2.0.16".into()));
+        assert_eq!(
+            result.dependencies[0].version_property.as_deref(),
+            Some("slf4j.version")
+        );
+        assert_eq!(
+            result.property_ranges.get("slf4j.version"),
+            Some(&Range::new(Position::new(2, 19), Position::new(2, 25)))
+        );
     }
 
     #[test]
@@ -70,15 +70,37 @@ pub fn package_url(name: &str) -> String {
 #[derive(Clone)]
 pub struct MavenCentralRegistry {
     cache: Arc<HttpCache>,
+    /// Repository replacing Maven Central, Google Maven and the Gradle
+    /// Plugin Portal, such as a repository manager proxying all three
+    repository: Option<String>,
 }
 
 impl MavenCentralRegistry {
     pub fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            repository: None,
+        }
+    }
+
+    /// Returns a client fetching every artifact's metadata from the Maven
+    /// repository at `url`, sharing this client's cache. Search still uses
+    /// Maven Central.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            repository: Some(url.trim_end_matches('/').to_string()),
+        }
     }
 
     async fn get_metadata(&self, name: &str) -> Result<(Vec<MavenVersion>, Option<String>)> {
-        let urls = metadata_urls(name);
+        let urls = match &self.repository {
+            Some(repository) => repository_metadata_url(repository, name)
+                .into_iter()
+                .collect(),
+            None => metadata_urls(name),
+        };
         if urls.is_empty() {
             tracing::debug!(package = %name, "skipping: invalid groupId:artifactId format");
             return Ok((vec![], None));
@@ -151,6 +173,15 @@ impl MavenCentralRegistry {
 ///
 /// Non-Google packages get two URLs: Maven Central (primary) and Gradle Plugin Portal (fallback).
 /// Google-hosted packages get only the Google Maven URL — they are not mirrored elsewhere.
+/// `maven-metadata.xml` URL of `name` in `repository`.
+fn repository_metadata_url(repository: &str, name: &str) -> Option<String> {
+    let (group_id, artifact_id) = name.split_once(':')?;
+    let group_path = group_id.replace('.', "/");
+    Some(format!(
+        "{repository}/{group_path}/{artifact_id}/maven-metadata.xml"
+    ))
+}
+
 fn metadata_urls(name: &str) -> Vec<String> {
     let Some((group_id, artifact_id)) = name.split_once(':') else {
         return vec![];
@@ -437,6 +468,13 @@ mod tests {
     #[test]
     fn test_metadata_urls_no_colon() {
         assert!(metadata_urls("bad").is_empty());
+        assert_eq!(
+            repository_metadata_url("https://nexus.example.com/maven", "androidx.core:core-ktx"),
+            Some(
+                "https://nexus.example.com/maven/androidx/core/core-ktx/maven-metadata.xml"
+                    .to_string()
+            )
+        );
     }
 
     #[test]
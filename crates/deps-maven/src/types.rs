@@ -12,6 +12,8 @@ pub struct MavenDependency {
     pub name_range: Range,
     pub version_req: Option<String>,
     pub version_range: Option<Range>,
+    /// Property key when the version is a `${property}` reference.
+    pub version_property: Option<String>,
     pub scope: MavenScope,
 }
 
@@ -178,6 +180,7 @@ mod tests {
             name_range: Range::new(Position::new(5, 4), Position::new(5, 17)),
             version_req: Some("3.14.0".into()),
             version_range: Some(Range::new(Position::new(7, 13), Position::new(7, 19))),
+            version_property: None,
             scope: MavenScope::Compile,
         }
     }
@@ -260,6 +263,7 @@ mod tests {
             name_range: Range::default(),
             version_req: None,
             version_range: None,
+            version_property: None,
             scope: MavenScope::Compile,
         };
         assert!(dep.version_requirement().is_none());
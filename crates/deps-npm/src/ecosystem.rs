@@ -5,14 +5,18 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Location, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::NpmFormatter;
 use crate::registry::NpmRegistry;
+use crate::types::NpmDependencySection;
 
 /// npm ecosystem implementation.
 ///
@@ -90,6 +94,13 @@ impl Ecosystem for NpmEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: NpmFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::NpmLockParser))
     }
@@ -123,11 +134,103 @@ impl Ecosystem for NpmEcosystem {
         })
     }
 
+    fn generate_definition<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        _content: &'a str,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            let dep = deps_core::lsp_helpers::find_dependency_at(parse_result, position)?;
+            if !dep
+                .version_requirement()?
+                .starts_with(crate::workspace::WORKSPACE_PROTOCOL)
+            {
+                return None;
+            }
+            let manifest_path = parse_result.uri().to_file_path()?.into_owned();
+            let name = dep.name().to_string();
+            // Expanding member globs walks the filesystem
+            tokio::task::spawn_blocking(move || {
+                crate::workspace::find_workspace_package(&manifest_path, &name)
+            })
+            .await
+            .ok()
+            .flatten()
+        })
+    }
+
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        _content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::NpmParseResult>()
+        else {
+            return vec![];
+        };
+        let entries = result.dependencies.iter().map(|dep| {
+            (
+                section_label(dep.section).to_string(),
+                SortableEntry::new(dep.name.clone(), dep.name_range.start.line),
+            )
+        });
+        SortableSection::group(SectionStyle::JSON_OBJECT, entries)
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let section = match dependency.kind {
+                DependencyKind::Normal => NpmDependencySection::Dependencies,
+                DependencyKind::Dev | DependencyKind::Test => NpmDependencySection::DevDependencies,
+                DependencyKind::Peer => NpmDependencySection::PeerDependencies,
+                DependencyKind::Optional => NpmDependencySection::OptionalDependencies,
+                _ => return None,
+            };
+            let key = section_label(section);
+            let version = if add_dependency::is_bare_version(&dependency.version) {
+                format!("^{}", dependency.version)
+            } else {
+                dependency.version.clone()
+            };
+            let entry = format!("\"{}\": \"{version}\"", dependency.name);
+
+            let sections = self.sortable_sections(parse_result, content);
+            let edits = match add_dependency::insert_into_section(
+                content,
+                &sections,
+                key,
+                &dependency.name,
+                &entry,
+            ) {
+                Some(edits) => edits,
+                None => vec![add_dependency::insert_json_member(content, key, &entry)?],
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+const fn section_label(section: NpmDependencySection) -> &'static str {
+    match section {
+        NpmDependencySection::Dependencies => "dependencies",
+        NpmDependencySection::DevDependencies => "devDependencies",
+        NpmDependencySection::PeerDependencies => "peerDependencies",
+        NpmDependencySection::OptionalDependencies => "optionalDependencies",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -473,6 +576,7 @@ mod tests {
                 parse_result.as_ref(),
                 VersionData::new(&cached_versions, &resolved_versions),
                 &uri,
+                20,
             )
             .await;
 
@@ -527,4 +631,80 @@ mod tests {
             .await;
         assert!(results.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = NpmEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/package.json");
+        let content = r#"{
+  "dependencies": {
+    "react": "^18.0.0",
+    "axios": "^1.0.0"
+  },
+  "devDependencies": {
+    "vitest": "^1.0.0"
+  }
+}"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(parse_result.as_ref(), content);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "dependencies");
+
+        let runs = deps_core::sort::sort_section(content, &sections[0]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "    \"axios\": \"^1.0.0\",\n    \"react\": \"^18.0.0\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = NpmEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/package.json");
+        let content = "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"express\": \"^4.18.0\",\n    \"react\": \"^18.0.0\"\n  }\n}\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "1.2.0".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("lodash", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"express\": \"^4.18.0\",\n    \"lodash\": \"^1.2.0\",\n    \"react\": \"^18.0.0\"\n  }\n}\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("zod", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"express\": \"^4.18.0\",\n    \"react\": \"^18.0.0\",\n    \"zod\": \"^1.2.0\"\n  }\n}\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(
+                parse_result.as_ref(),
+                content,
+                &add("vitest", Some("devDependencies")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"express\": \"^4.18.0\",\n    \"react\": \"^18.0.0\"\n  },\n  \"devDependencies\": {\n    \"vitest\": \"^1.2.0\"\n  }\n}\n"
+        );
+    }
 }
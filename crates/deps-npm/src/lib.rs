@@ -10,6 +10,7 @@ pub mod lockfile;
 pub mod parser;
 pub mod registry;
 pub mod types;
+pub mod workspace;
 
 pub use ecosystem::NpmEcosystem;
 pub use error::{NpmError, Result};
@@ -13,13 +13,13 @@ use tower_lsp_server::ls_types::{Position, Range, Uri};
 ///
 /// Stores byte offsets of each line start, enabling fast binary search
 /// for line-to-offset conversion. This avoids O(n) scans for each position lookup.
-struct LineOffsetTable {
+pub(crate) struct LineOffsetTable {
     offsets: Vec<usize>,
 }
 
 impl LineOffsetTable {
     /// Builds a line offset table from content in O(n) time.
-    fn new(content: &str) -> Self {
+    pub(crate) fn new(content: &str) -> Self {
         let mut offsets = vec![0];
         for (i, c) in content.char_indices() {
             if c == '\n' {
@@ -194,7 +194,7 @@ fn parse_dependency_section(
 ///
 /// Searches for the dependency as a JSON key-value pair to avoid false matches
 /// when the name appears elsewhere in the file (e.g., in scripts).
-fn find_dependency_positions(
+pub(crate) fn find_dependency_positions(
     content: &str,
     name: &str,
     version_req: Option<&String>,
@@ -10,6 +10,7 @@ use crate::types::{NpmPackage, NpmVersion};
 use deps_core::{DepsError, HttpCache, Result};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 const REGISTRY_BASE: &str = "https://registry.npmjs.org";
@@ -52,17 +53,17 @@ pub fn package_url(name: &str) -> String {
 /// keeps its `/` structure, with `scope` and `name` each percent-encoded
 /// individually) so a malicious or unusual name can't inject extra path
 /// segments or query syntax into the request.
-fn versions_url(name: &str) -> String {
+fn versions_url(base: &str, name: &str) -> String {
     if let Some(rest) = name.strip_prefix('@')
         && let Some((scope, pkg)) = rest.split_once('/')
     {
         return format!(
-            "{REGISTRY_BASE}/@{}/{}",
+            "{base}/@{}/{}",
             urlencoding::encode(scope),
             urlencoding::encode(pkg)
         );
     }
-    format!("{}/{}", REGISTRY_BASE, urlencoding::encode(name))
+    format!("{}/{}", base, urlencoding::encode(name))
 }
 
 /// Converts a 404 response into `DepsError::PackageNotFound`, passing through
@@ -85,12 +86,26 @@ fn not_found_or(err: DepsError, name: &str) -> DepsError {
 #[derive(Clone)]
 pub struct NpmRegistry {
     cache: Arc<HttpCache>,
+    base_url: Cow<'static, str>,
 }
 
 impl NpmRegistry {
     /// Creates a new npm registry client with the given HTTP cache.
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            base_url: Cow::Borrowed(REGISTRY_BASE),
+        }
+    }
+
+    /// Returns a client for the registry at `url` (as in `.npmrc`'s
+    /// `registry`), sharing this client's cache.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            base_url: Cow::Owned(url.trim_end_matches('/').to_string()),
+        }
     }
 
     /// Fetches all versions for a package from the npm registry.
@@ -126,7 +141,7 @@ impl NpmRegistry {
     /// # }
     /// ```
     pub async fn get_versions(&self, name: &str) -> Result<Vec<NpmVersion>> {
-        let url = versions_url(name);
+        let url = versions_url(&self.base_url, name);
         let data = self
             .cache
             .get_cached_with_headers(&url, &[(reqwest::header::ACCEPT, ABBREVIATED_ACCEPT)])
@@ -206,7 +221,7 @@ impl NpmRegistry {
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<NpmPackage>> {
         let url = format!(
             "{}/-/v1/search?text={}&size={}",
-            REGISTRY_BASE,
+            self.base_url,
             urlencoding::encode(query),
             limit
         );
@@ -352,6 +367,14 @@ impl deps_core::Registry for NpmRegistry {
         package_url(name)
     }
 
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        Some(format!(
+            "{}/v/{}",
+            package_url(name),
+            urlencoding::encode(version)
+        ))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -417,15 +440,25 @@ mod tests {
     #[test]
     fn test_versions_url_plain() {
         assert_eq!(
-            versions_url("express"),
+            versions_url(REGISTRY_BASE, "express"),
             "https://registry.npmjs.org/express"
         );
     }
 
+    #[test]
+    fn test_with_base_url() {
+        let registry = NpmRegistry::new(Arc::new(HttpCache::new()))
+            .with_base_url("https://npm.example.com/repository/npm/");
+        assert_eq!(
+            versions_url(&registry.base_url, "@types/node"),
+            "https://npm.example.com/repository/npm/@types/node"
+        );
+    }
+
     #[test]
     fn test_versions_url_scoped_preserves_structure() {
         assert_eq!(
-            versions_url("@types/node"),
+            versions_url(REGISTRY_BASE, "@types/node"),
             "https://registry.npmjs.org/@types/node"
         );
     }
@@ -435,7 +468,7 @@ mod tests {
         // A raw `/`, `?`, or `#` in an unscoped name must not survive into
         // the path/query, since `get_versions` doesn't normalize `name`
         // before building the request URL.
-        let url = versions_url("evil/../secret?x=1#frag");
+        let url = versions_url(REGISTRY_BASE, "evil/../secret?x=1#frag");
         assert!(!url.contains("/../"));
         assert!(!url.contains('?'));
         assert!(!url.contains('#'));
@@ -443,7 +476,7 @@ mod tests {
 
     #[test]
     fn test_versions_url_scoped_encodes_malicious_segments() {
-        let url = versions_url("@evil/../secret?x=1#frag");
+        let url = versions_url(REGISTRY_BASE, "@evil/../secret?x=1#frag");
         assert!(!url.contains("/../"));
         assert!(!url.contains('?'));
         assert!(!url.contains('#'));
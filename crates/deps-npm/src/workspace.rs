@@ -0,0 +1,193 @@
+//! Resolution of `workspace:` protocol dependencies.
+//!
+//! pnpm, yarn and bun link `"pkg": "workspace:*"` dependencies to a sibling
+//! package in the same monorepo. The workspace root is the nearest ancestor
+//! declaring members, either through the `workspaces` field of
+//! `package.json` or through `pnpm-workspace.yaml`.
+
+use std::path::{Path, PathBuf};
+use tower_lsp_server::ls_types::{Location, Uri};
+use yaml_rust2::YamlLoader;
+
+use crate::parser::{LineOffsetTable, find_dependency_positions};
+
+/// Prefix of version requirements that point at a workspace package.
+pub const WORKSPACE_PROTOCOL: &str = "workspace:";
+
+/// Member globs declared by a workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMembers {
+    /// Directory containing the workspace root manifest.
+    pub root: PathBuf,
+    /// Member globs, relative to `root`.
+    pub patterns: Vec<String>,
+    /// Negated (`!`-prefixed) globs, relative to `root`.
+    pub exclude: Vec<String>,
+}
+
+/// Finds the nearest workspace root at or above `manifest_dir`.
+pub fn find_workspace_members(manifest_dir: &Path) -> Option<WorkspaceMembers> {
+    manifest_dir.ancestors().find_map(|dir| {
+        let globs = read_pnpm_workspace(dir).or_else(|| read_package_json_workspaces(dir))?;
+        let (exclude, patterns): (Vec<String>, Vec<String>) =
+            globs.into_iter().partition(|g| g.starts_with('!'));
+        Some(WorkspaceMembers {
+            root: dir.to_path_buf(),
+            patterns,
+            exclude: exclude
+                .into_iter()
+                .map(|g| g.trim_start_matches('!').to_string())
+                .collect(),
+        })
+    })
+}
+
+fn read_pnpm_workspace(dir: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(dir.join("pnpm-workspace.yaml")).ok()?;
+    let docs = YamlLoader::load_from_str(&content).ok()?;
+    let packages = docs.first()?["packages"].as_vec()?;
+    Some(
+        packages
+            .iter()
+            .filter_map(|p| p.as_str().map(String::from))
+            .collect(),
+    )
+}
+
+fn read_package_json_workspaces(dir: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    let root: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let workspaces = root.get("workspaces")?;
+    // Either `["packages/*"]` or yarn's `{ "packages": ["packages/*"] }`
+    let globs = workspaces
+        .as_array()
+        .or_else(|| workspaces.get("packages").and_then(|p| p.as_array()))?;
+    Some(
+        globs
+            .iter()
+            .filter_map(|g| g.as_str().map(String::from))
+            .collect(),
+    )
+}
+
+/// Locates the `package.json` of the workspace package named `name`.
+///
+/// Returns the range of the package's `"name"` value, or `None` when the
+/// manifest is not part of a workspace or no member has that name.
+/// Reads and walks the filesystem, so async callers run it on a blocking
+/// thread.
+pub fn find_workspace_package(manifest_path: &Path, name: &str) -> Option<Location> {
+    let members = find_workspace_members(manifest_path.parent()?)?;
+    let mut candidates = vec![members.root.clone()];
+    candidates.extend(deps_core::workspace::expand_member_globs(
+        &members.root,
+        &members.patterns,
+        &members.exclude,
+    ));
+
+    candidates.into_iter().find_map(|dir| {
+        let path = dir.join("package.json");
+        let content = std::fs::read_to_string(&path).ok()?;
+        let root: serde_json::Value = serde_json::from_str(&content).ok()?;
+        if root.get("name")?.as_str()? != name {
+            return None;
+        }
+        let name_value = name.to_string();
+        let line_table = LineOffsetTable::new(&content);
+        let (_, value_range) =
+            find_dependency_positions(&content, "name", Some(&name_value), &line_table);
+        Some(Location::new(Uri::from_file_path(&path)?, value_range?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_workspace_members_package_json() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(
+            &temp_dir.path().join("package.json"),
+            r#"{ "workspaces": ["packages/*", "!packages/legacy"] }"#,
+        );
+        let app = temp_dir.path().join("packages/app");
+        std::fs::create_dir_all(&app).unwrap();
+
+        let members = find_workspace_members(&app).unwrap();
+        assert_eq!(members.root, temp_dir.path());
+        assert_eq!(members.patterns, vec!["packages/*"]);
+        assert_eq!(members.exclude, vec!["packages/legacy"]);
+    }
+
+    #[test]
+    fn test_find_workspace_members_yarn_object() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(
+            &temp_dir.path().join("package.json"),
+            r#"{ "workspaces": { "packages": ["libs/*"] } }"#,
+        );
+
+        let members = find_workspace_members(temp_dir.path()).unwrap();
+        assert_eq!(members.patterns, vec!["libs/*"]);
+    }
+
+    #[test]
+    fn test_find_workspace_members_pnpm() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(
+            &temp_dir.path().join("pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n  - '!**/test/**'\n",
+        );
+
+        let members = find_workspace_members(temp_dir.path()).unwrap();
+        assert_eq!(members.patterns, vec!["packages/*"]);
+        assert_eq!(members.exclude, vec!["**/test/**"]);
+    }
+
+    #[test]
+    fn test_find_workspace_members_none() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(
+            &temp_dir.path().join("package.json"),
+            r#"{ "name": "solo" }"#,
+        );
+        assert!(find_workspace_members(temp_dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_find_workspace_package() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write(
+            &temp_dir.path().join("package.json"),
+            r#"{ "private": true, "workspaces": ["packages/*"] }"#,
+        );
+        write(
+            &temp_dir.path().join("packages/utils/package.json"),
+            "{\n  \"name\": \"@acme/utils\",\n  \"version\": \"1.0.0\"\n}",
+        );
+        let app_manifest = temp_dir.path().join("packages/app/package.json");
+        write(
+            &app_manifest,
+            r#"{ "name": "@acme/app", "dependencies": { "@acme/utils": "workspace:*" } }"#,
+        );
+
+        let location = find_workspace_package(&app_manifest, "@acme/utils").unwrap();
+        assert_eq!(
+            location.uri,
+            Uri::from_file_path(temp_dir.path().join("packages/utils/package.json")).unwrap()
+        );
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(1, 11), Position::new(1, 22))
+        );
+
+        assert!(find_workspace_package(&app_manifest, "@acme/missing").is_none());
+    }
+}
@@ -0,0 +1,106 @@
+//! Central package management lookup.
+//!
+//! With central package management, a project's `<PackageReference Include="X" />`
+//! carries no version; MSBuild takes it from the `<PackageVersion>` entry in the
+//! nearest `Directory.Packages.props` at or above the project directory.
+
+use std::path::Path;
+use tower_lsp_server::ls_types::{Location, Uri};
+
+use crate::parser::parse_directory_packages_props;
+
+/// Filename of the MSBuild central package management file.
+pub const DIRECTORY_PACKAGES_PROPS: &str = "Directory.Packages.props";
+
+/// Finds the `PackageVersion` entry for `name` in the nearest
+/// `Directory.Packages.props` above `project_path`.
+///
+/// Package IDs are compared case-insensitively, as NuGet does. Returns the
+/// range of the `Version` value, falling back to the `Include` value when the
+/// entry has no version.
+pub async fn find_central_package_version(project_path: &Path, name: &str) -> Option<Location> {
+    let (uri, content) = read_directory_packages_props(project_path).await?;
+    let parsed = parse_directory_packages_props(&content, &uri).ok()?;
+    let dep = parsed
+        .dependencies
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))?;
+    Some(Location::new(
+        uri,
+        dep.version_range.unwrap_or(dep.name_range),
+    ))
+}
+
+/// Reads the nearest `Directory.Packages.props` at or above the directory of
+/// `project_path`, returning its URI and content.
+pub async fn read_directory_packages_props(project_path: &Path) -> Option<(Uri, String)> {
+    for dir in project_path.parent()?.ancestors() {
+        let props_path = dir.join(DIRECTORY_PACKAGES_PROPS);
+        let Ok(content) = tokio::fs::read_to_string(&props_path).await else {
+            continue;
+        };
+        // MSBuild stops at the nearest file; it does not merge parent files implicitly
+        return Some((Uri::from_file_path(&props_path)?, content));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    #[tokio::test]
+    async fn test_find_central_package_version() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join(DIRECTORY_PACKAGES_PROPS),
+            r#"<Project>
+  <ItemGroup>
+    <PackageVersion Include="Newtonsoft.Json" Version="13.0.3" />
+    <PackageVersion Include="Serilog" Version="3.1.1" />
+  </ItemGroup>
+</Project>"#,
+        )
+        .unwrap();
+        let project_dir = temp_dir.path().join("src/App");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let project_path = project_dir.join("App.csproj");
+
+        let location = find_central_package_version(&project_path, "serilog")
+            .await
+            .unwrap();
+        assert_eq!(
+            location.uri,
+            Uri::from_file_path(temp_dir.path().join(DIRECTORY_PACKAGES_PROPS)).unwrap()
+        );
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(3, 47), Position::new(3, 52))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_central_package_version_nearest_file_wins() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join(DIRECTORY_PACKAGES_PROPS),
+            r#"<Project><ItemGroup><PackageVersion Include="Serilog" Version="3.1.1" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
+        let nested = temp_dir.path().join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join(DIRECTORY_PACKAGES_PROPS), "<Project />").unwrap();
+
+        assert!(
+            find_central_package_version(&nested.join("App.csproj"), "Serilog")
+                .await
+                .is_none()
+        );
+        assert!(
+            find_central_package_version(&temp_dir.path().join("App.csproj"), "Serilog")
+                .await
+                .is_some()
+        );
+    }
+}
@@ -10,17 +10,23 @@
 //! package from `cached_versions`), so no special-casing is needed here.
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{
+    CompletionItem, Location, Position, TextEdit, Uri, WorkspaceEdit,
+};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::NuGetFormatter;
 use crate::lockfile::NuGetLockParser;
 use crate::registry::NuGetRegistry;
-use crate::types::NuGetParseResult;
+use crate::types::{NuGetDependency, NuGetParseResult};
 
 /// NuGet/.NET ecosystem implementation.
 ///
@@ -113,6 +119,14 @@ impl Ecosystem for NuGetEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            formatter: NuGetFormatter,
+            lockfile_provider: Arc::clone(&self.lockfile_provider),
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(self.lockfile_provider.clone() as Arc<dyn deps_core::lockfile::LockFileProvider>)
     }
@@ -143,11 +157,181 @@ impl Ecosystem for NuGetEcosystem {
         })
     }
 
+    fn generate_definition<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        position: Position,
+        _content: &'a str,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<Location>> {
+        Box::pin(async move {
+            let dep = deps_core::lsp_helpers::find_dependency_at(parse_result, position)?;
+            // Only versionless references inherit from central package management
+            if dep.version_requirement().is_some() {
+                return None;
+            }
+            let project_path = parse_result.uri().to_file_path()?;
+            crate::central::find_central_package_version(&project_path, dep.name()).await
+        })
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            if dependency.kind != DependencyKind::Normal {
+                return None;
+            }
+            let result = parse_result.as_any().downcast_ref::<NuGetParseResult>()?;
+            let uri = parse_result.uri();
+            let path = uri.path().as_str();
+            let filename = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+            let (name, version) = (&dependency.name, &dependency.version);
+
+            let edits = match filename.as_str() {
+                "directory.packages.props" => insert_element(
+                    content,
+                    &result.dependencies,
+                    "PackageVersion",
+                    name,
+                    &format!("<PackageVersion Include=\"{name}\" Version=\"{version}\" />"),
+                )?,
+                "packages.config" => insert_element(
+                    content,
+                    &result.dependencies,
+                    "package",
+                    name,
+                    &format!("<package id=\"{name}\" version=\"{version}\" />"),
+                )?,
+                _ => {
+                    let central = match uri.to_file_path() {
+                        Some(path) => crate::central::read_directory_packages_props(&path)
+                            .await
+                            .filter(|(_, props)| {
+                                props.contains("<ManagePackageVersionsCentrally>true")
+                            }),
+                        None => None,
+                    };
+                    let Some((props_uri, props)) = central else {
+                        let entry = format!(
+                            "<PackageReference Include=\"{name}\" Version=\"{version}\" />"
+                        );
+                        let edits = insert_element(
+                            content,
+                            &result.dependencies,
+                            "PackageReference",
+                            name,
+                            &entry,
+                        )?;
+                        return Some(add_dependency::workspace_edit(uri, edits));
+                    };
+
+                    // Central package management: versionless reference, version in the props file
+                    let reference = format!("<PackageReference Include=\"{name}\" />");
+                    let mut changes = HashMap::from([(
+                        uri.clone(),
+                        insert_element(
+                            content,
+                            &result.dependencies,
+                            "PackageReference",
+                            name,
+                            &reference,
+                        )?,
+                    )]);
+                    let parsed =
+                        crate::parser::parse_directory_packages_props(&props, &props_uri).ok()?;
+                    if !parsed
+                        .dependencies
+                        .iter()
+                        .any(|dep| dep.name.eq_ignore_ascii_case(name))
+                    {
+                        let entry =
+                            format!("<PackageVersion Include=\"{name}\" Version=\"{version}\" />");
+                        let edits = insert_element(
+                            &props,
+                            &parsed.dependencies,
+                            "PackageVersion",
+                            name,
+                            &entry,
+                        )?;
+                        changes.insert(props_uri, edits);
+                    }
+                    return Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        ..Default::default()
+                    });
+                }
+            };
+            Some(add_dependency::workspace_edit(uri, edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Inserts `entry`, a `<tag ... />` element, next to the existing `tag`
+/// elements, or into a new container when there are none.
+fn insert_element(
+    content: &str,
+    dependencies: &[NuGetDependency],
+    tag: &str,
+    key: &str,
+    entry: &str,
+) -> Option<Vec<TextEdit>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let entries: Vec<SortableEntry> = dependencies
+        .iter()
+        .filter_map(|dep| {
+            let first = dep.name_range.start.line as usize;
+            if !lines.get(first)?.trim_start().starts_with(&open) {
+                return None;
+            }
+            let last = if lines[first].contains("/>") {
+                first
+            } else {
+                (first..lines.len()).find(|&i| lines[i].contains(&close))?
+            };
+            Some(SortableEntry {
+                key: dep.name.clone(),
+                first_line: first as u32,
+                last_line: last as u32,
+            })
+        })
+        .collect();
+    if !entries.is_empty() {
+        let section = SortableSection {
+            label: tag.to_string(),
+            style: SectionStyle::XML_ELEMENT,
+            entries,
+        };
+        return deps_core::sort::insert_sorted(content, &section, key, entry);
+    }
+
+    let newline = add_dependency::newline(content);
+    let unit = add_dependency::indent_unit(content);
+    if tag == "package" {
+        let line = add_dependency::find_line(content, |l| l == "<packages>")?;
+        return Some(vec![add_dependency::insert_after_line(
+            content,
+            line,
+            &format!("{unit}{entry}"),
+        )]);
+    }
+    let line = add_dependency::find_line(content, |l| l == "</Project>")?;
+    let block = format!("<ItemGroup>{newline}{unit}{entry}{newline}</ItemGroup>");
+    Some(vec![add_dependency::insert_before_line(
+        content,
+        line,
+        &add_dependency::indent_lines(&block, unit, newline),
+    )])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -371,6 +555,7 @@ mod tests {
             parse_result.as_ref(),
             VersionData::new(&cached, &resolved),
             uri,
+            20,
         )
         .await
         .into_iter()
@@ -402,4 +587,101 @@ mod tests {
                 .is_empty()
         );
     }
+
+    #[tokio::test]
+    async fn test_add_dependency_project_file() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = NuGetEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/nonexistent/App/App.csproj");
+        let content = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+    <PackageReference Include="Serilog" Version="3.1.1" />
+  </ItemGroup>
+</Project>
+"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "Polly".into(),
+            version: "8.3.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+    <PackageReference Include="Polly" Version="8.3.0" />
+    <PackageReference Include="Serilog" Version="3.1.1" />
+  </ItemGroup>
+</Project>
+"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_new_item_group() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = NuGetEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/nonexistent/App/App.csproj");
+        let content = "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <PropertyGroup>\n    <TargetFramework>net8.0</TargetFramework>\n  </PropertyGroup>\n</Project>\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "Polly".into(),
+            version: "8.3.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <PropertyGroup>\n    <TargetFramework>net8.0</TargetFramework>\n  </PropertyGroup>\n  <ItemGroup>\n    <PackageReference Include=\"Polly\" Version=\"8.3.0\" />\n  </ItemGroup>\n</Project>\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_central_package_management() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let props_path = temp_dir
+            .path()
+            .join(crate::central::DIRECTORY_PACKAGES_PROPS);
+        let props = "<Project>\n  <PropertyGroup>\n    <ManagePackageVersionsCentrally>true</ManagePackageVersionsCentrally>\n  </PropertyGroup>\n  <ItemGroup>\n    <PackageVersion Include=\"Serilog\" Version=\"3.1.1\" />\n  </ItemGroup>\n</Project>\n";
+        std::fs::write(&props_path, props).unwrap();
+        let project_dir = temp_dir.path().join("App");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let uri = Uri::from_file_path(project_dir.join("App.csproj")).unwrap();
+        let props_uri = Uri::from_file_path(&props_path).unwrap();
+
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = NuGetEcosystem::new(cache);
+        let content = "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <ItemGroup>\n    <PackageReference Include=\"Serilog\" />\n  </ItemGroup>\n</Project>\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "Polly".into(),
+            version: "8.3.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &changes[&uri]),
+            "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <ItemGroup>\n    <PackageReference Include=\"Polly\" />\n    <PackageReference Include=\"Serilog\" />\n  </ItemGroup>\n</Project>\n"
+        );
+        assert!(
+            deps_core::test_util::apply_edits(props, &changes[&props_uri])
+                .contains("    <PackageVersion Include=\"Polly\" Version=\"8.3.0\" />\n    <PackageVersion Include=\"Serilog\"")
+        );
+    }
 }
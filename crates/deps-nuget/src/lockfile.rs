@@ -44,6 +44,8 @@ struct LockEntry {
     resolved: Option<String>,
     #[serde(default, rename = "contentHash")]
     content_hash: Option<String>,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
 }
 
 impl LockFileProvider for NuGetLockParser {
@@ -73,16 +75,13 @@ impl LockFileProvider for NuGetLockParser {
             // always fails on NuGet's 4-component versions and degrades to string
             // comparison (e.g. "1.10.0" < "1.9.0"). Only the single winner is ever handed
             // to `ResolvedPackages`, so that broken comparator is never reached.
-            let mut candidates: HashMap<String, Vec<(String, Option<String>)>> = HashMap::new();
+            let mut candidates: HashMap<String, Vec<(String, LockEntry)>> = HashMap::new();
             for packages in lock_data.dependencies.into_values() {
                 for (name, entry) in packages {
                     // "type": "Project" / "CentralTransitive" entries carry no `resolved`
                     // at all — skip rather than aborting the whole file (S2).
-                    if let Some(resolved) = entry.resolved {
-                        candidates
-                            .entry(name)
-                            .or_default()
-                            .push((resolved, entry.content_hash));
+                    if let Some(resolved) = entry.resolved.clone() {
+                        candidates.entry(name).or_default().push((resolved, entry));
                     }
                 }
             }
@@ -92,15 +91,17 @@ impl LockFileProvider for NuGetLockParser {
                 let best = versions
                     .into_iter()
                     .max_by(|a, b| crate::version::compare_versions(&a.0, &b.0));
-                if let Some((version, content_hash)) = best {
+                if let Some((version, entry)) = best {
+                    let mut dependencies: Vec<String> = entry.dependencies.into_keys().collect();
+                    dependencies.sort();
                     packages.insert(ResolvedPackage {
                         name,
                         version,
                         source: ResolvedSource::Registry {
                             url: NUGET_ORG_URL.into(),
-                            checksum: content_hash.unwrap_or_default(),
+                            checksum: entry.content_hash.unwrap_or_default(),
                         },
-                        dependencies: vec![],
+                        dependencies,
                     });
                 }
             }
@@ -145,6 +146,35 @@ mod tests {
         assert_eq!(resolved.get_version("Newtonsoft.Json"), Some("13.0.3"));
     }
 
+    #[tokio::test]
+    async fn test_parse_package_dependencies() {
+        let content = r#"{
+  "version": 1,
+  "dependencies": {
+    "net8.0": {
+      "Serilog.Sinks.Console": {
+        "type": "Direct",
+        "requested": "[5.0.1, )",
+        "resolved": "5.0.1",
+        "dependencies": { "Serilog": "3.1.1" }
+      },
+      "Serilog": { "type": "Transitive", "resolved": "3.1.1" }
+    }
+  }
+}"#;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("packages.lock.json");
+        tokio::fs::write(&path, content).await.unwrap();
+
+        let parser = NuGetLockParser;
+        let resolved = parser.parse_lockfile(&path).await.unwrap();
+        assert_eq!(
+            resolved.get("Serilog.Sinks.Console").unwrap().dependencies,
+            vec!["Serilog"]
+        );
+        assert!(resolved.get("Serilog").unwrap().dependencies.is_empty());
+    }
+
     #[tokio::test]
     async fn test_project_reference_entry_skipped() {
         let content = r#"{
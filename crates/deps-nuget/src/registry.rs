@@ -119,6 +119,13 @@ impl NuGetRegistry {
         Self::with_service_index_url(cache, SERVICE_INDEX_URL.to_string())
     }
 
+    /// Returns a client for the feed whose V3 service index is `url`,
+    /// sharing this client's cache.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        Self::with_service_index_url(Arc::clone(&self.cache), url.to_string())
+    }
+
     fn with_service_index_url(cache: Arc<HttpCache>, service_index_url: String) -> Self {
         Self {
             cache,
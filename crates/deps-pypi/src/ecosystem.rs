@@ -5,15 +5,19 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::PypiFormatter;
 use crate::parser::PypiParser;
 use crate::registry::PypiRegistry;
+use crate::types::{PypiDependency, PypiDependencySection};
 
 /// PyPI ecosystem implementation.
 ///
@@ -95,6 +99,14 @@ impl Ecosystem for PypiEcosystem {
         self.registry.clone() as Arc<dyn Registry>
     }
 
+    fn with_registry_url(&self, url: &str) -> Option<Arc<dyn Ecosystem>> {
+        Some(Arc::new(Self {
+            registry: Arc::new(self.registry.with_base_url(url)),
+            parser: PypiParser::new(),
+            formatter: PypiFormatter,
+        }))
+    }
+
     fn lockfile_provider(&self) -> Option<Arc<dyn deps_core::lockfile::LockFileProvider>> {
         Some(Arc::new(crate::lockfile::PypiLockParser))
     }
@@ -128,11 +140,149 @@ impl Ecosystem for PypiEcosystem {
         })
     }
 
+    fn sortable_sections(
+        &self,
+        parse_result: &dyn ParseResultTrait,
+        _content: &str,
+    ) -> Vec<SortableSection> {
+        let Some(result) = parse_result
+            .as_any()
+            .downcast_ref::<crate::parser::ParseResult>()
+        else {
+            return vec![];
+        };
+        let entry = |dep: &PypiDependency| {
+            (
+                section_label(&dep.section),
+                SortableEntry::new(dep.name.clone(), dep.name_range.start.line),
+            )
+        };
+        let (tables, arrays): (Vec<_>, Vec<_>) = result.dependencies.iter().partition(|dep| {
+            matches!(
+                dep.section,
+                PypiDependencySection::PoetryDependencies
+                    | PypiDependencySection::PoetryGroup { .. }
+            )
+        });
+
+        let mut sections =
+            SortableSection::group(SectionStyle::TOML_ARRAY, arrays.into_iter().map(entry));
+        sections.extend(SortableSection::group(
+            SectionStyle::TOML_TABLE,
+            tables.into_iter().map(entry),
+        ));
+        sections
+    }
+
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            let name = &dependency.name;
+            let bare = add_dependency::is_bare_version(&dependency.version);
+            let sections = self.sortable_sections(parse_result, content);
+
+            // Poetry 1.x projects declare dependencies only under [tool.poetry]
+            let poetry = add_dependency::find_line(content, |l| l == "[project]").is_none()
+                && add_dependency::find_line(content, |l| l.starts_with("[tool.poetry")).is_some();
+            if poetry {
+                let section = match &dependency.kind {
+                    DependencyKind::Normal => PypiDependencySection::PoetryDependencies,
+                    DependencyKind::Dev => PypiDependencySection::PoetryGroup {
+                        group: "dev".into(),
+                    },
+                    DependencyKind::Test => PypiDependencySection::PoetryGroup {
+                        group: "test".into(),
+                    },
+                    DependencyKind::Other(group) => PypiDependencySection::PoetryGroup {
+                        group: group.clone(),
+                    },
+                    _ => return None,
+                };
+                let label = section_label(&section);
+                let version = if bare {
+                    format!("^{}", dependency.version)
+                } else {
+                    dependency.version.clone()
+                };
+                let entry = format!("{name} = \"{version}\"");
+                let edits =
+                    add_dependency::insert_into_section(content, &sections, &label, name, &entry)
+                        .unwrap_or_else(|| {
+                            vec![add_dependency::insert_toml_table_entry(
+                                content,
+                                &format!("[{label}]"),
+                                &entry,
+                            )]
+                        });
+                return Some(add_dependency::workspace_edit(parse_result.uri(), edits));
+            }
+
+            let (table, key) = match &dependency.kind {
+                DependencyKind::Normal => ("project".to_string(), "dependencies".to_string()),
+                DependencyKind::Dev => ("dependency-groups".into(), "dev".into()),
+                DependencyKind::Test => ("dependency-groups".into(), "test".into()),
+                DependencyKind::Build => ("build-system".into(), "requires".into()),
+                DependencyKind::Other(group) => {
+                    ("project.optional-dependencies".into(), group.clone())
+                }
+                _ => return None,
+            };
+            let label = format!("{table}.{key}");
+            let requirement = if bare {
+                self.formatter
+                    .format_version_for_text_edit(&dependency.version)
+            } else {
+                dependency.version.clone()
+            };
+            let entry = format!("\"{name}{requirement}\"");
+
+            let section = sections.iter().find(|section| section.label == label);
+            let edits = match section {
+                Some(section) => {
+                    // Only multi-line arrays have room for another line
+                    let lines: Vec<&str> = content.lines().collect();
+                    let multiline = section.entries.iter().all(|entry| {
+                        lines
+                            .get(entry.first_line as usize)
+                            .is_some_and(|line| line.trim_start().starts_with(['"', '\'']))
+                    });
+                    if !multiline {
+                        return None;
+                    }
+                    deps_core::sort::insert_sorted(content, section, name, &entry)?
+                }
+                None => vec![add_dependency::insert_toml_array_entry(
+                    content, &table, &key, &entry,
+                )?],
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+fn section_label(section: &PypiDependencySection) -> String {
+    match section {
+        PypiDependencySection::BuildSystem => "build-system.requires".into(),
+        PypiDependencySection::Dependencies => "project.dependencies".into(),
+        PypiDependencySection::OptionalDependencies { group } => {
+            format!("project.optional-dependencies.{group}")
+        }
+        PypiDependencySection::DependencyGroup { group } => format!("dependency-groups.{group}"),
+        PypiDependencySection::PoetryDependencies => "tool.poetry.dependencies".into(),
+        PypiDependencySection::PoetryGroup { group } => {
+            format!("tool.poetry.group.{group}.dependencies")
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -500,6 +650,7 @@ dependencies = []
                 parse_result.as_ref(),
                 VersionData::new(&cached_versions, &resolved_versions),
                 &uri,
+                20,
             )
             .await;
 
@@ -540,4 +691,120 @@ dependencies = []
             .await;
         assert!(results.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_sortable_sections() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = PypiEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pyproject.toml");
+        let content = r#"[project]
+name = "test"
+dependencies = [
+    "requests>=2.0.0",  # http
+    "click>=8.0",
+]
+
+[tool.poetry.dependencies]
+python = "^3.9"
+pandas = "^2.0"
+numpy = "^1.24"
+"#;
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let sections = ecosystem.sortable_sections(parse_result.as_ref(), content);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].label, "project.dependencies");
+        assert_eq!(sections[1].label, "tool.poetry.dependencies");
+
+        let runs = deps_core::sort::sort_section(content, &sections[0]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "    \"click>=8.0\",\n    \"requests>=2.0.0\",  # http"
+        );
+        let runs = deps_core::sort::sort_section(content, &sections[1]);
+        assert_eq!(
+            runs[0].edit.as_ref().unwrap().new_text,
+            "numpy = \"^1.24\"\npandas = \"^2.0\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_pep621() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = PypiEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pyproject.toml");
+        let content = "[project]\nname = \"app\"\ndependencies = [\n    \"click>=8.0\",\n    \"requests>=2.0.0\",\n]\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let add = |name: &str, section: Option<&str>| NewDependency {
+            name: name.into(),
+            version: "0.27.0".into(),
+            kind: DependencyKind::parse(section),
+        };
+        let apply = |edit: WorkspaceEdit| {
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri])
+        };
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("httpx", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "[project]\nname = \"app\"\ndependencies = [\n    \"click>=8.0\",\n    \"httpx>=0.27.0,<1\",\n    \"requests>=2.0.0\",\n]\n"
+        );
+
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &add("pytest", Some("dev")))
+            .await
+            .unwrap();
+        assert_eq!(
+            apply(edit),
+            "[project]\nname = \"app\"\ndependencies = [\n    \"click>=8.0\",\n    \"requests>=2.0.0\",\n]\n\n[dependency-groups]\ndev = [\n    \"pytest>=0.27.0,<1\",\n]\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_poetry() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = PypiEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pyproject.toml");
+        let content = "[tool.poetry]\nname = \"app\"\n\n[tool.poetry.dependencies]\npython = \"^3.9\"\nrequests = \"^2.31\"\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "pytest".into(),
+            version: "8.0.0".into(),
+            kind: DependencyKind::Dev,
+        };
+        let edit = ecosystem
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "[tool.poetry]\nname = \"app\"\n\n[tool.poetry.dependencies]\npython = \"^3.9\"\nrequests = \"^2.31\"\n\n[tool.poetry.group.dev.dependencies]\npytest = \"^8.0.0\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_inline_array() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let ecosystem = PypiEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/pyproject.toml");
+        let content = "[project]\nname = \"app\"\ndependencies = [\"click>=8.0\"]\n";
+        let parse_result = ecosystem.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "httpx".into(),
+            version: "0.27.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        assert!(
+            ecosystem
+                .add_dependency(parse_result.as_ref(), content, &dependency)
+                .await
+                .is_none()
+        );
+    }
 }
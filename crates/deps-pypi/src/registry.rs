@@ -14,6 +14,7 @@ use deps_core::{DepsError, HttpCache, Result};
 use pep440_rs::{Version, VersionSpecifiers};
 use serde::Deserialize;
 use std::any::Any;
+use std::borrow::Cow;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -70,13 +71,13 @@ pub fn package_url(name: &str) -> String {
 /// The name segment is URL-encoded (matching `package_url`) since
 /// `normalize_package_name` only collapses `-`/`_`/`.` separators and leaves
 /// characters like `/`, `?`, `#` untouched.
-fn simple_api_url(normalized: &str) -> String {
-    format!("{PYPI_SIMPLE_BASE}/{}/", urlencoding::encode(normalized))
+fn simple_api_url(simple_base: &str, normalized: &str) -> String {
+    format!("{simple_base}/{}/", urlencoding::encode(normalized))
 }
 
 /// Builds the JSON API request URL for `normalized`'s package metadata.
-fn metadata_url(normalized: &str) -> String {
-    format!("{PYPI_BASE}/{}/json", urlencoding::encode(normalized))
+fn metadata_url(json_base: &str, normalized: &str) -> String {
+    format!("{json_base}/{}/json", urlencoding::encode(normalized))
 }
 
 /// Converts a 404 response into `DepsError::PackageNotFound`, passing through
@@ -115,12 +116,35 @@ fn not_found_or(err: DepsError, name: &str) -> DepsError {
 #[derive(Clone)]
 pub struct PypiRegistry {
     cache: Arc<HttpCache>,
+    simple_base: Cow<'static, str>,
+    json_base: Cow<'static, str>,
 }
 
 impl PypiRegistry {
     /// Creates a new PyPI registry client with the given HTTP cache.
     pub const fn new(cache: Arc<HttpCache>) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            simple_base: Cow::Borrowed(PYPI_SIMPLE_BASE),
+            json_base: Cow::Borrowed(PYPI_BASE),
+        }
+    }
+
+    /// Returns a client for the package index at `url`, sharing this
+    /// client's cache.
+    ///
+    /// `url` is the Simple API root, as in pip's `index-url`
+    /// (`https://pypi.example.com/simple`); the JSON API is expected next to
+    /// it under `/pypi`, as PyPI and its mirrors serve it.
+    #[must_use]
+    pub fn with_base_url(&self, url: &str) -> Self {
+        let simple_base = url.trim_end_matches('/');
+        let root = simple_base.strip_suffix("/simple").unwrap_or(simple_base);
+        Self {
+            cache: Arc::clone(&self.cache),
+            simple_base: Cow::Owned(simple_base.to_string()),
+            json_base: Cow::Owned(format!("{root}/pypi")),
+        }
     }
 
     /// Fetches all versions for a package from PyPI's Simple API (PEP 691).
@@ -157,7 +181,7 @@ impl PypiRegistry {
     /// ```
     pub async fn get_versions(&self, name: &str) -> Result<Vec<PypiVersion>> {
         let normalized = normalize_package_name(name);
-        let url = simple_api_url(&normalized);
+        let url = simple_api_url(&self.simple_base, &normalized);
         let data = self
             .cache
             .get_cached_with_headers(&url, &[(reqwest::header::ACCEPT, SIMPLE_API_ACCEPT)])
@@ -256,7 +280,7 @@ impl PypiRegistry {
     /// - JSON parsing fails
     pub async fn get_package_metadata(&self, name: &str) -> Result<PypiPackage> {
         let normalized = normalize_package_name(name);
-        let url = metadata_url(&normalized);
+        let url = metadata_url(&self.json_base, &normalized);
         let data = self
             .cache
             .get_cached(&url)
@@ -320,6 +344,14 @@ impl deps_core::Registry for PypiRegistry {
         package_url(name)
     }
 
+    fn release_url(&self, name: &str, version: &str) -> Option<String> {
+        Some(format!(
+            "{}/{}/",
+            package_url(name),
+            urlencoding::encode(version)
+        ))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -944,7 +976,7 @@ mod tests {
         // `normalize_package_name` only collapses `-`/`_`/`.` separators and
         // leaves characters like `/` untouched, so the URL builder itself
         // must encode them to prevent smuggling extra path segments.
-        let url = simple_api_url("evil/../secret");
+        let url = simple_api_url(PYPI_SIMPLE_BASE, "evil/../secret");
         assert!(url.starts_with(PYPI_SIMPLE_BASE));
         assert!(!url.contains("/../"));
         assert_eq!(url, format!("{PYPI_SIMPLE_BASE}/evil%2F..%2Fsecret/"));
@@ -952,7 +984,7 @@ mod tests {
 
     #[test]
     fn test_metadata_url_encodes_malicious_name() {
-        let url = metadata_url("pkg?x=1#frag");
+        let url = metadata_url(PYPI_BASE, "pkg?x=1#frag");
         assert!(!url.contains('?'));
         assert!(!url.contains('#'));
     }
@@ -960,15 +992,23 @@ mod tests {
     #[test]
     fn test_simple_api_url_normal_names() {
         assert_eq!(
-            simple_api_url("requests"),
+            simple_api_url(PYPI_SIMPLE_BASE, "requests"),
             "https://pypi.org/simple/requests/"
         );
         assert_eq!(
-            simple_api_url("zope-interface"),
+            simple_api_url(PYPI_SIMPLE_BASE, "zope-interface"),
             "https://pypi.org/simple/zope-interface/"
         );
     }
 
+    #[test]
+    fn test_with_base_url() {
+        let registry = PypiRegistry::new(Arc::new(HttpCache::new()))
+            .with_base_url("https://pypi.example.com/simple/");
+        assert_eq!(registry.simple_base, "https://pypi.example.com/simple");
+        assert_eq!(registry.json_base, "https://pypi.example.com/pypi");
+    }
+
     #[test]
     fn test_parse_package_info() {
         let json = r#"{
@@ -2,10 +2,13 @@
 
 use std::any::Any;
 use std::sync::Arc;
-use tower_lsp_server::ls_types::{CompletionItem, Position, Uri};
+use tower_lsp_server::ls_types::{CompletionItem, Position, Uri, WorkspaceEdit};
 
 use deps_core::{
-    Ecosystem, ParseResult as ParseResultTrait, Registry, Result, lsp_helpers::EcosystemFormatter,
+    Ecosystem, ParseResult as ParseResultTrait, Registry, Result,
+    add_dependency::{self, DependencyKind, NewDependency},
+    lsp_helpers::EcosystemFormatter,
+    sort::{SectionStyle, SortableEntry, SortableSection},
 };
 
 use crate::formatter::SwiftFormatter;
@@ -128,6 +131,96 @@ impl Ecosystem for SwiftEcosystem {
         })
     }
 
+    fn add_dependency<'a>(
+        &'a self,
+        parse_result: &'a dyn ParseResultTrait,
+        content: &'a str,
+        dependency: &'a NewDependency,
+    ) -> deps_core::ecosystem::BoxFuture<'a, Option<WorkspaceEdit>> {
+        Box::pin(async move {
+            if dependency.kind != DependencyKind::Normal {
+                return None;
+            }
+            let result = parse_result
+                .as_any()
+                .downcast_ref::<crate::types::SwiftParseResult>()?;
+            let (key, url) = if dependency.name.contains("://") {
+                let key = crate::parser::url_to_identity(&dependency.name)?;
+                (key, dependency.name.clone())
+            } else {
+                let url = format!("https://github.com/{}.git", dependency.name);
+                (dependency.name.clone(), url)
+            };
+            let version = dependency
+                .version
+                .strip_prefix('v')
+                .unwrap_or(&dependency.version);
+            let entry = format!(".package(url: \"{url}\", from: \"{version}\")");
+
+            let lines: Vec<&str> = content.lines().collect();
+            // Package dependencies precede `targets:`, whose entries have
+            // `dependencies:` of their own
+            let targets = lines
+                .iter()
+                .position(|line| line.trim_start().starts_with("targets:"))
+                .unwrap_or(lines.len());
+            let open = lines[..targets]
+                .iter()
+                .position(|line| line.trim_start().starts_with("dependencies:"))?;
+
+            let entries: Vec<SortableEntry> = result
+                .dependencies
+                .iter()
+                .filter(|dep| (open..targets).contains(&(dep.name_range.start.line as usize)))
+                .map(|dep| SortableEntry::new(dep.name.clone(), dep.name_range.start.line))
+                .collect();
+            if entries.iter().any(|entry| {
+                !lines[entry.first_line as usize]
+                    .trim_start()
+                    .starts_with(".package(")
+            }) {
+                return None;
+            }
+
+            let edits = if entries.is_empty() {
+                let newline = add_dependency::newline(content);
+                let line = lines[open];
+                let indent = add_dependency::indentation_of(line);
+                let unit = add_dependency::indent_unit(content);
+                if line.trim_end().ends_with("[]") || line.trim_end().ends_with("[],") {
+                    let start = line.rfind("[]")?;
+                    let tail = &line[start + 2..];
+                    vec![tower_lsp_server::ls_types::TextEdit {
+                        range: tower_lsp_server::ls_types::Range::new(
+                            Position::new(open as u32, 0),
+                            Position::new(open as u32, line.encode_utf16().count() as u32),
+                        ),
+                        new_text: format!(
+                            "{}[{newline}{indent}{unit}{entry},{newline}{indent}]{tail}",
+                            &line[..start]
+                        ),
+                    }]
+                } else if line.trim_end().ends_with('[') {
+                    vec![add_dependency::insert_after_line(
+                        content,
+                        open as u32,
+                        &format!("{indent}{unit}{entry},"),
+                    )]
+                } else {
+                    return None;
+                }
+            } else {
+                let section = SortableSection {
+                    label: "dependencies".into(),
+                    style: SectionStyle::JSON_OBJECT,
+                    entries,
+                };
+                deps_core::sort::insert_sorted(content, &section, &key, &entry)?
+            };
+            Some(add_dependency::workspace_edit(parse_result.uri(), edits))
+        })
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -193,4 +286,60 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().dependencies().is_empty());
     }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let eco = SwiftEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Package.swift");
+        let content = r#"let package = Package(
+    name: "App",
+    dependencies: [
+        .package(url: "https://github.com/apple/swift-log.git", from: "1.5.0"),
+        .package(url: "https://github.com/vapor/vapor.git", from: "4.0.0"),
+    ],
+    targets: [
+        .target(name: "App", dependencies: []),
+    ]
+)
+"#;
+        let parse_result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "apple/swift-nio".into(),
+            version: "2.65.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = eco
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        let result = deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]);
+        assert!(result.contains(
+            "swift-log.git\", from: \"1.5.0\"),\n        .package(url: \"https://github.com/apple/swift-nio.git\", from: \"2.65.0\"),\n        .package(url: \"https://github.com/vapor"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency_empty_array() {
+        let cache = Arc::new(deps_core::HttpCache::new());
+        let eco = SwiftEcosystem::new(cache);
+        let uri = deps_core::test_util::test_uri("/test/Package.swift");
+        let content = "let package = Package(\n    name: \"App\",\n    dependencies: [],\n    targets: []\n)\n";
+        let parse_result = eco.parse_manifest(content, &uri).await.unwrap();
+
+        let dependency = NewDependency {
+            name: "https://github.com/apple/swift-nio.git".into(),
+            version: "2.65.0".into(),
+            kind: DependencyKind::Normal,
+        };
+        let edit = eco
+            .add_dependency(parse_result.as_ref(), content, &dependency)
+            .await
+            .unwrap();
+        assert_eq!(
+            deps_core::test_util::apply_edits(content, &edit.changes.unwrap()[&uri]),
+            "let package = Package(\n    name: \"App\",\n    dependencies: [\n        .package(url: \"https://github.com/apple/swift-nio.git\", from: \"2.65.0\"),\n    ],\n    targets: []\n)\n"
+        );
+    }
 }
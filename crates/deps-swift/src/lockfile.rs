@@ -69,6 +69,11 @@ impl LockFileProvider for SwiftLockParser {
         locate_lockfile_for_manifest(manifest_uri, Self::LOCKFILE_NAMES)
     }
 
+    fn records_dependencies(&self) -> bool {
+        // Package.resolved pins repositories without their requirements
+        false
+    }
+
     fn parse_lockfile<'a>(
         &'a self,
         lockfile_path: &'a Path,